@@ -1,5 +1,5 @@
 module Events exposing
-    ( Event, Occurrence, Location, Store, Events, Locations
+    ( Event, Occurrence, Location, Coordinates, Store, Events, Locations
     , createEvent, readEvent, updateEvent, deleteEvent, createLocation, readLocation, updateLocation, deleteLocation
     , locations, events, mapEvents, mapLocations
     , fetchStore
@@ -10,7 +10,7 @@ module Events exposing
 
 # Types
 
-@docs Event, Occurrence, Location, Store, Events, Locations
+@docs Event, Occurrence, Location, Coordinates, Store, Events, Locations
 
 
 # API
@@ -39,6 +39,8 @@ type alias Event =
     { title : String
     , teaser : String
     , description : String
+    , published : Bool
+    , publishAt : Maybe DateTime
     , occurrences : List Occurrence
     }
 
@@ -46,13 +48,21 @@ type alias Event =
 type alias Occurrence =
     { start : DateTime
     , duration : Duration
-    , locationId : Id Location
+    , locationId : Maybe (Id Location)
     }
 
 
 type alias Location =
     { name : String
     , address : String
+    , coordinates : Maybe Coordinates
+    , manualCoordinates : Bool
+    }
+
+
+type alias Coordinates =
+    { latitude : Float
+    , longitude : Float
     }
 
 
@@ -106,14 +116,21 @@ type alias Queries =
     List Url.Builder.QueryParameter
 
 
+{-| Drafts are hidden from everyone but the admin.
+-}
+includeDrafts : Url.Builder.QueryParameter
+includeDrafts =
+    Url.Builder.string "drafts" "true"
+
+
 queryAll : Queries
 queryAll =
-    []
+    [ includeDrafts ]
 
 
 queryUpcoming : Naive.DateTime -> Queries
 queryUpcoming today =
-    [ Url.Builder.string "after" (Naive.encodeDateTimeAsString today) ]
+    [ Url.Builder.string "after" (Naive.encodeDateTimeAsString today), includeDrafts ]
 
 
 apiUrl : List String -> Queries -> String
@@ -283,10 +300,10 @@ decodeStore : Decode.Decoder Store
 decodeStore =
     let
         defaultLocation =
-            Location "" ""
+            Location "" "" Nothing False
 
         defaultEvent =
-            Event "" "" "" []
+            Event "" "" "" True Nothing []
     in
     Decode.field "locations" (IdDict.decodeIdDict defaultLocation decodeLocation)
         |> Decode.andThen
@@ -304,20 +321,26 @@ decodeEvent locs =
             { title = eventData.title
             , teaser = eventData.teaser
             , description = eventData.description
+            , published = eventData.published
+            , publishAt = eventData.publishAt
             , occurrences = occurrences
             }
         )
         (Decode.field "event"
-            (Decode.map3
-                (\title teaser description ->
+            (Decode.map5
+                (\title teaser description published publishAt ->
                     { title = title
                     , teaser = teaser
                     , description = description
+                    , published = published
+                    , publishAt = publishAt
                     }
                 )
                 (Decode.field "title" Decode.string)
                 (Decode.field "teaser" Decode.string)
                 (Decode.field "description" Decode.string)
+                (Decode.field "published" Decode.bool)
+                (Decode.field "publish_at" (Decode.nullable Naive.decodeDateTime))
             )
         )
         (Decode.field "occurrences" (Decode.list (decodeOccurrence locs)))
@@ -325,15 +348,20 @@ decodeEvent locs =
 
 decodeOccurrence : IdDict Location -> Decode.Decoder Occurrence
 decodeOccurrence locs =
-    Decode.field "location_id" decodeUnsafeId
+    Decode.field "location_id" (Decode.nullable decodeUnsafeId)
         |> Decode.andThen
-            (\unsafeId ->
-                case IdDict.validate unsafeId locs of
-                    Just id ->
-                        Decode.succeed id
-
+            (\maybeUnsafeId ->
+                case maybeUnsafeId of
                     Nothing ->
-                        Decode.fail "Invalid location id."
+                        Decode.succeed Nothing
+
+                    Just unsafeId ->
+                        case IdDict.validate unsafeId locs of
+                            Just id ->
+                                Decode.succeed (Just id)
+
+                            Nothing ->
+                                Decode.fail "Invalid location id."
             )
         |> Decode.map3
             Occurrence
@@ -343,10 +371,31 @@ decodeOccurrence locs =
 
 decodeLocation : Decode.Decoder Location
 decodeLocation =
-    Decode.map2
+    Decode.map4
         Location
         (Decode.field "name" Decode.string)
-        (Decode.field "address" Decode.string)
+        (Decode.field "address" decodeAddress)
+        (Decode.field "coordinates" (Decode.nullable decodeCoordinates))
+        (Decode.field "manual_coordinates" Decode.bool)
+
+
+decodeCoordinates : Decode.Decoder Coordinates
+decodeCoordinates =
+    Decode.map2
+        Coordinates
+        (Decode.field "latitude" Decode.float)
+        (Decode.field "longitude" Decode.float)
+
+
+{-| Addresses are edited as text, which the server splits into its parts.
+-}
+decodeAddress : Decode.Decoder String
+decodeAddress =
+    Decode.map3
+        (\street postalCode city -> street ++ ", " ++ postalCode ++ " " ++ city)
+        (Decode.field "street" Decode.string)
+        (Decode.field "postal_code" Decode.string)
+        (Decode.field "city" Decode.string)
 
 
 
@@ -361,6 +410,12 @@ encodeEvent event =
                 [ ( "title", Encode.string event.title )
                 , ( "teaser", Encode.string event.teaser )
                 , ( "description", Encode.string event.description )
+                , ( "published", Encode.bool event.published )
+                , ( "publish_at"
+                  , event.publishAt
+                        |> Maybe.map Naive.encodeDateTime
+                        |> Maybe.withDefault Encode.null
+                  )
                 ]
           )
         , ( "occurrences", Encode.list encodeOccurrence event.occurrences )
@@ -372,7 +427,10 @@ encodeOccurrence occurrence =
     Encode.object
         [ ( "start", Naive.encodeDateTime occurrence.start )
         , ( "duration", Naive.encodeAsMinutes occurrence.duration )
-        , ( "location_id", IdDict.encodeId occurrence.locationId )
+        , ( "location_id"
+          , Maybe.map IdDict.encodeId occurrence.locationId
+                |> Maybe.withDefault Encode.null
+          )
         ]
 
 
@@ -381,4 +439,18 @@ encodeLocation location =
     Encode.object
         [ ( "name", Encode.string location.name )
         , ( "address", Encode.string location.address )
+        , ( "coordinates"
+          , location.coordinates
+                |> Maybe.map encodeCoordinates
+                |> Maybe.withDefault Encode.null
+          )
+        , ( "manual_coordinates", Encode.bool location.manualCoordinates )
+        ]
+
+
+encodeCoordinates : Coordinates -> Encode.Value
+encodeCoordinates coordinates =
+    Encode.object
+        [ ( "latitude", Encode.float coordinates.latitude )
+        , ( "longitude", Encode.float coordinates.longitude )
         ]
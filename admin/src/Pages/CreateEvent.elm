@@ -81,6 +81,8 @@ fromEvents key store =
                 { title = Utils.inputString ""
                 , teaser = Utils.inputString ""
                 , description = Utils.inputString ""
+                , published = False
+                , publishAt = Utils.inputOptionalDateTime Nothing
                 , occurrences = []
                 }
             , batchAdd = batchAddModel
@@ -49,6 +49,7 @@ init key =
         inputs =
             { name = Utils.inputString ""
             , address = Utils.inputString ""
+            , coordinates = Edit.inputCoordinates Nothing
             }
     in
     Model key inputs
@@ -40,6 +40,7 @@ import Pages.Utils as Utils
         , inputString
         , labeled
         , updateInput
+        , viewCheckbox
         , viewDateTimeInput
         , viewInputNumber
         , viewInputText
@@ -80,6 +81,8 @@ type alias EventInput =
     { title : In String
     , teaser : In String
     , description : In String
+    , published : Bool
+    , publishAt : Input { date : String, time : String } (Maybe Naive.DateTime)
     , occurrences : List OccurrenceInput
     }
 
@@ -141,11 +144,13 @@ eventFromInputs locs inputs =
         maybeOccurrences =
             Maybe.combine (List.map (occurrenceFromInput locs) inputs.occurrences)
     in
-    Maybe.map4 Event
-        (extract inputs.title)
-        (extract inputs.teaser)
-        (extract inputs.description)
-        maybeOccurrences
+    Just Event
+        |> Maybe.andMap (extract inputs.title)
+        |> Maybe.andMap (extract inputs.teaser)
+        |> Maybe.andMap (extract inputs.description)
+        |> Maybe.andMap (Just inputs.published)
+        |> Maybe.andMap (extract inputs.publishAt)
+        |> Maybe.andMap maybeOccurrences
 
 
 occurrenceFromInput : Locations -> OccurrenceInput -> Maybe Occurrence
@@ -154,7 +159,7 @@ occurrenceFromInput locs input =
         Occurrence
         (extract input.start)
         (extract input.duration)
-        (extract input.locationId)
+        (extract input.locationId |> Maybe.map Just)
 
 
 inputsFromEvent : Locations -> Event -> EventInput
@@ -170,6 +175,8 @@ inputsFromEvent locations event =
     { title = inputString event.title
     , teaser = inputString event.teaser
     , description = inputString event.description
+    , published = event.published
+    , publishAt = Utils.inputOptionalDateTime event.publishAt
     , occurrences = List.map inputFromOccurrence event.occurrences
     }
 
@@ -196,11 +203,12 @@ durationValidator =
         )
 
 
-inputLocationId : Locations -> Id Location -> In (Id Location)
-inputLocationId locations id =
+inputLocationId : Locations -> Maybe (Id Location) -> In (Id Location)
+inputLocationId locations maybeId =
     let
         value =
-            IdDict.encodeIdForUrl id
+            Maybe.map IdDict.encodeIdForUrl maybeId
+                |> Maybe.withDefault ""
     in
     Utils.buildInput value (locationIdValidator locations)
 
@@ -311,6 +319,9 @@ type EventMsg
     = InputName String
     | InputTeaser String
     | InputDescription String
+    | InputPublished Bool
+    | InputPublishAtDate String
+    | InputPublishAtTime String
     | InputOccurrence Int OccurrenceMsg
     | AddOccurrence
 
@@ -421,6 +432,15 @@ updateEventInputs locations msg event =
         InputDescription newDescription ->
             { event | description = setInput newDescription event.description }
 
+        InputPublished newPublished ->
+            { event | published = newPublished }
+
+        InputPublishAtDate newDate ->
+            { event | publishAt = updateInput (\publishAt -> { publishAt | date = newDate }) event.publishAt }
+
+        InputPublishAtTime newTime ->
+            { event | publishAt = updateInput (\publishAt -> { publishAt | time = newTime }) event.publishAt }
+
         InputOccurrence index occurrenceMsg ->
             let
                 updateOccurrence : (OccurrenceInput -> OccurrenceInput) -> EventInput
@@ -581,6 +601,12 @@ viewEditEvent locations inputs =
         [ viewInputText "Titel" inputs.eventInputs.title (InputEvent << InputName)
         , viewInputText "Teaser" inputs.eventInputs.teaser (InputEvent << InputTeaser)
         , viewTextArea "Beschreibung" inputs.eventInputs.description (InputEvent << InputDescription)
+        , viewCheckbox "Veröffentlicht" inputs.eventInputs.published (InputEvent << InputPublished)
+        , viewDateTimeInput "Veröffentlichen ab (optional)"
+            inputs.eventInputs.publishAt
+            { dateChanged = InputEvent << InputPublishAtDate
+            , timeChanged = InputEvent << InputPublishAtTime
+            }
         ]
     , h2 [] [ text "Termine" ]
     , ol [ css [ spreadListItemStyle ] ]
@@ -8,6 +8,7 @@ module Pages.EditLocation exposing
     , Msg
     , fromEvents
     , init
+    , inputCoordinates
     , locationFromInputs
     , update
     , updateInputs
@@ -17,7 +18,7 @@ module Pages.EditLocation exposing
     )
 
 import Css exposing (row)
-import Events exposing (Event, Events, Location, Occurrence)
+import Events exposing (Coordinates, Event, Events, Location, Occurrence)
 import Html.Styled as Html exposing (Html, a, div, input, label, li, ol, p, text, textarea)
 import Html.Styled.Attributes exposing (css, href, type_, value)
 import Html.Styled.Events exposing (onInput)
@@ -29,6 +30,7 @@ import Pages.Utils as Utils
     exposing
         ( In
         , Input
+        , buildInput
         , extract
         , inputString
         , updateInput
@@ -42,6 +44,7 @@ import Routes
 import Time
 import Utils.NaiveDateTime as Naive
 import Utils.TimeFormat as TimeFormat
+import Utils.Validate as Validate
 
 
 type alias Model =
@@ -54,6 +57,7 @@ type alias Model =
 type alias LocationInput =
     { name : In String
     , address : In String
+    , coordinates : In (Maybe Coordinates)
     }
 
 
@@ -61,15 +65,61 @@ inputsFromLocation : Location -> LocationInput
 inputsFromLocation location =
     { name = inputString location.name
     , address = inputString location.address
+    , coordinates = inputCoordinates location.coordinates
     }
 
 
+{-| Coordinates that were looked up from the address are not edited, so that they are
+looked up again when the address changes.
+-}
+withoutLookedUpCoordinates : Location -> Location
+withoutLookedUpCoordinates location =
+    if location.manualCoordinates then
+        location
+
+    else
+        { location | coordinates = Nothing }
+
+
 locationFromInputs : LocationInput -> Maybe Location
 locationFromInputs inputs =
-    Maybe.map2
-        Location
+    Maybe.map3
+        (\name address coordinates -> Location name address coordinates (coordinates /= Nothing))
         (extract inputs.name)
         (extract inputs.address)
+        (extract inputs.coordinates)
+
+
+{-| Coordinates are entered like "50.7766, 6.0834", as map apps show them. Without them,
+they are looked up from the address.
+-}
+inputCoordinates : Maybe Coordinates -> In (Maybe Coordinates)
+inputCoordinates coordinates =
+    let
+        raw =
+            coordinates
+                |> Maybe.map (\{ latitude, longitude } -> String.fromFloat latitude ++ ", " ++ String.fromFloat longitude)
+                |> Maybe.withDefault ""
+    in
+    buildInput raw
+        (Validate.from
+            (\value ->
+                if String.isEmpty (String.trim value) then
+                    Ok Nothing
+
+                else
+                    case List.map (String.trim >> String.toFloat) (String.split "," value) of
+                        [ Just latitude, Just longitude ] ->
+                            if abs latitude <= 90 && abs longitude <= 180 then
+                                Ok (Just (Coordinates latitude longitude))
+
+                            else
+                                Err [ "Die Koordinaten liegen nicht auf der Erde." ]
+
+                        _ ->
+                            Err [ "Bitte als Breitengrad, Längengrad angeben, z. B. 50.7766, 6.0834." ]
+            )
+        )
 
 
 type alias LoadModel =
@@ -97,7 +147,7 @@ fromEvents rawId store =
             (\id ->
                 let
                     location =
-                        IdDict.get id locations
+                        withoutLookedUpCoordinates (IdDict.get id locations)
 
                     inputs =
                         inputsFromLocation location
@@ -138,6 +188,7 @@ type Msg
 type InputMsg
     = InputName String
     | InputAddress String
+    | InputCoordinates String
 
 
 update : Msg -> Model -> ( Model, Cmd Msg )
@@ -181,6 +232,9 @@ updateInputs msg location =
         InputAddress newAddress ->
             { location | address = setInput newAddress location.address }
 
+        InputCoordinates newCoordinates ->
+            { location | coordinates = setInput newCoordinates location.coordinates }
+
 
 updateLocation : Model -> (LocationInput -> LocationInput) -> Model
 updateLocation model locationUpdater =
@@ -221,6 +275,7 @@ viewEditLocation inputs =
     [ Utils.fields
         [ viewInputText "Bezeichnung" inputs.name InputName
         , viewTextArea "Adresse" inputs.address InputAddress
+        , viewInputText "Koordinaten (sonst aus der Adresse bestimmt)" inputs.coordinates InputCoordinates
         ]
     ]
 
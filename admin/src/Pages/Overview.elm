@@ -140,6 +140,15 @@ viewEvent locations event =
     in
     div []
         [ text event.title
+        , case ( event.published, event.publishAt ) of
+            ( False, _ ) ->
+                text " (Entwurf)"
+
+            ( True, Just publishAt ) ->
+                text (" (ab " ++ TimeFormat.fullDate publishAt ++ ")")
+
+            ( True, Nothing ) ->
+                text ""
         , ol [ css [ listStyle, Css.paddingLeft (em 1) ] ] listItems
         ]
 
@@ -147,11 +156,13 @@ viewEvent locations event =
 viewOccurrence : Locations -> Occurrence -> Html msg
 viewOccurrence locations occurrence =
     let
-        location =
-            IdDict.get occurrence.locationId locations
+        locationName =
+            occurrence.locationId
+                |> Maybe.map (\id -> (IdDict.get id locations).name)
+                |> Maybe.withDefault "Ort steht noch nicht fest"
     in
     div []
-        [ text <| TimeFormat.fullDate occurrence.start ++ " - " ++ location.name ]
+        [ text <| TimeFormat.fullDate occurrence.start ++ " - " ++ locationName ]
 
 
 viewLocation : Location -> Html msg
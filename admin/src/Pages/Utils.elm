@@ -12,11 +12,13 @@ module Pages.Utils exposing
     , fields
     , getRaw
     , inputDateTime
+    , inputOptionalDateTime
     , inputString
     , labeled
     , timeValidator
     , updateInput
     , validate
+    , viewCheckbox
     , viewDateTimeInput
     , viewInputNumber
     , viewInputText
@@ -28,8 +30,8 @@ module Pages.Utils exposing
 import Css exposing (center, column, em, flexStart, none, row, zero)
 import Css.Global as Css
 import Html.Styled as Html exposing (Html, a, div, input, label, li, nav, ol, text, textarea)
-import Html.Styled.Attributes exposing (css, disabled, href, type_, value)
-import Html.Styled.Events exposing (onClick, onInput)
+import Html.Styled.Attributes exposing (checked, css, disabled, href, type_, value)
+import Html.Styled.Events exposing (onCheck, onClick, onInput)
 import Parser
 import Routes exposing (Route)
 import Utils.NaiveDateTime as Naive
@@ -156,6 +158,30 @@ dateTimeValidator =
         )
 
 
+{-| Leaving both the date and the time empty means that there is none.
+-}
+inputOptionalDateTime : Maybe Naive.DateTime -> Input { date : String, time : String } (Maybe Naive.DateTime)
+inputOptionalDateTime maybeDateTime =
+    case maybeDateTime of
+        Just dateTime ->
+            buildInput (getRaw (inputDateTime dateTime)) optionalDateTimeValidator
+
+        Nothing ->
+            buildInput { date = "", time = "" } optionalDateTimeValidator
+
+
+optionalDateTimeValidator : Validator { date : String, time : String } (Maybe Naive.DateTime)
+optionalDateTimeValidator =
+    Validate.from
+        (\raw ->
+            if String.isEmpty raw.date && String.isEmpty raw.time then
+                Ok Nothing
+
+            else
+                Validate.validate dateTimeValidator raw |> Result.map Just
+        )
+
+
 dateValidator : Validator String Naive.Date
 dateValidator =
     Validate.from
@@ -188,6 +214,14 @@ viewInputNumber lbl (Input val validator) inputMsg =
         )
 
 
+viewCheckbox : String -> Bool -> (Bool -> msg) -> Html msg
+viewCheckbox lbl isChecked checkMsg =
+    label []
+        [ input [ type_ "checkbox", checked isChecked, onCheck checkMsg ] []
+        , text lbl
+        ]
+
+
 viewTextArea : String -> In a -> (String -> msg) -> Html msg
 viewTextArea lbl (Input val validator) inputMsg =
     labeled lbl
@@ -225,7 +259,7 @@ viewSelection lbl (Input val validator) options inputMsg =
 
 viewDateTimeInput :
     String
-    -> Input { date : String, time : String } Naive.DateTime
+    -> Input { date : String, time : String } a
     -> { dateChanged : String -> msg, timeChanged : String -> msg }
     -> Html msg
 viewDateTimeInput lbl (Input { date, time } validator) toMsgs =
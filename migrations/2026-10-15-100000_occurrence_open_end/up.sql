@@ -0,0 +1 @@
+ALTER TABLE occurrences ADD COLUMN open_end BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+-- Occurrences refer to events, so dropping them would violate foreign keys until they are
+-- inserted again.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE events_backup AS
+    SELECT id, title, teaser, description FROM events;
+DROP TABLE events;
+CREATE TABLE events (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    teaser VARCHAR NOT NULL,
+    description VARCHAR NOT NULL
+);
+INSERT INTO events SELECT * FROM events_backup;
+DROP TABLE events_backup;
@@ -0,0 +1,2 @@
+ALTER TABLE events ADD COLUMN contact_name VARCHAR;
+ALTER TABLE events ADD COLUMN contact_email VARCHAR;
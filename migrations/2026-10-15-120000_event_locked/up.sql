@@ -0,0 +1 @@
+ALTER TABLE events ADD COLUMN locked BOOLEAN NOT NULL DEFAULT 0;
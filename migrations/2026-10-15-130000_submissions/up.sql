@@ -0,0 +1,12 @@
+CREATE TABLE submissions (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    teaser VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    organizer_name VARCHAR NOT NULL,
+    organizer_email VARCHAR NOT NULL,
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    location_id BINARY(128) NOT NULL,
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
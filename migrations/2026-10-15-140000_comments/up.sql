@@ -0,0 +1,9 @@
+CREATE TABLE comments (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    name VARCHAR NOT NULL,
+    text VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    approved BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (event_id) REFERENCES events(id)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE deleted_events (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    deleted_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,29 @@
+-- Occurrences and comments refer to events, so dropping them would violate foreign keys
+-- until they are inserted again.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE events_backup AS
+    SELECT id, title, teaser, description, contact_name, contact_email, locked FROM events;
+DROP TABLE events;
+CREATE TABLE events (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    teaser VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    contact_name VARCHAR,
+    contact_email VARCHAR,
+    locked BOOLEAN NOT NULL DEFAULT 0
+);
+INSERT INTO events SELECT * FROM events_backup;
+DROP TABLE events_backup;
+
+CREATE TEMPORARY TABLE deleted_events_backup AS
+    SELECT id, title, deleted_at FROM deleted_events;
+DROP TABLE deleted_events;
+CREATE TABLE deleted_events (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    deleted_at TIMESTAMP NOT NULL
+);
+INSERT INTO deleted_events SELECT * FROM deleted_events_backup;
+DROP TABLE deleted_events_backup;
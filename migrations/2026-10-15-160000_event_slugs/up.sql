@@ -0,0 +1,4 @@
+ALTER TABLE events ADD COLUMN slug VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE deleted_events ADD COLUMN slug VARCHAR NOT NULL DEFAULT '';
+-- Existing events are assigned their slugs when the server starts.
+CREATE UNIQUE INDEX events_slug ON events (slug) WHERE slug != '';
@@ -0,0 +1,14 @@
+CREATE TABLE occurrences_backup (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    location_id BINARY(128) NOT NULL,
+    doors_open INTEGER,
+    open_end BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (event_id) REFERENCES events(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
+INSERT INTO occurrences_backup SELECT id, start, duration, event_id, location_id, doors_open, open_end FROM occurrences;
+DROP TABLE occurrences;
+ALTER TABLE occurrences_backup RENAME TO occurrences;
@@ -0,0 +1 @@
+ALTER TABLE occurrences ADD COLUMN stream_url VARCHAR;
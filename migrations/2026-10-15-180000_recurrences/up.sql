@@ -0,0 +1,17 @@
+CREATE TABLE recurrences (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    location_id BINARY(128) NOT NULL,
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    doors_open INTEGER,
+    open_end BOOLEAN NOT NULL DEFAULT 0,
+    stream_url VARCHAR,
+    interval_weeks INTEGER NOT NULL,
+    until DATE,
+    -- Occurrences up to this time have been created, NULL if none have been yet.
+    expanded_until TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES events(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
+ALTER TABLE occurrences ADD COLUMN recurrence_id BINARY(128) REFERENCES recurrences(id);
@@ -0,0 +1,11 @@
+CREATE TABLE recurrence_exceptions (
+    recurrence_id BINARY(128) NOT NULL,
+    -- The date the recurrence would take place on without the exception.
+    date DATE NOT NULL,
+    cancelled BOOLEAN NOT NULL DEFAULT 0,
+    start TIMESTAMP,
+    location_id BINARY(128),
+    PRIMARY KEY (recurrence_id, date),
+    FOREIGN KEY (recurrence_id) REFERENCES recurrences(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
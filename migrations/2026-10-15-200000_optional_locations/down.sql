@@ -0,0 +1,53 @@
+-- Undecided locations cannot be represented anymore, so their occurrences and recurrences
+-- are dropped.
+PRAGMA defer_foreign_keys = ON;
+
+DELETE FROM recurrence_exceptions
+    WHERE recurrence_id IN (SELECT id FROM recurrences WHERE location_id IS NULL);
+UPDATE occurrences SET recurrence_id = NULL
+    WHERE recurrence_id IN (SELECT id FROM recurrences WHERE location_id IS NULL);
+
+CREATE TEMPORARY TABLE occurrences_backup AS
+    SELECT id, start, duration, event_id, location_id, doors_open, open_end, stream_url,
+        recurrence_id
+    FROM occurrences WHERE location_id IS NOT NULL;
+DROP TABLE occurrences;
+CREATE TABLE occurrences (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    location_id BINARY(128) NOT NULL,
+    doors_open INTEGER,
+    open_end BOOLEAN NOT NULL DEFAULT 0,
+    stream_url VARCHAR,
+    recurrence_id BINARY(128) REFERENCES recurrences(id),
+    FOREIGN KEY (event_id) REFERENCES events(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
+INSERT INTO occurrences SELECT * FROM occurrences_backup;
+DROP TABLE occurrences_backup;
+
+CREATE TEMPORARY TABLE recurrences_backup AS
+    SELECT id, event_id, location_id, start, duration, doors_open, open_end, stream_url,
+        interval_weeks, until, expanded_until
+    FROM recurrences WHERE location_id IS NOT NULL;
+DROP TABLE recurrences;
+CREATE TABLE recurrences (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    location_id BINARY(128) NOT NULL,
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    doors_open INTEGER,
+    open_end BOOLEAN NOT NULL DEFAULT 0,
+    stream_url VARCHAR,
+    interval_weeks INTEGER NOT NULL,
+    until DATE,
+    -- Occurrences up to this time have been created, NULL if none have been yet.
+    expanded_until TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES events(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
+INSERT INTO recurrences SELECT * FROM recurrences_backup;
+DROP TABLE recurrences_backup;
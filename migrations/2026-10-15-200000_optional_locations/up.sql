@@ -0,0 +1,56 @@
+-- A NULL location means that it has not been decided yet. Occurrences referring to a
+-- location that does not exist (anymore) were shown like that before, so they become NULL.
+
+-- Recurrences are referenced by occurrences and exceptions, so dropping them would violate
+-- foreign keys until they are inserted again.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE occurrences_backup AS
+    SELECT id, start, duration, event_id,
+        CASE WHEN location_id IN (SELECT id FROM locations) THEN location_id END AS location_id,
+        doors_open, open_end, stream_url, recurrence_id
+    FROM occurrences;
+DROP TABLE occurrences;
+CREATE TABLE occurrences (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    location_id BINARY(128),
+    doors_open INTEGER,
+    open_end BOOLEAN NOT NULL DEFAULT 0,
+    stream_url VARCHAR,
+    recurrence_id BINARY(128) REFERENCES recurrences(id),
+    FOREIGN KEY (event_id) REFERENCES events(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
+INSERT INTO occurrences SELECT * FROM occurrences_backup;
+DROP TABLE occurrences_backup;
+
+CREATE TEMPORARY TABLE recurrences_backup AS
+    SELECT id, event_id,
+        CASE WHEN location_id IN (SELECT id FROM locations) THEN location_id END AS location_id,
+        start, duration, doors_open, open_end, stream_url, interval_weeks, until, expanded_until
+    FROM recurrences;
+DROP TABLE recurrences;
+CREATE TABLE recurrences (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    location_id BINARY(128),
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    doors_open INTEGER,
+    open_end BOOLEAN NOT NULL DEFAULT 0,
+    stream_url VARCHAR,
+    interval_weeks INTEGER NOT NULL,
+    until DATE,
+    -- Occurrences up to this time have been created, NULL if none have been yet.
+    expanded_until TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES events(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
+INSERT INTO recurrences SELECT * FROM recurrences_backup;
+DROP TABLE recurrences_backup;
+
+UPDATE recurrence_exceptions SET location_id = NULL
+    WHERE location_id NOT IN (SELECT id FROM locations);
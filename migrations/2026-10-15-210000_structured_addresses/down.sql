@@ -0,0 +1,15 @@
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE locations_backup AS
+    SELECT id, name,
+        CASE WHEN postal_code = '' AND city = '' THEN street
+            ELSE street || ', ' || trim(postal_code || ' ' || city) END AS address
+    FROM locations;
+DROP TABLE locations;
+CREATE TABLE locations (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    name VARCHAR NOT NULL,
+    address VARCHAR NOT NULL
+);
+INSERT INTO locations SELECT * FROM locations_backup;
+DROP TABLE locations_backup;
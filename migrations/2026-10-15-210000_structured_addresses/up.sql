@@ -0,0 +1,39 @@
+-- Splits addresses written like "Markt 1, 52062 Aachen" at their first comma or line break.
+-- Addresses that do not look like that are kept in the street, to be fixed by hand.
+
+-- Locations are referenced by occurrences, so dropping them would violate foreign keys
+-- until they are inserted again.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE locations_backup AS
+    SELECT id, name,
+        CASE WHEN splittable THEN trim(substr(normalized, 1, comma - 1), ' ,')
+            ELSE trim(normalized, ' ,') END AS street,
+        CASE WHEN splittable THEN substr(rest, 1, instr(rest, ' ') - 1) ELSE '' END AS postal_code,
+        CASE WHEN splittable THEN trim(substr(rest, instr(rest, ' ') + 1)) ELSE '' END AS city
+    FROM (
+        SELECT id, name, normalized, comma, rest,
+            comma > 0 AND instr(rest, ' ') > 0 AND rest GLOB '[0-9][0-9][0-9][0-9]*' AS splittable
+        FROM (
+            SELECT id, name, normalized, comma,
+                trim(substr(normalized, comma + 1), ' ,') AS rest
+            FROM (
+                SELECT id, name, normalized, instr(normalized, ',') AS comma
+                FROM (
+                    SELECT id, name,
+                        replace(replace(address, char(13), ''), char(10), ',') AS normalized
+                    FROM locations
+                )
+            )
+        )
+    );
+DROP TABLE locations;
+CREATE TABLE locations (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    name VARCHAR NOT NULL,
+    street VARCHAR NOT NULL,
+    postal_code VARCHAR NOT NULL,
+    city VARCHAR NOT NULL
+);
+INSERT INTO locations SELECT * FROM locations_backup;
+DROP TABLE locations_backup;
@@ -0,0 +1,22 @@
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE occurrences_backup AS
+    SELECT id, start, duration, event_id, location_id, doors_open, open_end, stream_url,
+        recurrence_id
+    FROM occurrences;
+DROP TABLE occurrences;
+CREATE TABLE occurrences (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    start TIMESTAMP NOT NULL,
+    duration INTEGER NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    location_id BINARY(128),
+    doors_open INTEGER,
+    open_end BOOLEAN NOT NULL DEFAULT 0,
+    stream_url VARCHAR,
+    recurrence_id BINARY(128) REFERENCES recurrences(id),
+    FOREIGN KEY (event_id) REFERENCES events(id),
+    FOREIGN KEY (location_id) REFERENCES locations(id)
+);
+INSERT INTO occurrences SELECT * FROM occurrences_backup;
+DROP TABLE occurrences_backup;
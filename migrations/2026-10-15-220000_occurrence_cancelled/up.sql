@@ -0,0 +1,2 @@
+ALTER TABLE occurrences ADD COLUMN cancelled BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE occurrences ADD COLUMN cancellation_reason VARCHAR;
@@ -0,0 +1,8 @@
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    recorded_at TIMESTAMP NOT NULL,
+    entity VARCHAR NOT NULL,
+    entity_id BINARY(128) NOT NULL,
+    action VARCHAR NOT NULL,
+    changes TEXT NOT NULL
+);
@@ -0,0 +1,41 @@
+PRAGMA defer_foreign_keys = ON;
+
+DELETE FROM comments WHERE event_id IN (SELECT id FROM events WHERE deleted_at IS NOT NULL);
+DELETE FROM recurrence_exceptions WHERE recurrence_id IN (
+    SELECT id FROM recurrences WHERE event_id IN (SELECT id FROM events WHERE deleted_at IS NOT NULL)
+);
+DELETE FROM recurrences WHERE event_id IN (SELECT id FROM events WHERE deleted_at IS NOT NULL);
+DELETE FROM occurrences WHERE event_id IN (SELECT id FROM events WHERE deleted_at IS NOT NULL);
+DELETE FROM events WHERE deleted_at IS NOT NULL;
+DELETE FROM locations WHERE deleted_at IS NOT NULL;
+
+CREATE TEMPORARY TABLE events_backup AS
+    SELECT id, title, teaser, description, contact_name, contact_email, locked, slug
+    FROM events;
+DROP TABLE events;
+CREATE TABLE events (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    teaser VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    contact_name VARCHAR,
+    contact_email VARCHAR,
+    locked BOOLEAN NOT NULL DEFAULT 0,
+    slug VARCHAR NOT NULL DEFAULT ''
+);
+INSERT INTO events SELECT * FROM events_backup;
+DROP TABLE events_backup;
+
+CREATE TEMPORARY TABLE locations_backup AS
+    SELECT id, name, street, postal_code, city
+    FROM locations;
+DROP TABLE locations;
+CREATE TABLE locations (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    name VARCHAR NOT NULL,
+    street VARCHAR NOT NULL,
+    postal_code VARCHAR NOT NULL,
+    city VARCHAR NOT NULL
+);
+INSERT INTO locations SELECT * FROM locations_backup;
+DROP TABLE locations_backup;
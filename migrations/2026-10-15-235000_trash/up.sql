@@ -0,0 +1,2 @@
+ALTER TABLE events ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE locations ADD COLUMN deleted_at TIMESTAMP;
@@ -0,0 +1,19 @@
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE events_backup AS
+    SELECT id, title, teaser, description, contact_name, contact_email, locked, slug, deleted_at
+    FROM events;
+DROP TABLE events;
+CREATE TABLE events (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    teaser VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+    contact_name VARCHAR,
+    contact_email VARCHAR,
+    locked BOOLEAN NOT NULL DEFAULT 0,
+    slug VARCHAR NOT NULL DEFAULT '',
+    deleted_at TIMESTAMP
+);
+INSERT INTO events SELECT * FROM events_backup;
+DROP TABLE events_backup;
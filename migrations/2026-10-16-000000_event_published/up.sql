@@ -0,0 +1 @@
+ALTER TABLE events ADD COLUMN published BOOLEAN NOT NULL DEFAULT 1;
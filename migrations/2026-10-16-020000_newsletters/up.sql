@@ -0,0 +1,6 @@
+CREATE TABLE newsletters (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    title VARCHAR NOT NULL,
+    date DATE NOT NULL,
+    content VARCHAR NOT NULL
+);
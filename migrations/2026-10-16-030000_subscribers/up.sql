@@ -0,0 +1,7 @@
+CREATE TABLE subscribers (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    email VARCHAR NOT NULL UNIQUE,
+    name VARCHAR,
+    consented_at DATE NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT 0
+);
@@ -0,0 +1,9 @@
+CREATE TABLE newsletter_deliveries (
+    newsletter_id BINARY(128) NOT NULL,
+    subscriber_id BINARY(128) NOT NULL,
+    sent_at TIMESTAMP NOT NULL,
+    error VARCHAR,
+    PRIMARY KEY (newsletter_id, subscriber_id),
+    FOREIGN KEY (newsletter_id) REFERENCES newsletters(id),
+    FOREIGN KEY (subscriber_id) REFERENCES subscribers(id)
+);
@@ -0,0 +1,14 @@
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE subscribers_backup AS
+    SELECT id, email, name, consented_at, confirmed FROM subscribers;
+DROP TABLE subscribers;
+CREATE TABLE subscribers (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    email VARCHAR NOT NULL UNIQUE,
+    name VARCHAR,
+    consented_at DATE NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT 0
+);
+INSERT INTO subscribers SELECT * FROM subscribers_backup;
+DROP TABLE subscribers_backup;
@@ -0,0 +1,16 @@
+PRAGMA defer_foreign_keys = ON;
+
+DROP INDEX subscribers_unsubscribe_token;
+CREATE TEMPORARY TABLE subscribers_backup AS
+    SELECT id, email, name, consented_at, confirmed, confirmation_token FROM subscribers;
+DROP TABLE subscribers;
+CREATE TABLE subscribers (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    email VARCHAR NOT NULL UNIQUE,
+    name VARCHAR,
+    consented_at DATE NOT NULL,
+    confirmed BOOLEAN NOT NULL DEFAULT 0,
+    confirmation_token VARCHAR
+);
+INSERT INTO subscribers SELECT * FROM subscribers_backup;
+DROP TABLE subscribers_backup;
@@ -0,0 +1,3 @@
+ALTER TABLE subscribers ADD COLUMN unsubscribe_token VARCHAR NOT NULL DEFAULT '';
+UPDATE subscribers SET unsubscribe_token = lower(hex(randomblob(32)));
+CREATE UNIQUE INDEX subscribers_unsubscribe_token ON subscribers (unsubscribe_token);
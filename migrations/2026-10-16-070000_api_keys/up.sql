@@ -0,0 +1,13 @@
+CREATE TABLE api_keys (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    secret VARCHAR NOT NULL UNIQUE,
+    name VARCHAR NOT NULL,
+    daily_quota INTEGER NOT NULL
+);
+CREATE TABLE api_key_usage (
+    api_key_id BINARY(128) NOT NULL,
+    date DATE NOT NULL,
+    requests INTEGER NOT NULL,
+    PRIMARY KEY (api_key_id, date),
+    FOREIGN KEY (api_key_id) REFERENCES api_keys(id)
+);
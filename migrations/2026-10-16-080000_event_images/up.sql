@@ -0,0 +1,10 @@
+CREATE TABLE event_images (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    event_id BINARY(128) NOT NULL,
+    file_name VARCHAR NOT NULL UNIQUE,
+    original_name VARCHAR NOT NULL,
+    content_type VARCHAR NOT NULL,
+    size BIGINT NOT NULL,
+    uploaded_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (event_id) REFERENCES events(id)
+);
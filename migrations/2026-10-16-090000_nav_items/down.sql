@@ -0,0 +1 @@
+DROP TABLE nav_items;
@@ -0,0 +1,7 @@
+CREATE TABLE nav_items (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    label VARCHAR NOT NULL,
+    url VARCHAR NOT NULL,
+    position INTEGER NOT NULL,
+    visible BOOLEAN NOT NULL DEFAULT 1
+);
@@ -0,0 +1,2 @@
+ALTER TABLE locations ADD COLUMN latitude DOUBLE;
+ALTER TABLE locations ADD COLUMN longitude DOUBLE;
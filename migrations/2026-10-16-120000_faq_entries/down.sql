@@ -0,0 +1 @@
+DROP TABLE faq_entries;
@@ -0,0 +1,7 @@
+CREATE TABLE faq_entries (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    question VARCHAR NOT NULL,
+    answer TEXT NOT NULL,
+    category VARCHAR NOT NULL,
+    position INTEGER NOT NULL
+);
@@ -0,0 +1,18 @@
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE locations_backup AS
+    SELECT id, name, street, postal_code, city, deleted_at, latitude, longitude
+    FROM locations;
+DROP TABLE locations;
+CREATE TABLE locations (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    name VARCHAR NOT NULL,
+    street VARCHAR NOT NULL,
+    postal_code VARCHAR NOT NULL,
+    city VARCHAR NOT NULL,
+    deleted_at TIMESTAMP,
+    latitude DOUBLE,
+    longitude DOUBLE
+);
+INSERT INTO locations SELECT * FROM locations_backup;
+DROP TABLE locations_backup;
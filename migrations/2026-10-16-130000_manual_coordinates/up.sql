@@ -0,0 +1,3 @@
+ALTER TABLE locations ADD COLUMN manual_coordinates BOOLEAN NOT NULL DEFAULT 0;
+-- Until now, coordinates could only be entered by hand.
+UPDATE locations SET manual_coordinates = 1 WHERE latitude IS NOT NULL;
@@ -0,0 +1,6 @@
+CREATE TABLE redirects (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    source VARCHAR NOT NULL UNIQUE,
+    target VARCHAR NOT NULL,
+    status INTEGER NOT NULL DEFAULT 301
+);
@@ -0,0 +1,14 @@
+-- The secrets cannot be recovered from their hashes, so the keys have to be issued again.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE api_keys_backup AS
+    SELECT id, secret_hash AS secret, name, daily_quota FROM api_keys;
+DROP TABLE api_keys;
+CREATE TABLE api_keys (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    secret VARCHAR NOT NULL UNIQUE,
+    name VARCHAR NOT NULL,
+    daily_quota INTEGER NOT NULL
+);
+INSERT INTO api_keys SELECT * FROM api_keys_backup;
+DROP TABLE api_keys_backup;
@@ -0,0 +1,15 @@
+-- Only hashes of the secrets are stored from now on. SQLite cannot compute them, so the
+-- existing secrets are marked and hashed when the server starts.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TEMPORARY TABLE api_keys_backup AS
+    SELECT id, 'unhashed:' || secret AS secret_hash, name, daily_quota FROM api_keys;
+DROP TABLE api_keys;
+CREATE TABLE api_keys (
+    id BINARY(128) PRIMARY KEY NOT NULL,
+    secret_hash VARCHAR NOT NULL UNIQUE,
+    name VARCHAR NOT NULL,
+    daily_quota INTEGER NOT NULL
+);
+INSERT INTO api_keys SELECT * FROM api_keys_backup;
+DROP TABLE api_keys_backup;
@@ -0,0 +1,187 @@
+use std::collections::{BTreeSet, HashMap};
+
+use rocket::fairing::{self, Fairing};
+use rocket::Rocket;
+
+use crate::store::{Event, Id, Location, OccurrenceWithLocation};
+use crate::website::{event_url, format_date, SITE_URL};
+
+/// Used unless `announcement_template` is configured.
+const DEFAULT_TEMPLATE: &str =
+    "💃 {title} 🕺\n\n{teaser}\n\n📅 {dates}\n📍 {locations}\n\n👉 {link}";
+
+/// What can be filled into the template.
+const PLACEHOLDERS: &[&str] = &["{title}", "{teaser}", "{dates}", "{locations}", "{link}"];
+
+/// The text of posts announcing an event on social media, configured as
+/// `announcement_template`. The placeholders are replaced by the event's details.
+pub struct AnnouncementTemplate(String);
+
+impl AnnouncementTemplate {
+    /// Lists the occurrences that take place, one per line, and the locations they take
+    /// place at.
+    pub fn render(
+        &self,
+        event: &Event,
+        occurrences: &[OccurrenceWithLocation],
+        locations: &HashMap<Id<Location>, Location>,
+    ) -> String {
+        let taking_place: Vec<&OccurrenceWithLocation> = occurrences
+            .iter()
+            .filter(|occurrence| !occurrence.occurrence.cancelled)
+            .collect();
+
+        let dates = if taking_place.is_empty() {
+            "Neue Termine folgen bald.".to_string()
+        } else {
+            taking_place
+                .iter()
+                .map(|occurrence| {
+                    let start = occurrence.occurrence.start;
+                    format!(
+                        "{}, {} Uhr",
+                        format_date(&start.date()),
+                        start.format("%H:%M")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        // Sorted, so that the text does not change between requests.
+        let location_names: BTreeSet<String> = taking_place
+            .iter()
+            .map(|occurrence| match occurrence.location(locations) {
+                Some(location) => format!("{}, {}", location.name, location.address),
+                None => "Ort wird noch bekannt gegeben".to_string(),
+            })
+            .collect();
+        let location_names: Vec<String> = location_names.into_iter().collect();
+
+        self.0
+            .replace("{title}", &event.title)
+            .replace("{teaser}", &event.teaser)
+            .replace("{dates}", &dates)
+            .replace("{locations}", &location_names.join("\n"))
+            .replace("{link}", &format!("{}{}", SITE_URL, event_url(&event.slug)))
+    }
+}
+
+/// Finds the first part of the template that looks like a placeholder but is none.
+fn unknown_placeholder(template: &str) -> Option<&str> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = start + rest[start..].find('}')? + 1;
+        let candidate = &rest[start..end];
+        if !PLACEHOLDERS.contains(&candidate) {
+            return Some(candidate);
+        }
+        rest = &rest[end..];
+    }
+    None
+}
+
+pub struct AnnouncementFairing;
+
+impl Fairing for AnnouncementFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Announcement Template Fairing",
+            kind: fairing::Kind::Attach,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let template = rocket
+            .config()
+            .get_str("announcement_template")
+            .unwrap_or(DEFAULT_TEMPLATE)
+            .to_string();
+        if let Some(placeholder) = unknown_placeholder(&template) {
+            eprintln!(
+                "The announcement template contains '{}', but only {} can be filled in.",
+                placeholder,
+                PLACEHOLDERS.join(", ")
+            );
+            return Err(rocket);
+        }
+
+        Ok(rocket.manage(AnnouncementTemplate(template)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{Address, Occurrence};
+
+    fn occurrence_at(
+        start: &str,
+        location_id: &Id<Location>,
+        cancelled: bool,
+    ) -> OccurrenceWithLocation {
+        OccurrenceWithLocation {
+            occurrence: Occurrence {
+                start: start.parse().unwrap(),
+                duration: chrono::Duration::minutes(180),
+                doors_open: None,
+                open_end: false,
+                stream_url: None,
+                cancelled,
+                cancellation_reason: None,
+            },
+            location_id: Some(location_id.clone()),
+        }
+    }
+
+    #[test]
+    fn announcements_list_the_dates_that_take_place() {
+        let event = Event {
+            title: "Social Dance".to_string(),
+            teaser: "Zum Tanzen.".to_string(),
+            description: String::new(),
+            contact_name: None,
+            contact_email: None,
+            locked: false,
+            slug: "social-dance".to_string(),
+            published: true,
+            publish_at: None,
+        };
+        let location_id: Id<Location> = uuid::Uuid::new_v4().into();
+        let mut locations = HashMap::new();
+        locations.insert(
+            location_id.clone(),
+            Location {
+                name: "Chico Mendès".to_string(),
+                address: Address {
+                    street: "Pontstraße 74-76".to_string(),
+                    postal_code: "52062".to_string(),
+                    city: "Aachen".to_string(),
+                },
+                coordinates: None,
+                manual_coordinates: false,
+            },
+        );
+        let occurrences = [
+            occurrence_at("2019-06-12T20:00:00", &location_id, false),
+            occurrence_at("2019-06-19T20:00:00", &location_id, true),
+            occurrence_at("2019-06-26T20:30:00", &location_id, false),
+        ];
+
+        let template =
+            AnnouncementTemplate("{title}: {teaser}\n{dates}\n{locations}\n{link}".to_string());
+        assert_eq!(
+            template.render(&event, &occurrences, &locations),
+            "Social Dance: Zum Tanzen.\n\
+             Mi, 12.06., 20:00 Uhr\n\
+             Mi, 26.06., 20:30 Uhr\n\
+             Chico Mendès, Pontstraße 74-76, 52062 Aachen\n\
+             https://lindyhop-aachen.de/veranstaltungen/social-dance"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_found() {
+        assert_eq!(unknown_placeholder(DEFAULT_TEMPLATE), None);
+        assert_eq!(unknown_placeholder("{title} am {date}"), Some("{date}"));
+    }
+}
@@ -1,148 +1,4343 @@
 use std::collections::HashMap;
 
-use rocket::Rocket;
+use chrono::{Datelike, Local, NaiveDate, Weekday};
+use rocket::http::{RawStr, Status};
+use rocket::request::Request;
+use rocket::response::{self, status::Custom, Responder};
+use rocket::{Rocket, State};
 use rocket_contrib::json::Json;
 
+use crate::maintenance::Maintenance;
+use crate::recording;
+use crate::search;
 use crate::store::{
-    Id, Location, LocationWithOccurrences, OccurrenceFilter, OccurrenceFilterError, Overview, Store,
+    self, Actions, AuditEntry, Id, Location, LocationReport, LocationWithOccurrences,
+    OccurrenceFilter, OccurrenceFilterError, Overview, OverviewPage, ScheduleDiff, ScheduleGap,
+    SearchResult, Store, StoreError,
 };
+use crate::website::StatisticsCache;
+
+mod docs;
+mod keys;
 
 pub fn mount(rocket: Rocket, prefix: &'static str) -> Rocket {
-    rocket
+    let read_only = store::is_read_only(&rocket);
+
+    let rocket = rocket
         .mount(
             prefix,
-            routes![api_overview, api_locations_with_occurrences],
+            routes![
+                api_overview,
+                api_locations_with_occurrences,
+                api_search,
+                docs::docs
+            ],
+        )
+        .mount(
+            &format!("{}/locations", prefix),
+            locations::routes(read_only),
+        )
+        .mount(&format!("{}/events", prefix), events::routes(read_only))
+        .mount(
+            &format!("{}/submissions", prefix),
+            submissions::routes(read_only),
+        )
+        .mount(&format!("{}/comments", prefix), comments::routes(read_only))
+        .mount(&format!("{}/trash", prefix), trash::routes(read_only))
+        .mount(
+            &format!("{}/navigation", prefix),
+            navigation::routes(read_only),
+        )
+        .mount(&format!("{}/pages", prefix), pages::routes(read_only))
+        .mount(&format!("{}/faq", prefix), faq::routes(read_only))
+        .mount(
+            &format!("{}/redirects", prefix),
+            redirects::routes(read_only),
+        )
+        .mount(
+            &format!("{}/newsletters", prefix),
+            newsletters::routes(read_only),
+        )
+        .mount(
+            &format!("{}/subscribers", prefix),
+            subscribers::routes(read_only),
+        )
+        .mount(&format!("{}/links", prefix), links::routes(read_only))
+        .mount(&format!("{}/schedule", prefix), routes![api_schedule_diff])
+        .mount(
+            &format!("{}/reports", prefix),
+            routes![api_location_reports, api_schedule_gaps],
         )
-        .mount(&format!("{}/locations", prefix), locations::routes())
-        .mount(&format!("{}/events", prefix), events::routes())
+        .mount(
+            &format!("{}/admin", prefix),
+            routes![api_invalidate_caches, api_maintenance, api_set_maintenance],
+        )
+        .mount(&format!("{}/debug", prefix), recording::routes());
+
+    // A snapshot is never changed, so it has no audit log, and usage cannot be counted.
+    if read_only {
+        rocket
+    } else {
+        rocket
+            .mount(prefix, routes![api_audit_log])
+            .mount(&format!("{}/admin/keys", prefix), keys::routes())
+            .mount(&format!("{}/keys", prefix), keys::rejection_routes())
+            .attach(keys::ApiKeyFairing { prefix })
+    }
+}
+
+/// Why a filtered read failed: either the filter or the page could not be understood, or
+/// the data could not be loaded.
+#[derive(Debug)]
+enum FilteredReadError {
+    Filter(OccurrenceFilterError),
+    Page(String),
+    Store(StoreError),
+}
+
+impl From<OccurrenceFilterError> for FilteredReadError {
+    fn from(err: OccurrenceFilterError) -> Self {
+        FilteredReadError::Filter(err)
+    }
+}
+
+impl From<StoreError> for FilteredReadError {
+    fn from(err: StoreError) -> Self {
+        FilteredReadError::Store(err)
+    }
+}
+
+impl<'r> Responder<'r> for FilteredReadError {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        match self {
+            FilteredReadError::Filter(err) => err.respond_to(request),
+            FilteredReadError::Page(message) => {
+                Custom(Status::UnprocessableEntity, message).respond_to(request)
+            }
+            FilteredReadError::Store(err) => Custom::from(err).respond_to(request),
+        }
+    }
+}
+
+/// Rejects ids of locations that do not exist, so that a typo is not mistaken for an
+/// undecided location.
+fn reject_unknown_locations<'a>(
+    store: &Store,
+    location_ids: impl IntoIterator<Item = &'a Id<Location>>,
+) -> Result<(), Custom<String>> {
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+    match location_ids
+        .into_iter()
+        .find(|id| !locations.contains_key(id))
+    {
+        Some(id) => Err(Custom(
+            Status::UnprocessableEntity,
+            format!("There is no location with the id {}.", id),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The most events a page of the overview may hold.
+const MAX_PAGE_LIMIT: usize = 200;
+
+/// The whole overview, or a page of it if a limit is given.
+#[derive(Responder)]
+enum OverviewResponse {
+    Whole(Json<Overview>),
+    Page(Json<OverviewPage>),
 }
 
-#[get("/?<filter..>")]
+#[get("/?<limit>&<offset>&<filter..>")]
 fn api_overview(
     store: Store,
-    filter: OccurrenceFilter,
-) -> Result<Json<Overview>, OccurrenceFilterError> {
-    Ok(Json(store.read_all(&filter)))
+    limit: Option<Result<usize, &RawStr>>,
+    offset: Option<Result<usize, &RawStr>>,
+    filter: Result<OccurrenceFilter, OccurrenceFilterError>,
+) -> Result<OverviewResponse, FilteredReadError> {
+    let filter = filter?;
+    let limit = match limit {
+        None => return Ok(OverviewResponse::Whole(Json(store.read_all(&filter)?))),
+        Some(Ok(limit)) if limit > 0 && limit <= MAX_PAGE_LIMIT => limit,
+        Some(_) => {
+            return Err(FilteredReadError::Page(format!(
+                "The limit must be a number from 1 to {}.",
+                MAX_PAGE_LIMIT
+            )))
+        }
+    };
+    let offset = offset.unwrap_or(Ok(0)).map_err(|_| {
+        FilteredReadError::Page("The offset must be a number from 0 on.".to_string())
+    })?;
+    Ok(OverviewResponse::Page(Json(
+        store.read_page(&filter, offset, limit)?,
+    )))
 }
 
 #[get("/locations_with_occurrences?<filter..>")]
 fn api_locations_with_occurrences(
     store: Store,
-    filter: OccurrenceFilter,
-) -> Result<Json<HashMap<Id<Location>, LocationWithOccurrences>>, OccurrenceFilterError> {
-    Ok(Json(store.locations_with_occurrences(&filter)))
+    filter: Result<OccurrenceFilter, OccurrenceFilterError>,
+) -> Result<Json<HashMap<Id<Location>, LocationWithOccurrences>>, FilteredReadError> {
+    let filter = filter?;
+    Ok(Json(store.locations_with_occurrences(&filter)?))
 }
 
-mod locations {
-    use std::collections::HashMap;
-    use std::iter::FromIterator;
+#[get("/search?<q>")]
+fn api_search(store: Store, q: String) -> Result<Json<Vec<SearchResult>>, Custom<String>> {
+    Ok(Json(search::search(&store, &q)?))
+}
+
+#[get("/locations?<filter..>")]
+fn api_location_reports(
+    store: Store,
+    filter: Result<OccurrenceFilter, OccurrenceFilterError>,
+) -> Result<Json<HashMap<Id<Location>, LocationReport>>, FilteredReadError> {
+    let filter = filter?;
+    Ok(Json(store.location_reports(&filter)?))
+}
+
+#[get("/gaps")]
+fn api_schedule_gaps(store: Store) -> Result<Json<Vec<ScheduleGap>>, Custom<String>> {
+    Ok(Json(store.schedule_gaps(Local::now().naive_local())?))
+}
+
+/// How the schedule of an ISO week like 2019-W24 differs from the week before, by default
+/// of the current week.
+#[get("/diff?<week>")]
+fn api_schedule_diff(
+    store: Store,
+    week: Option<String>,
+) -> Result<Json<ScheduleDiff>, Custom<String>> {
+    let monday = match week {
+        Some(week) => parse_iso_week(&week).ok_or_else(|| {
+            Custom(
+                Status::UnprocessableEntity,
+                format!("'{}' is not a week like 2019-W24.", week),
+            )
+        })?,
+        None => {
+            let today = Local::today().naive_local();
+            today - chrono::Duration::days(today.weekday().num_days_from_monday().into())
+        }
+    };
+
+    Ok(Json(store.schedule_diff(monday)?))
+}
+
+/// The Monday of an ISO week like 2019-W24.
+fn parse_iso_week(week: &str) -> Option<NaiveDate> {
+    let mut parts = week.splitn(2, "-W");
+    let year = parts.next()?.parse().ok()?;
+    let week = parts.next()?.parse().ok()?;
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+}
+
+/// The changes made to locations, events, and recurrences, most recent first, in pages
+/// starting at 1.
+#[get("/audit?<page>")]
+fn api_audit_log(store: Store, page: Option<u32>) -> Result<Json<Vec<AuditEntry>>, Custom<String>> {
+    store
+        .audit_log(page.unwrap_or(1))
+        .map_err(Custom::from)
+        .map(Json)
+}
+
+/// Drops everything computed ahead of time, so that changes made directly to the database
+/// show up without restarting the server.
+#[post("/cache/invalidate")]
+fn api_invalidate_caches(statistics: State<StatisticsCache>) {
+    statistics.invalidate();
+}
+
+#[get("/maintenance")]
+fn api_maintenance(maintenance: State<Maintenance>) -> Json<bool> {
+    Json(maintenance.is_enabled())
+}
+
+/// Replaces the public pages with a notice while the data is migrated. The admin and the
+/// API keep working.
+#[put("/maintenance", data = "<enabled>")]
+fn api_set_maintenance(maintenance: State<Maintenance>, enabled: Json<bool>) -> Json<bool> {
+    maintenance.set_enabled(enabled.0);
+    Json(enabled.0)
+}
 
+mod locations {
+    use crate::geocoding::Geocoder;
     use crate::store::Actions;
-    use crate::store::{Id, Location, Store};
+    use crate::store::{Id, Location, Store, StoreResult};
+    use std::collections::HashMap;
 
-    use rocket::Route;
+    use rocket::http::Status;
+    use rocket::response::status::Custom;
+    use rocket::{Route, State};
     use rocket_contrib::json::Json;
 
-    type Result<T> = std::result::Result<T, String>;
+    type Result<T> = std::result::Result<T, Custom<String>>;
 
     #[get("/")]
-    fn all(store: Store) -> Json<HashMap<Id<Location>, Location>> {
-        Json(HashMap::from_iter(store.all()))
+    fn all(store: Store) -> Result<Json<HashMap<Id<Location>, Location>>> {
+        Ok(Json(store.all()?))
     }
 
+    /// Unless coordinates are given by hand, they are looked up from the address.
     #[post("/", data = "<obj>")]
-    fn create(store: Store, obj: Json<Location>) -> Result<Json<Id<Location>>> {
-        store.create(obj.0).map_err(|err| err.to_string()).map(Json)
+    fn create(
+        store: Store,
+        geocoder: State<Geocoder>,
+        obj: Json<Location>,
+    ) -> std::result::Result<Json<Id<Location>>, Custom<String>> {
+        reject_invalid_location(&obj)?;
+
+        let mut location = obj.0;
+        let locate = !location.manual_coordinates;
+        if locate {
+            location.coordinates = None;
+        }
+        let address = location.address.clone();
+        let id = store.create(location).map_err(Custom::from)?;
+        if locate {
+            geocoder.locate_later(store, id.clone(), address);
+        }
+
+        Ok(Json(id))
     }
 
     #[get("/<id>")]
     fn read(store: Store, id: Id<Location>) -> Result<Json<Location>> {
-        store.read(id).map_err(|err| err.to_string()).map(Json)
+        store.read(id).map_err(Custom::from).map(Json)
     }
 
+    /// Coordinates that were looked up are kept as long as the address stays the same.
     #[put("/<id>", data = "<obj>")]
-    pub fn update(store: Store, id: Id<Location>, obj: Json<Location>) -> Result<Json<Location>> {
-        store
-            .update(id, obj.0)
-            .map_err(|err| err.to_string())
-            .map(Json)
+    pub fn update(
+        store: Store,
+        geocoder: State<Geocoder>,
+        id: Id<Location>,
+        obj: Json<Location>,
+    ) -> std::result::Result<Json<Location>, Custom<String>> {
+        reject_invalid_location(&obj)?;
+
+        let mut location = obj.0;
+        let mut locate = false;
+        if !location.manual_coordinates {
+            let previous: StoreResult<Location> = store.read(id.clone());
+            location.coordinates = match previous {
+                Ok(ref previous)
+                    if !previous.manual_coordinates && previous.address == location.address =>
+                {
+                    previous.coordinates
+                }
+                _ => {
+                    locate = true;
+                    None
+                }
+            };
+        }
+        let address = location.address.clone();
+        let previous = store.update(id.clone(), location).map_err(Custom::from)?;
+        if locate {
+            geocoder.locate_later(store, id, address);
+        }
+
+        Ok(Json(previous))
+    }
+
+    fn reject_invalid_location(location: &Location) -> std::result::Result<(), Custom<String>> {
+        location
+            .validate()
+            .map_err(|errors| Custom(Status::UnprocessableEntity, errors.to_string()))
     }
 
     #[delete("/<id>")]
     fn delete(store: Store, id: Id<Location>) -> Result<Json<Location>> {
-        store
-            .delete(id)
-            .map_err(|err| format!("{:?}", err))
-            .map(Json)
+        store.delete(id).map_err(Custom::from).map(Json)
     }
 
-    pub fn routes() -> Vec<Route> {
-        routes![all, create, read, update, delete]
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        if read_only {
+            routes![all, read]
+        } else {
+            routes![all, create, read, update, delete]
+        }
     }
 }
 
-mod events {
+mod navigation {
     use std::collections::HashMap;
-    use std::iter::FromIterator;
 
-    use crate::store::{
-        Event, EventWithOccurrences, Id, OccurrenceFilter, OccurrenceFilterError, Store,
-    };
+    use crate::store::{Actions, Id, NavItem, Store};
 
+    use rocket::response::status::Custom;
     use rocket::Route;
     use rocket_contrib::json::Json;
 
-    #[get("/?<filter..>")]
-    fn all(
-        store: Store,
-        filter: OccurrenceFilter,
-    ) -> Result<Json<HashMap<Id<Event>, EventWithOccurrences>>, OccurrenceFilterError> {
-        Ok(Json(HashMap::from_iter(
-            store.all_events_with_occurrences(&filter),
-        )))
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    /// Includes the hidden items.
+    #[get("/")]
+    fn all(store: Store) -> Result<Json<HashMap<Id<NavItem>, NavItem>>> {
+        Ok(Json(store.all()?))
     }
 
     #[post("/", data = "<obj>")]
-    fn create(store: Store, obj: Json<EventWithOccurrences>) -> Result<Json<Id<Event>>, String> {
-        store
-            .create_event_with_occurrences(obj.0)
-            .map_err(|err| err.to_string())
-            .map(Json)
+    fn create(store: Store, obj: Json<NavItem>) -> Result<Json<Id<NavItem>>> {
+        store.create(obj.0).map_err(Custom::from).map(Json)
     }
 
-    #[get("/<id>?<filter..>")]
-    fn read(
-        store: Store,
-        id: Id<Event>,
-        filter: OccurrenceFilter,
-    ) -> Result<Json<EventWithOccurrences>, OccurrenceFilterError> {
-        Ok(Json(
-            store.read_event_with_occurrences(id, &filter).unwrap(),
-        ))
+    #[get("/<id>")]
+    fn read(store: Store, id: Id<NavItem>) -> Result<Json<NavItem>> {
+        store.read(id).map_err(Custom::from).map(Json)
     }
 
-    #[put("/<id>?<filter..>", data = "<obj>")]
+    #[put("/<id>", data = "<obj>")]
+    fn update(store: Store, id: Id<NavItem>, obj: Json<NavItem>) -> Result<Json<NavItem>> {
+        store.update(id, obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[delete("/<id>")]
+    fn delete(store: Store, id: Id<NavItem>) -> Result<Json<NavItem>> {
+        store.delete(id).map_err(Custom::from).map(Json)
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        if read_only {
+            routes![all, read]
+        } else {
+            routes![all, create, read, update, delete]
+        }
+    }
+}
+
+mod pages {
+    use std::collections::HashMap;
+
+    use crate::store::{Actions, Id, Page, Store};
+    use crate::text;
+    use crate::website::RESERVED_SLUGS;
+
+    use rocket::http::Status;
+    use rocket::response::status::Custom;
+    use rocket::Route;
+    use rocket_contrib::json::Json;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    /// Includes the drafts.
+    #[get("/")]
+    fn all(store: Store) -> Result<Json<HashMap<Id<Page>, Page>>> {
+        Ok(Json(store.all()?))
+    }
+
+    #[post("/", data = "<obj>")]
+    fn create(store: Store, obj: Json<Page>) -> Result<Json<Id<Page>>> {
+        reject_invalid_page(&store, None, &obj)?;
+
+        store.create(obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[get("/<id>")]
+    fn read(store: Store, id: Id<Page>) -> Result<Json<Page>> {
+        store.read(id).map_err(Custom::from).map(Json)
+    }
+
+    #[put("/<id>", data = "<obj>")]
+    fn update(store: Store, id: Id<Page>, obj: Json<Page>) -> Result<Json<Page>> {
+        reject_invalid_page(&store, Some(&id), &obj)?;
+
+        store.update(id, obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[delete("/<id>")]
+    fn delete(store: Store, id: Id<Page>) -> Result<Json<Page>> {
+        store.delete(id).map_err(Custom::from).map(Json)
+    }
+
+    /// Pages are served at their slug, so it has to fit into a URL and must not be taken by
+    /// another page or route.
+    fn reject_invalid_page(store: &Store, id: Option<&Id<Page>>, page: &Page) -> Result<()> {
+        if page.slug.is_empty() || text::slugify(&page.slug) != page.slug {
+            return Err(Custom(
+                Status::UnprocessableEntity,
+                format!(
+                    "The slug '{}' may only contain lowercase letters, digits, and hyphens.",
+                    page.slug
+                ),
+            ));
+        }
+        if RESERVED_SLUGS.contains(&page.slug.as_str()) {
+            return Err(Custom(
+                Status::UnprocessableEntity,
+                format!("The slug '{}' is used by the website itself.", page.slug),
+            ));
+        }
+
+        let existing = store.page_by_slug(&page.slug).map_err(Custom::from)?;
+        match existing {
+            Some((ref existing_id, _)) if Some(existing_id) != id => Err(Custom(
+                Status::Conflict,
+                format!("The slug '{}' is used by another page.", page.slug),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        if read_only {
+            routes![all, read]
+        } else {
+            routes![all, create, read, update, delete]
+        }
+    }
+}
+
+mod faq {
+    use std::collections::HashMap;
+
+    use crate::store::{Actions, FaqEntry, Id, Store};
+
+    use rocket::response::status::Custom;
+    use rocket::Route;
+    use rocket_contrib::json::Json;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    #[get("/")]
+    fn all(store: Store) -> Result<Json<HashMap<Id<FaqEntry>, FaqEntry>>> {
+        Ok(Json(store.all()?))
+    }
+
+    #[post("/", data = "<obj>")]
+    fn create(store: Store, obj: Json<FaqEntry>) -> Result<Json<Id<FaqEntry>>> {
+        store.create(obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[get("/<id>")]
+    fn read(store: Store, id: Id<FaqEntry>) -> Result<Json<FaqEntry>> {
+        store.read(id).map_err(Custom::from).map(Json)
+    }
+
+    #[put("/<id>", data = "<obj>")]
+    fn update(store: Store, id: Id<FaqEntry>, obj: Json<FaqEntry>) -> Result<Json<FaqEntry>> {
+        store.update(id, obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[delete("/<id>")]
+    fn delete(store: Store, id: Id<FaqEntry>) -> Result<Json<FaqEntry>> {
+        store.delete(id).map_err(Custom::from).map(Json)
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        if read_only {
+            routes![all, read]
+        } else {
+            routes![all, create, read, update, delete]
+        }
+    }
+}
+
+mod redirects {
+    use std::collections::HashMap;
+
+    use crate::store::{Actions, Id, Store, UrlRedirect};
+
+    use rocket::http::Status;
+    use rocket::response::status::Custom;
+    use rocket::Route;
+    use rocket_contrib::json::Json;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    /// The status codes that browsers follow.
+    const STATUSES: &[u16] = &[301, 302, 307, 308];
+
+    #[get("/")]
+    fn all(store: Store) -> Result<Json<HashMap<Id<UrlRedirect>, UrlRedirect>>> {
+        Ok(Json(store.all()?))
+    }
+
+    #[post("/", data = "<obj>")]
+    fn create(store: Store, obj: Json<UrlRedirect>) -> Result<Json<Id<UrlRedirect>>> {
+        reject_invalid_redirect(&store, None, &obj)?;
+
+        store.create(obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[get("/<id>")]
+    fn read(store: Store, id: Id<UrlRedirect>) -> Result<Json<UrlRedirect>> {
+        store.read(id).map_err(Custom::from).map(Json)
+    }
+
+    #[put("/<id>", data = "<obj>")]
     fn update(
         store: Store,
-        id: Id<Event>,
-        obj: Json<EventWithOccurrences>,
-        filter: OccurrenceFilter,
-    ) -> Result<Json<EventWithOccurrences>, OccurrenceFilterError> {
-        Ok(Json(
-            store
-                .update_event_with_occurrences(id, obj.0, &filter)
-                .unwrap(),
-        ))
+        id: Id<UrlRedirect>,
+        obj: Json<UrlRedirect>,
+    ) -> Result<Json<UrlRedirect>> {
+        reject_invalid_redirect(&store, Some(&id), &obj)?;
+
+        store.update(id, obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[delete("/<id>")]
+    fn delete(store: Store, id: Id<UrlRedirect>) -> Result<Json<UrlRedirect>> {
+        store.delete(id).map_err(Custom::from).map(Json)
+    }
+
+    fn reject_invalid_redirect(
+        store: &Store,
+        id: Option<&Id<UrlRedirect>>,
+        redirect: &UrlRedirect,
+    ) -> Result<()> {
+        let unprocessable = |message: String| Err(Custom(Status::UnprocessableEntity, message));
+        // The home page is always served, and queries are not part of the path.
+        if !redirect.source.starts_with('/')
+            || redirect.source.trim_end_matches('/').is_empty()
+            || redirect.source.contains('?')
+        {
+            return unprocessable(format!(
+                "The source '{}' must be a path like /2018/05/social-dance/.",
+                redirect.source
+            ));
+        }
+        let target_is_url = ["/", "http://", "https://"]
+            .iter()
+            .any(|prefix| redirect.target.starts_with(prefix));
+        if !target_is_url {
+            return unprocessable(format!(
+                "The target '{}' must be a path or an http(s) URL.",
+                redirect.target
+            ));
+        }
+        if !STATUSES.contains(&redirect.status) {
+            return unprocessable(format!(
+                "The status {} must be one of 301, 302, 307, and 308.",
+                redirect.status
+            ));
+        }
+
+        // Sources differing only in a trailing slash would be the same.
+        match store.redirect_for(&redirect.source)? {
+            Some((ref existing_id, _)) if Some(existing_id) != id => Err(Custom(
+                Status::Conflict,
+                format!("The source '{}' is redirected already.", redirect.source),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        if read_only {
+            routes![all, read]
+        } else {
+            routes![all, create, read, update, delete]
+        }
+    }
+}
+
+mod newsletters {
+    use std::collections::HashMap;
+    use std::hash::BuildHasher;
+
+    use chrono::{Duration, Local, NaiveDate};
+
+    use crate::mail::{self, Mailer};
+    use crate::store::{
+        Actions, Delivery, Id, Location, Newsletter, OccurrenceFilter, OccurrenceWithEvent,
+        ScheduleHorizon, Store,
+    };
+    use crate::website::format_date;
+
+    use rocket::http::Status;
+    use rocket::response::status::Custom;
+    use rocket::{Route, State};
+    use rocket_contrib::json::Json;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    #[get("/")]
+    fn all(store: Store) -> Result<Json<HashMap<Id<Newsletter>, Newsletter>>> {
+        Ok(Json(store.all()?))
+    }
+
+    #[post("/", data = "<obj>")]
+    fn create(store: Store, obj: Json<Newsletter>) -> Result<Json<Id<Newsletter>>> {
+        store.create(obj.0).map_err(Custom::from).map(Json)
+    }
+
+    #[get("/<id>")]
+    fn read(store: Store, id: Id<Newsletter>) -> Result<Json<Newsletter>> {
+        store.read(id).map_err(Custom::from).map(Json)
+    }
+
+    #[put("/<id>", data = "<obj>")]
+    fn update(store: Store, id: Id<Newsletter>, obj: Json<Newsletter>) -> Result<Json<Newsletter>> {
+        store.update(id, obj.0).map_err(Custom::from).map(Json)
     }
 
     #[delete("/<id>")]
-    fn delete(store: Store, id: Id<Event>) -> Result<Json<EventWithOccurrences>, String> {
+    fn delete(store: Store, id: Id<Newsletter>) -> Result<Json<Newsletter>> {
+        store.delete(id).map_err(Custom::from).map(Json)
+    }
+
+    const DEFAULT_DRAFT_WEEKS: u32 = 4;
+
+    /// A draft listing the occurrences of the next weeks, to be stored once an
+    /// introduction is added. It covers at most the weeks within the schedule horizon, since
+    /// the schedule is only public that far ahead.
+    #[get("/generate?<weeks>")]
+    fn generate(
+        store: Store,
+        horizon: State<ScheduleHorizon>,
+        weeks: Option<u32>,
+    ) -> Result<Json<Newsletter>> {
+        let max_weeks = horizon.0.num_weeks().max(1) as u32;
+        let weeks = weeks.unwrap_or_else(|| DEFAULT_DRAFT_WEEKS.min(max_weeks));
+        if weeks == 0 || weeks > max_weeks {
+            return Err(Custom(
+                Status::UnprocessableEntity,
+                format!("The weeks must be between 1 and {}.", max_weeks),
+            ));
+        }
+
+        let now = Local::now().naive_local();
+        let end = now + Duration::weeks(weeks.into());
+        let filter = OccurrenceFilter {
+            after: Some(now),
+            before: Some(end),
+            ..OccurrenceFilter::default()
+        };
+        let locations: HashMap<Id<Location>, Location> = store.all()?;
+        let content = draft_content(store.occurrences_by_date(&filter)?.into_iter(), &locations);
+
+        Ok(Json(Newsletter {
+            title: format!(
+                "Unsere Termine vom {} bis {}",
+                now.format("%d.%m."),
+                end.format("%d.%m.")
+            ),
+            date: now.date(),
+            content,
+        }))
+    }
+
+    /// A paragraph per date, listing the occurrences that take place with their time,
+    /// location, and teaser. Cancelled occurrences are left out.
+    fn draft_content<S: BuildHasher>(
+        occurrences_by_date: impl Iterator<Item = (NaiveDate, Vec<OccurrenceWithEvent>)>,
+        locations: &HashMap<Id<Location>, Location, S>,
+    ) -> String {
+        let mut paragraphs = Vec::new();
+        for (date, entries) in occurrences_by_date {
+            let mut paragraph = format_date(&date);
+            for entry in entries
+                .iter()
+                .filter(|entry| !entry.occurrence.occurrence.cancelled)
+            {
+                paragraph.push_str(&format!(
+                    "\n{} {}",
+                    entry.occurrence.occurrence.start.format("%H:%M"),
+                    entry.event.title
+                ));
+                if let Some(location) = entry.occurrence.location(locations) {
+                    paragraph.push_str(&format!(", {}", location.name));
+                }
+                paragraph.push_str(&format!("\n{}", entry.event.teaser));
+            }
+            if paragraph.contains('\n') {
+                paragraphs.push(paragraph);
+            }
+        }
+
+        if paragraphs.is_empty() {
+            "In den nächsten Wochen stehen keine Termine an.".to_string()
+        } else {
+            paragraphs.join("\n\n")
+        }
+    }
+
+    /// Sends the newsletter to every confirmed subscriber it has not reached yet, and
+    /// returns whether it reached them.
+    #[post("/<id>/send")]
+    fn send(
+        store: Store,
+        mailer: State<Mailer>,
+        id: Id<Newsletter>,
+    ) -> Result<Json<Vec<Delivery>>> {
+        let newsletter = store.read(id.clone()).map_err(Custom::from)?;
+        let mut connection = mailer
+            .connect()
+            .map_err(|err| Custom(Status::ServiceUnavailable, err.to_string()))?;
+        for recipient in store.pending_recipients(id.clone())? {
+            let unsubscribe = mail::unsubscribe_link(&recipient.unsubscribe_token);
+            let html = mail::render_newsletter(&newsletter, &unsubscribe);
+            let error = connection
+                .send(
+                    &recipient.email,
+                    &newsletter.title,
+                    &html,
+                    Some(&unsubscribe),
+                )
+                .err()
+                .map(|err| err.to_string());
+            store.record_delivery(id.clone(), recipient.id, error)?;
+        }
+        connection.quit();
+
+        deliveries(store, id)
+    }
+
+    #[get("/<id>/deliveries")]
+    fn deliveries(store: Store, id: Id<Newsletter>) -> Result<Json<Vec<Delivery>>> {
+        store.deliveries(id).map_err(Custom::from).map(Json)
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        // Newsletters are not part of the snapshot, since they are not public.
+        if read_only {
+            routes![]
+        } else {
+            routes![all, create, read, update, delete, generate, send, deliveries]
+        }
+    }
+}
+
+mod subscribers {
+    use std::collections::HashMap;
+    use std::io::Read;
+
+    use crate::store::{Id, Store, Subscriber, SubscriberImport};
+
+    use rocket::http::Status;
+    use rocket::response::status::Custom;
+    use rocket::{Data, Route};
+    use rocket_contrib::json::Json;
+
+    /// Enough for the old mailing list many times over.
+    const MAX_IMPORT_BYTES: u64 = 1024 * 1024;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    #[get("/")]
+    fn all(store: Store) -> Result<Json<HashMap<Id<Subscriber>, Subscriber>>> {
+        store.subscribers().map_err(Custom::from).map(Json)
+    }
+
+    /// Imports subscribers from a CSV file, as described by `Subscriber::parse_csv`.
+    #[post("/import", data = "<csv>")]
+    fn import(store: Store, csv: Data) -> Result<Json<SubscriberImport>> {
+        let mut text = String::new();
+        csv.open()
+            .take(MAX_IMPORT_BYTES)
+            .read_to_string(&mut text)
+            .map_err(|err| Custom(Status::BadRequest, err.to_string()))?;
+        let subscribers =
+            Subscriber::parse_csv(&text).map_err(|err| Custom(Status::UnprocessableEntity, err))?;
+
         store
-            .delete_event_with_occurrences(id)
-            .map_err(|err| format!("{:?}", err))
+            .import_subscribers(subscribers)
+            .map_err(Custom::from)
             .map(Json)
     }
-    pub fn routes() -> Vec<Route> {
-        routes![all, create, read, update, delete]
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        // Subscribers are not part of the snapshot, since they are not public.
+        if read_only {
+            routes![]
+        } else {
+            routes![all, import]
+        }
+    }
+}
+
+mod events {
+    use std::collections::HashMap;
+
+    use super::reject_unknown_locations;
+    use crate::announcement::AnnouncementTemplate;
+    use crate::calendar::Calendar;
+    use crate::features::{self, Enabled};
+    use crate::media::{self, ImageType, MediaDir};
+    use crate::store::{
+        Actions, BookingConflict, DisplayCutoff, Event, EventImage, EventWithOccurrences, Id,
+        Location, OccurrenceFilter, OccurrenceSuggestion, Recurrence, RelatedEvent,
+        ScheduleHorizon, Store,
+    };
+
+    use rocket::http::{ContentType, Header, Status};
+    use rocket::response::content::Content;
+    use rocket::response::status::Custom;
+    use rocket::response::{self, Responder};
+    use rocket::{Data, Request, Route, State};
+    use rocket_contrib::json::Json;
+    use uuid::Uuid;
+
+    #[get("/?<filter..>")]
+    fn all(
+        store: Store,
+        filter: OccurrenceFilter,
+    ) -> Result<Json<HashMap<Id<Event>, EventWithOccurrences>>, Custom<String>> {
+        Ok(Json(store.all_events_with_occurrences(&filter)?))
+    }
+
+    /// Saving is not refused when an event is booked at the same time and location as
+    /// another, since that can be intended, e. g. for a workshop within a party. Instead, the
+    /// conflicts are added as JSON in the `X-Booking-Conflicts` header, so that the response
+    /// stays the same for clients that do not look for them.
+    struct WithConflicts<T>(T, Vec<BookingConflict>);
+
+    impl<'r, T: Responder<'r>> Responder<'r> for WithConflicts<T> {
+        fn respond_to(self, request: &Request) -> response::Result<'r> {
+            let mut response = self.0.respond_to(request)?;
+            if !self.1.is_empty() {
+                let conflicts = serde_json::to_string(&self.1).unwrap();
+                response.set_header(Header::new("X-Booking-Conflicts", ascii_json(&conflicts)));
+            }
+            Ok(response)
+        }
+    }
+
+    /// Headers may only contain ASCII, so other characters are escaped like `\u00fc`, which
+    /// JSON understands.
+    fn ascii_json(json: &str) -> String {
+        let mut ascii = String::new();
+        for c in json.chars() {
+            if c.is_ascii() {
+                ascii.push(c);
+            } else {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    ascii.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+        }
+        ascii
+    }
+
+    #[post("/", data = "<obj>")]
+    fn create(
+        store: Store,
+        obj: Json<EventWithOccurrences>,
+    ) -> Result<WithConflicts<Json<Id<Event>>>, Custom<String>> {
+        reject_unknown_locations(&store, occurrence_locations(&obj))?;
+
+        let occurrences = obj.occurrences.clone();
+        let id = store
+            .create_event_with_occurrences(obj.0)
+            .map_err(Custom::from)?;
+        let conflicts = store.booking_conflicts(&id, &occurrences)?;
+        Ok(WithConflicts(Json(id), conflicts))
+    }
+
+    fn occurrence_locations(
+        event_with_occurrences: &EventWithOccurrences,
+    ) -> impl Iterator<Item = &Id<Location>> {
+        event_with_occurrences
+            .occurrences
+            .iter()
+            .filter_map(|occurrence| occurrence.location_id.as_ref())
+    }
+
+    #[get("/<id>?<filter..>")]
+    fn read(
+        store: Store,
+        id: Id<Event>,
+        filter: OccurrenceFilter,
+    ) -> Result<Json<EventWithOccurrences>, Custom<String>> {
+        store
+            .read_event_with_occurrences(id, &filter)
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    #[get("/<id>/related")]
+    fn related(
+        store: Store,
+        cutoff: State<DisplayCutoff>,
+        horizon: State<ScheduleHorizon>,
+        id: Id<Event>,
+    ) -> Result<Json<Vec<RelatedEvent>>, Custom<String>> {
+        store
+            .related_events(id, &OccurrenceFilter::upcoming(&cutoff, &horizon))
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    /// Defaults for the next occurrence the admin adds, including those of drafts.
+    #[get("/<id>/suggestion")]
+    fn suggestion(
+        store: Store,
+        id: Id<Event>,
+    ) -> Result<Json<OccurrenceSuggestion>, Custom<String>> {
+        let occurrences = store
+            .read_event_with_occurrences(
+                id,
+                &OccurrenceFilter {
+                    include_drafts: true,
+                    ..OccurrenceFilter::default()
+                },
+            )?
+            .occurrences;
+
+        Ok(Json(OccurrenceSuggestion::from_history(
+            &occurrences,
+            chrono::Local::now().naive_local(),
+        )))
+    }
+
+    /// The event's upcoming occurrences as an iCalendar feed, for subscribing to a single event.
+    #[get("/<id>/calendar.ics")]
+    fn calendar(
+        _enabled: Enabled<features::Calendar>,
+        store: Store,
+        cutoff: State<DisplayCutoff>,
+        horizon: State<ScheduleHorizon>,
+        id: Id<Event>,
+    ) -> Result<Content<String>, Custom<String>> {
+        let event_with_occurrences = store
+            .read_event_with_occurrences(id.clone(), &OccurrenceFilter::upcoming(&cutoff, &horizon))
+            .map_err(Custom::from)?;
+        let locations: HashMap<Id<Location>, Location> = store.all()?;
+
+        let event = &event_with_occurrences.event;
+        let mut calendar = Calendar::new(&event.title);
+        for occurrence in &event_with_occurrences.occurrences {
+            calendar.add_occurrence(&id, event, occurrence, &locations);
+        }
+        Ok(Content(ContentType::Calendar, calendar.finish()))
+    }
+
+    /// A text announcing the event's upcoming occurrences, ready to be posted on social media.
+    #[get("/<id>/announcement")]
+    fn announcement(
+        store: Store,
+        template: State<AnnouncementTemplate>,
+        cutoff: State<DisplayCutoff>,
+        horizon: State<ScheduleHorizon>,
+        id: Id<Event>,
+    ) -> Result<Content<String>, Custom<String>> {
+        let event_with_occurrences = store
+            .read_event_with_occurrences(id, &OccurrenceFilter::upcoming(&cutoff, &horizon))
+            .map_err(Custom::from)?;
+        let locations: HashMap<Id<Location>, Location> = store.all()?;
+
+        Ok(Content(
+            ContentType::Plain,
+            template.render(
+                &event_with_occurrences.event,
+                &event_with_occurrences.occurrences,
+                &locations,
+            ),
+        ))
+    }
+
+    #[get("/<id>/images")]
+    fn images(
+        store: Store,
+        id: Id<Event>,
+    ) -> Result<Json<HashMap<Id<EventImage>, EventImage>>, Custom<String>> {
+        store.event_images(id).map_err(Custom::from).map(Json)
+    }
+
+    /// Stores every file of the `multipart/form-data` body as an image of the event. Unless
+    /// all of them are images, none is stored.
+    #[post("/<id>/images", data = "<data>")]
+    fn upload_images(
+        store: Store,
+        media_dir: State<MediaDir>,
+        id: Id<Event>,
+        content_type: &ContentType,
+        data: Data,
+    ) -> Result<Json<Vec<Id<EventImage>>>, Custom<String>> {
+        let uploads = media::read_uploads(content_type, data)?;
+        if uploads.is_empty() {
+            return Err(Custom(
+                Status::UnprocessableEntity,
+                "The upload does not contain any files.".to_string(),
+            ));
+        }
+        let mut images = Vec::new();
+        for upload in uploads {
+            match ImageType::detect(&upload.content) {
+                Some(image_type) => images.push((upload, image_type)),
+                None => {
+                    return Err(Custom(
+                        Status::UnsupportedMediaType,
+                        format!(
+                            "'{}' is not a PNG, JPEG, GIF, or WebP image.",
+                            upload.file_name
+                        ),
+                    ))
+                }
+            }
+        }
+
+        let uploaded_at = chrono::Local::now().naive_local();
+        let mut ids = Vec::new();
+        for (upload, image_type) in images {
+            let file_name = format!("{}.{}", Uuid::new_v4(), image_type.extension());
+            let image_id = store
+                .create_event_image(
+                    id.clone(),
+                    EventImage {
+                        file_name: file_name.clone(),
+                        original_name: upload.file_name,
+                        content_type: image_type.content_type().to_string(),
+                        size: upload.content.len() as u64,
+                        uploaded_at,
+                    },
+                )
+                .map_err(Custom::from)?;
+            if let Err(err) = media_dir.save_image(&file_name, &upload.content) {
+                let _ = store.delete_event_image(id.clone(), image_id);
+                return Err(Custom(Status::InternalServerError, err.to_string()));
+            }
+            ids.push(image_id);
+        }
+
+        Ok(Json(ids))
+    }
+
+    #[delete("/<id>/images/<image_id>")]
+    fn delete_image(
+        store: Store,
+        media_dir: State<MediaDir>,
+        id: Id<Event>,
+        image_id: Id<EventImage>,
+    ) -> Result<Json<EventImage>, Custom<String>> {
+        let image = store
+            .delete_event_image(id, image_id)
+            .map_err(Custom::from)?;
+        media_dir
+            .remove_image(&image.file_name)
+            .map_err(|err| Custom(Status::InternalServerError, err.to_string()))?;
+
+        Ok(Json(image))
+    }
+
+    #[put("/<id>?<filter..>", data = "<obj>")]
+    fn update(
+        store: Store,
+        id: Id<Event>,
+        obj: Json<EventWithOccurrences>,
+        filter: OccurrenceFilter,
+    ) -> Result<WithConflicts<Json<EventWithOccurrences>>, Custom<String>> {
+        reject_unknown_locations(&store, occurrence_locations(&obj))?;
+
+        let new_item = obj.0;
+        let conflicts = store.booking_conflicts(&id, &new_item.occurrences)?;
+        Ok(WithConflicts(
+            Json(store.update_event_with_occurrences(id, new_item, &filter)?),
+            conflicts,
+        ))
+    }
+
+    #[delete("/<id>")]
+    fn delete(store: Store, id: Id<Event>) -> Result<Json<EventWithOccurrences>, Custom<String>> {
+        store
+            .delete_event_with_occurrences(id)
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    fn reject_invalid_recurrence(
+        store: &Store,
+        recurrence: &Recurrence,
+    ) -> Result<(), Custom<String>> {
+        recurrence
+            .validate()
+            .map_err(|errors| Custom(Status::UnprocessableEntity, errors.to_string()))?;
+
+        let exception_locations = recurrence
+            .exceptions
+            .iter()
+            .filter_map(|exception| exception.location_id.as_ref());
+        reject_unknown_locations(
+            store,
+            recurrence
+                .first
+                .location_id
+                .iter()
+                .chain(exception_locations),
+        )
+    }
+
+    #[get("/<id>/recurrences")]
+    fn recurrences(
+        store: Store,
+        id: Id<Event>,
+    ) -> Result<Json<HashMap<Id<Recurrence>, Recurrence>>, Custom<String>> {
+        store.recurrences(id).map_err(Custom::from).map(Json)
+    }
+
+    #[post("/<id>/recurrences", data = "<obj>")]
+    fn create_recurrence(
+        store: Store,
+        id: Id<Event>,
+        obj: Json<Recurrence>,
+    ) -> Result<Json<Id<Recurrence>>, Custom<String>> {
+        reject_invalid_recurrence(&store, &obj)?;
+
+        store
+            .create_recurrence(id, obj.0)
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    #[put("/<id>/recurrences/<recurrence_id>", data = "<obj>")]
+    fn update_recurrence(
+        store: Store,
+        id: Id<Event>,
+        recurrence_id: Id<Recurrence>,
+        obj: Json<Recurrence>,
+    ) -> Result<Json<Recurrence>, Custom<String>> {
+        reject_invalid_recurrence(&store, &obj)?;
+
+        store
+            .update_recurrence(id, recurrence_id, obj.0)
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    #[delete("/<id>/recurrences/<recurrence_id>")]
+    fn delete_recurrence(
+        store: Store,
+        id: Id<Event>,
+        recurrence_id: Id<Recurrence>,
+    ) -> Result<Json<Recurrence>, Custom<String>> {
+        store
+            .delete_recurrence(id, recurrence_id)
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    #[put("/<id>/locked", data = "<locked>")]
+    fn set_locked(
+        store: Store,
+        id: Id<Event>,
+        locked: Json<bool>,
+    ) -> Result<Json<bool>, Custom<String>> {
+        store
+            .set_event_locked(id, locked.0)
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        if read_only {
+            routes![all, read, related, calendar, announcement]
+        } else {
+            routes![
+                all,
+                create,
+                read,
+                related,
+                suggestion,
+                calendar,
+                announcement,
+                images,
+                upload_images,
+                delete_image,
+                update,
+                delete,
+                set_locked,
+                recurrences,
+                create_recurrence,
+                update_recurrence,
+                delete_recurrence
+            ]
+        }
+    }
+}
+
+mod links {
+    use crate::links::LinkChecker;
+    use crate::store::{LinkReport, Store};
+
+    use rocket::http::Status;
+    use rocket::response::status::Custom;
+    use rocket::{Route, State};
+    use rocket_contrib::json::Json;
+
+    /// The report of the latest check, which runs when the server starts and then regularly.
+    #[get("/")]
+    fn report(checker: State<LinkChecker>) -> Result<Json<LinkReport>, Custom<String>> {
+        checker.report().map(Json).ok_or_else(|| {
+            Custom(
+                Status::NotFound,
+                "The links have not been checked yet.".to_string(),
+            )
+        })
+    }
+
+    /// Checks the links now, e. g. to see whether fixing them worked.
+    #[post("/check")]
+    fn check(
+        store: Store,
+        checker: State<LinkChecker>,
+    ) -> Result<Json<LinkReport>, Custom<String>> {
+        Ok(Json(checker.check_all(&store)?))
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        // There is nothing to fix while serving a read-only snapshot.
+        if read_only {
+            routes![]
+        } else {
+            routes![report, check]
+        }
+    }
+}
+
+mod submissions {
+    use std::collections::HashMap;
+
+    use super::reject_unknown_locations;
+    use crate::features::{Enabled, Submissions};
+    use crate::spam::{Candidate, ClientIp, Feature, SpamFilter};
+    use crate::store::{Event, Id, Store, Submission};
+
+    use rocket::http::Status;
+    use rocket::response::status::Custom;
+    use rocket::{Route, State};
+    use rocket_contrib::json::Json;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    /// The moderation queue.
+    #[get("/")]
+    fn pending(store: Store) -> Result<Json<HashMap<Id<Submission>, Submission>>> {
+        store.pending_submissions().map_err(Custom::from).map(Json)
+    }
+
+    #[post("/", data = "<obj>")]
+    fn create(
+        _enabled: Enabled<Submissions>,
+        store: Store,
+        spam: State<SpamFilter>,
+        client: ClientIp,
+        obj: Json<Submission>,
+    ) -> std::result::Result<Json<Id<Submission>>, Custom<String>> {
+        let candidate = Candidate {
+            honeypot: None,
+            texts: &[&obj.title, &obj.teaser, &obj.description],
+            client,
+        };
+        if spam.is_spam(Feature::Submissions, &candidate) {
+            return Err(Custom(
+                Status::UnprocessableEntity,
+                "The submission looks like spam.".to_string(),
+            ));
+        }
+        reject_unknown_locations(&store, Some(&obj.location_id))?;
+
+        store
+            .create_submission(obj.0)
+            .map_err(Custom::from)
+            .map(Json)
+    }
+
+    #[post("/<id>/approve")]
+    fn approve(store: Store, id: Id<Submission>) -> Result<Json<Id<Event>>> {
+        store.approve_submission(id).map_err(Custom::from).map(Json)
+    }
+
+    #[post("/<id>/reject")]
+    fn reject(store: Store, id: Id<Submission>) -> Result<Json<Submission>> {
+        store.reject_submission(id).map_err(Custom::from).map(Json)
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        // Submissions are not part of the snapshot, since they are not public.
+        if read_only {
+            routes![]
+        } else {
+            routes![pending, create, approve, reject]
+        }
+    }
+}
+
+mod comments {
+    use std::collections::HashMap;
+
+    use crate::store::{Comment, CommentWithEvent, Id, Store};
+
+    use rocket::response::status::Custom;
+    use rocket::Route;
+    use rocket_contrib::json::Json;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    /// The comments waiting for approval.
+    #[get("/")]
+    fn pending(store: Store) -> Result<Json<HashMap<Id<Comment>, CommentWithEvent>>> {
+        store.pending_comments().map_err(Custom::from).map(Json)
+    }
+
+    #[post("/<id>/approve")]
+    fn approve(store: Store, id: Id<Comment>) -> Result<Json<Comment>> {
+        store.approve_comment(id).map_err(Custom::from).map(Json)
+    }
+
+    #[delete("/<id>")]
+    fn delete(store: Store, id: Id<Comment>) -> Result<Json<Comment>> {
+        store.delete_comment(id).map_err(Custom::from).map(Json)
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        // Only approved comments are part of the snapshot, so there is nothing to moderate.
+        if read_only {
+            routes![]
+        } else {
+            routes![pending, approve, delete]
+        }
+    }
+}
+
+mod trash {
+    use crate::store::{Event, EventWithOccurrences, Id, Location, Store, Trash};
+
+    use rocket::response::status::Custom;
+    use rocket::Route;
+    use rocket_contrib::json::Json;
+
+    type Result<T> = std::result::Result<T, Custom<String>>;
+
+    /// The deleted locations and events.
+    #[get("/")]
+    fn all(store: Store) -> Result<Json<Trash>> {
+        store.trash().map_err(Custom::from).map(Json)
+    }
+
+    #[post("/locations/<id>/restore")]
+    fn restore_location(store: Store, id: Id<Location>) -> Result<Json<Location>> {
+        store.restore_location(id).map_err(Custom::from).map(Json)
+    }
+
+    #[post("/events/<id>/restore")]
+    fn restore_event(store: Store, id: Id<Event>) -> Result<Json<EventWithOccurrences>> {
+        store.restore_event(id).map_err(Custom::from).map(Json)
+    }
+
+    pub fn routes(read_only: bool) -> Vec<Route> {
+        // Nothing can be deleted from a snapshot.
+        if read_only {
+            routes![]
+        } else {
+            routes![all, restore_location, restore_event]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::ops::Deref;
+    use std::path::PathBuf;
+
+    use rocket::config::{Config, ConfigBuilder, Environment, Value};
+    use rocket::http::{ContentType, Header, Method, Status, StatusClass};
+    use rocket::local::Client;
+    use serde_json::Map;
+    use uuid::Uuid;
+
+    use crate::store::Store;
+
+    /// A client for a server with a fresh database, which is removed afterwards.
+    struct TestClient {
+        client: Client,
+        db_path: PathBuf,
+        media_dir: PathBuf,
+    }
+
+    impl Deref for TestClient {
+        type Target = Client;
+
+        fn deref(&self) -> &Client {
+            &self.client
+        }
+    }
+
+    impl Drop for TestClient {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.db_path);
+            let _ = fs::remove_dir_all(&self.media_dir);
+        }
+    }
+
+    fn client() -> TestClient {
+        client_with_features(HashMap::new())
+    }
+
+    fn client_with_features(features: HashMap<&str, bool>) -> TestClient {
+        client_with_config(|config| config.extra("features", features))
+    }
+
+    fn client_with_config(configure: impl FnOnce(ConfigBuilder) -> ConfigBuilder) -> TestClient {
+        let db_path = std::env::temp_dir().join(format!("lindyhop-test-{}.sqlite", Uuid::new_v4()));
+        client_for_database(db_path, configure)
+    }
+
+    /// A client for a server with the database at this path, which is removed afterwards.
+    fn client_for_database(
+        db_path: PathBuf,
+        configure: impl FnOnce(ConfigBuilder) -> ConfigBuilder,
+    ) -> TestClient {
+        let mut database = HashMap::new();
+        database.insert("url", Value::from(db_path.to_str().unwrap()));
+        let mut databases = HashMap::new();
+        databases.insert("sqlite_database", database);
+        let media_dir = db_path.with_extension("media");
+        let config = configure(
+            Config::build(Environment::Development)
+                .extra("databases", databases)
+                .extra("media_dir", media_dir.to_str().unwrap())
+                // Stands in for ImageMagick, writing as many bytes as the variant is wide.
+                .extra(
+                    "image_resize_command",
+                    "dd if=/dev/zero of={output} bs={width} count=1",
+                ),
+        )
+        .finalize()
+        .unwrap();
+
+        let rocket = rocket::custom(config)
+            .attach(Store::fairing())
+            .attach(crate::timing::RouteTimingFairing)
+            .attach(crate::recording::RecordingFairing::default())
+            .attach(crate::spam::SpamFairing)
+            .attach(crate::features::FeaturesFairing)
+            .attach(crate::mail::MailFairing)
+            .attach(crate::announcement::AnnouncementFairing)
+            .attach(crate::media::MediaFairing)
+            .attach(crate::links::LinkCheckFairing)
+            .attach(crate::geocoding::GeocodingFairing)
+            .attach(crate::maintenance::MaintenanceFairing)
+            .attach(crate::website::StatisticsCache::fairing());
+        let read_only = crate::store::is_read_only(&rocket);
+        // The website is mounted as well, since comments can only be created through its form.
+        let rocket = super::mount(
+            rocket
+                .mount("/", crate::website::routes(read_only))
+                .mount("/", crate::media::routes())
+                .mount("/", crate::offline::routes())
+                .mount("/", crate::calendar::routes()),
+            "/api",
+        );
+        TestClient {
+            client: Client::new(rocket).unwrap(),
+            db_path,
+            media_dir,
+        }
+    }
+
+    /// Compares the JSON against the snapshot stored in `src/snapshots/<name>.json`.
+    /// Since ids are random, they are replaced by placeholders numbered in order of appearance.
+    ///
+    /// Run the tests with `UPDATE_SNAPSHOTS=1` to accept changes.
+    fn assert_json_snapshot(name: &str, json: &str) {
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let mut ids = Vec::new();
+        let actual = serde_json::to_string_pretty(&replace_ids(value, &mut ids)).unwrap() + "\n";
+
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("{}.json", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "Missing snapshot '{}'. Run with UPDATE_SNAPSHOTS=1 to create it.",
+                path.display()
+            )
+        });
+        assert_eq!(
+            expected, actual,
+            "JSON differs from snapshot '{}'. Run with UPDATE_SNAPSHOTS=1 if the change is intended.",
+            name
+        );
+    }
+
+    fn replace_ids(value: serde_json::Value, ids: &mut Vec<String>) -> serde_json::Value {
+        use serde_json::Value::*;
+
+        match value {
+            String(string) => String(replace_id(string, ids)),
+            Array(values) => Array(
+                values
+                    .into_iter()
+                    .map(|value| replace_ids(value, ids))
+                    .collect(),
+            ),
+            Object(map) => Object(
+                map.into_iter()
+                    .map(|(key, value)| (replace_id(key, ids), replace_ids(value, ids)))
+                    .collect::<Map<_, _>>(),
+            ),
+            other => other,
+        }
+    }
+
+    fn replace_id(string: String, ids: &mut Vec<String>) -> String {
+        if Uuid::parse_str(&string).is_err() {
+            return string;
+        }
+
+        let index = match ids.iter().position(|id| id == &string) {
+            Some(index) => index,
+            None => {
+                ids.push(string);
+                ids.len() - 1
+            }
+        };
+        format!("[id {}]", index + 1)
+    }
+
+    fn request(client: &Client, method: &str, uri: &str, body: Option<&str>) -> String {
+        let method = match method {
+            "GET" => Method::Get,
+            "POST" => Method::Post,
+            "PUT" => Method::Put,
+            "DELETE" => Method::Delete,
+            _ => unreachable!(),
+        };
+        let mut request = client.req(method, uri.to_string());
+        if let Some(body) = body {
+            request = request.header(ContentType::JSON).body(body);
+        }
+        let mut response = request.dispatch();
+        assert_eq!(response.status(), Status::Ok, "{} {}", method, uri);
+        response.body_string().unwrap()
+    }
+
+    const LOCATION: &str =
+        r#"{ "name": "Chico Mendès", "address": "Pontstraße 74-76, 52062 Aachen" }"#;
+
+    fn event(location_id: &str) -> String {
+        format!(
+            r#"{{
+                "event": {{
+                    "title": "Social Dance",
+                    "teaser": "Zum Tanzen.",
+                    "description": "Einmal im Monat."
+                }},
+                "occurrences": [{{
+                    "start": "2019-06-12T20:00:00",
+                    "duration": 180,
+                    "location_id": "{}"
+                }}]
+            }}"#,
+            location_id
+        )
+    }
+
+    fn id(json: &str) -> String {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn location_endpoints() {
+        let client = client();
+
+        let created = request(&client, "POST", "/api/locations", Some(LOCATION));
+        assert_json_snapshot("locations_create", &created);
+        let location_id = id(&created);
+        let uri = format!("/api/locations/{}", location_id);
+
+        assert_json_snapshot(
+            "locations_all",
+            &request(&client, "GET", "/api/locations", None),
+        );
+        assert_json_snapshot("locations_read", &request(&client, "GET", &uri, None));
+        let updated = r#"{
+            "name": "Sencillito",
+            "address": { "street": "Pontstraße 74-76", "postal_code": "52062", "city": "Aachen" }
+        }"#;
+        assert_json_snapshot(
+            "locations_update",
+            &request(&client, "PUT", &uri, Some(updated)),
+        );
+
+        let invalid = LOCATION.replace("52062", "520");
+        let response = client
+            .put(uri.clone())
+            .header(ContentType::JSON)
+            .body(invalid)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        assert_json_snapshot("locations_delete", &request(&client, "DELETE", &uri, None));
+    }
+
+    #[test]
+    fn event_endpoints() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+
+        let created = request(&client, "POST", "/api/events", Some(&event(&location_id)));
+        let event_id = id(&created);
+        let uri = format!("/api/events/{}", event_id);
+
+        assert_json_snapshot("events_all", &request(&client, "GET", "/api/events", None));
+        assert_json_snapshot("events_read", &request(&client, "GET", &uri, None));
+        let updated = event(&location_id).replace("Social Dance", "Social");
+        assert_json_snapshot(
+            "events_update",
+            &request(&client, "PUT", &uri, Some(&updated)),
+        );
+        // Renaming keeps the slug, so shared links stay valid.
+        let renamed: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", &uri, None)).unwrap();
+        assert_eq!(renamed["event"]["slug"], "social-dance");
+        let duplicate = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+        let duplicate: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}", duplicate),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(duplicate["event"]["slug"], "social-dance-2");
+        assert_json_snapshot("events_delete", &request(&client, "DELETE", &uri, None));
+
+        let old_link = client
+            .get(format!("/veranstaltungen/{}", event_id))
+            .dispatch();
+        assert_eq!(old_link.status(), Status::PermanentRedirect);
+        assert_eq!(
+            old_link.headers().get_one("Location"),
+            Some("/veranstaltungen/social-dance")
+        );
+        let page = client.get("/veranstaltungen/social-dance").dispatch();
+        assert_eq!(page.status(), Status::Gone);
+    }
+
+    #[test]
+    fn snapshots_serve_every_mounted_route() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+        let page_id = id(&request(
+            &client,
+            "POST",
+            "/api/pages",
+            Some(r#"{ "slug": "ueber-uns", "title": "Über uns", "body": "Wir tanzen.", "published": true }"#),
+        ));
+        let locations: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            "/api/locations_with_occurrences",
+            None,
+        ))
+        .unwrap();
+        let occurrence_id = locations[&location_id]["occurrences"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        let unknown = Uuid::new_v4().to_string();
+        let snapshot = client_for_database(client.db_path.clone(), |config| {
+            config.extra("read_only_snapshot", true)
+        });
+
+        for route in snapshot.rocket().routes() {
+            // Parameters are filled in with an item of the kind the route shows, if there is one.
+            let mut previous = "";
+            let segments: Vec<&str> = route
+                .uri
+                .path()
+                .split('/')
+                .map(|segment| {
+                    let argument = match (previous, segment) {
+                        (_, segment) if !segment.starts_with('<') => segment,
+                        ("veranstaltungen", "<slug>") => "social-dance",
+                        ("events", _) | ("veranstaltungen", _) => &event_id,
+                        ("locations", _) | ("orte", _) => &location_id,
+                        ("termine", _) => &occurrence_id,
+                        ("pages", _) => &page_id,
+                        ("", "<slug>") => "ueber-uns",
+                        _ => &unknown,
+                    };
+                    previous = segment;
+                    argument
+                })
+                .collect();
+            let uri = segments.join("/");
+
+            // Only switching maintenance takes a body, and the site stays available.
+            let response = snapshot
+                .req(route.method, uri.clone())
+                .header(ContentType::JSON)
+                .body("false")
+                .dispatch();
+            assert_ne!(
+                response.status().class(),
+                StatusClass::ServerError,
+                "{} {}",
+                route.method,
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn locked_events_cannot_be_changed() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let uri = format!(
+            "/api/events/{}",
+            id(&request(
+                &client,
+                "POST",
+                "/api/events",
+                Some(&event(&location_id))
+            ))
+        );
+        let locked = |client: &Client| {
+            let event: serde_json::Value =
+                serde_json::from_str(&request(client, "GET", &uri, None)).unwrap();
+            event["event"]["locked"].as_bool().unwrap()
+        };
+
+        // Locking is only changed through its own endpoint.
+        let locking = event(&location_id).replace(r#""title""#, r#""locked": true, "title""#);
+        request(&client, "PUT", &uri, Some(&locking));
+        assert!(!locked(&client));
+
+        request(&client, "PUT", &format!("{}/locked", uri), Some("true"));
+        assert!(locked(&client));
+        let unlocking = event(&location_id).replace(r#""title""#, r#""locked": false, "title""#);
+        for (method, path, body) in &[
+            (Method::Put, uri.clone(), Some(unlocking)),
+            (Method::Delete, uri.clone(), None),
+            (
+                Method::Post,
+                format!("{}/recurrences", uri),
+                Some(format!(
+                    r#"{{
+                        "start": "2019-06-19T20:00:00",
+                        "duration": 180,
+                        "location_id": "{}",
+                        "interval_weeks": 1
+                    }}"#,
+                    location_id
+                )),
+            ),
+        ] {
+            let mut request = client.req(*method, path);
+            if let Some(body) = body {
+                request = request.header(ContentType::JSON).body(body);
+            }
+            let mut response = request.dispatch();
+            assert_eq!(response.status(), Status::Locked, "{} {}", method, path);
+            assert_eq!(
+                response.body_string().unwrap(),
+                "The event is locked and has to be unlocked before changing it."
+            );
+        }
+        assert!(locked(&client));
+
+        request(&client, "PUT", &format!("{}/locked", uri), Some("false"));
+        request(&client, "DELETE", &uri, None);
+    }
+
+    #[test]
+    fn event_images_are_uploaded_and_served() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+        let png = b"\x89PNG\r\n\x1a\n not really a picture";
+        let upload = |file_name: &str, content: &[u8]| {
+            let mut body = format!(
+                "--XyZ\r\nContent-Disposition: form-data; name=\"image\"; filename=\"{}\"\r\n\
+                 Content-Type: image/png\r\n\r\n",
+                file_name
+            )
+            .into_bytes();
+            body.extend_from_slice(content);
+            body.extend_from_slice(b"\r\n--XyZ--\r\n");
+            client
+                .post(format!("/api/events/{}/images", event_id))
+                .header(ContentType::with_params(
+                    "multipart",
+                    "form-data",
+                    ("boundary", "XyZ"),
+                ))
+                .body(body)
+                .dispatch()
+        };
+
+        let mut response = upload("flyer.png", png);
+        assert_eq!(response.status(), Status::Ok);
+        let ids: Vec<String> = serde_json::from_str(&response.body_string().unwrap()).unwrap();
+        assert_eq!(ids.len(), 1);
+        assert_eq!(
+            upload("flyer.html", b"<script>").status(),
+            Status::UnsupportedMediaType
+        );
+
+        let images: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}/images", event_id),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(images.as_object().unwrap().len(), 1);
+        let image = &images[&ids[0]];
+        assert_eq!(image["original_name"], "flyer.png");
+        assert_eq!(image["content_type"], "image/png");
+        let media_url = format!("/media/{}", image["file_name"].as_str().unwrap());
+        let mut served = client.get(media_url.clone()).dispatch();
+        assert_eq!(served.status(), Status::Ok);
+        assert_eq!(served.content_type(), Some(ContentType::PNG));
+        assert_eq!(served.body_bytes().unwrap(), png.to_vec());
+        let variant_size = |size: &str| {
+            client
+                .get(format!("{}?size={}", media_url, size))
+                .dispatch()
+                .body_bytes()
+                .map(|content| content.len())
+        };
+        assert_eq!(variant_size("thumbnail"), Some(200));
+        assert_eq!(variant_size("card"), Some(600));
+        assert_eq!(variant_size("poster"), Some(png.len()));
+
+        request(
+            &client,
+            "DELETE",
+            &format!("/api/events/{}/images/{}", event_id, ids[0]),
+            None,
+        );
+        assert_eq!(
+            client
+                .get(format!("{}?size=thumbnail", media_url))
+                .dispatch()
+                .status(),
+            Status::NotFound
+        );
+        assert_eq!(client.get(media_url).dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn broken_links_are_reported() {
+        // Answers with 404 for paths containing "gone" and 200 otherwise.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            use std::io::{BufRead, BufReader, Write};
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .unwrap();
+                let response: &[u8] = if request_line.contains("gone") {
+                    b"HTTP/1.1 404 Not Found\r\n\r\n"
+                } else {
+                    b"HTTP/1.1 200 OK\r\n\r\n"
+                };
+                // HTTPS links are only connected to, so the connection may be closed already.
+                let _ = stream.write_all(response);
+            }
+        });
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let description = format!(
+            "Infos unter http://127.0.0.1:{0}/infos. Fotos: http://127.0.0.1:{0}/gone. \
+             Tickets: https://127.0.0.1:{0}/tickets",
+            port
+        );
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id).replace("Einmal im Monat.", &description)),
+        ));
+        assert_eq!(
+            client.get("/api/links").dispatch().status(),
+            Status::NotFound
+        );
+
+        let report: serde_json::Value =
+            serde_json::from_str(&request(&client, "POST", "/api/links/check", None)).unwrap();
+        assert_eq!(report["checked"], 2);
+        assert_eq!(report["broken"].as_array().unwrap().len(), 1);
+        let broken = &report["broken"][0];
+        assert_eq!(broken["url"], format!("http://127.0.0.1:{}/gone", port));
+        assert_eq!(broken["event_id"], event_id);
+        assert_eq!(broken["problem"], "HTTP 404");
+        // Without TLS, the page behind an HTTPS link cannot be requested.
+        assert_eq!(
+            report["unchecked"],
+            serde_json::json!([format!("https://127.0.0.1:{}/tickets", port)])
+        );
+
+        let latest: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/links", None)).unwrap();
+        assert_eq!(latest, report);
+    }
+
+    #[test]
+    fn coordinates_are_looked_up_from_the_address() {
+        // Answers like Nominatim, placing every address on the same spot.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            use std::io::{BufRead, BufReader, Write};
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .unwrap();
+                assert!(request_line.contains("q=Pontstra%C3%9Fe%2074-76,%2052062%20Aachen"));
+                stream
+                    .write_all(
+                        b"HTTP/1.0 200 OK\r\n\r\n[{ \"lat\": \"50.7783\", \"lon\": \"6.0806\" }]",
+                    )
+                    .unwrap();
+            }
+        });
+        let client = client_with_config(|config| {
+            config.extra(
+                "geocoding_url",
+                format!("http://127.0.0.1:{}/search?q={{address}}", port),
+            )
+        });
+        let located = || -> Option<serde_json::Value> {
+            for _ in 0..50 {
+                let location: serde_json::Value =
+                    serde_json::from_str(&request(&client, "GET", "/api/locations", None)).unwrap();
+                let coordinates =
+                    location.as_object().unwrap().values().next().unwrap()["coordinates"].clone();
+                if !coordinates.is_null() {
+                    return Some(coordinates);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            None
+        };
+
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        assert_eq!(
+            located(),
+            Some(serde_json::json!({ "latitude": 50.7783, "longitude": 6.0806 }))
+        );
+
+        // Coordinates given by hand take precedence.
+        let manual = LOCATION.replace(
+            "\"address\"",
+            r#""coordinates": { "latitude": 50.7766, "longitude": 6.0834 }, "manual_coordinates": true, "address""#,
+        );
+        let uri = format!("/api/locations/{}", location_id);
+        request(&client, "PUT", &uri, Some(&manual));
+        assert_eq!(
+            located(),
+            Some(serde_json::json!({ "latitude": 50.7766, "longitude": 6.0834 }))
+        );
+    }
+
+    #[test]
+    fn api_keys_have_daily_quotas() {
+        let client = client();
+        let created: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "POST",
+            "/api/admin/keys",
+            Some(r#"{ "name": "Partner", "daily_quota": 2 }"#),
+        ))
+        .unwrap();
+        let key_id = created["id"].as_str().unwrap().to_string();
+        let secret = created["secret"].as_str().unwrap().to_string();
+        // Only a hash of the secret is stored, so it is not listed.
+        let keys: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/admin/keys", None)).unwrap();
+        assert_eq!(keys[&key_id]["name"], "Partner");
+        assert!(keys[&key_id].get("secret").is_none());
+        let get_with_key = |secret: &str| {
+            client
+                .get("/api/locations")
+                .header(Header::new("X-Api-Key", secret.to_string()))
+                .dispatch()
+        };
+
+        let response = get_with_key(&secret);
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("X-RateLimit-Remaining"),
+            Some("1")
+        );
+        assert_eq!(get_with_key(&secret).status(), Status::Ok);
+        let mut response = get_with_key(&secret);
+        assert_eq!(response.status(), Status::TooManyRequests);
+        assert_eq!(
+            response.body_string().unwrap(),
+            "The daily quota of 2 requests is used up. It is reset at midnight."
+        );
+        assert_eq!(get_with_key("unknown").status(), Status::Unauthorized);
+        // Requests without a key are not limited.
+        request(&client, "GET", "/api/locations", None);
+        // Only the requests that were rejected are answered there.
+        let response = client.get("/api/keys/rejected").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        // Keys are managed with the admin routes, not next to the public endpoints.
+        let response = client
+            .post("/api/keys")
+            .header(ContentType::JSON)
+            .body(r#"{ "name": "Partner", "daily_quota": 2 }"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+
+        let keys: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/admin/keys", None)).unwrap();
+        assert_eq!(keys[&key_id]["used_today"], 3);
+        let usage: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/admin/keys/{}/usage", key_id),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(usage.as_object().unwrap().values().next().unwrap(), 3);
+
+        // Raising the quota lets the partner continue.
+        request(
+            &client,
+            "PUT",
+            &format!("/api/admin/keys/{}", key_id),
+            Some(r#"{ "name": "Partner", "daily_quota": 10 }"#),
+        );
+        assert_eq!(get_with_key(&secret).status(), Status::Ok);
+
+        request(
+            &client,
+            "DELETE",
+            &format!("/api/admin/keys/{}", key_id),
+            None,
+        );
+        assert_eq!(get_with_key(&secret).status(), Status::Unauthorized);
+    }
+
+    #[test]
+    fn newsletter_endpoints() {
+        let client = client();
+        let newsletter = r#"{
+            "title": "Neuigkeiten im Juni",
+            "date": "2019-06-01",
+            "content": "Diesen Monat gibt es einen Workshop."
+        }"#;
+
+        let newsletter_id = id(&request(
+            &client,
+            "POST",
+            "/api/newsletters",
+            Some(newsletter),
+        ));
+        let uri = format!("/api/newsletters/{}", newsletter_id);
+        let all: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/newsletters", None)).unwrap();
+        assert_eq!(all[&newsletter_id]["title"], "Neuigkeiten im Juni");
+
+        let previous: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "PUT",
+            &uri,
+            Some(&newsletter.replace("Juni", "Juli")),
+        ))
+        .unwrap();
+        assert_eq!(previous["title"], "Neuigkeiten im Juni");
+        let updated: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", &uri, None)).unwrap();
+        assert_eq!(updated["title"], "Neuigkeiten im Juli");
+        assert_eq!(updated["date"], "2019-06-01");
+
+        request(&client, "DELETE", &uri, None);
+        assert_eq!(client.get(uri).dispatch().status(), Status::NotFound);
+    }
+
+    /// Sends mail to the server started by `crate::mail::test_server::start`.
+    fn mail_config(port: u16) -> HashMap<&'static str, Value> {
+        let mut mail = HashMap::new();
+        mail.insert("host", Value::from("127.0.0.1"));
+        mail.insert("port", Value::from(i64::from(port)));
+        mail.insert("from", Value::from("newsletter@lindyhop-aachen.de"));
+        mail
+    }
+
+    #[test]
+    fn newsletter_drafts_list_upcoming_occurrences() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let next_week = chrono::Local::today().naive_local() + chrono::Duration::days(7);
+        let event = format!(
+            r#"{{
+                "event": {{ "title": "Social Dance", "teaser": "Zum Tanzen.", "description": "" }},
+                "occurrences": [
+                    {{ "start": "{0}T20:00:00", "duration": 180, "location_id": "{1}" }},
+                    {{ "start": "{0}T22:00:00", "duration": 60, "location_id": "{1}", "cancelled": true }},
+                    {{ "start": "{2}T20:00:00", "duration": 180, "location_id": "{1}" }}
+                ]
+            }}"#,
+            next_week,
+            location_id,
+            next_week + chrono::Duration::weeks(4),
+        );
+        request(&client, "POST", "/api/events", Some(&event));
+
+        let draft: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            "/api/newsletters/generate?weeks=2",
+            None,
+        ))
+        .unwrap();
+        assert_eq!(
+            draft["content"],
+            format!(
+                "{}\n20:00 Social Dance, Chico Mendès\nZum Tanzen.",
+                crate::website::format_date(&next_week)
+            )
+        );
+
+        for weeks in &[0, 27] {
+            let response = client
+                .get(format!("/api/newsletters/generate?weeks={}", weeks))
+                .dispatch();
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        }
+
+        // Drafts do not reach beyond the schedule horizon.
+        let client =
+            client_with_config(|config| config.extra("schedule_horizon_days", Value::from(20)));
+        request(&client, "GET", "/api/newsletters/generate?weeks=2", None);
+        let mut response = client.get("/api/newsletters/generate?weeks=3").dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.body_string().unwrap(),
+            "The weeks must be between 1 and 2."
+        );
+    }
+
+    #[test]
+    fn navigation_is_rendered_from_the_database() {
+        let client = client();
+        let nav_item = |label: &str, position: i32, visible: bool| {
+            format!(
+                r#"{{ "label": "{}", "url": "/{}", "position": {}, "visible": {} }}"#,
+                label,
+                label.to_lowercase(),
+                position,
+                visible
+            )
+        };
+        let ball_id = id(&request(
+            &client,
+            "POST",
+            "/api/navigation",
+            Some(&nav_item("Weihnachtsball", 2, true)),
+        ));
+        request(
+            &client,
+            "POST",
+            "/api/navigation",
+            Some(&nav_item("Kurse", 1, true)),
+        );
+        request(
+            &client,
+            "POST",
+            "/api/navigation",
+            Some(&nav_item("Sommerfest", 0, false)),
+        );
+
+        let page = request(&client, "GET", "/", None);
+        let courses = page.find(r#"<a href="/kurse">Kurse</a>"#).unwrap();
+        let ball = page
+            .find(r#"<a href="/weihnachtsball">Weihnachtsball</a>"#)
+            .unwrap();
+        assert!(courses < ball);
+        assert!(!page.contains("Sommerfest"));
+
+        request(
+            &client,
+            "PUT",
+            &format!("/api/navigation/{}", ball_id),
+            Some(&nav_item("Weihnachtsball", 2, false)),
+        );
+        let page = request(&client, "GET", "/archiv", None);
+        assert!(page.contains("Kurse"));
+        assert!(!page.contains("Weihnachtsball"));
+    }
+
+    #[test]
+    fn pages_are_rendered_from_markdown() {
+        let client = client();
+        let page = |slug: &str, published: bool| {
+            format!(
+                r#"{{ "slug": "{}", "title": "Über uns", "body": "Wir tanzen **Lindy Hop** <3", "published": {} }}"#,
+                slug, published
+            )
+        };
+        let page_id = id(&request(
+            &client,
+            "POST",
+            "/api/pages",
+            Some(&page("ueber-uns", true)),
+        ));
+
+        let rendered = request(&client, "GET", "/ueber-uns", None);
+        assert!(rendered.contains("<h1>Über uns</h1>"));
+        assert!(rendered.contains("<p>Wir tanzen <strong>Lindy Hop</strong> &lt;3</p>"));
+
+        let uri = format!("/api/pages/{}", page_id);
+        request(&client, "PUT", &uri, Some(&page("ueber-uns", false)));
+        let response = client.get("/ueber-uns").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+
+        for (slug, status) in &[
+            ("ueber-uns", Status::Conflict),
+            ("Über uns", Status::UnprocessableEntity),
+            ("archiv", Status::UnprocessableEntity),
+        ] {
+            let response = client
+                .post("/api/pages")
+                .header(ContentType::JSON)
+                .body(page(slug, true))
+                .dispatch();
+            assert_eq!(response.status(), *status);
+        }
+    }
+
+    #[test]
+    fn faq_is_grouped_by_category() {
+        let client = client();
+        let entry = |question: &str, category: &str, position: i32| {
+            format!(
+                r#"{{ "question": "{}", "answer": "Nein, **gar nicht**.", "category": "{}", "position": {} }}"#,
+                question, category, position
+            )
+        };
+        request(
+            &client,
+            "POST",
+            "/api/faq",
+            Some(&entry("Muss ich bezahlen?", "Social Dance", 2)),
+        );
+        request(
+            &client,
+            "POST",
+            "/api/faq",
+            Some(&entry("Brauche ich Vorkenntnisse?", "Kurse", 1)),
+        );
+        let partner_id = id(&request(
+            &client,
+            "POST",
+            "/api/faq",
+            Some(&entry("Brauche ich einen Partner?", "Kurse", 3)),
+        ));
+
+        let page = request(&client, "GET", "/faq", None);
+        let courses = page.find("<h2>Kurse</h2>").unwrap();
+        let partner = page.find("Brauche ich einen Partner?").unwrap();
+        let social = page.find("<h2>Social Dance</h2>").unwrap();
+        assert!(courses < partner && partner < social);
+        assert!(page.contains("<p>Nein, <strong>gar nicht</strong>.</p>"));
+
+        let start = page.find(r#"<script type="application/ld+json">"#).unwrap();
+        let data = &page[start..];
+        let data = &data[data.find('>').unwrap() + 1..data.find("</script>").unwrap()];
+        let data: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(data["@type"], "FAQPage");
+        assert_eq!(data["mainEntity"].as_array().unwrap().len(), 3);
+        assert_eq!(data["mainEntity"][0]["name"], "Brauche ich Vorkenntnisse?");
+
+        request(&client, "DELETE", &format!("/api/faq/{}", partner_id), None);
+        let page = request(&client, "GET", "/faq", None);
+        assert!(!page.contains("Brauche ich einen Partner?"));
+    }
+
+    #[test]
+    fn public_pages_are_replaced_during_maintenance() {
+        let client = client();
+        request(&client, "PUT", "/api/admin/maintenance", Some("true"));
+
+        for response in vec![
+            client.get("/").dispatch(),
+            client.get("/faq").dispatch(),
+            client
+                .post("/newsletter/anmelden")
+                .header(ContentType::Form)
+                .body("email=lindy@example.com")
+                .dispatch(),
+        ] {
+            let mut response = response;
+            assert_eq!(response.status(), Status::ServiceUnavailable);
+            assert_eq!(response.headers().get_one("Retry-After"), Some("1800"));
+            assert!(response.body_string().unwrap().contains("Wir räumen auf"));
+        }
+        assert_eq!(
+            request(&client, "GET", "/api/admin/maintenance", None),
+            "true"
+        );
+        request(&client, "GET", "/api/events", None);
+
+        request(&client, "PUT", "/api/admin/maintenance", Some("false"));
+        request(&client, "GET", "/", None);
+        let response = client.get("/wartung").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn invalid_fields_are_listed() {
+        let client = client();
+        let mut response = client
+            .post("/api/events")
+            .header(ContentType::JSON)
+            .body(
+                event(&id(&request(
+                    &client,
+                    "POST",
+                    "/api/locations",
+                    Some(LOCATION),
+                )))
+                .replace("Social Dance", "")
+                .replace("\"duration\": 180", "\"duration\": 0"),
+            )
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.body_string().unwrap(),
+            "event.title: The title must not be empty.\n\
+             occurrences[0].duration: The duration must be at least one minute."
+        );
+
+        let mut response = client
+            .post("/api/locations")
+            .header(ContentType::JSON)
+            .body(LOCATION.replace("Chico Mendès", " "))
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.body_string().unwrap(),
+            "name: The name must not be empty."
+        );
+    }
+
+    #[test]
+    fn missing_items_are_not_found() {
+        let client = client();
+        let unknown = "9a3c2a3e-6f0b-4c7e-9d55-3e1f0c8b2d11";
+
+        for (method, path) in &[
+            (Method::Get, format!("/api/locations/{}", unknown)),
+            (Method::Delete, format!("/api/locations/{}", unknown)),
+            (Method::Put, format!("/api/events/{}/locked", unknown)),
+            (Method::Delete, format!("/api/comments/{}", unknown)),
+        ] {
+            let mut response = client.req(*method, path).body("true").dispatch();
+            assert_eq!(response.status(), Status::NotFound, "{} {}", method, path);
+            assert_eq!(response.body_string().unwrap(), "The item does not exist.");
+        }
+
+        let response = client.get(format!("/orte/{}", unknown)).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn old_urls_are_redirected() {
+        let client = client();
+        let redirect = |source: &str, target: &str, status: u16| {
+            format!(
+                r#"{{ "source": "{}", "target": "{}", "status": {} }}"#,
+                source, target, status
+            )
+        };
+        request(
+            &client,
+            "POST",
+            "/api/redirects",
+            Some(&redirect(
+                "/2018/05/social-dance/",
+                "/veranstaltungen/social-dance",
+                301,
+            )),
+        );
+        request(
+            &client,
+            "POST",
+            "/api/redirects",
+            Some(&redirect("/kurse", "https://example.com/kurse", 307)),
+        );
+
+        for (path, status, location) in &[
+            (
+                "/2018/05/social-dance",
+                Status::MovedPermanently,
+                "/veranstaltungen/social-dance",
+            ),
+            (
+                "/kurse/",
+                Status::TemporaryRedirect,
+                "https://example.com/kurse",
+            ),
+        ] {
+            let response = client.get(*path).dispatch();
+            assert_eq!(response.status(), *status);
+            assert_eq!(response.headers().get_one("Location"), Some(*location));
+        }
+        let response = client.get("/2018/06/workshop/").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        request(&client, "GET", "/faq", None);
+
+        for (body, status) in &[
+            (redirect("/kurse/", "/", 301), Status::Conflict),
+            (redirect("kurse", "/", 301), Status::UnprocessableEntity),
+            (
+                redirect("/alt", "javascript:alert(1)", 301),
+                Status::UnprocessableEntity,
+            ),
+            (redirect("/alt", "/", 303), Status::UnprocessableEntity),
+        ] {
+            let response = client
+                .post("/api/redirects")
+                .header(ContentType::JSON)
+                .body(body)
+                .dispatch();
+            assert_eq!(response.status(), *status);
+        }
+    }
+
+    #[test]
+    fn overlapping_bookings_are_reported() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let party_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+        let workshop = |start: &str| {
+            format!(
+                r#"{{
+                    "event": {{ "title": "Workshop", "teaser": "", "description": "" }},
+                    "occurrences": [{{
+                        "start": "{}",
+                        "duration": 60,
+                        "location_id": "{}"
+                    }}]
+                }}"#,
+                start, location_id
+            )
+        };
+
+        let response = client
+            .post("/api/events")
+            .header(ContentType::JSON)
+            .body(workshop("2019-06-12T22:30:00"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let conflicts: serde_json::Value =
+            serde_json::from_str(response.headers().get_one("X-Booking-Conflicts").unwrap())
+                .unwrap();
+        assert_eq!(
+            conflicts,
+            serde_json::json!([{
+                "location_id": location_id,
+                "start": "2019-06-12T22:30:00",
+                "event_id": party_id,
+                "event_title": "Social Dance",
+                "other_start": "2019-06-12T20:00:00"
+            }])
+        );
+
+        // Occurrences that merely follow each other do not conflict.
+        let mut response = client
+            .post("/api/events")
+            .header(ContentType::JSON)
+            .body(workshop("2019-06-12T23:30:00"))
+            .dispatch();
+        assert_eq!(response.headers().get_one("X-Booking-Conflicts"), None);
+
+        let workshop_id = id(&response.body_string().unwrap());
+        let response = client
+            .put(format!("/api/events/{}", workshop_id))
+            .header(ContentType::JSON)
+            .body(workshop("2019-06-12T19:30:00"))
+            .dispatch();
+        assert!(response.headers().get_one("X-Booking-Conflicts").is_some());
+    }
+
+    #[test]
+    fn events_pages_and_questions_are_searched() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        request(&client, "POST", "/api/events", Some(&event(&location_id)));
+        request(
+            &client,
+            "POST",
+            "/api/pages",
+            Some(
+                r#"{ "slug": "social-dance", "title": "Social Dance", "body": "Jeden **Monat** tanzen wir.", "published": true }"#,
+            ),
+        );
+        request(
+            &client,
+            "POST",
+            "/api/pages",
+            Some(
+                r#"{ "slug": "entwurf", "title": "Entwurf", "body": "Jeden Monat.", "published": false }"#,
+            ),
+        );
+        request(
+            &client,
+            "POST",
+            "/api/faq",
+            Some(
+                r#"{ "question": "Wann ist Social Dance?", "answer": "Einmal im Monat wird getanzt.", "category": "Social Dance", "position": 1 }"#,
+            ),
+        );
+
+        let results: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/search?q=monat%20TANZ", None))
+                .unwrap();
+        assert_eq!(
+            results,
+            serde_json::json!([
+                {
+                    "type": "event",
+                    "title": "Social Dance",
+                    "excerpt": "Zum Tanzen.",
+                    "url": "/veranstaltungen/social-dance"
+                },
+                {
+                    "type": "page",
+                    "title": "Social Dance",
+                    "excerpt": "Jeden Monat tanzen wir.",
+                    "url": "/social-dance"
+                },
+                {
+                    "type": "faq",
+                    "title": "Wann ist Social Dance?",
+                    "excerpt": "Einmal im Monat wird getanzt.",
+                    "url": "/faq"
+                }
+            ])
+        );
+
+        let results = request(&client, "GET", "/api/search?q=Balboa", None);
+        assert_eq!(results, "[]");
+    }
+
+    #[test]
+    fn past_newsletters_are_archived() {
+        let mut features = HashMap::new();
+        features.insert("newsletter", true);
+        let client = client_with_features(features);
+        let past_id = id(&request(
+            &client,
+            "POST",
+            "/api/newsletters",
+            Some(r#"{ "title": "Juni", "date": "2019-06-01", "content": "Workshop!\n\nParty!" }"#),
+        ));
+        let next_week = chrono::Local::today().naive_local() + chrono::Duration::days(7);
+        let draft_id = id(&request(
+            &client,
+            "POST",
+            "/api/newsletters",
+            Some(&format!(
+                r#"{{ "title": "Entwurf", "date": "{}", "content": "Bald." }}"#,
+                next_week
+            )),
+        ));
+
+        let archive = request(&client, "GET", "/newsletter/archiv", None);
+        assert!(archive.contains(&format!("/newsletter/archiv/{}", past_id)));
+        assert!(!archive.contains("Entwurf"));
+        let issue = request(
+            &client,
+            "GET",
+            &format!("/newsletter/archiv/{}", past_id),
+            None,
+        );
+        assert!(issue.contains("<h1>Juni</h1>"));
+        assert!(issue.contains(">Party!</p>"));
+        assert_eq!(
+            client
+                .get(format!("/newsletter/archiv/{}", draft_id))
+                .dispatch()
+                .status(),
+            Status::NotFound
+        );
+
+        assert_eq!(
+            self::client().get("/newsletter/archiv").dispatch().status(),
+            Status::NotFound
+        );
+    }
+
+    #[test]
+    fn newsletter_sending() {
+        let unconfigured = client();
+        let (port, messages) = crate::mail::test_server::start(&["gone@example.com"]);
+        let client = client_with_config(|config| config.extra("mail", mail_config(port)));
+        request(
+            &client,
+            "POST",
+            "/api/subscribers/import",
+            Some("kim@example.com,Kim,2019-03-04\ngone@example.com,,2019-03-04\n"),
+        );
+        let newsletter_id = id(&request(
+            &client,
+            "POST",
+            "/api/newsletters",
+            Some(r#"{ "title": "Juni", "date": "2019-06-01", "content": "Workshop!" }"#),
+        ));
+        let uri = format!("/api/newsletters/{}/send", newsletter_id);
+
+        let deliveries: serde_json::Value =
+            serde_json::from_str(&request(&client, "POST", &uri, None)).unwrap();
+        assert_eq!(deliveries[0]["email"], "gone@example.com");
+        assert!(deliveries[0]["error"].as_str().unwrap().contains("550"));
+        assert_eq!(deliveries[1]["email"], "kim@example.com");
+        assert!(deliveries[1]["error"].is_null());
+        assert_eq!(messages.lock().unwrap().len(), 1);
+
+        // Sending again only retries the subscribers it failed for.
+        request(&client, "POST", &uri, None);
+        assert_eq!(messages.lock().unwrap().len(), 1);
+
+        let newsletter_id = id(&request(
+            &unconfigured,
+            "POST",
+            "/api/newsletters",
+            Some(r#"{ "title": "Juni", "date": "2019-06-01", "content": "Workshop!" }"#),
+        ));
+        let response = unconfigured
+            .post(format!("/api/newsletters/{}/send", newsletter_id))
+            .dispatch();
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+    }
+
+    #[test]
+    fn newsletters_link_to_unsubscribe() {
+        let (port, messages) = crate::mail::test_server::start(&[]);
+        let client = client_with_config(|config| config.extra("mail", mail_config(port)));
+        request(
+            &client,
+            "POST",
+            "/api/subscribers/import",
+            Some("kim@example.com,Kim,2019-03-04\nalex@example.com,,2019-03-04\n"),
+        );
+        let newsletter_id = id(&request(
+            &client,
+            "POST",
+            "/api/newsletters",
+            Some(r#"{ "title": "Juni", "date": "2019-06-01", "content": "Workshop!" }"#),
+        ));
+        request(
+            &client,
+            "POST",
+            &format!("/api/newsletters/{}/send", newsletter_id),
+            None,
+        );
+
+        let links: Vec<String> = messages
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|message| {
+                let header = message
+                    .lines()
+                    .find(|line| line.starts_with("List-Unsubscribe: "))
+                    .unwrap();
+                let link = header.trim_start_matches("List-Unsubscribe: <");
+                let link = link.trim_end_matches('>');
+                let body = &message[message.find("\r\n\r\n").unwrap()..];
+                let html = base64::decode(&body.split_whitespace().collect::<String>()).unwrap();
+                assert!(String::from_utf8(html).unwrap().contains(link));
+                link.trim_start_matches("https://lindyhop-aachen.de")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(links.len(), 2);
+        assert_ne!(links[0], links[1]);
+
+        let mut response = client.get(links[0].clone()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert!(response.body_string().unwrap().contains("Abgemeldet"));
+        assert_eq!(
+            client.get(links[0].clone()).dispatch().status(),
+            Status::NotFound
+        );
+        // Mail clients unsubscribe with a POST, see RFC 8058.
+        let response = client
+            .post(links[1].clone())
+            .header(ContentType::Form)
+            .body("List-Unsubscribe=One-Click")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let subscribers = request(&client, "GET", "/api/subscribers", None);
+        assert_eq!(subscribers, "{}");
+        let deliveries = request(
+            &client,
+            "GET",
+            &format!("/api/newsletters/{}/deliveries", newsletter_id),
+            None,
+        );
+        assert_eq!(deliveries, "[]");
+    }
+
+    #[test]
+    fn subscriptions_need_confirmation() {
+        let (port, messages) = crate::mail::test_server::start(&[]);
+        let client = client_with_config(|config| {
+            let mut features = HashMap::new();
+            features.insert("newsletter", true);
+            config
+                .extra("mail", mail_config(port))
+                .extra("features", features)
+        });
+        let subscribe = || {
+            let mut response = client
+                .post("/newsletter/anmelden")
+                .header(ContentType::Form)
+                .body("email=Kim%40Example.com&name=Kim&homepage=")
+                .dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert!(response.body_string().unwrap().contains("Fast geschafft"));
+        };
+        let token = || {
+            let messages = messages.lock().unwrap();
+            let message = messages.last().unwrap();
+            let body = &message[message.find("\r\n\r\n").unwrap()..];
+            let html = base64::decode(&body.split_whitespace().collect::<String>()).unwrap();
+            let html = String::from_utf8(html).unwrap();
+            let start = html.find("/newsletter/bestaetigen/").unwrap();
+            html[start..].split('"').next().unwrap().to_string()
+        };
+        let subscribers = || -> serde_json::Value {
+            serde_json::from_str(&request(&client, "GET", "/api/subscribers", None)).unwrap()
+        };
+
+        subscribe();
+        let first_link = token();
+        // Signing up again sends a new link, which replaces the first.
+        subscribe();
+        let link = token();
+        assert_ne!(link, first_link);
+        let subscriber = subscribers().as_object().unwrap().values().next().cloned();
+        assert_eq!(subscriber.clone().unwrap()["email"], "kim@example.com");
+        assert_eq!(subscriber.unwrap()["confirmed"], false);
+
+        assert_eq!(client.get(first_link).dispatch().status(), Status::NotFound);
+        assert_eq!(client.get(link.clone()).dispatch().status(), Status::Ok);
+        let subscriber = subscribers().as_object().unwrap().values().next().cloned();
+        assert_eq!(subscriber.unwrap()["confirmed"], true);
+        assert_eq!(client.get(link).dispatch().status(), Status::NotFound);
+
+        // Confirmed addresses are not mailed again.
+        subscribe();
+        assert_eq!(messages.lock().unwrap().len(), 2);
+
+        let response = client
+            .post("/newsletter/anmelden")
+            .header(ContentType::Form)
+            .body("email=kim&name=&homepage=")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(messages.lock().unwrap().len(), 2);
+
+        assert_eq!(
+            self::client()
+                .get("/newsletter/anmelden")
+                .dispatch()
+                .status(),
+            Status::NotFound
+        );
+    }
+
+    #[test]
+    fn subscriptions_reject_addresses_with_line_breaks() {
+        let (port, messages) = crate::mail::test_server::start(&[]);
+        let client = client_with_config(|config| {
+            let mut features = HashMap::new();
+            features.insert("newsletter", true);
+            config
+                .extra("mail", mail_config(port))
+                .extra("features", features)
+        });
+
+        for email in &[
+            "kim%40example.com%0D%0ARCPT%20TO%3A%3Cvictim%40example.com%3E",
+            "kim%40example.com%0ABcc%3A%20victim%40example.com",
+        ] {
+            let mut response = client
+                .post("/newsletter/anmelden")
+                .header(ContentType::Form)
+                .body(format!("email={}&name=Kim&homepage=", email))
+                .dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert!(response
+                .body_string()
+                .unwrap()
+                .contains("Bitte gib eine gültige E-Mail-Adresse an."));
+        }
+        assert!(messages.lock().unwrap().is_empty());
+        assert_eq!(request(&client, "GET", "/api/subscribers", None), "{}");
+    }
+
+    #[test]
+    fn subscriber_import() {
+        let client = client();
+        let csv = "email,name,consent date\n\
+                   kim@example.com,Kim,2019-03-04 12:34:56\n\
+                   alex@example.com,,2019-05-01\n";
+
+        let import = request(&client, "POST", "/api/subscribers/import", Some(csv));
+        assert_eq!(import, r#"{"imported":2,"duplicates":[]}"#);
+        let import = request(
+            &client,
+            "POST",
+            "/api/subscribers/import",
+            Some("Kim@example.com,Kim,2019-03-04\nsam@example.com,Sam,2019-06-01\n"),
+        );
+        assert_eq!(import, r#"{"imported":1,"duplicates":["kim@example.com"]}"#);
+
+        let subscribers: HashMap<String, serde_json::Value> =
+            serde_json::from_str(&request(&client, "GET", "/api/subscribers", None)).unwrap();
+        assert_eq!(subscribers.len(), 3);
+        assert!(subscribers
+            .values()
+            .all(|subscriber| subscriber["confirmed"] == true));
+
+        let response = client
+            .post("/api/subscribers/import")
+            .body("kim@example.com,Kim")
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn trash_and_restore() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let next_week = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = event(&location_id).replace(
+            "2019-06-12T20:00:00",
+            &next_week.format("%Y-%m-%dT20:00:00").to_string(),
+        );
+        let event_id = id(&request(&client, "POST", "/api/events", Some(&upcoming)));
+
+        request(
+            &client,
+            "DELETE",
+            &format!("/api/events/{}", event_id),
+            None,
+        );
+        assert_eq!(request(&client, "GET", "/api/events", None), "{}");
+        assert!(!request(&client, "GET", "/", None).contains("Social Dance"));
+        let trash: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/trash", None)).unwrap();
+        let trashed = &trash["events"][&event_id];
+        assert_eq!(trashed["item"]["event"]["title"], "Social Dance");
+        assert_eq!(trashed["item"]["occurrences"].as_array().unwrap().len(), 1);
+
+        request(
+            &client,
+            "POST",
+            &format!("/api/trash/events/{}/restore", event_id),
+            None,
+        );
+        assert!(request(&client, "GET", "/", None).contains("Social Dance"));
+        let page = client.get("/veranstaltungen/social-dance").dispatch();
+        assert_eq!(page.status(), Status::Ok);
+
+        request(
+            &client,
+            "DELETE",
+            &format!("/api/locations/{}", location_id),
+            None,
+        );
+        assert_eq!(request(&client, "GET", "/api/locations", None), "{}");
+        request(
+            &client,
+            "POST",
+            &format!("/api/trash/locations/{}/restore", location_id),
+            None,
+        );
+        let locations: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/locations", None)).unwrap();
+        assert_eq!(locations[&location_id]["name"], "Chico Mendès");
+
+        let response = client
+            .post(format!("/api/trash/locations/{}/restore", location_id))
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn empty_teaser_is_derived() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let without_teaser = event(&location_id).replace("Zum Tanzen.", "").replace(
+            "Einmal im Monat.",
+            r"## Social\n\nEinmal im **Monat** im [Chico](https://example.com). Mit DJ.",
+        );
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&without_teaser),
+        ));
+
+        let read: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}", event_id),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(read["event"]["teaser"], "Einmal im Monat im Chico.");
+    }
+
+    #[test]
+    fn related_endpoint() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let next_week = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = event(&location_id).replace(
+            "2019-06-12T20:00:00",
+            &next_week.format("%Y-%m-%dT20:00:00").to_string(),
+        );
+        let event_id = id(&request(&client, "POST", "/api/events", Some(&upcoming)));
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&upcoming.replace("Social Dance", "Practice")),
+        );
+
+        assert_json_snapshot(
+            "events_related",
+            &request(
+                &client,
+                "GET",
+                &format!("/api/events/{}/related", event_id),
+                None,
+            ),
+        );
+    }
+
+    #[test]
+    fn event_calendar_endpoint() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let next_week = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = event(&location_id).replace(
+            "2019-06-12T20:00:00",
+            &next_week.format("%Y-%m-%dT20:00:00").to_string(),
+        );
+        let event_id = id(&request(&client, "POST", "/api/events", Some(&upcoming)));
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&upcoming.replace("Social Dance", "Practice")),
+        );
+
+        let calendar = request(
+            &client,
+            "GET",
+            &format!("/api/events/{}/calendar.ics", event_id),
+            None,
+        );
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 1);
+        assert!(calendar.contains(&format!(
+            "DTSTART;TZID=Europe/Berlin:{}\r\n",
+            next_week.format("%Y%m%dT200000")
+        )));
+        assert!(calendar.contains("SUMMARY:Social Dance\r\n"));
+        assert!(!calendar.contains("Practice"));
+    }
+
+    #[test]
+    fn announcements_use_the_configured_template() {
+        let client = client_with_config(|config| {
+            config.extra("announcement_template", "{title} am {dates} in {locations}")
+        });
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let next_week = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = event(&location_id).replace(
+            "2019-06-12T20:00:00",
+            &next_week.format("%Y-%m-%dT20:00:00").to_string(),
+        );
+        let event_id = id(&request(&client, "POST", "/api/events", Some(&upcoming)));
+
+        let announcement = request(
+            &client,
+            "GET",
+            &format!("/api/events/{}/announcement", event_id),
+            None,
+        );
+        assert_eq!(
+            announcement,
+            format!(
+                "Social Dance am {}, 20:00 Uhr in Chico Mendès, Pontstraße 74-76, 52062 Aachen",
+                crate::website::format_date(&next_week.date())
+            )
+        );
+    }
+
+    #[test]
+    fn disabled_features_are_not_served() {
+        let mut features = HashMap::new();
+        features.insert("comments", false);
+        features.insert("submissions", false);
+        features.insert("calendar", false);
+        let client = client_with_features(features);
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+
+        let event_page = request(&client, "GET", "/veranstaltungen/social-dance", None);
+        assert!(!event_page.contains("Kommentar"));
+        assert!(!event_page.contains("/einreichen"));
+        assert!(!event_page.contains("calendar.ics"));
+
+        let submission = format!(
+            r#"{{
+                "title": "Blues Night",
+                "teaser": "Zum Tanzen.",
+                "description": "",
+                "organizer_name": "Kim",
+                "organizer_email": "kim@example.com",
+                "start": "2019-06-14T21:00:00",
+                "duration": 120,
+                "location_id": "{}"
+            }}"#,
+            location_id
+        );
+        let response = client
+            .post("/api/submissions")
+            .header(ContentType::JSON)
+            .body(submission)
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert_eq!(
+            client.get("/einreichen").dispatch().status(),
+            Status::NotFound
+        );
+        let response = client
+            .post(format!("/veranstaltungen/{}/kommentare", event_id))
+            .header(ContentType::Form)
+            .body("name=Kim&text=Hallo&homepage=")
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        let response = client
+            .get(format!("/api/events/{}/calendar.ics", event_id))
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn schedule_has_structured_data() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let next_week = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = event(&location_id)
+            .replace(
+                "2019-06-12T20:00:00",
+                &next_week.format("%Y-%m-%dT20:00:00").to_string(),
+            )
+            .replace("Social Dance", "Social </script> Dance");
+        request(&client, "POST", "/api/events", Some(&upcoming));
+
+        let schedule = request(&client, "GET", "/", None);
+        let start = schedule
+            .find(r#"<script type="application/ld+json">"#)
+            .unwrap();
+        let data = &schedule[start..];
+        let data = &data[data.find('>').unwrap() + 1..data.find("</script>").unwrap()];
+        let data: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(data["@type"], "Event");
+        assert_eq!(data["name"], "Social </script> Dance");
+        assert!(data["startDate"]
+            .as_str()
+            .unwrap()
+            .starts_with(&next_week.format("%Y-%m-%dT20:00:00").to_string()));
+        assert_eq!(data["location"]["name"], "Chico Mendès");
+    }
+
+    #[test]
+    fn schedule_is_available_offline() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        request(&client, "POST", "/api/events", Some(&event(&location_id)));
+        let tomorrow = chrono::Local::now().naive_local().date() + chrono::Duration::days(1);
+        let upcoming = event(&location_id)
+            .replace(
+                "2019-06-12T20:00:00",
+                &tomorrow.format("%Y-%m-%dT20:00:00").to_string(),
+            )
+            .replace("Social Dance", "Tanzabend");
+        request(&client, "POST", "/api/events", Some(&upcoming));
+
+        let mut response = client.get("/manifest.webmanifest").dispatch();
+        assert_eq!(
+            response.content_type(),
+            Some(ContentType::new("application", "manifest+json"))
+        );
+        let manifest: serde_json::Value =
+            serde_json::from_str(&response.body_string().unwrap()).unwrap();
+        let icon = manifest["icons"][0]["src"].as_str().unwrap();
+        let response = client.get(icon).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::SVG));
+
+        let response = client.get("/sw.js").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::JavaScript));
+
+        let mut response = client.get("/offline-data.json").dispatch();
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let data: serde_json::Value =
+            serde_json::from_str(&response.body_string().unwrap()).unwrap();
+        // The past event is left out.
+        let days = data["days"].as_object().unwrap();
+        assert_eq!(days.len(), 1);
+        let entries = days[&tomorrow.to_string()].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["event"]["title"], "Tanzabend");
+        assert_eq!(data["locations"][&location_id]["name"], "Chico Mendès");
+    }
+
+    #[test]
+    fn locations_have_coordinates() {
+        let client = client();
+        let with_coordinates = LOCATION.replace(
+            "\"address\"",
+            r#""coordinates": { "latitude": 50.7766, "longitude": 6.0834 }, "manual_coordinates": true, "address""#,
+        );
+        let location_id = id(&request(
+            &client,
+            "POST",
+            "/api/locations",
+            Some(&with_coordinates),
+        ));
+        let uri = format!("/api/locations/{}", location_id);
+        let location: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", &uri, None)).unwrap();
+        assert_eq!(
+            location["coordinates"],
+            serde_json::json!({ "latitude": 50.7766, "longitude": 6.0834 })
+        );
+
+        let next_week = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = event(&location_id).replace(
+            "2019-06-12T20:00:00",
+            &next_week.format("%Y-%m-%dT20:00:00").to_string(),
+        );
+        request(&client, "POST", "/api/events", Some(&upcoming));
+        let schedule = request(&client, "GET", "/", None);
+        assert!(schedule.contains(r#""@type":"GeoCoordinates""#));
+
+        // Removing the coordinates clears them.
+        request(&client, "PUT", &uri, Some(LOCATION));
+        let location: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", &uri, None)).unwrap();
+        assert_eq!(location["coordinates"], serde_json::Value::Null);
+
+        let invalid = with_coordinates.replace("50.7766", "95.0");
+        let response = client
+            .put(uri)
+            .header(ContentType::JSON)
+            .body(invalid)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn location_pages_show_the_upcoming_occurrences() {
+        let client = client();
+        let with_coordinates = LOCATION.replace(
+            "\"address\"",
+            r#""coordinates": { "latitude": 50.7766, "longitude": 6.0834 }, "manual_coordinates": true, "address""#,
+        );
+        let location_id = id(&request(
+            &client,
+            "POST",
+            "/api/locations",
+            Some(&with_coordinates),
+        ));
+        let other_location_id = id(&request(
+            &client,
+            "POST",
+            "/api/locations",
+            Some(&LOCATION.replace("Chico Mendès", "Sencillito")),
+        ));
+        let next_week = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = |location_id: &str, title: &str| {
+            event(location_id)
+                .replace(
+                    "2019-06-12T20:00:00",
+                    &next_week.format("%Y-%m-%dT20:00:00").to_string(),
+                )
+                .replace("Social Dance", title)
+        };
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&upcoming(&location_id, "Social Dance")),
+        );
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&upcoming(&other_location_id, "Practice")),
+        );
+
+        let page = request(&client, "GET", &format!("/orte/{}", location_id), None);
+        assert!(page.contains("<h1>Chico Mendès</h1>"));
+        assert!(page.contains("marker=50.7766,6.0834"));
+        assert!(page.contains("Social Dance"));
+        assert!(!page.contains("Practice"));
+
+        let page = request(
+            &client,
+            "GET",
+            &format!("/orte/{}", other_location_id),
+            None,
+        );
+        assert!(!page.contains("<iframe"));
+        assert!(page.contains(&format!(r#"href="/orte/{}""#, other_location_id)));
+
+        let response = client.get(format!("/orte/{}", Uuid::new_v4())).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn statistics_page() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        request(&client, "POST", "/api/events", Some(&event(&location_id)));
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id).replace("2019-06-12", "2020-06-12")),
+        );
+
+        let page = request(&client, "GET", "/statistik", None);
+        assert!(page.contains("<dd>6</dd>"));
+        assert!(page.contains("Chico Mendès (2 Termine)"));
+        assert!(page.contains("<th>2019</th><td>1</td>"));
+        assert!(page.contains("<th>2020</th><td>1</td>"));
+
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id).replace("2019-06-12", "2021-06-12")),
+        );
+        let page = request(&client, "GET", "/statistik", None);
+        assert!(page.contains("<dd>9</dd>"));
+
+        let response = client.post("/api/admin/cache/invalidate").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+
+    #[test]
+    fn occurrence_page() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        request(&client, "POST", "/api/events", Some(&event(&location_id)));
+        let locations: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            "/api/locations_with_occurrences",
+            None,
+        ))
+        .unwrap();
+        let occurrence_id = locations[&location_id]["occurrences"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+
+        let page = request(&client, "GET", &format!("/termine/{}", occurrence_id), None);
+        assert!(page.contains(&format!(
+            r#"Mi, 12.06., 20:00 - <a href="/orte/{}">Chico Mendès</a>"#,
+            location_id
+        )));
+        assert!(page.contains("Pontstraße 74-76<br>52062 Aachen"));
+        assert!(page.contains(
+            "https://www.openstreetmap.org/search?query=Pontstra%C3%9Fe%2074-76,%2052062%20Aachen"
+        ));
+        assert!(page.contains("Einmal im Monat."));
+        assert!(page.contains(r#"href="/veranstaltungen/social-dance""#));
+
+        let missing = client
+            .get(format!("/termine/{}", Uuid::new_v4()))
+            .dispatch();
+        assert_eq!(missing.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn undecided_and_unknown_locations() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let next_week = (chrono::Local::now() + chrono::Duration::days(7))
+            .naive_local()
+            .date();
+        let upcoming_event = |location: &str| {
+            event("")
+                .replace("2019-06-12T20:00:00", &format!("{}T20:00:00", next_week))
+                .replace(
+                    r#""location_id": """#,
+                    &format!(r#""location_id": {}"#, location),
+                )
+        };
+
+        let undecided_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&upcoming_event("null")),
+        ));
+        let undecided: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}", undecided_id),
+            None,
+        ))
+        .unwrap();
+        assert!(undecided["occurrences"][0]["location_id"].is_null());
+        let page = request(&client, "GET", "/veranstaltungen/social-dance", None);
+        assert!(page.contains("Steht noch nicht fest."));
+
+        let response = client
+            .post("/api/events")
+            .header(ContentType::JSON)
+            .body(upcoming_event(&format!(r#""{}""#, Uuid::new_v4())))
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let decided_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&upcoming_event(&format!(r#""{}""#, location_id))),
+        ));
+        request(
+            &client,
+            "DELETE",
+            &format!("/api/locations/{}", location_id),
+            None,
+        );
+        let decided: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}", decided_id),
+            None,
+        ))
+        .unwrap();
+        assert!(decided["occurrences"][0]["location_id"].is_null());
+    }
+
+    #[test]
+    fn stream_url_is_shown_shortly_before_start() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let with_stream = |start: chrono::NaiveDateTime, title: &str| {
+            event(&location_id)
+                .replace(
+                    "2019-06-12T20:00:00",
+                    &start.format("%Y-%m-%dT%H:%M:00").to_string(),
+                )
+                .replace("Social Dance", title)
+                .replace(
+                    r#""duration": 180,"#,
+                    &format!(
+                        r#""duration": 180, "stream_url": "https://stream.example/{}","#,
+                        title
+                    ),
+                )
+        };
+        let now = chrono::Local::now().naive_local();
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&with_stream(now + chrono::Duration::minutes(20), "soon")),
+        );
+        request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&with_stream(now + chrono::Duration::days(7), "later")),
+        );
+
+        let schedule = request(&client, "GET", "/", None);
+        assert!(schedule.contains(r#"href="https://stream.example/soon""#));
+        assert!(!schedule.contains("https://stream.example/later"));
+
+        let script = event(&location_id).replace(
+            r#""duration": 180,"#,
+            r#""duration": 180, "stream_url": "javascript:alert(1)","#,
+        );
+        let response = client
+            .post("/api/events")
+            .header(ContentType::JSON)
+            .body(script)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn cancelled_occurrences_are_struck_through() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let start = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let cancelled = event(&location_id)
+            .replace(
+                "2019-06-12T20:00:00",
+                &start.format("%Y-%m-%dT20:00:00").to_string(),
+            )
+            .replace(
+                r#""duration": 180,"#,
+                r#""duration": 180, "cancelled": true, "cancellation_reason": "Krankheit","#,
+            );
+        let event_id = id(&request(&client, "POST", "/api/events", Some(&cancelled)));
+
+        let stored: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}", event_id),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(stored["occurrences"][0]["cancelled"], true);
+        assert_eq!(stored["occurrences"][0]["cancellation_reason"], "Krankheit");
+
+        let schedule = request(&client, "GET", "/", None);
+        assert!(schedule.contains(r#"class="event cancelled""#));
+        assert!(schedule.contains("Fällt aus: Krankheit"));
+        assert!(schedule.contains("https://schema.org/EventCancelled"));
+
+        let calendar = request(
+            &client,
+            "GET",
+            &format!("/api/events/{}/calendar.ics", event_id),
+            None,
+        );
+        assert!(calendar.contains("STATUS:CANCELLED"));
+    }
+
+    #[test]
+    fn drafts_are_only_listed_for_the_admin() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let start = chrono::Local::now().naive_local() + chrono::Duration::days(7);
+        let upcoming = event(&location_id).replace(
+            "2019-06-12T20:00:00",
+            &start.format("%Y-%m-%dT20:00:00").to_string(),
+        );
+        let draft = upcoming.replace(
+            r#""description": "Einmal im Monat.""#,
+            r#""description": "Einmal im Monat.", "published": false"#,
+        );
+        let event_id = id(&request(&client, "POST", "/api/events", Some(&draft)));
+
+        let overview: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/", None)).unwrap();
+        assert!(overview["events"].get(&event_id).is_none());
+        assert!(!request(&client, "GET", "/", None).contains("Social Dance"));
+        let response = client.get(format!("/api/events/{}", event_id)).dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+
+        let overview: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/?drafts=true", None)).unwrap();
+        assert_eq!(overview["events"][&event_id]["event"]["published"], false);
+        let stored: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}?drafts=true", event_id),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(stored["occurrences"].as_array().unwrap().len(), 1);
+
+        request(
+            &client,
+            "PUT",
+            &format!("/api/events/{}", event_id),
+            Some(&upcoming),
+        );
+        let published: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}", event_id),
+            None,
+        ))
+        .unwrap();
+        // Editing a draft replaces its occurrences instead of adding to them.
+        assert_eq!(published["occurrences"].as_array().unwrap().len(), 1);
+        assert!(request(&client, "GET", "/", None).contains("Social Dance"));
+    }
+
+    #[test]
+    fn scheduled_events_are_hidden_until_published() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let now = chrono::Local::now().naive_local();
+        let with_publish_at = |publish_at: chrono::NaiveDateTime| {
+            event(&location_id)
+                .replace(
+                    "2019-06-12T20:00:00",
+                    &(now + chrono::Duration::days(7))
+                        .format("%Y-%m-%dT20:00:00")
+                        .to_string(),
+                )
+                .replace(
+                    r#""description": "Einmal im Monat.""#,
+                    &format!(
+                        r#""description": "Einmal im Monat.", "publish_at": "{}""#,
+                        publish_at.format("%Y-%m-%dT%H:%M:%S")
+                    ),
+                )
+        };
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&with_publish_at(now + chrono::Duration::days(1))),
+        ));
+
+        assert!(!request(&client, "GET", "/", None).contains("Social Dance"));
+        let overview: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/", None)).unwrap();
+        assert!(overview["events"].get(&event_id).is_none());
+        let overview: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/?drafts=true", None)).unwrap();
+        assert!(overview["events"].get(&event_id).is_some());
+
+        request(
+            &client,
+            "PUT",
+            &format!("/api/events/{}", event_id),
+            Some(&with_publish_at(now - chrono::Duration::minutes(1))),
+        );
+        assert!(request(&client, "GET", "/", None).contains("Social Dance"));
+    }
+
+    #[test]
+    fn audit_log() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+        request(
+            &client,
+            "PUT",
+            &format!("/api/events/{}", event_id),
+            Some(&event(&location_id).replace("T20:00:00", "T21:00:00")),
+        );
+
+        let log: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", "/api/audit", None)).unwrap();
+        let actions: Vec<(&str, &str)> = log
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                (
+                    entry["entity"].as_str().unwrap(),
+                    entry["action"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                ("event", "update"),
+                ("event", "create"),
+                ("location", "create")
+            ]
+        );
+
+        let update = &log[0];
+        assert_eq!(update["entity_id"], event_id.as_str());
+        // Only what changed is listed.
+        assert!(update["changes"].get("event").is_none());
+        let occurrences = &update["changes"]["occurrences"];
+        assert_eq!(occurrences["before"][0]["start"], "2019-06-12T20:00:00");
+        assert_eq!(occurrences["after"][0]["start"], "2019-06-12T21:00:00");
+
+        let next_page = request(&client, "GET", "/api/audit?page=2", None);
+        assert_eq!(next_page, "[]");
+    }
+
+    /// Creates an event without occurrences and returns its id.
+    fn event_without_occurrences(client: &Client, location_id: &str) -> String {
+        let without_occurrences = event(location_id)
+            .replace(r#""occurrences": [{"#, r#""ignored": [{"#)
+            .replace(
+                r#"}]
+            }"#,
+                r#"}], "occurrences": []
+            }"#,
+            );
+        id(&request(
+            client,
+            "POST",
+            "/api/events",
+            Some(&without_occurrences),
+        ))
+    }
+
+    #[test]
+    fn recurrence_endpoints() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = event_without_occurrences(&client, &location_id);
+        let event_uri = format!("/api/events/{}", event_id);
+        let recurrences_uri = format!("{}/recurrences", event_uri);
+        let occurrence_count = || {
+            let event: serde_json::Value =
+                serde_json::from_str(&request(&client, "GET", &event_uri, None)).unwrap();
+            event["occurrences"].as_array().unwrap().len()
+        };
+
+        let next_week = (chrono::Local::now() + chrono::Duration::days(7))
+            .naive_local()
+            .date();
+        let recurrence = |interval_weeks: u32| {
+            format!(
+                r#"{{
+                    "start": "{}T20:00:00",
+                    "duration": 180,
+                    "location_id": "{}",
+                    "interval_weeks": {},
+                    "until": "{}"
+                }}"#,
+                next_week,
+                location_id,
+                interval_weeks,
+                next_week + chrono::Duration::days(21)
+            )
+        };
+        let recurrence_id = id(&request(
+            &client,
+            "POST",
+            &recurrences_uri,
+            Some(&recurrence(1)),
+        ));
+        assert_eq!(occurrence_count(), 4);
+        let recurrences: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", &recurrences_uri, None)).unwrap();
+        assert_eq!(recurrences[&recurrence_id]["interval_weeks"], 1);
+
+        let recurrence_uri = format!("{}/{}", recurrences_uri, recurrence_id);
+        request(&client, "PUT", &recurrence_uri, Some(&recurrence(2)));
+        assert_eq!(occurrence_count(), 2);
+
+        // Saving the event unchanged keeps its occurrences attributed to the recurrence.
+        let unchanged = request(&client, "GET", &event_uri, None);
+        request(&client, "PUT", &event_uri, Some(&unchanged));
+        request(&client, "DELETE", &recurrence_uri, None);
+        assert_eq!(occurrence_count(), 0);
+
+        let response = client
+            .post(recurrences_uri.clone())
+            .header(ContentType::JSON)
+            .body(recurrence(0))
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn recurrence_exceptions() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let other_location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = event_without_occurrences(&client, &location_id);
+        let event_uri = format!("/api/events/{}", event_id);
+        let recurrences_uri = format!("{}/recurrences", event_uri);
+
+        let next_week = (chrono::Local::now() + chrono::Duration::days(7))
+            .naive_local()
+            .date();
+        let week = |weeks: i64| next_week + chrono::Duration::weeks(weeks);
+        let recurrence = |exceptions: &str| {
+            format!(
+                r#"{{
+                    "start": "{}T20:00:00",
+                    "duration": 180,
+                    "location_id": "{}",
+                    "interval_weeks": 1,
+                    "until": "{}",
+                    "exceptions": [{}]
+                }}"#,
+                next_week,
+                location_id,
+                week(3),
+                exceptions
+            )
+        };
+        let exceptions = format!(
+            r#"{{ "date": "{}", "cancelled": true }},
+            {{ "date": "{}", "start": "{}T21:00:00", "location_id": "{}" }}"#,
+            week(1),
+            week(2),
+            week(2),
+            other_location_id
+        );
+        let recurrence_id = id(&request(
+            &client,
+            "POST",
+            &recurrences_uri,
+            Some(&recurrence(&exceptions)),
+        ));
+
+        let event: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", &event_uri, None)).unwrap();
+        let occurrences: Vec<(String, String)> = event["occurrences"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|occurrence| {
+                (
+                    occurrence["start"].as_str().unwrap().to_string(),
+                    occurrence["location_id"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(occurrences.len(), 3);
+        assert!(!occurrences
+            .iter()
+            .any(|(start, _)| start.starts_with(&week(1).to_string())));
+        assert!(occurrences.contains(&(format!("{}T21:00:00", week(2)), other_location_id.clone())));
+
+        let recurrences: serde_json::Value =
+            serde_json::from_str(&request(&client, "GET", &recurrences_uri, None)).unwrap();
+        assert_eq!(
+            recurrences[&recurrence_id]["exceptions"][0]["cancelled"],
+            true
+        );
+
+        let off_schedule = format!(r#"{{ "date": "{}", "cancelled": true }}"#, next_week.succ());
+        let response = client
+            .put(format!("{}/{}", recurrences_uri, recurrence_id))
+            .header(ContentType::JSON)
+            .body(recurrence(&off_schedule))
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn overview_endpoints() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        request(&client, "POST", "/api/events", Some(&event(&location_id)));
+
+        assert_json_snapshot(
+            "overview",
+            &request(&client, "GET", "/api?after=2019-06-01T00:00:00", None),
+        );
+        assert_json_snapshot(
+            "locations_with_occurrences",
+            &request(&client, "GET", "/api/locations_with_occurrences", None),
+        );
+    }
+
+    #[test]
+    fn schedule_diff_compares_with_the_week_before() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        // The 3rd and the 10th of June 2019 are the Mondays of the weeks 23 and 24.
+        let event = format!(
+            r#"{{
+                "event": {{ "title": "Social Dance", "teaser": "Zum Tanzen.", "description": "" }},
+                "occurrences": [
+                    {{ "start": "2019-06-03T00:00:00", "duration": 60, "location_id": "{0}" }},
+                    {{ "start": "2019-06-05T20:00:00", "duration": 180, "location_id": "{0}" }},
+                    {{ "start": "2019-06-12T21:00:00", "duration": 180, "location_id": "{0}" }}
+                ]
+            }}"#,
+            location_id
+        );
+        request(&client, "POST", "/api/events", Some(&event));
+
+        let diff: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            "/api/schedule/diff?week=2019-W24",
+            None,
+        ))
+        .unwrap();
+        assert_eq!(diff["new"].as_array().unwrap().len(), 0);
+        assert_eq!(diff["cancelled"].as_array().unwrap().len(), 0);
+        let moved = diff["moved"].as_array().unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(
+            moved[0]["occurrence"]["occurrence"]["start"],
+            "2019-06-12T21:00:00"
+        );
+        assert_eq!(moved[0]["previous"]["start"], "2019-06-05T20:00:00");
+
+        // The occurrence at midnight on Monday belongs to week 23.
+        let diff: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            "/api/schedule/diff?week=2019-W23",
+            None,
+        ))
+        .unwrap();
+        assert_eq!(diff["new"].as_array().unwrap().len(), 2);
+
+        let response = client.get("/api/schedule/diff?week=2019-24").dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+
+    #[test]
+    fn occurrences_are_filtered_by_weekday_and_time() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        // The 12th of June 2019 is a Wednesday.
+        let event = format!(
+            r#"{{
+                "event": {{ "title": "Social Dance", "teaser": "Zum Tanzen.", "description": "" }},
+                "occurrences": [
+                    {{ "start": "2019-06-12T20:00:00", "duration": 180, "location_id": "{0}" }},
+                    {{ "start": "2019-06-12T18:00:00", "duration": 60, "location_id": "{0}" }},
+                    {{ "start": "2019-06-13T20:00:00", "duration": 180, "location_id": "{0}" }},
+                    {{ "start": "2019-06-16T19:00:00", "duration": 120, "location_id": "{0}" }}
+                ]
+            }}"#,
+            location_id
+        );
+        request(&client, "POST", "/api/events", Some(&event));
+        let starts = |query: &str| -> Vec<String> {
+            let overview: serde_json::Value =
+                serde_json::from_str(&request(&client, "GET", &format!("/api?{}", query), None))
+                    .unwrap();
+            let mut starts: Vec<String> = overview["events"]
+                .as_object()
+                .unwrap()
+                .values()
+                .flat_map(|event| event["occurrences"].as_array().unwrap().clone())
+                .map(|occurrence| occurrence["start"].as_str().unwrap().to_string())
+                .collect();
+            starts.sort();
+            starts
+        };
+
+        assert_eq!(
+            starts("weekday=wednesday&time_from=19:00"),
+            vec!["2019-06-12T20:00:00"]
+        );
+        assert_eq!(
+            starts("weekday=wed&weekday=Sunday&time_until=19:00"),
+            vec!["2019-06-12T18:00:00", "2019-06-16T19:00:00"]
+        );
+        assert_eq!(starts("time_from=19:00&time_until=19:00").len(), 1);
+
+        assert_eq!(
+            starts("after=2019-06-12T19:00:00&before=2019-06-14T00:00:00&time_from=19:00"),
+            vec!["2019-06-12T20:00:00", "2019-06-13T20:00:00"]
+        );
+
+        for invalid in &[
+            "weekday=mittwoch",
+            "time_from=7pm",
+            "time_from=20:00&time_until=19:00",
+            "after=2019-06-14T00:00:00&before=2019-06-12T00:00:00",
+        ] {
+            let response = client.get(format!("/api?{}", invalid)).dispatch();
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        }
+    }
+
+    #[test]
+    fn occurrences_are_filtered_by_date() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event = format!(
+            r#"{{
+                "event": {{ "title": "Social Dance", "teaser": "Zum Tanzen.", "description": "" }},
+                "occurrences": [
+                    {{ "start": "2019-06-11T23:30:00", "duration": 60, "location_id": "{0}" }},
+                    {{ "start": "2019-06-12T00:00:00", "duration": 60, "location_id": "{0}" }},
+                    {{ "start": "2019-06-26T23:59:00", "duration": 60, "location_id": "{0}" }},
+                    {{ "start": "2019-06-27T00:00:00", "duration": 60, "location_id": "{0}" }}
+                ]
+            }}"#,
+            location_id
+        );
+        let event_id = id(&request(&client, "POST", "/api/events", Some(&event)));
+        let starts = |query: &str| -> Vec<String> {
+            let entry: serde_json::Value = serde_json::from_str(&request(
+                &client,
+                "GET",
+                &format!("/api/events/{}?{}", event_id, query),
+                None,
+            ))
+            .unwrap();
+            entry["occurrences"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|occurrence| occurrence["start"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            starts("from=2019-06-12&until=2019-06-26"),
+            vec!["2019-06-12T00:00:00", "2019-06-26T23:59:00"]
+        );
+        assert_eq!(starts("until=2019-06-11"), vec!["2019-06-11T23:30:00"]);
+        // The last day chrono can represent has no following day to compare against.
+        assert_eq!(starts("until=%2B262143-12-31").len(), 4);
+        request(&client, "GET", "/api?until=%2B262143-12-31", None);
+
+        for invalid in &[
+            "from=12.06.2019",
+            "until=2019-06-31",
+            "from=2019-06-26&until=2019-06-12",
+        ] {
+            let response = client.get(format!("/api?{}", invalid)).dispatch();
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+        }
+    }
+
+    fn submission(location_id: &str) -> String {
+        format!(
+            r#"{{
+                "title": "Blues Night",
+                "teaser": "Zum Tanzen.",
+                "description": "Organisiert von Blues Aachen.",
+                "organizer_name": "Kim",
+                "organizer_email": "kim@example.com",
+                "start": "2019-06-14T21:00:00",
+                "duration": 120,
+                "location_id": "{}"
+            }}"#,
+            location_id
+        )
+    }
+
+    #[test]
+    fn submission_endpoints() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+
+        let approved = id(&request(
+            &client,
+            "POST",
+            "/api/submissions",
+            Some(&submission(&location_id)),
+        ));
+        assert_json_snapshot(
+            "submissions_pending",
+            &request(&client, "GET", "/api/submissions", None),
+        );
+
+        let rejected = id(&request(
+            &client,
+            "POST",
+            "/api/submissions",
+            Some(&submission(&location_id).replace("Blues Night", "Spam")),
+        ));
+
+        assert_json_snapshot(
+            "submissions_reject",
+            &request(
+                &client,
+                "POST",
+                &format!("/api/submissions/{}/reject", rejected),
+                None,
+            ),
+        );
+        let event_id = id(&request(
+            &client,
+            "POST",
+            &format!("/api/submissions/{}/approve", approved),
+            None,
+        ));
+        assert_json_snapshot(
+            "submissions_approved_event",
+            &request(&client, "GET", &format!("/api/events/{}", event_id), None),
+        );
+        assert_eq!(request(&client, "GET", "/api/submissions", None), "{}");
+    }
+
+    #[test]
+    fn invalid_submissions_are_rejected() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+
+        let invalid = submission(&location_id)
+            .replace(r#""Blues Night""#, r#""  ""#)
+            .replace(r#""duration": 120"#, r#""duration": 0"#);
+        let mut response = client
+            .post("/api/submissions")
+            .header(ContentType::JSON)
+            .body(invalid)
+            .dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_eq!(
+            response.body_string().unwrap(),
+            "event.title: The title must not be empty.\n\
+             occurrences[0].duration: The duration must be at least one minute."
+        );
+        assert_eq!(request(&client, "GET", "/api/submissions", None), "{}");
+    }
+
+    #[test]
+    fn comment_endpoints() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+        let response = client
+            .post(format!("/veranstaltungen/{}/kommentare", event_id))
+            .header(ContentType::Form)
+            .body("name=Kim&text=Ist+das+anf%C3%A4ngerfreundlich%3F&homepage=")
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let pending = request(&client, "GET", "/api/comments", None);
+        let comment_id = serde_json::from_str::<Map<String, serde_json::Value>>(&pending)
+            .unwrap()
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        let approved = request(
+            &client,
+            "POST",
+            &format!("/api/comments/{}/approve", comment_id),
+            None,
+        );
+        let approved: serde_json::Value = serde_json::from_str(&approved).unwrap();
+        assert_eq!(approved["approved"], true);
+        assert_eq!(approved["text"], "Ist das anfängerfreundlich?");
+        assert_eq!(request(&client, "GET", "/api/comments", None), "{}");
+    }
+
+    #[test]
+    fn weeks_without_events_are_gaps() {
+        use chrono::{Datelike, Duration, Local};
+
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let today = Local::today().naive_local();
+        let next_monday =
+            today + Duration::days(7 - i64::from(today.weekday().num_days_from_monday()));
+        let next_week = next_monday.and_hms(20, 0, 0);
+        let week_after = next_week + Duration::weeks(1);
+        let event = format!(
+            r#"{{
+                "event": {{ "title": "Social Dance", "teaser": "", "description": "" }},
+                "occurrences": [
+                    {{ "start": "{}", "duration": 180, "location_id": "{}" }},
+                    {{ "start": "{}", "duration": 180, "location_id": "{}", "cancelled": true }}
+                ]
+            }}"#,
+            next_week.format("%Y-%m-%dT%H:%M:%S"),
+            location_id,
+            week_after.format("%Y-%m-%dT%H:%M:%S"),
+            location_id
+        );
+        request(&client, "POST", "/api/events", Some(&event));
+
+        let gaps: Vec<serde_json::Value> =
+            serde_json::from_str(&request(&client, "GET", "/api/reports/gaps", None)).unwrap();
+        let mondays: Vec<String> = gaps
+            .iter()
+            .map(|gap| gap["monday"].as_str().unwrap().to_string())
+            .collect();
+        let date = |date: chrono::NaiveDate| date.format("%Y-%m-%d").to_string();
+        assert!(!mondays.contains(&date(next_monday)));
+        // The only occurrence of that week is cancelled.
+        assert!(mondays.contains(&date(next_monday + Duration::weeks(1))));
+        assert_eq!(
+            gaps[0]["week"],
+            next_monday.pred().format("%G-W%V").to_string()
+        );
+    }
+
+    #[test]
+    fn report_endpoints() {
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        request(&client, "POST", "/api/events", Some(&event(&location_id)));
+
+        assert_json_snapshot(
+            "reports_locations",
+            &request(&client, "GET", "/api/reports/locations", None),
+        );
+    }
+
+    #[test]
+    fn overview_is_paginated() {
+        use crate::fixtures::{FixtureOptions, Fixtures};
+
+        let client = client();
+        Fixtures::generate(&FixtureOptions {
+            events: 5,
+            ..FixtureOptions::default()
+        })
+        .insert(&Store::detached(client.rocket()).unwrap())
+        .unwrap();
+
+        let page = |query: &str| -> serde_json::Value {
+            serde_json::from_str(&request(&client, "GET", &format!("/api/?{}", query), None))
+                .unwrap()
+        };
+        let first = page("limit=2");
+        let second = page("limit=2&offset=2");
+        let last = page("limit=2&offset=4");
+        assert_eq!(first["total_events"], 5);
+        assert_eq!(first["offset"], 0);
+        let events: Vec<&serde_json::Value> = [&first, &second, &last]
+            .iter()
+            .flat_map(|page| page["events"].as_array().unwrap())
+            .collect();
+        assert_eq!(events.len(), 5);
+        let mut ids: Vec<&str> = events
+            .iter()
+            .map(|event| event[0].as_str().unwrap())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 5);
+        // The events are ordered by their first occurrence across the pages.
+        let first_starts: Vec<&str> = events
+            .iter()
+            .map(|event| {
+                event[1]["occurrences"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|occurrence| occurrence["start"].as_str().unwrap())
+                    .min()
+                    .unwrap()
+            })
+            .collect();
+        let mut sorted = first_starts.clone();
+        sorted.sort();
+        assert_eq!(first_starts, sorted);
+
+        // Without a limit, the whole overview is returned as before.
+        assert!(page("")["total_events"].is_null());
+
+        for query in &["limit=0", "limit=201", "limit=two", "limit=2&offset=-1"] {
+            let response = client.get(format!("/api/?{}", query)).dispatch();
+            assert_eq!(response.status(), Status::UnprocessableEntity, "{}", query);
+        }
+    }
+
+    #[test]
+    fn occurrences_are_suggested_from_history() {
+        use chrono::{Datelike, Local, NaiveDateTime, Weekday};
+
+        let client = client();
+        let location_id = id(&request(&client, "POST", "/api/locations", Some(LOCATION)));
+        let event_id = id(&request(
+            &client,
+            "POST",
+            "/api/events",
+            Some(&event(&location_id)),
+        ));
+
+        let suggestion: serde_json::Value = serde_json::from_str(&request(
+            &client,
+            "GET",
+            &format!("/api/events/{}/suggestion", event_id),
+            None,
+        ))
+        .unwrap();
+        let start: NaiveDateTime = suggestion["start"].as_str().unwrap().parse().unwrap();
+        assert_eq!(start.weekday(), Weekday::Wed);
+        assert_eq!(start.time().to_string(), "20:00:00");
+        assert!(start.date() >= Local::today().naive_local());
+        assert_eq!(suggestion["duration"], 180);
+        assert_eq!(suggestion["location_id"], location_id.as_str());
+    }
+
+    #[test]
+    fn generated_fixtures_are_served() {
+        use crate::fixtures::{FixtureOptions, Fixtures};
+
+        let client = client();
+        let fixtures = Fixtures::generate(&FixtureOptions {
+            events: 12,
+            locations: 3,
+            ..FixtureOptions::default()
+        });
+        fixtures
+            .insert(&Store::detached(client.rocket()).unwrap())
+            .unwrap();
+
+        let locations: Map<String, serde_json::Value> =
+            serde_json::from_str(&request(&client, "GET", "/api/locations", None)).unwrap();
+        assert_eq!(locations.len(), 3);
+        let events: Map<String, serde_json::Value> =
+            serde_json::from_str(&request(&client, "GET", "/api/events", None)).unwrap();
+        assert_eq!(events.len(), 12);
+        // Regular events have been expanded, workshops last three days at most.
+        let occurrence_counts = events
+            .values()
+            .map(|entry| entry["occurrences"].as_array().unwrap().len());
+        let regular = fixtures
+            .events
+            .iter()
+            .filter(|fixture| fixture.recurrence.is_some())
+            .count();
+        assert_eq!(
+            occurrence_counts.filter(|count| *count > 3).count(),
+            regular
+        );
     }
 }
@@ -0,0 +1,697 @@
+use maud::{html, Markup, DOCTYPE};
+
+/// A documented endpoint. Paths are relative to the API prefix.
+struct Endpoint {
+    method: &'static str,
+    path: &'static str,
+    description: &'static str,
+    example: Option<&'static str>,
+}
+
+const FILTER_DESCRIPTION: &str =
+    "Occurrences can be filtered with the query parameters \
+     after and before, which take a date and time like 2019-06-12T20:00:00. \
+     To list only some days, like the next two weeks, from and until take a date like \
+     2019-06-12, and include occurrences starting on that day. \
+     To find regular events that fit a schedule, weekday takes a day of the week like \
+     wednesday and can be given more than once, and time_from and time_until take a time \
+     of day like 19:00 that the occurrences start at or after, respectively at or before. \
+     Events that are not published yet, or are scheduled to be published later, are left \
+     out unless drafts=true is given.";
+
+const ERROR_DESCRIPTION: &str =
+    "Failures are answered with a message in plain text and a status of 404 if the item \
+     does not exist, 503 if the database is busy, so that the request can be retried, and \
+     500 if the database failed otherwise.";
+
+const ENDPOINTS: &[Endpoint] = &[
+    Endpoint {
+        method: "GET",
+        path: "/",
+        description: "All locations and all events with their occurrences.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/?limit=<limit>&offset=<offset>",
+        description: "A page of the overview, for fetching it incrementally. It holds up to \
+                      limit events, at most 200, after skipping the first offset ones, by \
+                      default none. Events are listed as [id, event] pairs in order of their \
+                      first occurrence, those without any last. Every page has all \
+                      locations, the offset and limit, and the total_events and \
+                      total_occurrences on all pages. Limits or offsets that are not numbers \
+                      in range fail with 422.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/locations_with_occurrences",
+        description: "All locations, each with the occurrences taking place there.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/search?q=<query>",
+        description: "The published events and pages and the questions of the FAQ page that \
+                      contain every word of the query, ignoring case. Each result has a type \
+                      of event, page, or faq, a title, an excerpt, and the URL of its page. \
+                      Results are ordered by type and then by title.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/schedule/diff?week=<week>",
+        description: "How the schedule of an ISO week like 2019-W24, by default the current \
+                      one, differs from the week before. Occurrences of events that did not \
+                      take place the week before are new. Those that took place on another \
+                      day, at another time, or at another location are moved, along with \
+                      their previous occurrence. Cancelled occurrences are listed as well.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/locations",
+        description: "All locations by id.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/locations/<id>",
+        description: "A single location.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/locations",
+        description: "Creates a location and returns its id. The address can also be given \
+                      as text like \"Pontstraße 74-76, 52062 Aachen\", which is split into \
+                      its parts. Addresses without a German, Belgian, or Dutch postal code \
+                      fail with 422. The coordinates are looked up from the address in the \
+                      background if a geocoding service is configured. To enter them by \
+                      hand instead, give them in degrees with manual_coordinates set to \
+                      true. Latitudes beyond 90 or longitudes beyond 180 fail with 422.",
+        example: Some(
+            r#"{
+  "name": "Chico Mendès",
+  "address": { "street": "Pontstraße 74-76", "postal_code": "52062", "city": "Aachen" },
+  "coordinates": { "latitude": 50.7766, "longitude": 6.0834 },
+  "manual_coordinates": true
+}"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/locations/<id>",
+        description: "Replaces a location and returns the new version. Coordinates that \
+                      were looked up are kept unless the address changes.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/locations/<id>",
+        description: "Moves a location to the trash and returns it. The occurrences taking \
+                      place there become undecided.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events",
+        description: "All events with their occurrences by id.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events/<id>",
+        description: "A single event with its occurrences.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events/<id>/related",
+        description: "Up to five upcoming events taking place at the same locations, \
+                      most similar first.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events/<id>/suggestion",
+        description: "Defaults for a new occurrence of the event, taken from its past \
+                      occurrences, or from its planned ones if none took place yet. The \
+                      start is the next date after the last occurrence on the most common \
+                      day of the week and time. The duration and location_id are the most \
+                      common ones, and the most recent of those if several are as common. \
+                      Cancelled occurrences are ignored, and each is null without any \
+                      occurrences to go by.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events/<id>/calendar.ics",
+        description: "The upcoming occurrences of a single event as an iCalendar feed \
+                      to subscribe to. Unlike the other endpoints, this does not return JSON.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events/<id>/announcement",
+        description: "A plain text announcing the event's upcoming occurrences and locations, \
+                      ready to be posted on social media. Its layout is configured as \
+                      announcement_template with the placeholders {title}, {teaser}, {dates}, \
+                      {locations} and {link}.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events/<id>/images",
+        description: "The images uploaded for the event by their id, with the file_name they \
+                      are served under at /media/<file_name>. Add ?size=thumbnail, card, or \
+                      full for a version scaled down to 200, 600, or 1600 pixels.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/events/<id>/images",
+        description: "Uploads every file of a multipart/form-data body of at most 10 MiB as an \
+                      image of the event and returns their ids. PNG, JPEG, GIF, and WebP \
+                      images are accepted, anything else fails with 415. The files are stored \
+                      in the directory configured as media_dir, and scaled down with the \
+                      image_resize_command, ImageMagick's convert unless configured.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/events/<id>/images/<image_id>",
+        description: "Removes the image and its file, and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/events",
+        description: "Creates an event with its occurrences and returns its id. \
+                      Durations are given in minutes. An occurrence whose location has not \
+                      been decided yet has a location_id of null, unknown ids fail with 422. \
+                      The slug addressing the event's page \
+                      is derived from the title and kept when the event is updated. \
+                      Occurrences taking place online can have a stream_url, which the \
+                      website shows from 30 minutes before the start. Occurrences that \
+                      do not take place are marked as cancelled, optionally with a \
+                      cancellation_reason, and shown struck through instead of disappearing. \
+                      Events with published set to false are drafts, which are only listed \
+                      with drafts=true, so they can be prepared before they are announced. \
+                      Events with a publish_at date and time are treated as drafts until then. \
+                      Events without a title and occurrences without a duration or starting \
+                      before 2000 fail with 422, which lists each problem on its own line \
+                      like \"occurrences[0].duration: …\". \
+                      If occurrences overlap with those of other events at the same location, \
+                      the event is saved anyway and the X-Booking-Conflicts header lists \
+                      them as JSON, each with the location_id, the start, the other event's \
+                      event_id and event_title, and the other_start of its occurrence.",
+        example: Some(
+            r#"{
+  "event": {
+    "title": "Social Dance",
+    "teaser": "Zum Tanzen.",
+    "description": "Einmal im Monat."
+  },
+  "occurrences": [{
+    "start": "2019-06-12T20:00:00",
+    "duration": 180,
+    "location_id": "<location id>"
+  }]
+}"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/events/<id>",
+        description:
+            "Replaces an event with its occurrences. Fails with 423 if the event is locked. \
+             Like when creating, overlapping bookings are listed in the X-Booking-Conflicts \
+             header.",
+        example: None,
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/events/<id>/locked",
+        description: "Locks or unlocks an event and returns whether it was locked before.",
+        example: Some("true"),
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/events/<id>",
+        description: "Moves an event with its occurrences to the trash and returns it. \
+                      Fails with 423 if the event is locked.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/events/<id>/recurrences",
+        description: "The rules by which the event's occurrences repeat, by their ids.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/events/<id>/recurrences",
+        description: "Repeats an occurrence every interval_weeks weeks, optionally until a \
+                      date, and returns the id of the rule. The occurrences are created up \
+                      to the schedule horizon and can be edited like any other. Exceptions \
+                      cancel a single date or change its start or location. \
+                      Fails with 423 if the event is locked.",
+        example: Some(
+            r#"{
+  "start": "2019-06-12T20:00:00",
+  "duration": 180,
+  "location_id": "<location id>",
+  "interval_weeks": 2,
+  "until": "2019-12-31",
+  "exceptions": [
+    { "date": "2019-12-25", "cancelled": true },
+    { "date": "2019-07-10", "start": "2019-07-10T19:00:00" }
+  ]
+}"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/events/<id>/recurrences/<recurrence_id>",
+        description: "Replaces a rule and returns the previous version. Its upcoming \
+                      occurrences are recreated, past ones are kept. \
+                      Fails with 423 if the event is locked.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/events/<id>/recurrences/<recurrence_id>",
+        description: "Deletes a rule with its upcoming occurrences and returns it. \
+                      Past occurrences are kept. Fails with 423 if the event is locked.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/submissions",
+        description: "All events proposed by external organizers that await moderation.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/submissions",
+        description: "Proposes an event and returns the id of the submission. Submissions \
+                      without a title, without a duration, or starting before 2000 fail \
+                      with 422, which lists each problem on its own line.",
+        example: Some(
+            r#"{
+  "title": "Blues Night",
+  "teaser": "Zum Tanzen.",
+  "description": "Organisiert von Blues Aachen.",
+  "organizer_name": "Kim",
+  "organizer_email": "kim@example.com",
+  "start": "2019-06-14T21:00:00",
+  "duration": 120,
+  "location_id": "<location id>"
+}"#,
+        ),
+    },
+    Endpoint {
+        method: "POST",
+        path: "/submissions/<id>/approve",
+        description: "Turns a submission into an event and returns the event's id.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/submissions/<id>/reject",
+        description: "Removes a submission from the queue and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/trash",
+        description: "The deleted locations and events, each with when it was deleted.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/trash/locations/<id>/restore",
+        description: "Restores a deleted location and returns it. The occurrences that took \
+                      place there stay undecided.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/trash/events/<id>/restore",
+        description: "Restores a deleted event with its occurrences, recurrences, and \
+                      comments, and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/navigation",
+        description: "All entries of the website's navigation menu by id, including the \
+                      hidden ones.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/navigation",
+        description: "Adds an entry to the navigation menu and returns its id. Entries are \
+                      shown in ascending order of their position, unless visible is false.",
+        example: Some(
+            r#"{ "label": "Weihnachtsball", "url": "/veranstaltungen/weihnachtsball", "position": 1, "visible": true }"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/navigation/<id>",
+        description: "Replaces the entry and returns its previous version.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/navigation/<id>",
+        description: "Removes the entry and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/pages",
+        description: "All informational pages of the website by id, including the drafts.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/pages",
+        description: "Creates a page and returns its id. Published pages are served at their \
+                      slug, which may only contain lowercase letters, digits, and hyphens and \
+                      fails with 422 if the website uses it itself, or with 409 if another \
+                      page does. The body is written in Markdown with paragraphs, headings, \
+                      lists, emphasis, and links.",
+        example: Some(
+            r#"{ "slug": "ueber-uns", "title": "Über uns", "body": "Wir tanzen **Lindy Hop** in Aachen.", "published": true }"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/pages/<id>",
+        description: "Replaces the page and returns its previous version.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/pages/<id>",
+        description: "Removes the page and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/faq",
+        description: "All questions of the FAQ page by id.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/faq",
+        description: "Adds a question to the FAQ page and returns its id. Questions are \
+                      grouped by category and shown in ascending order of their position, \
+                      categories in the order of their first question. The answer is written \
+                      in Markdown.",
+        example: Some(
+            r#"{ "question": "Brauche ich einen Partner?", "answer": "Nein, in den Kursen wird gewechselt.", "category": "Kurse", "position": 1 }"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/faq/<id>",
+        description: "Replaces the question and returns its previous version.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/faq/<id>",
+        description: "Removes the question and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/redirects",
+        description: "All redirects of old URLs by id.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/redirects",
+        description: "Redirects visitors of an old path, e. g. of the previous WordPress \
+                      site, to a path of this site or a URL elsewhere, and returns the \
+                      redirect's id. A trailing slash of the path is ignored. The status is \
+                      301 unless 302, 307, or 308 is given. Redirects take precedence over \
+                      event and informational pages with the same path. A path that is \
+                      redirected already fails with 409.",
+        example: Some(
+            r#"{ "source": "/2018/05/social-dance/", "target": "/veranstaltungen/social-dance", "status": 301 }"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/redirects/<id>",
+        description: "Replaces the redirect and returns its previous version.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/redirects/<id>",
+        description: "Removes the redirect and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/newsletters",
+        description: "All drafted newsletter issues by id.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/newsletters/<id>",
+        description: "A single newsletter issue.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/newsletters",
+        description: "Stores a newsletter issue and returns its id. The date is the day it \
+                      is sent on.",
+        example: Some(
+            r#"{
+  "title": "Neuigkeiten im Juni",
+  "date": "2019-06-01",
+  "content": "Diesen Monat gibt es einen Workshop."
+}"#,
+        ),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/newsletters/generate?weeks=<weeks>",
+        description: "A draft newsletter issue listing the occurrences of the next weeks, \
+                      4 unless given, by date with their time, location, and teaser. It is \
+                      not stored, so an introduction can be added before it is created. \
+                      Weeks beyond the schedule horizon fail with 422.",
+        example: None,
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/newsletters/<id>",
+        description: "Replaces a newsletter issue and returns the previous version.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/newsletters/<id>",
+        description: "Deletes a newsletter issue and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/newsletters/<id>/send",
+        description: "Emails a newsletter issue to every confirmed subscriber it has not \
+                      reached yet, so sending it again retries those it failed for, and \
+                      returns the deliveries. Each mail links to /newsletter/abmelden/<token> \
+                      for its subscriber to unsubscribe. Fails with 503 if sending mail is \
+                      not configured.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/newsletters/<id>/deliveries",
+        description: "Per subscriber, when the newsletter issue was last sent to them and \
+                      the error if it did not reach them.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/subscribers",
+        description: "All subscribers of the newsletter by id.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/subscribers/import",
+        description: "Imports subscribers from CSV, one per line with email, optional name, \
+                      and the date they consented, as exported from the old mailing list. \
+                      They are confirmed already. Addresses that are subscribed already are \
+                      skipped and listed as duplicates. Malformed lines fail with 422 and \
+                      nothing is imported. Unlike the other endpoints, this does not take JSON.",
+        example: Some(
+            "email,name,consent date\n\
+             kim@example.com,Kim,2019-03-04 12:34:56\n\
+             alex@example.com,,2019-05-01",
+        ),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/comments",
+        description: "All comments on events that await approval.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/comments/<id>/approve",
+        description: "Approves a comment, so it is shown on the event's page.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/comments/<id>",
+        description: "Deletes a comment and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/admin/keys",
+        description: "All API keys by id, each with the number of requests made with it \
+                      today. Only hashes of the secrets are stored, so they are not listed.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/admin/keys",
+        description: "Issues an API key to a partner and returns its id and secret. This is \
+                      the only time the secret is shown. Partners send it in the X-Api-Key \
+                      header. Reading requests with a key are counted, and once more than \
+                      daily_quota were made on a day, they fail with 429 until midnight. \
+                      Unknown keys fail with 401. Requests without a key are not limited.",
+        example: Some(
+            r#"{
+  "name": "Tanzkalender NRW",
+  "daily_quota": 1000
+}"#,
+        ),
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/admin/keys/<id>",
+        description: "Renames an API key or changes its quota, and returns the previous \
+                      version. The secret stays the same.",
+        example: None,
+    },
+    Endpoint {
+        method: "DELETE",
+        path: "/admin/keys/<id>",
+        description: "Revokes an API key and returns it.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/admin/keys/<id>/usage",
+        description: "The number of requests made with an API key per day.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/links",
+        description: "The latest check of the links in the events' texts and the stream URLs \
+                      of their occurrences, listing the broken ones with their events. Links \
+                      are checked when the server starts and then every \
+                      link_check_interval_hours, 24 unless configured. Fails with 404 until \
+                      the first check finished.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/links/check",
+        description: "Checks the links now and returns the report. Only pages that are gone \
+                      or whose server failed count as broken. Unless an HTTP proxy is \
+                      configured as link_check_proxy, HTTPS pages cannot be requested, so \
+                      HTTPS links whose server is reachable are listed as unchecked.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/audit?page=<page>",
+        description: "The changes made to locations, events, and recurrences, most recent \
+                      first, 50 per page starting at page 1. Each lists the values that \
+                      changed with their values before and after.",
+        example: None,
+    },
+    Endpoint {
+        method: "POST",
+        path: "/admin/cache/invalidate",
+        description: "Recomputes the statistics on the next request instead of after an \
+                      hour, e.g. after the database was edited by hand.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/admin/maintenance",
+        description: "Whether the site is in maintenance.",
+        example: None,
+    },
+    Endpoint {
+        method: "PUT",
+        path: "/admin/maintenance",
+        description: "Switches maintenance on with true and off with false. During \
+                      maintenance, all public pages answer with 503, a Retry-After header, \
+                      and a notice, while the admin and the API keep working.",
+        example: Some("true"),
+    },
+    Endpoint {
+        method: "GET",
+        path: "/reports/locations",
+        description:
+            "Per location, the number of occurrences per month and the total hours booked.",
+        example: None,
+    },
+    Endpoint {
+        method: "GET",
+        path: "/reports/gaps",
+        description: "The weeks from the current one up to the schedule horizon in which no \
+                      published event takes place, each with its ISO week like 2019-W24 and the \
+                      date of its Monday. Cancelled occurrences are not counted.",
+        example: None,
+    },
+];
+
+#[get("/docs")]
+pub fn docs() -> Markup {
+    html! {
+        ( DOCTYPE )
+        html lang="en" {
+            head {
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                title { "Lindy Hop Aachen API" }
+                link href="/static/main.css" rel="stylesheet";
+            }
+            body {
+                h1 { "Lindy Hop Aachen API" }
+                p { "All endpoints exchange JSON. " ( FILTER_DESCRIPTION ) }
+                p { ( ERROR_DESCRIPTION ) }
+                @for endpoint in ENDPOINTS {
+                    section.endpoint {
+                        h2 { code { ( endpoint.method ) " /api" ( endpoint.path ) } }
+                        p { ( endpoint.description ) }
+                        @if let Some(example) = endpoint.example {
+                            pre { code { ( example ) } }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
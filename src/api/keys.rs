@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Local, NaiveDate};
+use rocket::fairing::{self, Fairing};
+use rocket::http::uri::Origin;
+use rocket::http::{Header, Method, Status};
+use rocket::request::{self, FromRequest};
+use rocket::response::status::Custom;
+use rocket::{Data, Outcome, Request, Response, Route};
+use rocket_contrib::json::Json;
+
+use crate::store::{ApiKey, DailyUsage, Id, IssuedApiKey, NewApiKey, Store};
+
+/// The header partners send their key in.
+const HEADER: &str = "X-Api-Key";
+
+type Result<T> = std::result::Result<T, Custom<String>>;
+
+fn today() -> NaiveDate {
+    Local::today().naive_local()
+}
+
+/// Where requests with an unknown key or a used up quota are sent, relative to the API
+/// prefix.
+const REJECTED_PATH: &str = "/keys/rejected";
+
+/// Counts the reading requests made to the API with a key, and sends them to `rejected`
+/// once the key's daily quota is used up, so that their handlers do not run. Requests
+/// without a key are served as before.
+pub struct ApiKeyFairing {
+    pub prefix: &'static str,
+}
+
+/// What became of the key sent with a request, if any.
+struct KeyCheck(Option<KeyOutcome>);
+
+enum KeyOutcome {
+    Unknown,
+    Counted(DailyUsage),
+}
+
+impl KeyOutcome {
+    /// How the request is answered instead, if it is rejected.
+    fn rejection(&self) -> Option<Custom<String>> {
+        match self {
+            KeyOutcome::Unknown => Some(Custom(
+                Status::Unauthorized,
+                format!("The {} header does not contain a known key.", HEADER),
+            )),
+            KeyOutcome::Counted(usage) if usage.is_exceeded() => Some(Custom(
+                Status::TooManyRequests,
+                format!(
+                    "The daily quota of {} requests is used up. It is reset at midnight.",
+                    usage.quota
+                ),
+            )),
+            KeyOutcome::Counted(_) => None,
+        }
+    }
+}
+
+impl Fairing for ApiKeyFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "API Key Fairing",
+            kind: fairing::Kind::Request | fairing::Kind::Response,
+        }
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        if request.method() != Method::Get || !request.uri().path().starts_with(self.prefix) {
+            return;
+        }
+        let secret = match request.headers().get_one(HEADER) {
+            Some(secret) => secret.to_string(),
+            None => return,
+        };
+        let store = match request.guard::<Store>().succeeded() {
+            Some(store) => store,
+            None => return,
+        };
+
+        let outcome = match store.count_api_request(&secret, today()) {
+            Ok(Some(usage)) => KeyOutcome::Counted(usage),
+            Ok(None) => KeyOutcome::Unknown,
+            Err(err) => {
+                // Partners should not suffer from our database hiccups.
+                eprintln!("Failed to count an API request: {:?}", err);
+                return;
+            }
+        };
+        if outcome.rejection().is_some() {
+            let path = format!("{}{}", self.prefix, REJECTED_PATH);
+            request.set_uri(Origin::parse_owned(path).unwrap());
+        }
+        request.local_cache(|| KeyCheck(Some(outcome)));
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if let Some(KeyOutcome::Counted(usage)) = &request.local_cache(|| KeyCheck(None)).0 {
+            response.set_header(Header::new("X-RateLimit-Limit", usage.quota.to_string()));
+            response.set_header(Header::new(
+                "X-RateLimit-Remaining",
+                usage.remaining().to_string(),
+            ));
+        }
+    }
+}
+
+/// The answer to a request that `ApiKeyFairing` rejected.
+struct Rejection(Custom<String>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Rejection {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let check = request.local_cache(|| KeyCheck(None));
+        match check.0.as_ref().and_then(KeyOutcome::rejection) {
+            Some(rejection) => Outcome::Success(Rejection(rejection)),
+            // Requested directly instead of being sent here.
+            None => Outcome::Forward(()),
+        }
+    }
+}
+
+#[get("/rejected")]
+fn rejected(rejection: Rejection) -> Custom<String> {
+    rejection.0
+}
+
+/// Mounted with `ApiKeyFairing` at its prefix.
+pub fn rejection_routes() -> Vec<Route> {
+    routes![rejected]
+}
+
+#[get("/")]
+fn all(store: Store) -> Result<Json<HashMap<Id<ApiKey>, IssuedApiKey>>> {
+    store.api_keys(today()).map_err(Custom::from).map(Json)
+}
+
+/// The secret is only part of this response, since only its hash is stored.
+#[post("/", data = "<obj>")]
+fn create(store: Store, obj: Json<ApiKey>) -> Result<Json<NewApiKey>> {
+    store.create_api_key(obj.0).map_err(Custom::from).map(Json)
+}
+
+#[put("/<id>", data = "<obj>")]
+fn update(store: Store, id: Id<ApiKey>, obj: Json<ApiKey>) -> Result<Json<ApiKey>> {
+    store
+        .update_api_key(id, obj.0)
+        .map_err(Custom::from)
+        .map(Json)
+}
+
+#[delete("/<id>")]
+fn delete(store: Store, id: Id<ApiKey>) -> Result<Json<ApiKey>> {
+    store.delete_api_key(id).map_err(Custom::from).map(Json)
+}
+
+#[get("/<id>/usage")]
+fn usage(store: Store, id: Id<ApiKey>) -> Result<Json<BTreeMap<NaiveDate, u32>>> {
+    store.api_key_usage(id).map_err(Custom::from).map(Json)
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![all, create, update, delete, usage]
+}
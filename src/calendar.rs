@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use rocket::http::ContentType;
+use rocket::response::content::Content;
+use rocket::{Route, State};
+
+use crate::features::{self, Enabled};
+use crate::store::{
+    Actions, DisplayCutoff, Event, Id, Location, OccurrenceFilter, OccurrenceWithLocation,
+    ScheduleHorizon, Store, StoreError,
+};
+
+/// Lines longer than this many octets have to be folded, see RFC 5545, section 3.1.
+const MAX_LINE_OCTETS: usize = 75;
+
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Occurrences are entered in the local time of Aachen.
+const TIME_ZONE: &str = "Europe/Berlin";
+
+/// The rules of `TIME_ZONE` since 1996, when the EU settled on switching on the last Sundays
+/// of March and October. Without them, calendars would have to guess the offset and would
+/// shift occurrences by an hour across the transitions.
+const TIME_ZONE_RULES: &[(&str, &str)] = &[
+    ("BEGIN", "DAYLIGHT"),
+    ("TZOFFSETFROM", "+0100"),
+    ("TZOFFSETTO", "+0200"),
+    ("TZNAME", "CEST"),
+    ("DTSTART", "19700329T020000"),
+    ("RRULE", "FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU"),
+    ("END", "DAYLIGHT"),
+    ("BEGIN", "STANDARD"),
+    ("TZOFFSETFROM", "+0200"),
+    ("TZOFFSETTO", "+0100"),
+    ("TZNAME", "CET"),
+    ("DTSTART", "19701025T030000"),
+    ("RRULE", "FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU"),
+    ("END", "STANDARD"),
+];
+
+/// Serializes occurrences into an iCalendar feed, so they can be subscribed to.
+pub struct Calendar {
+    lines: Vec<String>,
+}
+
+impl Calendar {
+    pub fn new(name: &str) -> Self {
+        let mut calendar = Calendar { lines: Vec::new() };
+        calendar.property("BEGIN", "VCALENDAR");
+        calendar.property("VERSION", "2.0");
+        calendar.property("PRODID", "-//Lindy Hop Aachen//Kalender//DE");
+        calendar.property("CALSCALE", "GREGORIAN");
+        calendar.text_property("X-WR-CALNAME", name);
+        calendar.property("BEGIN", "VTIMEZONE");
+        calendar.property("TZID", TIME_ZONE);
+        for (name, value) in TIME_ZONE_RULES {
+            calendar.property(name, value);
+        }
+        calendar.property("END", "VTIMEZONE");
+        calendar
+    }
+
+    pub fn add_occurrence(
+        &mut self,
+        event_id: &Id<Event>,
+        event: &Event,
+        occurrence: &OccurrenceWithLocation,
+        locations: &HashMap<Id<Location>, Location>,
+    ) {
+        let start = occurrence.occurrence.start;
+
+        self.property("BEGIN", "VEVENT");
+        // The start identifies an occurrence of an event well enough, and unlike the
+        // occurrence's id it is available wherever occurrences are listed.
+        self.property(
+            "UID",
+            &format!(
+                "{}-{}@lindyhop-aachen.de",
+                event_id,
+                start.format(DATE_TIME_FORMAT)
+            ),
+        );
+        self.property(
+            "DTSTAMP",
+            &format!("{}Z", format_date_time(Utc::now().naive_utc())),
+        );
+        self.local_date_time_property("DTSTART", start);
+        self.local_date_time_property("DTEND", occurrence.occurrence.end());
+        if occurrence.occurrence.cancelled {
+            self.property("STATUS", "CANCELLED");
+        }
+        self.text_property("SUMMARY", &event.title);
+        self.text_property("DESCRIPTION", &describe(event, occurrence));
+        if let Some(location) = occurrence.location(locations) {
+            self.text_property(
+                "LOCATION",
+                &format!("{}, {}", location.name, location.address),
+            );
+        }
+        self.property("END", "VEVENT");
+    }
+
+    pub fn finish(mut self) -> String {
+        self.property("END", "VCALENDAR");
+
+        let mut ics = String::new();
+        for line in &self.lines {
+            ics.push_str(&fold(line));
+            ics.push_str("\r\n");
+        }
+        ics
+    }
+
+    fn property(&mut self, name: &str, value: &str) {
+        self.lines.push(format!("{}:{}", name, value));
+    }
+
+    fn text_property(&mut self, name: &str, value: &str) {
+        self.property(name, &escape_text(value))
+    }
+
+    fn local_date_time_property(&mut self, name: &str, date_time: NaiveDateTime) {
+        self.property(
+            &format!("{};TZID={}", name, TIME_ZONE),
+            &format_date_time(date_time),
+        )
+    }
+}
+
+fn format_date_time(date_time: NaiveDateTime) -> String {
+    date_time.format(DATE_TIME_FORMAT).to_string()
+}
+
+/// The teaser, amended by the details that iCalendar has no property for.
+fn describe(event: &Event, occurrence: &OccurrenceWithLocation) -> String {
+    let mut description = event.teaser.clone();
+    if occurrence.occurrence.cancelled {
+        description.insert_str(0, "Fällt aus. ");
+        if let Some(reason) = &occurrence.occurrence.cancellation_reason {
+            description.push_str(&format!("\nGrund: {}", reason));
+        }
+    }
+    if let Some(doors_open) = occurrence.occurrence.doors_open_at() {
+        description.push_str(&format!("\nEinlass {}", doors_open.format("%H:%M")));
+    }
+    if occurrence.occurrence.open_end {
+        description.push_str("\nOpen end");
+    }
+    if let Some(stream_url) = &occurrence.occurrence.stream_url {
+        description.push_str(&format!("\nLivestream: {}", stream_url));
+    }
+    description
+}
+
+/// Escapes a TEXT value, see RFC 5545, section 3.3.11.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits a content line into lines of at most 75 octets, each continuation starting
+/// with a space. Multi-octet characters like umlauts are never split.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continuation line's length.
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+    folded
+}
+
+#[get("/kalender.ics")]
+fn schedule(
+    _enabled: Enabled<features::Calendar>,
+    store: Store,
+    cutoff: State<DisplayCutoff>,
+    horizon: State<ScheduleHorizon>,
+) -> Result<Content<String>, StoreError> {
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+    let mut calendar = Calendar::new("Lindy Hop Aachen");
+    for (_, entries) in store.occurrences_by_date(&OccurrenceFilter::upcoming(&cutoff, &horizon))? {
+        for entry in entries {
+            calendar.add_occurrence(&entry.event_id, &entry.event, &entry.occurrence, &locations);
+        }
+    }
+
+    Ok(Content(ContentType::Calendar, calendar.finish()))
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![schedule]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unescape_text(text: &str) -> String {
+        let mut unescaped = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match (c, c == '\\') {
+                (_, true) => match chars.next() {
+                    Some('n') | Some('N') => unescaped.push('\n'),
+                    Some(escaped) => unescaped.push(escaped),
+                    None => {}
+                },
+                (c, false) => unescaped.push(c),
+            }
+        }
+        unescaped
+    }
+
+    fn unfold(folded: &str) -> String {
+        folded.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let text = "Tanzen, Üben; Spaß\nmit C:\\Backslash";
+        assert_eq!(
+            escape_text(text),
+            "Tanzen\\, Üben\\; Spaß\\nmit C:\\\\Backslash"
+        );
+        assert_eq!(unescape_text(&escape_text(text)), text);
+    }
+
+    #[test]
+    fn long_lines_are_folded_without_splitting_characters() {
+        let line = format!(
+            "DESCRIPTION:{}",
+            "Äußerst schöne Tanzveranstaltung. ".repeat(5)
+        );
+        let folded = fold(&line);
+
+        for physical_line in folded.split("\r\n") {
+            assert!(physical_line.len() <= MAX_LINE_OCTETS);
+        }
+        assert!(folded
+            .split("\r\n")
+            .skip(1)
+            .all(|line| line.starts_with(' ')));
+        assert_eq!(unfold(&folded), line);
+    }
+
+    fn occurrence_at(start: &str) -> OccurrenceWithLocation {
+        use crate::store::Occurrence;
+
+        OccurrenceWithLocation {
+            occurrence: Occurrence {
+                start: start.parse().unwrap(),
+                duration: chrono::Duration::minutes(180),
+                doors_open: None,
+                open_end: false,
+                stream_url: None,
+                cancelled: false,
+                cancellation_reason: None,
+            },
+            location_id: None,
+        }
+    }
+
+    #[test]
+    fn times_keep_their_wall_clock_time_across_transitions() {
+        let event = Event {
+            title: "Social Dance".to_string(),
+            teaser: "Zum Tanzen.".to_string(),
+            description: String::new(),
+            contact_name: None,
+            contact_email: None,
+            locked: false,
+            slug: "social-dance".to_string(),
+            published: true,
+            publish_at: None,
+        };
+        let event_id: Id<Event> = uuid::Uuid::new_v4().into();
+        let mut calendar = Calendar::new("Test");
+        // The days on which the clocks are set forward and back in 2019.
+        for start in &["2019-03-31T20:00:00", "2019-10-27T01:30:00"] {
+            calendar.add_occurrence(&event_id, &event, &occurrence_at(start), &HashMap::new());
+        }
+        let ics = unfold(&calendar.finish());
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert!(lines.contains(&"DTSTART;TZID=Europe/Berlin:20190331T200000"));
+        assert!(lines.contains(&"DTEND;TZID=Europe/Berlin:20190331T230000"));
+        assert!(lines.contains(&"DTSTART;TZID=Europe/Berlin:20191027T013000"));
+        // Durations are measured in wall clock time, like the schedule shows them.
+        assert!(lines.contains(&"DTEND;TZID=Europe/Berlin:20191027T043000"));
+
+        let time_zone = lines.iter().position(|&line| line == "BEGIN:VTIMEZONE");
+        let first_event = lines.iter().position(|&line| line == "BEGIN:VEVENT");
+        assert!(time_zone.unwrap() < first_event.unwrap());
+        assert!(lines.contains(&"TZID:Europe/Berlin"));
+        assert!(lines
+            .iter()
+            .filter(|line| line.starts_with("DTSTAMP:"))
+            .all(|line| line.ends_with('Z')));
+    }
+
+    #[test]
+    fn time_zone_transitions_are_on_last_sundays() {
+        use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+        let onsets: Vec<NaiveDateTime> = TIME_ZONE_RULES
+            .iter()
+            .filter(|(name, _)| *name == "DTSTART")
+            .map(|(_, value)| NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT).unwrap())
+            .collect();
+        assert_eq!(onsets.len(), 2);
+        for onset in onsets {
+            let date: NaiveDate = onset.date();
+            assert_eq!(date.weekday(), Weekday::Sun);
+            assert_ne!((date + Duration::weeks(1)).month(), 3);
+            assert_ne!((date + Duration::weeks(1)).month(), 10);
+            assert!(date.month() == 3 || date.month() == 10);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use rocket::fairing::{self, Fairing};
+use rocket::request::{self, FromRequest, Request};
+use rocket::{Outcome, Rocket, State};
+
+/// A part of the site that can be switched on and off in the `features` table of the
+/// configuration, e.g. `[production.features]` with `comments = false`. This allows to
+/// deploy new parts dark and enable them per environment without changing code.
+pub trait FeatureFlag {
+    const NAME: &'static str;
+    /// Whether the feature is enabled if it is missing from the configuration.
+    const DEFAULT: bool;
+}
+
+pub struct Comments;
+
+impl FeatureFlag for Comments {
+    const NAME: &'static str = "comments";
+    const DEFAULT: bool = true;
+}
+
+pub struct Submissions;
+
+impl FeatureFlag for Submissions {
+    const NAME: &'static str = "submissions";
+    const DEFAULT: bool = true;
+}
+
+pub struct Calendar;
+
+impl FeatureFlag for Calendar {
+    const NAME: &'static str = "calendar";
+    const DEFAULT: bool = true;
+}
+
+pub struct Newsletter;
+
+impl FeatureFlag for Newsletter {
+    const NAME: &'static str = "newsletter";
+    /// Signing up needs mail to be configured, so it is off unless it is.
+    const DEFAULT: bool = false;
+}
+
+const FLAGS: [(&str, bool); 4] = [
+    (Comments::NAME, Comments::DEFAULT),
+    (Submissions::NAME, Submissions::DEFAULT),
+    (Calendar::NAME, Calendar::DEFAULT),
+    (Newsletter::NAME, Newsletter::DEFAULT),
+];
+
+/// Which features are enabled, for templates to leave out links to disabled ones.
+pub struct Features {
+    enabled: HashMap<&'static str, bool>,
+}
+
+impl Features {
+    pub fn is_enabled<F: FeatureFlag>(&self) -> bool {
+        self.enabled[F::NAME]
+    }
+}
+
+pub struct FeaturesFairing;
+
+impl Fairing for FeaturesFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Feature Flags Fairing",
+            kind: fairing::Kind::Attach,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let mut enabled: HashMap<&'static str, bool> = FLAGS.iter().cloned().collect();
+
+        if let Ok(table) = rocket.config().get_table("features") {
+            for (name, value) in table {
+                let flag = match enabled.get_mut(name.as_str()) {
+                    Some(flag) => flag,
+                    None => {
+                        eprintln!("There is no feature called '{}'.", name);
+                        return Err(rocket);
+                    }
+                };
+                match value.as_bool() {
+                    Some(value) => *flag = value,
+                    None => {
+                        eprintln!(
+                            "The feature '{}' must be enabled with true or false, but is {}.",
+                            name, value
+                        );
+                        return Err(rocket);
+                    }
+                }
+            }
+        }
+
+        Ok(rocket.manage(Features { enabled }))
+    }
+}
+
+/// Guards routes belonging to a feature. If the feature is disabled, the request is
+/// forwarded, so it usually ends in a 404 as if the route did not exist.
+pub struct Enabled<F>(PhantomData<F>);
+
+impl<'a, 'r, F: FeatureFlag> FromRequest<'a, 'r> for Enabled<F> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let features = request.guard::<State<Features>>()?;
+        if features.is_enabled::<F>() {
+            Outcome::Success(Enabled(PhantomData))
+        } else {
+            Outcome::Forward(())
+        }
+    }
+}
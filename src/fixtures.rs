@@ -0,0 +1,346 @@
+use chrono::{Duration, NaiveDate, NaiveTime};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rocket::Rocket;
+
+use crate::store::{
+    Actions, Address, Event, EventWithOccurrences, Id, Location, Occurrence, OccurrenceFilter,
+    OccurrenceWithLocation, Recurrence, Store, StoreResult,
+};
+
+const USAGE: &str =
+    "Usage: lindyhop-aachen seed [--events N] [--locations N] [--years N] [--seed N]";
+
+/// Venues in Aachen, the first few of which are used as they are. Further ones are numbered.
+const VENUES: &[(&str, &str, &str)] = &[
+    ("Chico Mendès", "Pontstraße 74-76", "52062"),
+    ("Musikbunker", "Goffartstraße 26", "52066"),
+    ("Apollo", "Pontstraße 141-149", "52062"),
+    ("Welthaus", "An der Schanz 1", "52064"),
+    ("Frankenberger Park", "Bismarckstraße 63", "52066"),
+    ("Hochschulsport", "Ahornstraße 55", "52074"),
+];
+
+const REGULAR_TITLES: &[&str] = &[
+    "Social Dance",
+    "Lindy Hop Practice",
+    "Balboa Social",
+    "Anfängerkurs",
+    "Solo Jazz Training",
+    "Tanzabend",
+];
+
+const WORKSHOP_TITLES: &[&str] = &[
+    "Workshop-Wochenende",
+    "Lindy Hop Exchange",
+    "Charleston Workshop",
+    "Live-Musik-Ball",
+];
+
+/// How much data to generate. The same options always give the same data.
+#[derive(Debug, Clone)]
+pub struct FixtureOptions {
+    pub seed: u64,
+    pub events: usize,
+    pub locations: usize,
+    /// How long ago the regular events started repeating weekly.
+    pub years: u32,
+    /// The day the data is generated around. Regular events run up to the schedule
+    /// horizon after it.
+    pub today: NaiveDate,
+}
+
+impl Default for FixtureOptions {
+    fn default() -> Self {
+        FixtureOptions {
+            seed: 0,
+            events: 20,
+            locations: 4,
+            years: 1,
+            today: chrono::Local::today().naive_local(),
+        }
+    }
+}
+
+/// A generated event, whose occurrences take place at one of the generated locations.
+#[derive(Debug, Clone)]
+pub struct FixtureEvent {
+    pub event: Event,
+    /// The index of the location in `Fixtures::locations`.
+    pub location: usize,
+    /// The occurrences of a one-off event, e. g. the days of a workshop weekend.
+    pub occurrences: Vec<Occurrence>,
+    /// The first occurrence and the interval in weeks of a regular event.
+    pub recurrence: Option<(Occurrence, u32)>,
+}
+
+/// Realistic data for benchmarks, tests and development, generated from a seed.
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    pub locations: Vec<Location>,
+    pub events: Vec<FixtureEvent>,
+}
+
+impl Fixtures {
+    /// About three quarters of the events are regular ones, the rest are workshops with
+    /// one to three days.
+    pub fn generate(options: &FixtureOptions) -> Fixtures {
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let locations = (0..options.locations.max(1))
+            .map(location)
+            .collect::<Vec<_>>();
+        let first_day = options.today - Duration::weeks(52 * i64::from(options.years));
+        let events = (0..options.events)
+            .map(|index| {
+                let location = rng.gen_range(0, locations.len());
+                if rng.gen_bool(0.75) {
+                    regular_event(&mut rng, index, first_day, location)
+                } else {
+                    workshop(&mut rng, index, first_day, options.today, location)
+                }
+            })
+            .collect();
+
+        Fixtures { locations, events }
+    }
+
+    /// Saves everything at once, so that nothing is saved if anything fails.
+    pub fn insert(&self, store: &Store) -> StoreResult<()> {
+        store.transaction(|| {
+            let location_ids = self
+                .locations
+                .iter()
+                .map(|location| store.create(location.clone()))
+                .collect::<StoreResult<Vec<Id<Location>>>>()?;
+            for fixture in &self.events {
+                let location_id = Some(location_ids[fixture.location].clone());
+                let with_location = |occurrence: &Occurrence| OccurrenceWithLocation {
+                    occurrence: occurrence.clone(),
+                    location_id: location_id.clone(),
+                };
+                let event_id = store.create_event_with_occurrences(EventWithOccurrences {
+                    event: fixture.event.clone(),
+                    occurrences: fixture.occurrences.iter().map(with_location).collect(),
+                })?;
+                if let Some((first, interval_weeks)) = &fixture.recurrence {
+                    store.create_recurrence(
+                        event_id,
+                        Recurrence {
+                            first: with_location(first),
+                            interval_weeks: *interval_weeks,
+                            until: None,
+                            exceptions: Vec::new(),
+                        },
+                    )?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+fn location(index: usize) -> Location {
+    let (name, street, postal_code) = VENUES[index % VENUES.len()];
+    let name = if index < VENUES.len() {
+        name.to_string()
+    } else {
+        format!("{} {}", name, index / VENUES.len() + 1)
+    };
+
+    Location {
+        name,
+        address: Address {
+            street: street.to_string(),
+            postal_code: postal_code.to_string(),
+            city: "Aachen".to_string(),
+        },
+        coordinates: None,
+        manual_coordinates: false,
+    }
+}
+
+fn event(title: &str, index: usize) -> Event {
+    Event {
+        title: format!("{} {}", title, index + 1),
+        teaser: format!("{} in Aachen, alle sind willkommen.", title),
+        description: "Wir tanzen Lindy Hop zu Swing-Musik. Ein Partner ist nicht nötig."
+            .to_string(),
+        contact_name: None,
+        contact_email: None,
+        locked: false,
+        slug: String::new(),
+        published: true,
+        publish_at: None,
+    }
+}
+
+/// An evening starting on the day or within the following week.
+fn occurrence(rng: &mut StdRng, day: NaiveDate) -> Occurrence {
+    let start = (day + Duration::days(rng.gen_range(0, 7))).and_time(NaiveTime::from_hms(
+        rng.gen_range(18, 22),
+        30 * rng.gen_range(0, 2),
+        0,
+    ));
+
+    Occurrence {
+        start,
+        duration: Duration::minutes(30 * rng.gen_range(3, 7)),
+        doors_open: None,
+        open_end: rng.gen_bool(0.2),
+        stream_url: None,
+        cancelled: false,
+        cancellation_reason: None,
+    }
+}
+
+fn regular_event(
+    rng: &mut StdRng,
+    index: usize,
+    first_day: NaiveDate,
+    location: usize,
+) -> FixtureEvent {
+    let title = REGULAR_TITLES[rng.gen_range(0, REGULAR_TITLES.len())];
+    let interval_weeks = if rng.gen_bool(0.8) { 1 } else { 2 };
+
+    FixtureEvent {
+        event: event(title, index),
+        location,
+        occurrences: Vec::new(),
+        recurrence: Some((occurrence(rng, first_day), interval_weeks)),
+    }
+}
+
+fn workshop(
+    rng: &mut StdRng,
+    index: usize,
+    first_day: NaiveDate,
+    today: NaiveDate,
+    location: usize,
+) -> FixtureEvent {
+    let title = WORKSHOP_TITLES[rng.gen_range(0, WORKSHOP_TITLES.len())];
+    // Some workshops are still to come, like the ones announced on the site.
+    let days = (today - first_day).num_days() + 120;
+    let day = first_day + Duration::days(rng.gen_range(0, days));
+    let first = occurrence(rng, day);
+    let occurrences = (0..rng.gen_range(1, 4))
+        .map(|day| Occurrence {
+            start: first.start + Duration::days(day),
+            ..first.clone()
+        })
+        .collect();
+
+    FixtureEvent {
+        event: event(title, index),
+        location,
+        occurrences,
+        recurrence: None,
+    }
+}
+
+/// Fills an empty database with generated data for development, e. g.
+/// `lindyhop-aachen seed --events 50 --years 2`.
+pub fn seed_command(rocket: &Rocket, args: &[String]) -> Result<(), String> {
+    let options = parse_options(args)?;
+    let store = Store::detached(rocket)
+        .ok_or_else(|| "The database is not available in read-only mode.".to_string())?;
+    let existing = store
+        .all_events_with_occurrences(&OccurrenceFilter {
+            include_drafts: true,
+            ..OccurrenceFilter::default()
+        })
+        .map_err(|err| err.to_string())?;
+    if !existing.is_empty() {
+        return Err("The database already contains events, only empty ones are seeded.".into());
+    }
+
+    let fixtures = Fixtures::generate(&options);
+    fixtures
+        .insert(&store)
+        .map_err(|err| format!("Nothing was seeded, since saving failed: {}", err))?;
+    println!(
+        "Seeded {} events at {} locations with seed {}.",
+        fixtures.events.len(),
+        fixtures.locations.len(),
+        options.seed
+    );
+    Ok(())
+}
+
+fn parse_options(args: &[String]) -> Result<FixtureOptions, String> {
+    let mut options = FixtureOptions::default();
+    for pair in args.chunks(2) {
+        let (flag, value) = match pair {
+            [flag, value] => (flag.as_str(), value),
+            _ => return Err(USAGE.to_string()),
+        };
+        let number = value.parse::<u64>().map_err(|_| USAGE.to_string())?;
+        match flag {
+            "--events" => options.events = number as usize,
+            "--locations" => options.locations = number as usize,
+            "--years" => options.years = number as u32,
+            "--seed" => options.seed = number,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> FixtureOptions {
+        FixtureOptions {
+            seed: 7,
+            events: 30,
+            locations: 8,
+            years: 2,
+            today: NaiveDate::from_ymd(2019, 6, 12),
+        }
+    }
+
+    #[test]
+    fn the_same_seed_generates_the_same_data() {
+        let first = Fixtures::generate(&options());
+        let second = Fixtures::generate(&options());
+
+        assert_eq!(format!("{:?}", first), format!("{:?}", second));
+        assert_eq!(first.events.len(), 30);
+        assert_eq!(first.locations[6].name, "Chico Mendès 2");
+        assert_ne!(
+            format!("{:?}", first),
+            format!(
+                "{:?}",
+                Fixtures::generate(&FixtureOptions {
+                    seed: 8,
+                    ..options()
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn generated_events_are_valid() {
+        let fixtures = Fixtures::generate(&options());
+
+        for fixture in &fixtures.events {
+            let occurrences = fixture
+                .occurrences
+                .iter()
+                .chain(fixture.recurrence.iter().map(|(first, _)| first))
+                .map(|occurrence| OccurrenceWithLocation {
+                    occurrence: occurrence.clone(),
+                    location_id: None,
+                })
+                .collect();
+            let event = EventWithOccurrences {
+                event: fixture.event.clone(),
+                occurrences,
+            };
+            assert_eq!(event.validate(), Ok(()));
+        }
+        for location in &fixtures.locations {
+            assert_eq!(location.validate(), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rocket::fairing::{self, Fairing};
+use rocket::http::uri::Uri;
+use rocket::Rocket;
+use serde_json::Value;
+
+use crate::http::{self, Url};
+use crate::store::{Address, Coordinates, Id, Location, Store};
+
+/// Nominatim's usage policy asks for an identifying user agent.
+const USER_AGENT: &str = "lindyhop-aachen.de";
+
+/// Nominatim's usage policy allows at most one request per second.
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Looks up the coordinates of addresses with a geocoding service that answers like
+/// Nominatim, configured as `geocoding_url`, e. g.
+/// `"https://nominatim.openstreetmap.org/search?format=json&limit=1&q={address}"`. Without
+/// one, coordinates can only be entered by hand.
+///
+/// Without TLS, an HTTPS service can only be used through an HTTP proxy that fetches HTTPS
+/// URLs, configured as `geocoding_proxy`.
+pub struct Geocoder {
+    /// Feeds the worker looking up the coordinates, if a service is configured.
+    lookups: Option<Mutex<Sender<Lookup>>>,
+}
+
+struct Lookup {
+    store: Store,
+    id: Id<Location>,
+    address: Address,
+}
+
+impl Geocoder {
+    /// Looks up the coordinates in the background, so that saving the location need not
+    /// wait for the service, and stores them with the location. The lookups are made one
+    /// after another, at most one per second.
+    pub fn locate_later(&self, store: Store, id: Id<Location>, address: Address) {
+        if let Some(lookups) = &self.lookups {
+            // The worker only stops once the geocoder is dropped.
+            let _ = lookups.lock().unwrap().send(Lookup { store, id, address });
+        }
+    }
+}
+
+/// Spaces requests at least the interval apart.
+struct Throttle {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl Throttle {
+    fn new(interval: Duration) -> Throttle {
+        Throttle {
+            interval,
+            last: None,
+        }
+    }
+
+    fn wait(&mut self) {
+        if let Some(last) = self.last {
+            let elapsed = last.elapsed();
+            if elapsed < self.interval {
+                thread::sleep(self.interval - elapsed);
+            }
+        }
+        self.last = Some(Instant::now());
+    }
+}
+
+fn spawn_worker(url: String, proxy: Option<String>) -> Sender<Lookup> {
+    let (sender, lookups) = mpsc::channel::<Lookup>();
+    thread::spawn(move || {
+        let mut throttle = Throttle::new(MIN_INTERVAL);
+        for Lookup { store, id, address } in lookups {
+            throttle.wait();
+            let url = url.replace("{address}", &Uri::percent_encode(&address.to_string()));
+            match locate(&url, proxy.as_ref().map(String::as_str)) {
+                Ok(Some(coordinates)) => {
+                    if let Err(err) = store.set_located_coordinates(id, &address, coordinates) {
+                        eprintln!("Failed to store the coordinates of '{}': {}", address, err);
+                    }
+                }
+                Ok(None) => eprintln!("No coordinates were found for '{}'.", address),
+                Err(err) => eprintln!(
+                    "Failed to look up the coordinates of '{}': {}",
+                    address, err
+                ),
+            }
+        }
+    });
+    sender
+}
+
+fn locate(url: &str, proxy: Option<&str>) -> Result<Option<Coordinates>, String> {
+    let (status, body) = http::get(url, proxy, USER_AGENT).map_err(|err| err.to_string())?;
+    if status != 200 {
+        return Err(format!("HTTP {}", status));
+    }
+    parse_places(&body)
+}
+
+/// Nominatim answers with a list of places, the best match first. Their coordinates are
+/// given as strings.
+fn parse_places(body: &[u8]) -> Result<Option<Coordinates>, String> {
+    let places: Vec<Value> = serde_json::from_slice(body).map_err(|err| err.to_string())?;
+    let place = match places.first() {
+        Some(place) => place,
+        None => return Ok(None),
+    };
+    let degrees = |field: &str| match &place[field] {
+        Value::String(degrees) => degrees.parse().ok(),
+        degrees => degrees.as_f64(),
+    };
+
+    match (degrees("lat"), degrees("lon")) {
+        (Some(latitude), Some(longitude)) => Ok(Some(Coordinates {
+            latitude,
+            longitude,
+        })),
+        _ => Err("The place has no coordinates.".to_string()),
+    }
+}
+
+pub struct GeocodingFairing;
+
+impl Fairing for GeocodingFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Geocoding Fairing",
+            kind: fairing::Kind::Attach,
+        }
+    }
+
+    /// Fails if the service cannot be reached, which is the case for HTTPS without a proxy.
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let setting = |name| rocket.config().get_str(name).ok().map(String::from);
+        let (url, proxy) = (setting("geocoding_url"), setting("geocoding_proxy"));
+        let lookups = match url {
+            None => None,
+            Some(url) => {
+                match (Url::parse(&url), &proxy) {
+                    (None, _) => {
+                        eprintln!("The geocoding_url {} is not an HTTP URL.", url);
+                        return Err(rocket);
+                    }
+                    (Some(ref parsed), None) if parsed.https => {
+                        eprintln!(
+                            "The geocoding_url {} uses HTTPS, which needs a geocoding_proxy.",
+                            url
+                        );
+                        return Err(rocket);
+                    }
+                    _ => {}
+                }
+                Some(Mutex::new(spawn_worker(url, proxy)))
+            }
+        };
+
+        Ok(rocket.manage(Geocoder { lookups }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates_are_read_from_the_best_match() {
+        let body = r#"[
+            { "display_name": "Chico Mendès, Pontstraße", "lat": "50.7783", "lon": "6.0806" },
+            { "display_name": "Pontstraße", "lat": "50.7781", "lon": "6.0808" }
+        ]"#;
+        assert_eq!(
+            parse_places(body.as_bytes()),
+            Ok(Some(Coordinates {
+                latitude: 50.7783,
+                longitude: 6.0806
+            }))
+        );
+        assert_eq!(parse_places(b"[]"), Ok(None));
+        assert!(parse_places(br#"[{ "lat": "north" }]"#).is_err());
+    }
+
+    #[test]
+    fn requests_are_spaced_apart() {
+        let mut throttle = Throttle::new(Duration::from_millis(50));
+        let start = Instant::now();
+        for _ in 0..3 {
+            throttle.wait();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn https_services_need_a_proxy() {
+        use rocket::config::{Config, Environment};
+
+        let geocoder_attached = |proxy: Option<&str>| {
+            let mut config = Config::build(Environment::Development).extra(
+                "geocoding_url",
+                "https://nominatim.openstreetmap.org/search?format=json&q={address}",
+            );
+            if let Some(proxy) = proxy {
+                config = config.extra("geocoding_proxy", proxy);
+            }
+            rocket::custom(config.finalize().unwrap())
+                .attach(GeocodingFairing)
+                .state::<Geocoder>()
+                .is_some()
+        };
+        assert!(!geocoder_attached(None));
+        assert!(geocoder_attached(Some("localhost:3128")));
+    }
+}
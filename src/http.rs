@@ -0,0 +1,157 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connects to the first address the host resolves to that accepts connections.
+pub fn connect(address: &str) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "Unknown host");
+    for socket_address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&socket_address, TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_error = err,
+        }
+    }
+    Err(last_error)
+}
+
+/// Sends the request and returns the status code of the response, with the rest of the
+/// response left to read.
+fn send(address: &str, request: &str) -> io::Result<(u16, BufReader<TcpStream>)> {
+    let mut stream = connect(address)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    stream.write_all(request.as_bytes())?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Not an HTTP response"))?;
+    Ok((status, reader))
+}
+
+/// Returns the status code of the response.
+pub fn head(address: &str, target: &str, host: &str, user_agent: &str) -> io::Result<u16> {
+    let request = format!(
+        "HEAD {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nConnection: close\r\n\r\n",
+        target, host, user_agent
+    );
+    send(address, &request).map(|(status, _)| status)
+}
+
+/// Returns the status code and the body of the response. HTTP/1.0 is asked for, so that
+/// the body is not sent in chunks.
+///
+/// Without TLS, HTTPS URLs can only be fetched through an HTTP proxy that fetches them.
+pub fn get(url: &str, proxy: Option<&str>, user_agent: &str) -> io::Result<(u16, Vec<u8>)> {
+    let parsed = Url::parse(url)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a valid URL"))?;
+    let (address, target) = match proxy {
+        Some(proxy) => (proxy.to_string(), parsed.without_fragment.to_string()),
+        None if parsed.https => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "HTTPS URLs can only be fetched through a proxy",
+            ))
+        }
+        None => (parsed.address(), parsed.path),
+    };
+    let request = format!(
+        "GET {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: {}\r\n\r\n",
+        target, parsed.authority, user_agent
+    );
+
+    let (status, mut reader) = send(&address, &request)?;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        header.clear();
+    }
+    let mut body = Vec::new();
+    reader.read_to_end(&mut body)?;
+    Ok((status, body))
+}
+
+pub struct Url<'a> {
+    pub https: bool,
+    /// The host with the port, if one is given.
+    pub authority: &'a str,
+    pub host: &'a str,
+    pub port: u16,
+    /// The path with the query.
+    pub path: String,
+    pub without_fragment: &'a str,
+}
+
+impl<'a> Url<'a> {
+    pub fn parse(url: &'a str) -> Option<Url<'a>> {
+        let without_fragment = url.split('#').next()?;
+        let (https, rest) = if url.starts_with("https://") {
+            (true, &without_fragment["https://".len()..])
+        } else if url.starts_with("http://") {
+            (false, &without_fragment["http://".len()..])
+        } else {
+            return None;
+        };
+        let (authority, path) = match rest.find(|c| c == '/' || c == '?') {
+            Some(index) if rest[index..].starts_with('/') => {
+                (&rest[..index], rest[index..].to_string())
+            }
+            Some(index) => (&rest[..index], format!("/{}", &rest[index..])),
+            None => (rest, "/".to_string()),
+        };
+        // Credentials in links are not sent along.
+        let authority = authority.rsplit('@').next()?;
+        let (host, port) = match authority.rfind(':') {
+            Some(index) => (&authority[..index], authority[index + 1..].parse().ok()?),
+            None => (authority, if https { 443 } else { 80 }),
+        };
+        if host.is_empty() {
+            return None;
+        }
+
+        Some(Url {
+            https,
+            authority,
+            host,
+            port,
+            path,
+            without_fragment,
+        })
+    }
+
+    /// Where to connect to, e. g. `lindyhop-aachen.de:443`.
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_are_split_for_requests() {
+        let url = Url::parse("http://user@localhost:8000?page=2#top").unwrap();
+        assert_eq!(
+            (
+                url.https,
+                url.authority,
+                url.host,
+                url.port,
+                url.path.as_str()
+            ),
+            (false, "localhost:8000", "localhost", 8000, "/?page=2")
+        );
+        let url = Url::parse("https://lindyhop-aachen.de/veranstaltungen/social-dance").unwrap();
+        assert_eq!(
+            (url.port, url.path),
+            (443, "/veranstaltungen/social-dance".to_string())
+        );
+        assert!(Url::parse("mailto:info@lindyhop-aachen.de").is_none());
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::Local;
+use rocket::fairing::{self, Fairing};
+use rocket::Rocket;
+
+use crate::http::{self, Url};
+use crate::store::{BrokenLink, Event, Id, LinkReport, OccurrenceFilter, Store, StoreResult};
+
+const USER_AGENT: &str = "lindyhop-aachen.de link checker";
+
+/// Checks the links in the texts of all events and the stream URLs of their occurrences,
+/// and keeps the latest report, managed as state.
+///
+/// Without TLS, HTTPS links can only be checked for whether their server accepts
+/// connections, so the report lists them as unchecked. To check them fully, configure an
+/// HTTP proxy that fetches HTTPS URLs as `link_check_proxy`, e. g. `"localhost:3128"`.
+#[derive(Clone)]
+pub struct LinkChecker {
+    proxy: Option<String>,
+    report: Arc<Mutex<Option<LinkReport>>>,
+}
+
+impl LinkChecker {
+    /// The report of the latest check, if there has been one since the server started.
+    pub fn report(&self) -> Option<LinkReport> {
+        self.report.lock().unwrap().clone()
+    }
+
+    pub fn check_all(&self, store: &Store) -> StoreResult<LinkReport> {
+        let mut links: BTreeMap<String, Vec<(Id<Event>, String)>> = BTreeMap::new();
+        for (event_id, event_with_occurrences) in
+            store.all_events_with_occurrences(&OccurrenceFilter::default())?
+        {
+            let event = &event_with_occurrences.event;
+            let mut event_links: Vec<&str> = find_links(&event.teaser);
+            event_links.extend(find_links(&event.description));
+            event_links.extend(
+                event_with_occurrences
+                    .occurrences
+                    .iter()
+                    .filter_map(|occurrence| occurrence.occurrence.stream_url.as_ref())
+                    .map(String::as_str),
+            );
+            event_links.sort();
+            event_links.dedup();
+            for link in event_links {
+                links
+                    .entry(link.to_string())
+                    .or_default()
+                    .push((event_id.clone(), event.title.clone()));
+            }
+        }
+
+        let mut broken = Vec::new();
+        let mut unchecked = Vec::new();
+        for (url, events) in &links {
+            match self.check(url) {
+                LinkStatus::Working => {}
+                LinkStatus::Unchecked => unchecked.push(url.clone()),
+                LinkStatus::Broken(problem) => {
+                    for (event_id, event_title) in events {
+                        broken.push(BrokenLink {
+                            url: url.clone(),
+                            event_id: event_id.clone(),
+                            event_title: event_title.clone(),
+                            problem: problem.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let report = LinkReport {
+            checked_at: Local::now().naive_local(),
+            checked: links.len() - unchecked.len(),
+            broken,
+            unchecked,
+        };
+        *self.report.lock().unwrap() = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Sends a HEAD request for the URL. Only responses saying that the page is gone or that
+    /// the server failed count as broken, since many sites turn away requests that do not
+    /// come from a browser.
+    fn check(&self, url: &str) -> LinkStatus {
+        let parsed = match Url::parse(url) {
+            Some(parsed) => parsed,
+            None => return LinkStatus::Broken("Not a valid URL".to_string()),
+        };
+        let (address, target) = match &self.proxy {
+            Some(proxy) => (proxy.clone(), parsed.without_fragment.to_string()),
+            None if parsed.https => {
+                return match http::connect(&parsed.address()) {
+                    Ok(_) => LinkStatus::Unchecked,
+                    Err(err) => LinkStatus::Broken(err.to_string()),
+                };
+            }
+            None => (parsed.address(), parsed.path),
+        };
+
+        match http::head(&address, &target, parsed.authority, USER_AGENT) {
+            Ok(status) if status == 404 || status == 410 || status >= 500 => {
+                LinkStatus::Broken(format!("HTTP {}", status))
+            }
+            Ok(_) => LinkStatus::Working,
+            Err(err) => LinkStatus::Broken(err.to_string()),
+        }
+    }
+}
+
+enum LinkStatus {
+    Working,
+    Broken(String),
+    /// The server accepts connections, but the page cannot be requested without TLS.
+    Unchecked,
+}
+
+/// The HTTP and HTTPS URLs written in the text, without punctuation that follows them.
+pub fn find_links(text: &str) -> Vec<&str> {
+    let mut links = Vec::new();
+    let mut rest = text;
+    while let Some(start) = ["http://", "https://"]
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || "<>\"'".contains(c))
+            .unwrap_or_else(|| candidate.len());
+        let link = candidate[..end].trim_end_matches(|c: char| ".,;:!?)]".contains(c));
+        if !link.ends_with("//") {
+            links.push(link);
+        }
+        rest = &candidate[end..];
+    }
+    links
+}
+
+pub struct LinkCheckFairing;
+
+impl Fairing for LinkCheckFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Link Check Fairing",
+            kind: fairing::Kind::Attach | fairing::Kind::Launch,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let proxy = rocket
+            .config()
+            .get_str("link_check_proxy")
+            .ok()
+            .map(String::from);
+
+        Ok(rocket.manage(LinkChecker {
+            proxy,
+            report: Arc::new(Mutex::new(None)),
+        }))
+    }
+
+    /// Checks the links right away and then every `link_check_interval_hours`, unless that
+    /// is 0.
+    fn on_launch(&self, rocket: &Rocket) {
+        let interval_hours = rocket
+            .config()
+            .get_int("link_check_interval_hours")
+            .unwrap_or(24);
+        if interval_hours <= 0 {
+            return;
+        }
+        let checker = rocket.state::<LinkChecker>().unwrap().clone();
+        let store = match Store::detached(rocket) {
+            Some(store) => store,
+            // There is nothing to fix while serving a read-only snapshot.
+            None => return,
+        };
+
+        let interval = Duration::from_secs(interval_hours as u64 * 60 * 60);
+        thread::spawn(move || loop {
+            match checker.check_all(&store) {
+                Ok(ref report) if !report.broken.is_empty() => eprintln!(
+                    "{} of {} links on events are broken.",
+                    report.broken.len(),
+                    report.checked
+                ),
+                Ok(_) => {}
+                Err(err) => eprintln!("Failed to check the links: {}", err),
+            }
+            thread::sleep(interval);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_are_found_in_texts() {
+        assert_eq!(
+            find_links(
+                "Fotos gibt es auf https://www.facebook.com/lindyhopaachen/photos. \
+                 Anmeldung (http://example.com/anmelden?kurs=1), nicht https://"
+            ),
+            vec![
+                "https://www.facebook.com/lindyhopaachen/photos",
+                "http://example.com/anmelden?kurs=1"
+            ]
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use chrono::Utc;
+use maud::{html, Markup, DOCTYPE};
+use rocket::fairing::{self, Fairing};
+use rocket::Rocket;
+
+use crate::store::{is_email_address, Newsletter};
+use crate::website::SITE_URL;
+
+const DEFAULT_PORT: i64 = 25;
+
+/// How long to wait for the mail server before giving up on it.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Base64 encoded lines must not be longer than this, see RFC 2045, section 6.8.
+const MAX_LINE_LENGTH: usize = 76;
+
+/// Identifies the newsletter, so that mail clients can filter it, see RFC 2919.
+const LIST_ID: &str = "Lindy Hop Aachen Newsletter <newsletter.lindyhop-aachen.de>";
+
+/// Where and as whom mail is sent, configured as a `mail` table with `host`, `port`, and
+/// `from`. Mail is handed to an SMTP relay without encryption or authentication, so the
+/// relay should run on the server itself and take care of those towards the provider.
+///
+/// Bounces go to `envelope_from` if it is set, e.g. an address of the domain the relay is
+/// allowed to send for by SPF. Otherwise they go to `from`.
+struct MailConfig {
+    host: String,
+    port: u16,
+    from: String,
+    envelope_from: Option<String>,
+}
+
+/// Sends mail, if it is configured.
+pub struct Mailer(Option<MailConfig>);
+
+impl Mailer {
+    pub fn connect(&self) -> io::Result<Connection> {
+        match &self.0 {
+            Some(config) => Connection::open(config),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Sending mail is not configured.",
+            )),
+        }
+    }
+
+    /// Sends a single HTML message over a connection of its own.
+    pub fn send(&self, to: &str, subject: &str, html: &str) -> io::Result<()> {
+        let mut connection = self.connect()?;
+        connection.send(to, subject, html, None)?;
+        connection.quit();
+        Ok(())
+    }
+}
+
+pub struct MailFairing;
+
+impl Fairing for MailFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Mail Fairing",
+            kind: fairing::Kind::Attach,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let table = match rocket.config().get_table("mail") {
+            Ok(table) => table,
+            Err(_) => return Ok(rocket.manage(Mailer(None))),
+        };
+        let host = table.get("host").and_then(|host| host.as_str());
+        let from = table.get("from").and_then(|from| from.as_str());
+        let port = table
+            .get("port")
+            .map_or(Some(DEFAULT_PORT), |port| port.as_integer());
+        let config = match (host, from, port) {
+            (Some(host), Some(from), Some(port)) if port > 0 && port <= 65535 => MailConfig {
+                host: host.to_string(),
+                port: port as u16,
+                from: from.to_string(),
+                envelope_from: None,
+            },
+            _ => {
+                eprintln!(
+                    "The mail configuration needs a host, a sender in from, and optionally a port."
+                );
+                return Err(rocket);
+            }
+        };
+        let config = match table.get("envelope_from").map(|value| value.as_str()) {
+            None => config,
+            Some(Some(address)) if is_email_address(address) => MailConfig {
+                envelope_from: Some(address.to_string()),
+                ..config
+            },
+            Some(_) => {
+                eprintln!("The envelope_from of the mail configuration is not an email address.");
+                return Err(rocket);
+            }
+        };
+
+        Ok(rocket.manage(Mailer(Some(config))))
+    }
+}
+
+/// A connection to the SMTP relay, see RFC 5321.
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    from: String,
+    envelope_from: String,
+}
+
+impl Connection {
+    fn open(config: &MailConfig) -> io::Result<Connection> {
+        let stream = TcpStream::connect((config.host.as_str(), config.port))?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut connection = Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            from: config.from.clone(),
+            envelope_from: config
+                .envelope_from
+                .clone()
+                .unwrap_or_else(|| config.from.clone()),
+        };
+        connection.expect(220)?;
+        connection.command("EHLO lindyhop-aachen.de", 250)?;
+        Ok(connection)
+    }
+
+    /// Sends an HTML message to a single recipient. A rejected recipient does not end the
+    /// connection, so the message can still be sent to the others. Mail clients offer the
+    /// `unsubscribe` link as a button, see RFC 2369 and RFC 8058. Messages with the link are
+    /// sent to the list, so they are marked with its `List-Id`.
+    pub fn send(
+        &mut self,
+        to: &str,
+        subject: &str,
+        html: &str,
+        unsubscribe: Option<&str>,
+    ) -> io::Result<()> {
+        // Otherwise a line break in the address would add commands or headers.
+        if !is_email_address(to) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is not an email address.", to),
+            ));
+        }
+        let result = self.transaction(to, subject, html, unsubscribe);
+        if result.is_err() {
+            self.command("RSET", 250)?;
+        }
+        result
+    }
+
+    fn transaction(
+        &mut self,
+        to: &str,
+        subject: &str,
+        html: &str,
+        unsubscribe: Option<&str>,
+    ) -> io::Result<()> {
+        let from = self.from.clone();
+        let envelope_from = self.envelope_from.clone();
+        self.command(&format!("MAIL FROM:<{}>", envelope_from), 250)?;
+        self.command(&format!("RCPT TO:<{}>", to), 250)?;
+        self.command("DATA", 354)?;
+        // Base64 lines never start with a dot, so nothing needs to be escaped.
+        self.writer
+            .write_all(format_message(&from, to, subject, html, unsubscribe).as_bytes())?;
+        self.command(".", 250)
+    }
+
+    pub fn quit(mut self) {
+        // The messages are accepted already, so a failure here does not matter.
+        let _ = self.command("QUIT", 221);
+    }
+
+    fn command(&mut self, line: &str, expected: u16) -> io::Result<()> {
+        self.writer.write_all(format!("{}\r\n", line).as_bytes())?;
+        self.expect(expected)
+    }
+
+    /// Reads a reply, which may span several lines like `250-first` and `250 last`.
+    fn expect(&mut self, expected: u16) -> io::Result<()> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "The mail server closed the connection.",
+                ));
+            }
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+        }
+
+        let code: Option<u16> = line.get(..3).and_then(|code| code.parse().ok());
+        if code == Some(expected) {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("The mail server replied: {}", line.trim_end()),
+            ))
+        }
+    }
+}
+
+fn format_message(
+    from: &str,
+    to: &str,
+    subject: &str,
+    html: &str,
+    unsubscribe: Option<&str>,
+) -> String {
+    let mut message = format!(
+        "From: {}\r\n\
+         To: {}\r\n\
+         Subject: {}\r\n\
+         Date: {}\r\n",
+        from,
+        to,
+        encode_header(subject),
+        Utc::now().to_rfc2822()
+    );
+    if let Some(link) = unsubscribe {
+        message.push_str(&format!(
+            "List-Id: {}\r\n\
+             List-Unsubscribe: <{}>\r\n\
+             List-Unsubscribe-Post: List-Unsubscribe=One-Click\r\n",
+            LIST_ID, link
+        ));
+    }
+    message.push_str(
+        "MIME-Version: 1.0\r\n\
+         Content-Type: text/html; charset=utf-8\r\n\
+         Content-Transfer-Encoding: base64\r\n\
+         \r\n",
+    );
+    let body = base64::encode(html);
+    for line in body.as_bytes().chunks(MAX_LINE_LENGTH) {
+        // Base64 consists of ASCII characters only.
+        message.push_str(std::str::from_utf8(line).unwrap());
+        message.push_str("\r\n");
+    }
+    message
+}
+
+/// Headers may only contain ASCII, so anything else is encoded, see RFC 2047.
+fn encode_header(text: &str) -> String {
+    if text.is_ascii() {
+        text.to_string()
+    } else {
+        format!("=?utf-8?B?{}?=", base64::encode(text))
+    }
+}
+
+/// The link unsubscribing the subscriber with the token, see `/newsletter/abmelden/<token>`.
+pub fn unsubscribe_link(token: &str) -> String {
+    format!("{}/newsletter/abmelden/{}", SITE_URL, token)
+}
+
+/// The title and content of the newsletter, shared by the mail and the archive on the
+/// website. Paragraphs are separated by blank lines.
+pub fn render_newsletter_content(newsletter: &Newsletter) -> Markup {
+    html! {
+        h1 { ( newsletter.title ) }
+        @for paragraph in newsletter.content.split("\n\n") {
+            p style="white-space: pre-line" { ( paragraph.trim() ) }
+        }
+    }
+}
+
+/// The newsletter as the body of an email.
+pub fn render_newsletter(newsletter: &Newsletter, unsubscribe_link: &str) -> String {
+    let markup = html! {
+        ( DOCTYPE )
+        html lang="de" {
+            head {
+                meta charset="utf-8";
+                title { ( newsletter.title ) }
+            }
+            body {
+                ( render_newsletter_content(newsletter) )
+                hr;
+                p {
+                    small {
+                        "Du bekommst diesen Newsletter, weil du ihn abonniert hast. "
+                        a href=( unsubscribe_link ) { "Abmelden" }
+                    }
+                }
+            }
+        }
+    };
+    markup.into_string()
+}
+
+/// The mail asking a new subscriber to confirm their address, see
+/// `/newsletter/bestaetigen/<token>`.
+pub fn render_confirmation(token: &str) -> String {
+    let link = format!("{}/newsletter/bestaetigen/{}", SITE_URL, token);
+    let markup = html! {
+        ( DOCTYPE )
+        html lang="de" {
+            head {
+                meta charset="utf-8";
+                title { "Newsletter bestätigen" }
+            }
+            body {
+                p { "Hallo!" }
+                p { "Du möchtest den Newsletter von Lindy Hop Aachen bekommen? Dann bestätige das bitte mit diesem Link:" }
+                p { a href=( link ) { ( link ) } }
+                p { "Falls du dich nicht angemeldet hast, ignoriere diese E-Mail einfach. Du bekommst dann keine weiteren E-Mails von uns." }
+            }
+        }
+    };
+    markup.into_string()
+}
+
+/// An SMTP server accepting everything but the given recipients, for testing.
+#[cfg(test)]
+pub mod test_server {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// Starts the server on a free port and returns the port and the messages it received.
+    /// Like a server delivering them, it adds the envelope sender as `Return-Path`.
+    pub fn start(rejected: &'static [&'static str]) -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let received = messages.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut writer = stream.unwrap();
+                let mut reader = BufReader::new(writer.try_clone().unwrap());
+                writer.write_all(b"220 test\r\n").unwrap();
+                let mut line = String::new();
+                let mut sender = String::new();
+                while reader.read_line(&mut line).unwrap() > 0 {
+                    let reply: &[u8] = if line.starts_with("EHLO") {
+                        b"250-test\r\n250 8BITMIME\r\n"
+                    } else if line.starts_with("MAIL FROM:") {
+                        sender = line["MAIL FROM:".len()..].trim_end().to_string();
+                        b"250 OK\r\n"
+                    } else if line.starts_with("RCPT TO:") {
+                        if rejected.iter().any(|address| line.contains(address)) {
+                            b"550 No such user\r\n"
+                        } else {
+                            b"250 OK\r\n"
+                        }
+                    } else if line.starts_with("DATA") {
+                        writer.write_all(b"354 Go ahead\r\n").unwrap();
+                        let mut message = format!("Return-Path: {}\r\n", sender);
+                        loop {
+                            line.clear();
+                            reader.read_line(&mut line).unwrap();
+                            if line == ".\r\n" {
+                                break;
+                            }
+                            message.push_str(&line);
+                        }
+                        received.lock().unwrap().push(message);
+                        b"250 Queued\r\n"
+                    } else if line.starts_with("QUIT") {
+                        writer.write_all(b"221 Bye\r\n").unwrap();
+                        break;
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    writer.write_all(reply).unwrap();
+                    line.clear();
+                }
+            }
+        });
+        (port, messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(port: u16) -> MailConfig {
+        MailConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            from: "newsletter@lindyhop-aachen.de".to_string(),
+            envelope_from: None,
+        }
+    }
+
+    fn connect(port: u16) -> Connection {
+        Connection::open(&config(port)).unwrap()
+    }
+
+    #[test]
+    fn messages_are_delivered_as_base64_html() {
+        let (port, messages) = test_server::start(&[]);
+        let mut connection = connect(port);
+        let html = "<p>Schöne Grüße</p>".repeat(10);
+        connection
+            .send(
+                "kim@example.com",
+                "Neuigkeiten für Juni",
+                &html,
+                Some("https://lindyhop-aachen.de/newsletter/abmelden/abc"),
+            )
+            .unwrap();
+        connection.quit();
+
+        let messages = messages.lock().unwrap();
+        let (headers, body) = messages[0].split_at(messages[0].find("\r\n\r\n").unwrap());
+        assert!(headers.contains("To: kim@example.com\r\n"));
+        assert!(headers.contains("From: newsletter@lindyhop-aachen.de\r\n"));
+        assert!(headers.contains(&format!(
+            "Subject: =?utf-8?B?{}?=",
+            base64::encode("Neuigkeiten für Juni")
+        )));
+        assert!(headers.contains(
+            "List-Unsubscribe: <https://lindyhop-aachen.de/newsletter/abmelden/abc>\r\n"
+        ));
+        assert!(headers
+            .contains("List-Id: Lindy Hop Aachen Newsletter <newsletter.lindyhop-aachen.de>\r\n"));
+        assert!(headers.starts_with("Return-Path: <newsletter@lindyhop-aachen.de>\r\n"));
+        assert!(headers.is_ascii());
+
+        let lines: Vec<&str> = body.trim().split("\r\n").collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(base64::decode(&lines.concat()).unwrap(), html.as_bytes());
+    }
+
+    #[test]
+    fn bounces_go_to_the_envelope_sender() {
+        let (port, messages) = test_server::start(&[]);
+        let mut connection = Connection::open(&MailConfig {
+            envelope_from: Some("bounces@mail.lindyhop-aachen.de".to_string()),
+            ..config(port)
+        })
+        .unwrap();
+        connection
+            .send("kim@example.com", "Hallo", "<p>Hallo</p>", None)
+            .unwrap();
+        connection.quit();
+
+        let messages = messages.lock().unwrap();
+        let headers = &messages[0][..messages[0].find("\r\n\r\n").unwrap()];
+        assert!(headers.starts_with("Return-Path: <bounces@mail.lindyhop-aachen.de>\r\n"));
+        assert!(headers.contains("From: newsletter@lindyhop-aachen.de\r\n"));
+        // Only mail to the list is marked as such.
+        assert!(!headers.contains("List-Id:"));
+        assert!(!headers.contains("List-Unsubscribe:"));
+    }
+
+    #[test]
+    fn rejected_recipients_do_not_end_the_connection() {
+        let (port, messages) = test_server::start(&["gone@example.com"]);
+        let mut connection = connect(port);
+        let error = connection
+            .send("gone@example.com", "Hallo", "<p>Hallo</p>", None)
+            .unwrap_err();
+        assert!(error.to_string().contains("550"));
+        connection
+            .send("kim@example.com", "Hallo", "<p>Hallo</p>", None)
+            .unwrap();
+        connection.quit();
+
+        assert_eq!(messages.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn addresses_with_line_breaks_are_refused() {
+        let (port, messages) = test_server::start(&[]);
+        let mut connection = connect(port);
+        let error = connection
+            .send(
+                "kim@example.com\r\nRCPT TO:<victim@example.com>",
+                "Hallo",
+                "<p>Hallo</p>",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        connection.quit();
+
+        assert!(messages.lock().unwrap().is_empty());
+    }
+}
@@ -1,7 +1,27 @@
 #![feature(proc_macro_hygiene, decl_macro, custom_attribute)]
+#![cfg_attr(test, feature(test))]
 
+mod announcement;
 mod api;
+mod calendar;
+mod features;
+mod fixtures;
+mod geocoding;
+mod http;
+mod links;
+mod mail;
+mod maintenance;
+mod markdown;
+mod media;
+mod offline;
+mod recording;
+mod search;
+mod spam;
 mod store;
+mod text;
+mod timing;
+mod website;
+mod wordpress;
 
 #[macro_use]
 extern crate rocket;
@@ -12,117 +32,13 @@ extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
 
-use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use chrono::prelude::*;
-use maud::{html, Markup, DOCTYPE};
 use rocket::fairing::AdHoc;
 use rocket::response::NamedFile;
 use rocket::State;
 
-use store::{
-    Actions, Event, Id, Location, OccurrenceFilter, OccurrenceWithEvent, OccurrenceWithLocation,
-    Store,
-};
-
-#[get("/")]
-fn index(store: Store) -> Markup {
-    html! {
-        ( DOCTYPE )
-        html lang="de" {
-            head {
-                meta name="viewport" content="width=device-width, initial-scale=1";
-
-                link href="static/main.css" rel="stylesheet";
-            }
-            body {
-                header {
-                    h1 { "Lindy Hop Aachen" }
-                }
-                main {
-                    ol.schedule {
-                        @let locations: HashMap<Id<Location>, Location> = store.all();
-                        @for occurrences_for_date in store.occurrences_by_date(&OccurrenceFilter::upcoming()) {
-                            li { ( render_entry(&occurrences_for_date, &locations) ) }
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-
-fn render_entry(
-    (date, entries): &(NaiveDate, Vec<OccurrenceWithEvent>),
-    locations: &HashMap<Id<Location>, Location>,
-) -> Markup {
-    html! {
-        div.date { ( format_date(date) ) }
-        ol.events {
-            @for occurrence_entry in entries {
-                li.event { ( render_occurrence(occurrence_entry, locations) ) }
-            }
-        }
-    }
-}
-
-fn format_date(date: &NaiveDate) -> String {
-    use chrono::Weekday::*;
-
-    let day = match date.weekday() {
-        Mon => "Mo",
-        Tue => "Di",
-        Wed => "Mi",
-        Thu => "Do",
-        Fri => "Fr",
-        Sat => "Sa",
-        Sun => "So",
-    };
-    let format = format!("{}, %d.%m.", day);
-
-    date.format(&format).to_string()
-}
-
-fn render_occurrence(
-    entry: &OccurrenceWithEvent,
-    locations: &HashMap<Id<Location>, Location>,
-) -> Markup {
-    html! {
-        @let entry_html =  html_from_occurrence(&entry.occurrence, &entry.event, locations);
-        div.quick-info { ( entry_html.quick_info ) }
-        h2.title { ( entry_html.title ) }
-        div.content {
-            div.description {
-                div.teaser { ( entry_html.teaser ) }
-            }
-        }
-    }
-}
-
-struct OccurrenceHtml {
-    title: Markup,
-    quick_info: Markup,
-    teaser: Markup,
-}
-
-fn html_from_occurrence(
-    occurrence: &OccurrenceWithLocation,
-    event: &Event,
-    locations: &HashMap<Id<Location>, Location>,
-) -> OccurrenceHtml {
-    let maybe_location = locations.get(&occurrence.location_id);
-    let location_name = match maybe_location {
-        Some(location) => &location.name,
-        None => "Steht noch nicht fest.",
-    };
-
-    OccurrenceHtml {
-        title: html! { ( event.title ) },
-        quick_info: html! { ( format!("{} - {}", occurrence.occurrence.start.format("%H:%M"), location_name) ) },
-        teaser: html! { ( event.teaser ) },
-    }
-}
+use store::Store;
 
 #[get("/admin")]
 fn admin_route() -> Option<NamedFile> {
@@ -152,8 +68,37 @@ fn static_file(file: PathBuf, assets_dir: State<AssetsDir>) -> Option<NamedFile>
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("import-wordpress") {
+        let rocket = rocket::ignite().attach(Store::fairing());
+        if let Err(err) = wordpress::import_command(&rocket, &args[1..]) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("seed") {
+        let rocket = rocket::ignite().attach(Store::fairing());
+        if let Err(err) = fixtures::seed_command(&rocket, &args[1..]) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let rocket = rocket::ignite()
         .attach(Store::fairing())
+        .attach(timing::RouteTimingFairing)
+        .attach(recording::RecordingFairing::default())
+        .attach(spam::SpamFairing)
+        .attach(features::FeaturesFairing)
+        .attach(mail::MailFairing)
+        .attach(announcement::AnnouncementFairing)
+        .attach(media::MediaFairing)
+        .attach(links::LinkCheckFairing)
+        .attach(geocoding::GeocodingFairing)
+        .attach(maintenance::MaintenanceFairing)
+        .attach(website::StatisticsCache::fairing())
         .attach(AdHoc::on_attach("Assets Config", |rocket| {
             let assets_dir = PathBuf::from(rocket.config().get_str("assets_dir").unwrap_or("."));
             if assets_dir.exists() {
@@ -167,9 +112,19 @@ fn main() {
                 Err(rocket)
             }
         }))
-        .mount(
-            "/",
-            routes![static_file, index, admin_route, admin_subroute],
-        );
+        .mount("/", routes![static_file])
+        .mount("/", media::routes())
+        .mount("/", offline::routes())
+        .mount("/", calendar::routes());
+    let read_only = store::is_read_only(&rocket);
+    let rocket = rocket.mount("/", website::routes(read_only));
+
+    // There is nothing to administrate while serving a read-only snapshot.
+    let rocket = if read_only {
+        rocket
+    } else {
+        rocket.mount("/", routes![admin_route, admin_subroute])
+    };
+
     api::mount(rocket, "/api").launch();
 }
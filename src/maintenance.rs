@@ -0,0 +1,81 @@
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use maud::Markup;
+use rocket::fairing::{self, Fairing};
+use rocket::http::uri::Origin;
+use rocket::http::{ContentType, Header, Method, Status};
+use rocket::response::{self, Responder, Response};
+use rocket::{Data, Request, Rocket, State};
+
+/// Where public requests are sent while the site is in maintenance.
+const MAINTENANCE_PATH: &str = "/wartung";
+
+/// Migrations of the data are usually done within half an hour.
+const RETRY_AFTER_SECONDS: u32 = 30 * 60;
+
+/// These keep working during maintenance, so that the data can be migrated and the page
+/// still looks like the site.
+const UNAFFECTED_PREFIXES: &[&str] = &["/api", "/admin", "/static"];
+
+/// Whether the public pages are replaced by a notice, e. g. while the data is migrated.
+/// It starts out as configured with `maintenance`, and is switched through the API.
+pub struct Maintenance(AtomicBool);
+
+impl Maintenance {
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::SeqCst)
+    }
+}
+
+/// Sends public requests to the maintenance page, so that their handlers do not touch the
+/// data while it is migrated.
+pub struct MaintenanceFairing;
+
+impl Fairing for MaintenanceFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Maintenance Fairing",
+            kind: fairing::Kind::Attach | fairing::Kind::Request,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let enabled = rocket.config().get_bool("maintenance").unwrap_or(false);
+
+        Ok(rocket.manage(Maintenance(AtomicBool::new(enabled))))
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        let enabled = request
+            .guard::<State<Maintenance>>()
+            .succeeded()
+            .map_or(false, |maintenance| maintenance.is_enabled());
+        let path = request.uri().path();
+        let unaffected = UNAFFECTED_PREFIXES
+            .iter()
+            .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)));
+        if enabled && !unaffected {
+            request.set_method(Method::Get);
+            request.set_uri(Origin::parse(MAINTENANCE_PATH).unwrap());
+        }
+    }
+}
+
+/// The maintenance page, answered with 503 and a hint when to try again.
+pub struct MaintenancePage(pub Markup);
+
+impl<'r> Responder<'r> for MaintenancePage {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        Response::build()
+            .status(Status::ServiceUnavailable)
+            .header(ContentType::HTML)
+            .header(Header::new("Retry-After", RETRY_AFTER_SECONDS.to_string()))
+            .sized_body(Cursor::new(self.0.into_string()))
+            .ok()
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt::Write;
+
+use maud::{Escaper, Markup, PreEscaped};
+
+/// Renders the Markdown that pages are written in. Only what informational pages need is
+/// supported: paragraphs, headings, lists, emphasis, and links. Everything else is shown as
+/// text, and HTML in the text is escaped.
+pub fn render(text: &str) -> Markup {
+    let mut html = String::new();
+    for block in blocks(text) {
+        match block {
+            Block::Heading(level, content) => {
+                // The page's title is the only first-level heading.
+                let tag = format!("h{}", (level + 1).min(6));
+                html.push_str(&format!("<{}>{}</{}>", tag, inline(&content), tag));
+            }
+            Block::Paragraph(content) => {
+                html.push_str(&format!("<p>{}</p>", inline(&content)));
+            }
+            Block::List(kind, items) => {
+                let tag = match kind {
+                    ListKind::Bullets => "ul",
+                    ListKind::Numbers => "ol",
+                };
+                html.push_str(&format!("<{}>", tag));
+                for item in items {
+                    html.push_str(&format!("<li>{}</li>", inline(&item)));
+                }
+                html.push_str(&format!("</{}>", tag));
+            }
+        }
+    }
+    PreEscaped(html)
+}
+
+/// The text without its Markdown, e. g. for excerpts. Blocks are separated by spaces and
+/// links are replaced by their text.
+pub fn plain_text(text: &str) -> String {
+    let contents: Vec<String> = blocks(text)
+        .into_iter()
+        .flat_map(|block| match block {
+            Block::Heading(_, content) | Block::Paragraph(content) => vec![content],
+            Block::List(_, items) => items,
+        })
+        .map(|content| render_inline(&content, Format::Text).replace('\n', " "))
+        .collect();
+    contents.join(" ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListKind {
+    Bullets,
+    Numbers,
+}
+
+/// Whether inline Markdown is rendered as HTML or left out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Html,
+    Text,
+}
+
+#[derive(Debug, PartialEq)]
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    List(ListKind, Vec<String>),
+}
+
+fn blocks(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Block> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            blocks.extend(current.take());
+        } else if let Some((level, content)) = heading(trimmed) {
+            blocks.extend(current.take());
+            blocks.push(Block::Heading(level, content.to_string()));
+        } else if let Some((kind, content)) = list_item(trimmed) {
+            match &mut current {
+                Some(Block::List(current_kind, items)) if *current_kind == kind => {
+                    items.push(content.to_string())
+                }
+                _ => {
+                    blocks.extend(current.take());
+                    current = Some(Block::List(kind, vec![content.to_string()]));
+                }
+            }
+        } else {
+            // Lines continue the paragraph or the list item before them.
+            match &mut current {
+                Some(Block::Paragraph(content)) => {
+                    content.push('\n');
+                    content.push_str(trimmed);
+                }
+                Some(Block::List(_, items)) => {
+                    let item = items.last_mut().unwrap();
+                    item.push('\n');
+                    item.push_str(trimmed);
+                }
+                _ => current = Some(Block::Paragraph(trimmed.to_string())),
+            }
+        }
+    }
+    blocks.extend(current);
+    blocks
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || !line[level..].starts_with(' ') {
+        return None;
+    }
+    Some((level, line[level..].trim()))
+}
+
+fn list_item(line: &str) -> Option<(ListKind, &str)> {
+    if line.starts_with("- ") || line.starts_with("* ") {
+        return Some((ListKind::Bullets, line[2..].trim()));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && line[digits..].starts_with(". ") {
+        return Some((ListKind::Numbers, line[digits + 2..].trim()));
+    }
+    None
+}
+
+/// Renders emphasis and links within a block.
+fn inline(text: &str) -> String {
+    render_inline(text, Format::Html)
+}
+
+fn render_inline(text: &str, format: Format) -> String {
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let consumed = enclosed(rest, "**", "strong", format, &mut rendered)
+            .or_else(|| enclosed(rest, "*", "em", format, &mut rendered))
+            .or_else(|| enclosed(rest, "_", "em", format, &mut rendered))
+            .or_else(|| link(rest, format, &mut rendered));
+        match consumed {
+            Some(length) => rest = &rest[length..],
+            None => {
+                match format {
+                    Format::Html => Escaper::new(&mut rendered).write_char(c).unwrap(),
+                    Format::Text => rendered.push(c),
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    rendered
+}
+
+/// Renders text between two markers like `**fett**` in the tag and returns the length of the
+/// Markdown it replaces.
+fn enclosed(
+    text: &str,
+    marker: &str,
+    tag: &str,
+    format: Format,
+    html: &mut String,
+) -> Option<usize> {
+    if !text.starts_with(marker) {
+        return None;
+    }
+    let content_length = text[marker.len()..].find(marker)?;
+    let content = &text[marker.len()..marker.len() + content_length];
+    if content.trim().is_empty() || content.starts_with(' ') {
+        return None;
+    }
+    let content = render_inline(content, format);
+    match format {
+        Format::Html => html.push_str(&format!("<{}>{}</{}>", tag, content, tag)),
+        Format::Text => html.push_str(&content),
+    }
+    Some(content_length + 2 * marker.len())
+}
+
+/// Renders `[Text](https://…)` and returns the length of the Markdown it replaces. Only
+/// links to websites, mail addresses, and paths of this site are rendered, so that pages
+/// cannot contain scripts.
+fn link(text: &str, format: Format, html: &mut String) -> Option<usize> {
+    if !text.starts_with('[') {
+        return None;
+    }
+    let label_end = text.find("](")?;
+    let url_end = label_end + text[label_end..].find(')')?;
+    let label = &text[1..label_end];
+    let url = &text[label_end + 2..url_end];
+    let safe = ["http://", "https://", "mailto:", "/", "#"]
+        .iter()
+        .any(|prefix| url.starts_with(prefix));
+    if label.is_empty() || !safe {
+        return None;
+    }
+
+    let label = render_inline(label, format);
+    match format {
+        Format::Html => {
+            html.push_str("<a href=\"");
+            Escaper::new(&mut *html).write_str(url).unwrap();
+            html.push_str(&format!("\">{}</a>", label));
+        }
+        Format::Text => html.push_str(&label),
+    }
+    Some(url_end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_are_rendered() {
+        let text = "# Über uns\n\
+                    Wir tanzen *Lindy Hop*\n\
+                    in Aachen.\n\
+                    \n\
+                    - Social Dance\n\
+                    - Kurse,\n  \
+                    jedes Semester\n\
+                    1. Anmelden";
+        assert_eq!(
+            render(text).into_string(),
+            "<h2>Über uns</h2>\
+             <p>Wir tanzen <em>Lindy Hop</em>\nin Aachen.</p>\
+             <ul><li>Social Dance</li><li>Kurse,\njedes Semester</li></ul>\
+             <ol><li>Anmelden</li></ol>"
+        );
+    }
+
+    #[test]
+    fn only_safe_links_are_rendered() {
+        assert_eq!(
+            inline("**[FAQ](/faq)** oder [Mail](mailto:info@lindyhop-aachen.de?a=1&b=2)"),
+            "<strong><a href=\"/faq\">FAQ</a></strong> oder \
+             <a href=\"mailto:info@lindyhop-aachen.de?a=1&amp;b=2\">Mail</a>"
+        );
+        assert_eq!(
+            inline("[Klick](javascript:alert(1)) <script>"),
+            "[Klick](javascript:alert(1)) &lt;script&gt;"
+        );
+        assert_eq!(inline("2 * 3 * 4"), "2 * 3 * 4");
+    }
+
+    #[test]
+    fn plain_text_leaves_out_the_markdown() {
+        assert_eq!(
+            plain_text("## Anfahrt\nMit dem **Bus** zum [Ponttor](/orte/1).\n\n- Linie 3 & 13"),
+            "Anfahrt Mit dem Bus zum Ponttor. Linie 3 & 13"
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use rocket::fairing::{self, Fairing};
+use rocket::http::{ContentType, RawStr, Status};
+use rocket::request::FromFormValue;
+use rocket::response::status::Custom;
+use rocket::response::NamedFile;
+use rocket::{Data, Rocket, Route, State};
+
+/// Big enough for photos straight from a phone.
+const MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Resizes images with ImageMagick, unless `image_resize_command` is configured. The
+/// placeholders in its arguments are replaced by the paths of the image and of the variant,
+/// and by the variant's width. An empty command generates no variants.
+const DEFAULT_RESIZE_COMMAND: &str =
+    "convert {input} -auto-orient -resize {width}x{width}> {output}";
+
+/// Smaller versions of uploaded images, so that pages need not load photos straight from a
+/// camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variant {
+    Thumbnail,
+    Card,
+    Full,
+}
+
+impl Variant {
+    const ALL: [Variant; 3] = [Variant::Thumbnail, Variant::Card, Variant::Full];
+
+    /// Images are scaled down to fit this width and height, but never up.
+    fn width(self) -> u32 {
+        match self {
+            Variant::Thumbnail => 200,
+            Variant::Card => 600,
+            Variant::Full => 1600,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Variant::Thumbnail => "thumbnail",
+            Variant::Card => "card",
+            Variant::Full => "full",
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for Variant {
+    type Error = &'v RawStr;
+
+    fn from_form_value(value: &'v RawStr) -> Result<Variant, &'v RawStr> {
+        Variant::ALL
+            .iter()
+            .cloned()
+            .find(|variant| variant.as_str() == value.as_str())
+            .ok_or(value)
+    }
+}
+
+/// `flyer.png` becomes `flyer-thumbnail.png`.
+fn variant_file_name(file_name: &str, variant: Variant) -> String {
+    match file_name.rfind('.') {
+        Some(dot) => format!(
+            "{}-{}{}",
+            &file_name[..dot],
+            variant.as_str(),
+            &file_name[dot..]
+        ),
+        None => format!("{}-{}", file_name, variant.as_str()),
+    }
+}
+
+/// Where uploaded files are stored, configured as `media_dir`.
+#[derive(Debug)]
+pub struct MediaDir {
+    path: PathBuf,
+    resize_command: Vec<String>,
+}
+
+impl MediaDir {
+    /// Stores the image with its variants. If the variants cannot be generated, e. g. because
+    /// ImageMagick is not installed, the image is served in full instead.
+    pub fn save_image(&self, file_name: &str, content: &[u8]) -> io::Result<()> {
+        fs::write(self.path.join(file_name), content)?;
+        for &variant in Variant::ALL.iter() {
+            if let Err(err) = self.resize(file_name, variant) {
+                eprintln!(
+                    "Failed to generate the {} variant of '{}': {}",
+                    variant.as_str(),
+                    file_name,
+                    err
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn resize(&self, file_name: &str, variant: Variant) -> io::Result<()> {
+        if self.resize_command.is_empty() {
+            return Ok(());
+        }
+        let input = self.path.join(file_name);
+        let output = self.path.join(variant_file_name(file_name, variant));
+        let arguments: Vec<String> = self
+            .resize_command
+            .iter()
+            .map(|argument| {
+                argument
+                    .replace("{input}", &input.to_string_lossy())
+                    .replace("{output}", &output.to_string_lossy())
+                    .replace("{width}", &variant.width().to_string())
+            })
+            .collect();
+
+        let status = Command::new(&arguments[0])
+            .args(&arguments[1..])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("'{}' failed with {}", arguments[0], status),
+            ))
+        }
+    }
+
+    /// Removes the image with its variants. Files that are already gone are fine.
+    pub fn remove_image(&self, file_name: &str) -> io::Result<()> {
+        let variants = Variant::ALL
+            .iter()
+            .map(|&variant| variant_file_name(file_name, variant));
+        for name in std::iter::once(file_name.to_string()).chain(variants) {
+            match fs::remove_file(self.path.join(name)) {
+                Err(ref err) if err.kind() == io::ErrorKind::NotFound => {}
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct MediaFairing;
+
+impl Fairing for MediaFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Media Directory Fairing",
+            kind: fairing::Kind::Attach,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let path = PathBuf::from(rocket.config().get_str("media_dir").unwrap_or("media"));
+        if let Err(err) = fs::create_dir_all(&path) {
+            eprintln!(
+                "Failed to create the media directory '{}': {}",
+                path.display(),
+                err
+            );
+            return Err(rocket);
+        }
+        let resize_command = rocket
+            .config()
+            .get_str("image_resize_command")
+            .unwrap_or(DEFAULT_RESIZE_COMMAND)
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+
+        Ok(rocket.manage(MediaDir {
+            path,
+            resize_command,
+        }))
+    }
+}
+
+/// Serves the variant of the size if there is one, e. g. `/media/<file>?size=thumbnail`,
+/// and the file in full otherwise.
+#[get("/media/<file..>?<size>")]
+fn media_file(
+    file: PathBuf,
+    size: Option<Variant>,
+    media_dir: State<MediaDir>,
+) -> Option<NamedFile> {
+    let variant = size.and_then(|variant| {
+        let file_name = file.to_str()?;
+        NamedFile::open(media_dir.path.join(variant_file_name(file_name, variant))).ok()
+    });
+    variant.or_else(|| NamedFile::open(media_dir.path.join(file)).ok())
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![media_file]
+}
+
+/// The image formats browsers can show.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageType {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl ImageType {
+    /// Recognizes the format by the first bytes of the file, since the content type sent by
+    /// the browser cannot be trusted.
+    pub fn detect(content: &[u8]) -> Option<ImageType> {
+        if content.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageType::Png)
+        } else if content.starts_with(b"\xff\xd8\xff") {
+            Some(ImageType::Jpeg)
+        } else if content.starts_with(b"GIF87a") || content.starts_with(b"GIF89a") {
+            Some(ImageType::Gif)
+        } else if content.len() >= 12 && &content[..4] == b"RIFF" && &content[8..12] == b"WEBP" {
+            Some(ImageType::Webp)
+        } else {
+            None
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageType::Png => "png",
+            ImageType::Jpeg => "jpg",
+            ImageType::Gif => "gif",
+            ImageType::Webp => "webp",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ImageType::Png => "image/png",
+            ImageType::Jpeg => "image/jpeg",
+            ImageType::Gif => "image/gif",
+            ImageType::Webp => "image/webp",
+        }
+    }
+}
+
+/// A file from a `multipart/form-data` body.
+#[derive(Debug, PartialEq)]
+pub struct Upload {
+    /// The name of the file on the uploader's computer.
+    pub file_name: String,
+    pub content: Vec<u8>,
+}
+
+/// Reads the files from a `multipart/form-data` body. Other fields are ignored.
+pub fn read_uploads(content_type: &ContentType, data: Data) -> Result<Vec<Upload>, Custom<String>> {
+    let boundary = match content_type.params().find(|&(name, _)| name == "boundary") {
+        Some((_, boundary)) if content_type.is_form_data() => boundary.to_string(),
+        _ => {
+            return Err(Custom(
+                Status::UnsupportedMediaType,
+                "Files must be uploaded as multipart/form-data.".to_string(),
+            ))
+        }
+    };
+
+    let mut body = Vec::new();
+    data.open()
+        .take(MAX_UPLOAD_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| Custom(Status::BadRequest, err.to_string()))?;
+    if body.len() as u64 > MAX_UPLOAD_BYTES {
+        return Err(Custom(
+            Status::PayloadTooLarge,
+            format!(
+                "Uploads must not be larger than {} MiB.",
+                MAX_UPLOAD_BYTES / 1024 / 1024
+            ),
+        ));
+    }
+
+    parse_multipart(&body, &boundary).ok_or_else(|| {
+        Custom(
+            Status::BadRequest,
+            "The multipart/form-data body is malformed.".to_string(),
+        )
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Returns `None` if the body does not follow RFC 7578.
+fn parse_multipart(body: &[u8], boundary: &str) -> Option<Vec<Upload>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut uploads = Vec::new();
+
+    let mut rest = &body[find(body, &delimiter)? + delimiter.len()..];
+    loop {
+        if rest.starts_with(b"--") {
+            return Some(uploads);
+        }
+        if !rest.starts_with(b"\r\n") {
+            return None;
+        }
+        rest = &rest[2..];
+
+        let headers_end = find(rest, b"\r\n\r\n")?;
+        let headers = std::str::from_utf8(&rest[..headers_end]).ok()?;
+        rest = &rest[headers_end + 4..];
+
+        let mut part_delimiter = b"\r\n".to_vec();
+        part_delimiter.extend_from_slice(&delimiter);
+        let content_end = find(rest, &part_delimiter)?;
+        let content = &rest[..content_end];
+        rest = &rest[content_end + part_delimiter.len()..];
+
+        if let Some(file_name) = headers.split("\r\n").find_map(disposition_file_name) {
+            uploads.push(Upload {
+                file_name,
+                content: content.to_vec(),
+            });
+        }
+    }
+}
+
+/// The `filename` parameter of a `Content-Disposition` header, if the line is one and names
+/// a file.
+fn disposition_file_name(header: &str) -> Option<String> {
+    let colon = header.find(':')?;
+    if !header[..colon]
+        .trim()
+        .eq_ignore_ascii_case("content-disposition")
+    {
+        return None;
+    }
+
+    header[colon + 1..]
+        .split(';')
+        .filter_map(|parameter| {
+            let equals = parameter.find('=')?;
+            if parameter[..equals].trim() == "filename" {
+                Some(parameter[equals + 1..].trim().trim_matches('"').to_string())
+            } else {
+                None
+            }
+        })
+        .find(|file_name| !file_name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_read_from_multipart_bodies() {
+        let body = b"--XyZ\r\n\
+            Content-Disposition: form-data; name=\"caption\"\r\n\r\n\
+            Ignored\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"image\"; filename=\"flyer.png\"\r\n\
+            Content-Type: image/png\r\n\r\n\
+            \x89PNG\r\n\x1a\n\r\n--X\r\n\
+            --XyZ\r\n\
+            content-disposition: form-data; name=\"empty\"; filename=\"\"\r\n\r\n\
+            \r\n\
+            --XyZ--\r\n";
+
+        assert_eq!(
+            parse_multipart(body, "XyZ"),
+            Some(vec![Upload {
+                file_name: "flyer.png".to_string(),
+                content: b"\x89PNG\r\n\x1a\n\r\n--X".to_vec(),
+            }])
+        );
+        assert_eq!(parse_multipart(b"--XyZ\r\nno headers", "XyZ"), None);
+    }
+
+    #[test]
+    fn images_are_recognized_by_their_content() {
+        assert_eq!(
+            ImageType::detect(b"\xff\xd8\xff\xe0 JFIF"),
+            Some(ImageType::Jpeg)
+        );
+        assert_eq!(
+            ImageType::detect(b"RIFF\x00\x00\x00\x00WEBPVP8 "),
+            Some(ImageType::Webp)
+        );
+        assert_eq!(ImageType::detect(b"<html>"), None);
+    }
+}
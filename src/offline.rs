@@ -0,0 +1,119 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use chrono::{Duration, Local, NaiveDate};
+use rocket::http::ContentType;
+use rocket::response::content::Content;
+use rocket::{Route, State};
+use rocket_contrib::json::{Json, JsonValue};
+use serde::Serialize;
+
+use crate::store::{
+    Actions, DisplayCutoff, Id, Location, OccurrenceFilter, OccurrenceWithEvent, ScheduleHorizon,
+    Store, StoreError,
+};
+
+/// How many days ahead the offline snapshot covers.
+const OFFLINE_DAYS: i64 = 14;
+
+const SERVICE_WORKER: &str = include_str!("service-worker.js");
+
+/// Browsers only offer to install the site if the manifest has an icon. A vector icon
+/// scales to every size they ask for.
+const ICON: &str = include_str!("icon.svg");
+
+#[get("/manifest.webmanifest")]
+fn manifest() -> Content<JsonValue> {
+    Content(
+        ContentType::new("application", "manifest+json"),
+        json!({
+            "name": "Lindy Hop Aachen",
+            "short_name": "Lindy Hop",
+            "lang": "de",
+            "start_url": "/",
+            "scope": "/",
+            "display": "standalone",
+            "background_color": "#e9e3ef",
+            "theme_color": "#9e133d",
+            "icons": [{
+                "src": "/icon.svg",
+                "sizes": "any",
+                "type": "image/svg+xml",
+            }],
+        }),
+    )
+}
+
+#[get("/icon.svg")]
+fn icon() -> Content<&'static str> {
+    Content(ContentType::SVG, ICON)
+}
+
+// The service worker has to be served from the root, otherwise its scope
+// would not include the start page.
+#[get("/sw.js")]
+fn service_worker() -> Content<&'static str> {
+    Content(ContentType::JavaScript, SERVICE_WORKER)
+}
+
+#[derive(Serialize)]
+struct OfflineData {
+    version: String,
+    days: BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>,
+    locations: HashMap<Id<Location>, Location>,
+}
+
+#[get("/offline-data.json")]
+fn offline_data(
+    store: Store,
+    cutoff: State<DisplayCutoff>,
+    horizon: State<ScheduleHorizon>,
+) -> Result<Json<OfflineData>, StoreError> {
+    let mut filter = OccurrenceFilter::upcoming(&cutoff, &horizon);
+    let offline_end = Local::now().naive_local() + Duration::days(OFFLINE_DAYS);
+    filter.before = filter.before.map(|before| before.min(offline_end));
+
+    let days = store.occurrences_by_date(&filter)?;
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+
+    Ok(Json(OfflineData {
+        version: snapshot_version(&days, &locations),
+        days,
+        locations,
+    }))
+}
+
+/// Derives a version from the snapshot's content, so that clients can tell
+/// whether their cached copy is still current.
+fn snapshot_version<S: BuildHasher>(
+    days: &BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>,
+    locations: &HashMap<Id<Location>, Location, S>,
+) -> String {
+    let mut hasher = DefaultHasher::new();
+    for (date, entries) in days {
+        date.hash(&mut hasher);
+        for entry in entries {
+            serde_json::to_string(entry).unwrap().hash(&mut hasher);
+        }
+    }
+    // HashMap iteration order is arbitrary, so combine the locations' hashes
+    // in an order-independent way.
+    let locations_hash = locations
+        .iter()
+        .map(|entry| {
+            let mut location_hasher = DefaultHasher::new();
+            serde_json::to_string(&entry)
+                .unwrap()
+                .hash(&mut location_hasher);
+            location_hasher.finish()
+        })
+        .fold(0u64, u64::wrapping_add);
+    locations_hash.hash(&mut hasher);
+
+    format!("{:016x}", hasher.finish())
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![manifest, icon, service_worker, offline_data]
+}
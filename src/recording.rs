@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDateTime;
+use rocket::fairing::{self, Fairing};
+use rocket::http::Method;
+use rocket::{Data, Request, Response, Rocket, Route, State};
+use rocket_contrib::json::Json;
+use serde::Serialize;
+
+const DEFAULT_CAPACITY: i64 = 50;
+
+/// Records the bodies of mutating requests and their responses, so that reports like
+/// "my save disappeared" can be diagnosed. Only active if `record_requests` is enabled.
+///
+/// Only the first 512 bytes of request bodies are available to fairings, so longer
+/// bodies are recorded truncated.
+pub struct RecordingFairing {
+    log: Arc<RequestLog>,
+}
+
+impl Default for RecordingFairing {
+    fn default() -> Self {
+        RecordingFairing {
+            log: Arc::new(RequestLog {
+                enabled: AtomicBool::new(false),
+                capacity: AtomicUsize::new(0),
+                entries: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+}
+
+pub struct RequestLog {
+    enabled: AtomicBool,
+    capacity: AtomicUsize,
+    entries: Mutex<VecDeque<Recording>>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Recording {
+    time: NaiveDateTime,
+    method: String,
+    uri: String,
+    request_body: String,
+    request_body_truncated: bool,
+    status: u16,
+    response_body: String,
+}
+
+struct RequestBody {
+    body: String,
+    truncated: bool,
+}
+
+fn is_mutating(method: Method) -> bool {
+    match method {
+        Method::Post | Method::Put | Method::Patch | Method::Delete => true,
+        _ => false,
+    }
+}
+
+impl Fairing for RecordingFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Request Recording Fairing",
+            kind: fairing::Kind::Attach | fairing::Kind::Request | fairing::Kind::Response,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let enabled = rocket.config().get_bool("record_requests").unwrap_or(false);
+        let capacity = rocket
+            .config()
+            .get_int("record_requests_capacity")
+            .unwrap_or(DEFAULT_CAPACITY);
+        if capacity <= 0 {
+            eprintln!(
+                "The request recording capacity must be positive, but is {}.",
+                capacity
+            );
+            return Err(rocket);
+        }
+
+        self.log.enabled.store(enabled, Ordering::Relaxed);
+        self.log
+            .capacity
+            .store(capacity as usize, Ordering::Relaxed);
+        Ok(rocket.manage(self.log.clone()))
+    }
+
+    fn on_request(&self, request: &mut Request, data: &Data) {
+        if !self.log.enabled.load(Ordering::Relaxed) || !is_mutating(request.method()) {
+            return;
+        }
+
+        request.local_cache(|| RequestBody {
+            body: String::from_utf8_lossy(data.peek()).into_owned(),
+            truncated: !data.peek_complete(),
+        });
+    }
+
+    fn on_response(&self, request: &Request, response: &mut Response) {
+        if !self.log.enabled.load(Ordering::Relaxed) || !is_mutating(request.method()) {
+            return;
+        }
+
+        let response_body = response.body_string().unwrap_or_default();
+        response.set_sized_body(Cursor::new(response_body.clone()));
+        let request_body = request.local_cache(|| RequestBody {
+            body: String::new(),
+            truncated: false,
+        });
+
+        let recording = Recording {
+            time: chrono::Local::now().naive_local(),
+            method: request.method().to_string(),
+            uri: request.uri().to_string(),
+            request_body: request_body.body.clone(),
+            request_body_truncated: request_body.truncated,
+            status: response.status().code,
+            response_body,
+        };
+
+        let capacity = self.log.capacity.load(Ordering::Relaxed);
+        let mut entries = self.log.entries.lock().unwrap();
+        if entries.len() >= capacity {
+            entries.pop_front();
+        }
+        entries.push_back(recording);
+    }
+}
+
+/// The recorded requests, most recent first.
+#[get("/requests")]
+fn requests(log: State<Arc<RequestLog>>) -> Option<Json<Vec<Recording>>> {
+    if !log.enabled.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let entries = log.entries.lock().unwrap();
+    Some(Json(entries.iter().rev().cloned().collect()))
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![requests]
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::markdown;
+use crate::store::{
+    Actions, FaqEntry, Id, OccurrenceFilter, Page, SearchResult, SearchResultKind, Store,
+    StoreResult,
+};
+use crate::text;
+use crate::website::event_url;
+
+const EXCERPT_LENGTH: usize = 160;
+
+/// Finds the published events and pages and the questions that contain every word of the
+/// query, ignoring case. Results are ordered by their type and then by their title, so that
+/// they can be shown in groups.
+pub fn search(store: &Store, query: &str) -> StoreResult<Vec<SearchResult>> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut candidates: Vec<(SearchResult, String)> = Vec::new();
+    for entry in store
+        .all_events_with_occurrences(&OccurrenceFilter::default())?
+        .values()
+    {
+        let event = &entry.event;
+        candidates.push((
+            result(
+                SearchResultKind::Event,
+                &event.title,
+                &event.teaser,
+                event_url(&event.slug),
+            ),
+            format!("{} {}", event.teaser, event.description),
+        ));
+    }
+
+    let pages: HashMap<Id<Page>, Page> = store.all()?;
+    for page in pages.values().filter(|page| page.published) {
+        let body = markdown::plain_text(&page.body);
+        candidates.push((
+            result(
+                SearchResultKind::Page,
+                &page.title,
+                &body,
+                format!("/{}", page.slug),
+            ),
+            body,
+        ));
+    }
+
+    let entries: HashMap<Id<FaqEntry>, FaqEntry> = store.all()?;
+    for entry in entries.values() {
+        let answer = markdown::plain_text(&entry.answer);
+        candidates.push((
+            result(
+                SearchResultKind::Faq,
+                &entry.question,
+                &answer,
+                "/faq".to_string(),
+            ),
+            answer,
+        ));
+    }
+
+    let mut results: Vec<SearchResult> = candidates
+        .into_iter()
+        .filter(|(result, text)| matches(&words, &format!("{} {}", result.title, text)))
+        .map(|(result, _)| result)
+        .collect();
+    results.sort_by(|a, b| (a.kind, &a.title).cmp(&(b.kind, &b.title)));
+    Ok(results)
+}
+
+fn result(kind: SearchResultKind, title: &str, text: &str, url: String) -> SearchResult {
+    SearchResult {
+        kind,
+        title: title.to_string(),
+        excerpt: text::truncate(text, EXCERPT_LENGTH),
+        url,
+    }
+}
+
+/// Whether the text contains all of the lowercase words.
+fn matches(words: &[String], text: &str) -> bool {
+    let text = text.to_lowercase();
+    words.iter().all(|word| text.contains(word.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_word_must_be_contained() {
+        let words = vec!["lindy".to_string(), "über".to_string()];
+        assert!(matches(&words, "Alles ÜBER Lindy Hop"));
+        assert!(!matches(&words, "Alles über Balboa"));
+    }
+}
@@ -0,0 +1,30 @@
+// Keeps the start page, its styles, and the schedule snapshot available
+// offline. Requests go to the network first and fall back to the cache, so
+// visitors with reception always see the current schedule.
+const CACHE = "lindyhop-aachen-offline";
+const PRECACHED = ["/", "/static/main.css", "/offline-data.json"];
+
+self.addEventListener("install", event => {
+  event.waitUntil(caches.open(CACHE).then(cache => cache.addAll(PRECACHED)));
+});
+
+self.addEventListener("activate", event => {
+  event.waitUntil(self.clients.claim());
+});
+
+self.addEventListener("fetch", event => {
+  const url = new URL(event.request.url);
+  if (event.request.method !== "GET" || !PRECACHED.includes(url.pathname)) {
+    return;
+  }
+
+  event.respondWith(
+    fetch(event.request)
+      .then(response => {
+        const copy = response.clone();
+        caches.open(CACHE).then(cache => cache.put(event.request, copy));
+        return response;
+      })
+      .catch(() => caches.match(event.request))
+  );
+});
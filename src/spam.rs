@@ -0,0 +1,152 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rocket::fairing::{self, Fairing};
+use rocket::request::{self, FromRequest, Request};
+use rocket::{Outcome, Rocket};
+
+/// Content scoring at least this is rejected, unless configured otherwise.
+const DEFAULT_THRESHOLD: i64 = 50;
+
+const HONEYPOT_SCORE: u32 = 100;
+/// Added for every link beyond the first. Spam usually advertises websites,
+/// genuine questions rarely need more than one link.
+const LINK_SCORE: u32 = 25;
+/// Added for every post from the same client beyond `RATE_LIMIT` within `RATE_WINDOW`.
+const RATE_SCORE: u32 = 25;
+const RATE_LIMIT: usize = 3;
+const RATE_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// The parts of the site that accept content from visitors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    Comments,
+    Submissions,
+    Newsletter,
+}
+
+impl Feature {
+    const ALL: [Feature; 3] = [Feature::Comments, Feature::Submissions, Feature::Newsletter];
+
+    fn config_key(self) -> &'static str {
+        match self {
+            Feature::Comments => "spam_threshold_comments",
+            Feature::Submissions => "spam_threshold_submissions",
+            Feature::Newsletter => "spam_threshold_newsletter",
+        }
+    }
+}
+
+/// What a visitor sent, as far as it matters for spam detection.
+pub struct Candidate<'a> {
+    /// A form field hidden from humans, so anything entered there comes from a bot.
+    pub honeypot: Option<&'a str>,
+    pub texts: &'a [&'a str],
+    pub client: ClientIp,
+}
+
+/// Scores content sent by visitors and rejects it if the score reaches the
+/// feature's threshold, configured as `spam_threshold_comments`,
+/// `spam_threshold_submissions`, and `spam_threshold_newsletter`.
+pub struct SpamFilter {
+    thresholds: HashMap<Feature, u32>,
+    recent_posts: Mutex<HashMap<(Feature, IpAddr), VecDeque<Instant>>>,
+}
+
+impl SpamFilter {
+    pub fn is_spam(&self, feature: Feature, candidate: &Candidate) -> bool {
+        self.score(feature, candidate) >= self.thresholds[&feature]
+    }
+
+    fn score(&self, feature: Feature, candidate: &Candidate) -> u32 {
+        let honeypot = match candidate.honeypot {
+            Some(honeypot) if !honeypot.is_empty() => HONEYPOT_SCORE,
+            _ => 0,
+        };
+        let links: usize = candidate
+            .texts
+            .iter()
+            .map(|text| text.matches("http://").count() + text.matches("https://").count())
+            .sum();
+        let link_score = links.saturating_sub(1) as u32 * LINK_SCORE;
+
+        honeypot + link_score + self.rate_score(feature, &candidate.client)
+    }
+
+    /// Records the post and scores how many other posts the client recently made.
+    fn rate_score(&self, feature: Feature, client: &ClientIp) -> u32 {
+        let ip = match client.0 {
+            Some(ip) => ip,
+            None => return 0,
+        };
+
+        let now = Instant::now();
+        let mut recent_posts = self.recent_posts.lock().unwrap();
+        // Forget about clients that have been quiet for a while, so the map does not grow forever.
+        recent_posts.retain(|_, posts| {
+            posts
+                .back()
+                .map_or(false, |last| now.duration_since(*last) < RATE_WINDOW)
+        });
+
+        let posts = recent_posts.entry((feature, ip)).or_default();
+        while posts
+            .front()
+            .map_or(false, |first| now.duration_since(*first) >= RATE_WINDOW)
+        {
+            posts.pop_front();
+        }
+        posts.push_back(now);
+
+        posts.len().saturating_sub(RATE_LIMIT) as u32 * RATE_SCORE
+    }
+}
+
+pub struct SpamFairing;
+
+impl Fairing for SpamFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Spam Filter Fairing",
+            kind: fairing::Kind::Attach,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let mut thresholds = HashMap::new();
+        for &feature in Feature::ALL.iter() {
+            let threshold = rocket
+                .config()
+                .get_int(feature.config_key())
+                .unwrap_or(DEFAULT_THRESHOLD);
+            if threshold <= 0 {
+                eprintln!(
+                    "The spam threshold '{}' must be positive, but is {}.",
+                    feature.config_key(),
+                    threshold
+                );
+                return Err(rocket);
+            }
+            thresholds.insert(feature, threshold as u32);
+        }
+
+        Ok(rocket.manage(SpamFilter {
+            thresholds,
+            recent_posts: Mutex::new(HashMap::new()),
+        }))
+    }
+}
+
+/// The client's IP address, if known. Unlike `SocketAddr`, this guard never fails.
+#[derive(Clone, Copy)]
+pub struct ClientIp(Option<IpAddr>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ClientIp {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(ClientIp(request.client_ip()))
+    }
+}
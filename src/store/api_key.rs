@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use diesel::{self, prelude::*};
+
+use super::db::{SqlApiKey, SqlApiKeyUsage, SqlId};
+use super::*;
+
+/// Marks the secrets of keys issued before only hashes were stored, see the migration
+/// `api_key_hashes`.
+const UNHASHED_PREFIX: &str = "unhashed:";
+
+/// The secrets are random and long, so a fast hash without salt cannot be reversed.
+fn hash_secret(secret: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, secret.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Hashes the secrets of the keys issued before only hashes were stored.
+pub fn hash_unhashed_secrets(conn: &SqliteConnection) -> QueryResult<()> {
+    use db::schema::api_keys::dsl::{api_keys, secret_hash};
+
+    conn.transaction(|| {
+        let unhashed = api_keys
+            .filter(secret_hash.like(format!("{}%", UNHASHED_PREFIX)))
+            .load::<SqlApiKey>(conn)?;
+        for api_key in unhashed {
+            let secret = &api_key.secret_hash[UNHASHED_PREFIX.len()..];
+            diesel::update(&api_key)
+                .set(secret_hash.eq(hash_secret(secret)))
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+/// How much of its daily quota a key used.
+#[derive(Debug, Clone, Copy)]
+pub struct DailyUsage {
+    /// Including the requests that were rejected for exceeding the quota.
+    pub requests: u32,
+    pub quota: u32,
+}
+
+impl DailyUsage {
+    pub fn is_exceeded(self) -> bool {
+        self.requests > self.quota
+    }
+
+    pub fn remaining(self) -> u32 {
+        self.quota.saturating_sub(self.requests)
+    }
+}
+
+/// API keys are not public, so they are neither part of the snapshot nor of the audit log.
+impl Store {
+    pub fn api_keys(&self, today: NaiveDate) -> StoreResult<HashMap<Id<ApiKey>, IssuedApiKey>> {
+        use db::schema::api_key_usage::dsl::{api_key_id, api_key_usage, date, requests};
+        use db::schema::api_keys::dsl::api_keys;
+
+        let used_today: HashMap<SqlId<ApiKey>, i32> = api_key_usage
+            .select((api_key_id, requests))
+            .filter(date.eq(today))
+            .load::<(SqlId<ApiKey>, i32)>(self.connection()?)?
+            .into_iter()
+            .collect();
+
+        Ok(api_keys
+            .load::<SqlApiKey>(self.connection()?)?
+            .into_iter()
+            .map(|api_key| {
+                let used = used_today.get(&api_key.id).cloned().unwrap_or(0);
+                (
+                    api_key.id.into(),
+                    IssuedApiKey {
+                        name: api_key.name,
+                        daily_quota: api_key.daily_quota as u32,
+                        used_today: used as u32,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Issues a key with a new secret, which is returned but not stored.
+    pub fn create_api_key(&self, api_key: ApiKey) -> StoreResult<NewApiKey> {
+        use db::schema::api_keys::dsl::api_keys;
+
+        let secret = new_token();
+        let sql_api_key = SqlApiKey::from((api_key, hash_secret(&secret)));
+        self.write(|| {
+            diesel::insert_into(api_keys)
+                .values(&sql_api_key)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(NewApiKey {
+            id: sql_api_key.id.into(),
+            secret,
+        })
+    }
+
+    /// Renames the key or changes its quota, keeping its secret, and returns the previous
+    /// version.
+    pub fn update_api_key(&self, id: Id<ApiKey>, api_key: ApiKey) -> StoreResult<ApiKey> {
+        use db::schema::api_keys::dsl::{api_keys, daily_quota, name};
+
+        let raw_id: SqlId<ApiKey> = id.into();
+        self.write(|| {
+            let previous = api_keys
+                .find(&raw_id)
+                .first::<SqlApiKey>(self.connection()?)?;
+            diesel::update(&previous)
+                .set((
+                    name.eq(&api_key.name),
+                    daily_quota.eq(api_key.daily_quota as i32),
+                ))
+                .execute(self.connection()?)?;
+
+            Ok(previous.into())
+        })
+    }
+
+    /// Revokes the key, forgetting its usage, and returns it.
+    pub fn delete_api_key(&self, id: Id<ApiKey>) -> StoreResult<ApiKey> {
+        use db::schema::api_key_usage::dsl::{api_key_id, api_key_usage};
+        use db::schema::api_keys::dsl::api_keys;
+
+        let raw_id: SqlId<ApiKey> = id.into();
+        self.write(|| {
+            let previous = api_keys
+                .find(&raw_id)
+                .first::<SqlApiKey>(self.connection()?)?;
+            diesel::delete(api_key_usage.filter(api_key_id.eq(&raw_id)))
+                .execute(self.connection()?)?;
+            diesel::delete(&previous).execute(self.connection()?)?;
+
+            Ok(previous.into())
+        })
+    }
+
+    /// The number of requests made with the key per day.
+    pub fn api_key_usage(&self, id: Id<ApiKey>) -> StoreResult<BTreeMap<NaiveDate, u32>> {
+        use db::schema::api_key_usage::dsl::{api_key_id, api_key_usage};
+        use db::schema::api_keys::dsl::api_keys;
+
+        let raw_id: SqlId<ApiKey> = id.into();
+        // Fails if the key does not exist.
+        api_keys
+            .find(&raw_id)
+            .first::<SqlApiKey>(self.connection()?)?;
+
+        Ok(api_key_usage
+            .filter(api_key_id.eq(&raw_id))
+            .load::<SqlApiKeyUsage>(self.connection()?)?
+            .into_iter()
+            .map(|usage| (usage.date, usage.requests as u32))
+            .collect())
+    }
+
+    /// Counts a request made with the secret, and returns how much of the key's quota is
+    /// used today. Returns `None` if no key has the secret.
+    pub fn count_api_request(
+        &self,
+        secret: &str,
+        today: NaiveDate,
+    ) -> StoreResult<Option<DailyUsage>> {
+        use db::schema::api_key_usage::dsl::{api_key_id, api_key_usage, date, requests};
+        use db::schema::api_keys::dsl::{api_keys, secret_hash};
+
+        let hash = hash_secret(secret);
+        self.write(|| {
+            let api_key = match api_keys
+                .filter(secret_hash.eq(&hash))
+                .first::<SqlApiKey>(self.connection()?)
+                .optional()?
+            {
+                Some(api_key) => api_key,
+                None => return Ok(None),
+            };
+
+            let usage_today = api_key_usage
+                .filter(api_key_id.eq(&api_key.id))
+                .filter(date.eq(today));
+            let updated = diesel::update(usage_today)
+                .set(requests.eq(requests + 1))
+                .execute(self.connection()?)?;
+            if updated == 0 {
+                diesel::insert_into(api_key_usage)
+                    .values(&SqlApiKeyUsage {
+                        api_key_id: api_key.id.clone(),
+                        date: today,
+                        requests: 1,
+                    })
+                    .execute(self.connection()?)?;
+            }
+            let used = usage_today
+                .select(requests)
+                .first::<i32>(self.connection()?)?;
+
+            Ok(Some(DailyUsage {
+                requests: used as u32,
+                quota: api_key.daily_quota as u32,
+            }))
+        })
+    }
+}
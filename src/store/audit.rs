@@ -0,0 +1,100 @@
+use chrono::{Local, NaiveDateTime};
+use diesel::{self, prelude::*};
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::db::{NewSqlAuditEntry, SqlAuditEntry};
+use super::*;
+
+/// How many entries a page of the audit log holds.
+const AUDIT_PAGE_SIZE: i64 = 50;
+
+/// A change made to a location, an event, or a recurrence.
+#[derive(Serialize, Debug)]
+pub struct AuditEntry {
+    pub recorded_at: NaiveDateTime,
+    pub entity: String,
+    pub entity_id: Uuid,
+    /// One of `create`, `update`, and `delete`.
+    pub action: String,
+    /// The values that changed, each as an object with the value `before` and `after`
+    /// the change. Objects are compared field by field, so an update lists only the
+    /// fields that changed.
+    pub changes: Value,
+}
+
+impl From<SqlAuditEntry> for AuditEntry {
+    fn from(entry: SqlAuditEntry) -> Self {
+        AuditEntry {
+            recorded_at: entry.recorded_at,
+            entity: entry.entity,
+            entity_id: Uuid::from_slice(&entry.entity_id).unwrap_or_else(|_| Uuid::nil()),
+            action: entry.action,
+            changes: serde_json::from_str(&entry.changes).unwrap_or(Value::Null),
+        }
+    }
+}
+
+fn diff(before: &Value, after: &Value) -> Value {
+    match (before, after) {
+        (Value::Object(before_fields), Value::Object(after_fields)) => {
+            let mut changes = serde_json::Map::new();
+            for key in before_fields.keys().chain(after_fields.keys()) {
+                let before_value = before_fields.get(key).unwrap_or(&Value::Null);
+                let after_value = after_fields.get(key).unwrap_or(&Value::Null);
+                if before_value != after_value && !changes.contains_key(key) {
+                    changes.insert(key.clone(), diff(before_value, after_value));
+                }
+            }
+            Value::Object(changes)
+        }
+        _ => serde_json::json!({ "before": before, "after": after }),
+    }
+}
+
+fn to_json<T: Serialize>(item: Option<&T>) -> Value {
+    item.map_or(Value::Null, |item| {
+        serde_json::to_value(item).expect("Stored items are always serializable.")
+    })
+}
+
+impl Store {
+    /// Saves the change in the audit log, within the write making it, so that either both
+    /// are saved or neither is.
+    pub(super) fn audit<T: Serialize>(
+        &self,
+        change: &Change,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> StoreResult<()> {
+        use db::schema::audit_log::dsl::audit_log;
+
+        let changes = diff(&to_json(before), &to_json(after));
+        diesel::insert_into(audit_log)
+            .values(&NewSqlAuditEntry {
+                recorded_at: Local::now().naive_local(),
+                entity: change.entity.as_str().to_string(),
+                entity_id: change.entity_id.as_bytes().to_vec(),
+                action: change.action.as_str().to_string(),
+                changes: changes.to_string(),
+            })
+            .execute(self.connection()?)?;
+        Ok(())
+    }
+
+    /// A page of the audit log, starting at 1, with the most recent changes first.
+    pub fn audit_log(&self, page: u32) -> StoreResult<Vec<AuditEntry>> {
+        use db::schema::audit_log::dsl::{audit_log, id};
+
+        let page = i64::from(page.max(1));
+        Ok(audit_log
+            .order(id.desc())
+            .limit(AUDIT_PAGE_SIZE)
+            .offset((page - 1) * AUDIT_PAGE_SIZE)
+            .load::<SqlAuditEntry>(self.connection()?)?
+            .into_iter()
+            .map(AuditEntry::from)
+            .collect())
+    }
+}
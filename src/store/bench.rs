@@ -0,0 +1,68 @@
+//! Benchmarks for the queries behind the public pages, run with `cargo bench`.
+//!
+//! They live inside the crate, since there is no library for `benches/` to link against.
+//! `cargo test` runs every benchmark once, which keeps them compiling.
+
+extern crate test;
+
+use chrono::Duration;
+use test::Bencher;
+
+use super::tests::TestDatabase;
+use super::*;
+use crate::fixtures::{FixtureOptions, Fixtures};
+
+/// Regular events from the past year up to the schedule horizon, about 10,000 occurrences,
+/// so that the upcoming filter selects about a third of them.
+fn generate() -> TestDatabase {
+    let database = TestDatabase::new();
+    Fixtures::generate(&FixtureOptions {
+        events: 190,
+        locations: 10,
+        years: 1,
+        ..FixtureOptions::default()
+    })
+    .insert(&database.store)
+    .unwrap();
+
+    database
+}
+
+fn upcoming() -> OccurrenceFilter {
+    OccurrenceFilter::upcoming(
+        &DisplayCutoff(Duration::hours(3)),
+        &ScheduleHorizon(Duration::weeks(26)),
+    )
+}
+
+#[bench]
+fn occurrences_by_date(b: &mut Bencher) {
+    let dataset = generate();
+    let filter = OccurrenceFilter::default();
+
+    b.iter(|| dataset.store.occurrences_by_date(&filter).unwrap());
+}
+
+#[bench]
+fn occurrences_by_date_upcoming(b: &mut Bencher) {
+    let dataset = generate();
+    let filter = upcoming();
+
+    b.iter(|| dataset.store.occurrences_by_date(&filter).unwrap());
+}
+
+#[bench]
+fn all_events_with_occurrences(b: &mut Bencher) {
+    let dataset = generate();
+    let filter = OccurrenceFilter::default();
+
+    b.iter(|| dataset.store.all_events_with_occurrences(&filter).unwrap());
+}
+
+#[bench]
+fn all_events_with_occurrences_upcoming(b: &mut Bencher) {
+    let dataset = generate();
+    let filter = upcoming();
+
+    b.iter(|| dataset.store.all_events_with_occurrences(&filter).unwrap());
+}
@@ -0,0 +1,99 @@
+use std::sync::RwLock;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::db::SqlId;
+use super::{Id, Store, StoreResult};
+
+/// The kinds of items whose changes are published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entity {
+    Location,
+    Event,
+    Recurrence,
+}
+
+impl Entity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Entity::Location => "location",
+            Entity::Event => "event",
+            Entity::Recurrence => "recurrence",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl ChangeAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeAction::Created => "create",
+            ChangeAction::Updated => "update",
+            ChangeAction::Deleted => "delete",
+        }
+    }
+}
+
+/// A change the store has saved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub entity: Entity,
+    pub entity_id: Uuid,
+    pub action: ChangeAction,
+}
+
+type Subscriber = Box<dyn Fn(&Change) + Send + Sync>;
+
+/// Tells the parts of the server that depend on the stored data about changes, so that the
+/// store need not know about them. Managed as state by the store's fairing.
+///
+/// Subscribers are only called once the change has been committed.
+#[derive(Default)]
+pub struct ChangeBus {
+    subscribers: RwLock<Vec<Subscriber>>,
+}
+
+impl ChangeBus {
+    pub fn subscribe(&self, subscriber: impl Fn(&Change) + Send + Sync + 'static) {
+        self.subscribers.write().unwrap().push(Box::new(subscriber));
+    }
+
+    pub(super) fn publish(&self, change: &Change) {
+        for subscriber in self.subscribers.read().unwrap().iter() {
+            subscriber(change);
+        }
+    }
+}
+
+impl Store {
+    /// Records a change made within the current write. A change without a previous version
+    /// is a creation, one without a new version a deletion.
+    pub(super) fn record_change<Item, T: Serialize>(
+        &self,
+        entity: Entity,
+        entity_id: &SqlId<Item>,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> StoreResult<()> {
+        let entity_id: Id<Item> = entity_id.clone().into();
+        let change = Change {
+            entity,
+            entity_id: entity_id.into(),
+            action: match (&before, &after) {
+                (None, _) => ChangeAction::Created,
+                (Some(_), Some(_)) => ChangeAction::Updated,
+                (Some(_), None) => ChangeAction::Deleted,
+            },
+        };
+        self.audit(&change, before, after)?;
+        self.pending_changes.borrow_mut().push(change);
+        Ok(())
+    }
+}
@@ -12,10 +12,20 @@ embed_migrations!();
 pub fn initialize(rocket: Rocket) -> Result<Rocket, Rocket> {
     let conn = Connection::get_one(&rocket).expect("Database connection failed.");
 
-    match embedded_migrations::run(&*conn) {
+    if let Err(e) = embedded_migrations::run(&*conn) {
+        eprintln!("Failed to run database migrations: {:?}", e);
+        return Err(rocket);
+    }
+
+    if let Err(e) = super::slug::assign_missing(&*conn) {
+        eprintln!("Failed to assign slugs to existing events: {:?}", e);
+        return Err(rocket);
+    }
+
+    match super::api_key::hash_unhashed_secrets(&*conn) {
         Ok(()) => Ok(rocket),
         Err(e) => {
-            eprintln!("Failed to run database migrations: {:?}", e);
+            eprintln!("Failed to hash the secrets of existing API keys: {:?}", e);
             Err(rocket)
         }
     }
@@ -28,6 +38,13 @@ pub mod schema {
             title -> Text,
             teaser -> Text,
             description -> Text,
+            contact_name -> Nullable<Text>,
+            contact_email -> Nullable<Text>,
+            locked -> Bool,
+            slug -> Text,
+            deleted_at -> Nullable<Timestamp>,
+            published -> Bool,
+            publish_at -> Nullable<Timestamp>,
         }
     }
     table! {
@@ -36,6 +53,67 @@ pub mod schema {
             event_id -> Binary,
             start -> Timestamp,
             duration -> Integer,
+            location_id -> Nullable<Binary>,
+            doors_open -> Nullable<Integer>,
+            open_end -> Bool,
+            stream_url -> Nullable<Text>,
+            recurrence_id -> Nullable<Binary>,
+            cancelled -> Bool,
+            cancellation_reason -> Nullable<Text>,
+        }
+    }
+    table! {
+        recurrences {
+            id -> Binary,
+            event_id -> Binary,
+            location_id -> Nullable<Binary>,
+            start -> Timestamp,
+            duration -> Integer,
+            doors_open -> Nullable<Integer>,
+            open_end -> Bool,
+            stream_url -> Nullable<Text>,
+            interval_weeks -> Integer,
+            until -> Nullable<Date>,
+            expanded_until -> Nullable<Timestamp>,
+        }
+    }
+    table! {
+        recurrence_exceptions (recurrence_id, date) {
+            recurrence_id -> Binary,
+            date -> Date,
+            cancelled -> Bool,
+            start -> Nullable<Timestamp>,
+            location_id -> Nullable<Binary>,
+        }
+    }
+    table! {
+        deleted_events {
+            id -> Binary,
+            title -> Text,
+            deleted_at -> Timestamp,
+            slug -> Text,
+        }
+    }
+    table! {
+        comments {
+            id -> Binary,
+            event_id -> Binary,
+            name -> Text,
+            text -> Text,
+            created_at -> Timestamp,
+            approved -> Bool,
+        }
+    }
+    table! {
+        submissions {
+            id -> Binary,
+            title -> Text,
+            teaser -> Text,
+            description -> Text,
+            organizer_name -> Text,
+            organizer_email -> Text,
+            start -> Timestamp,
+            duration -> Integer,
             location_id -> Binary,
         }
     }
@@ -43,11 +121,118 @@ pub mod schema {
         locations {
             id -> Binary,
             name -> Text,
-            address -> Text,
+            street -> Text,
+            postal_code -> Text,
+            city -> Text,
+            deleted_at -> Nullable<Timestamp>,
+            latitude -> Nullable<Double>,
+            longitude -> Nullable<Double>,
+            manual_coordinates -> Bool,
+        }
+    }
+    table! {
+        audit_log {
+            id -> Integer,
+            recorded_at -> Timestamp,
+            entity -> Text,
+            entity_id -> Binary,
+            action -> Text,
+            changes -> Text,
+        }
+    }
+    table! {
+        newsletters {
+            id -> Binary,
+            title -> Text,
+            date -> Date,
+            content -> Text,
         }
     }
+    table! {
+        subscribers {
+            id -> Binary,
+            email -> Text,
+            name -> Nullable<Text>,
+            consented_at -> Date,
+            confirmed -> Bool,
+            confirmation_token -> Nullable<Text>,
+            unsubscribe_token -> Text,
+        }
+    }
+    table! {
+        newsletter_deliveries (newsletter_id, subscriber_id) {
+            newsletter_id -> Binary,
+            subscriber_id -> Binary,
+            sent_at -> Timestamp,
+            error -> Nullable<Text>,
+        }
+    }
+    table! {
+        api_keys {
+            id -> Binary,
+            secret_hash -> Text,
+            name -> Text,
+            daily_quota -> Integer,
+        }
+    }
+    table! {
+        api_key_usage (api_key_id, date) {
+            api_key_id -> Binary,
+            date -> Date,
+            requests -> Integer,
+        }
+    }
+    table! {
+        event_images {
+            id -> Binary,
+            event_id -> Binary,
+            file_name -> Text,
+            original_name -> Text,
+            content_type -> Text,
+            size -> BigInt,
+            uploaded_at -> Timestamp,
+        }
+    }
+    table! {
+        nav_items {
+            id -> Binary,
+            label -> Text,
+            url -> Text,
+            position -> Integer,
+            visible -> Bool,
+        }
+    }
+    table! {
+        pages {
+            id -> Binary,
+            slug -> Text,
+            title -> Text,
+            body -> Text,
+            published -> Bool,
+        }
+    }
+    table! {
+        faq_entries {
+            id -> Binary,
+            question -> Text,
+            answer -> Text,
+            category -> Text,
+            position -> Integer,
+        }
+    }
+    table! {
+        redirects {
+            id -> Binary,
+            source -> Text,
+            target -> Text,
+            status -> Integer,
+        }
+    }
+    // Lets the recurrences and occurrences of trashed events be filtered out with subqueries.
+    allow_tables_to_appear_in_same_query!(events, occurrences, recurrences);
 }
 
+use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::marker::PhantomData;
 
@@ -56,30 +241,52 @@ use diesel::backend::Backend;
 use diesel::deserialize;
 use diesel::expression::{bound::Bound, AsExpression};
 use diesel::serialize::{self, Output};
-use diesel::sql_types::{Binary, HasSqlType};
+use diesel::sql_types::{Binary, HasSqlType, Nullable};
 use diesel::sqlite::Sqlite;
 use diesel::types::{FromSql, ToSql};
 use schema::*;
 
 // SqlId implementation inspired by https://github.com/forte-music/core/blob/fc9cd6217708b0dd6ae684df3a53276804479c59/src/models/id.rs#L67
-#[derive(Debug, Deserialize, FromSqlRow, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Deserialize, FromSqlRow)]
 pub struct SqlId<Item>(Uuid, PhantomData<Item>);
 
+// Implemented manually for the same reason as on `Id`.
+impl<Item> Clone for SqlId<Item> {
+    fn clone(&self) -> Self {
+        self.0.into()
+    }
+}
+
+impl<Item> PartialEq for SqlId<Item> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<Item> Eq for SqlId<Item> {}
+
+impl<Item> Hash for SqlId<Item> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
 impl<Item> From<Uuid> for SqlId<Item> {
     fn from(uuid: Uuid) -> Self {
         SqlId(uuid, PhantomData)
     }
 }
 
-impl<Item> From<SqlId<Item>> for super::Id<Item> {
-    fn from(id: SqlId<Item>) -> super::Id<Item> {
-        id.0.into()
+// `From` cannot be implemented for `Id`, since it is defined in another crate.
+impl<Item> Into<super::Id<Item>> for SqlId<Item> {
+    fn into(self) -> super::Id<Item> {
+        self.0.into()
     }
 }
 
 impl<Item> From<super::Id<Item>> for SqlId<Item> {
     fn from(id: super::Id<Item>) -> SqlId<Item> {
-        id.id.into()
+        Uuid::from(id).into()
     }
 }
 
@@ -90,6 +297,12 @@ impl<DB: Backend + HasSqlType<Binary>, Item: Debug> ToSql<Binary, DB> for SqlId<
     }
 }
 
+impl<DB: Backend + HasSqlType<Binary>, Item: Debug> ToSql<Nullable<Binary>, DB> for SqlId<Item> {
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        <Self as ToSql<Binary, DB>>::to_sql(self, out)
+    }
+}
+
 impl<Item> FromSql<Binary, Sqlite> for SqlId<Item> {
     fn from_sql(bytes: Option<&<Sqlite as Backend>::RawValue>) -> deserialize::Result<Self> {
         let bytes_vec = <Vec<u8> as FromSql<Binary, Sqlite>>::from_sql(bytes)?;
@@ -113,13 +326,38 @@ impl<'a, Item> AsExpression<Binary> for &'a SqlId<Item> {
     }
 }
 
+impl<Item> AsExpression<Nullable<Binary>> for SqlId<Item> {
+    type Expression = Bound<Nullable<Binary>, SqlId<Item>>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl<'a, Item> AsExpression<Nullable<Binary>> for &'a SqlId<Item> {
+    type Expression = Bound<Nullable<Binary>, &'a SqlId<Item>>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
 #[derive(Queryable, Insertable, Debug, Identifiable, Clone, PartialEq, AsChangeset)]
 #[table_name = "events"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct SqlEvent {
     pub id: SqlId<Event>,
     pub title: String,
     pub teaser: String,
     pub description: String,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub locked: bool,
+    pub slug: String,
+    /// When the event was moved to the trash, if it was.
+    pub deleted_at: Option<NaiveDateTime>,
+    pub published: bool,
+    pub publish_at: Option<NaiveDateTime>,
 }
 
 impl From<SqlEvent> for (super::Id<Event>, Event) {
@@ -130,6 +368,12 @@ impl From<SqlEvent> for (super::Id<Event>, Event) {
                 title: event.title,
                 teaser: event.teaser,
                 description: event.description,
+                contact_name: event.contact_name,
+                contact_email: event.contact_email,
+                locked: event.locked,
+                slug: event.slug,
+                published: event.published,
+                publish_at: event.publish_at,
             },
         )
     }
@@ -144,6 +388,13 @@ impl From<Event> for SqlEvent {
             title: event.title,
             teaser: event.teaser,
             description: event.description,
+            contact_name: event.contact_name,
+            contact_email: event.contact_email,
+            locked: event.locked,
+            slug: event.slug,
+            deleted_at: None,
+            published: event.published,
+            publish_at: event.publish_at,
         }
     }
 }
@@ -159,7 +410,14 @@ pub struct SqlOccurrence {
     pub event_id: SqlId<Event>,
     pub start: NaiveDateTime,
     pub duration: i32,
-    pub location_id: SqlId<Location>,
+    pub location_id: Option<SqlId<Location>>,
+    pub doors_open: Option<i32>,
+    pub open_end: bool,
+    pub stream_url: Option<String>,
+    /// The recurrence the occurrence was created from, if any.
+    pub recurrence_id: Option<SqlId<Recurrence>>,
+    pub cancelled: bool,
+    pub cancellation_reason: Option<String>,
 }
 
 impl From<SqlOccurrence> for (Id<Occurrence>, OccurrenceWithLocation) {
@@ -169,9 +427,16 @@ impl From<SqlOccurrence> for (Id<Occurrence>, OccurrenceWithLocation) {
             (OccurrenceWithLocation {
                 occurrence: Occurrence {
                     start: occurrence.start,
-                    duration: occurrence.duration as u32,
+                    duration: chrono::Duration::minutes(occurrence.duration.into()),
+                    doors_open: occurrence
+                        .doors_open
+                        .map(|minutes| chrono::Duration::minutes(minutes.into())),
+                    open_end: occurrence.open_end,
+                    stream_url: occurrence.stream_url,
+                    cancelled: occurrence.cancelled,
+                    cancellation_reason: occurrence.cancellation_reason,
                 },
-                location_id: occurrence.location_id.into(),
+                location_id: occurrence.location_id.map(|id| id.into()),
             }),
         )
     }
@@ -192,19 +457,163 @@ impl From<(OccurrenceWithLocation, SqlId<Event>)> for SqlOccurrence {
         SqlOccurrence {
             id: id.into(),
             start: occurrence.start,
-            duration: occurrence.duration as i32,
-            location_id: location_id.into(),
+            duration: occurrence.duration.num_minutes() as i32,
+            location_id: location_id.map(|id| id.into()),
             event_id,
+            doors_open: occurrence
+                .doors_open
+                .map(|doors_open| doors_open.num_minutes() as i32),
+            open_end: occurrence.open_end,
+            stream_url: occurrence.stream_url,
+            recurrence_id: None,
+            cancelled: occurrence.cancelled,
+            cancellation_reason: occurrence.cancellation_reason,
         }
     }
 }
 
+#[derive(Queryable, Insertable, Clone, Debug, Identifiable, AsChangeset, Associations)]
+#[belongs_to(SqlEvent, foreign_key = "event_id")]
+#[table_name = "recurrences"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct SqlRecurrence {
+    pub id: SqlId<Recurrence>,
+    pub event_id: SqlId<Event>,
+    pub location_id: Option<SqlId<Location>>,
+    pub start: NaiveDateTime,
+    pub duration: i32,
+    pub doors_open: Option<i32>,
+    pub open_end: bool,
+    pub stream_url: Option<String>,
+    pub interval_weeks: i32,
+    pub until: Option<NaiveDate>,
+    pub expanded_until: Option<NaiveDateTime>,
+}
+
+impl SqlRecurrence {
+    pub fn new(recurrence: Recurrence, event_id: SqlId<Event>) -> Self {
+        // Reuses the conversion of occurrences, so both store their fields alike.
+        let first: SqlOccurrence = (recurrence.first, event_id.clone()).into();
+
+        SqlRecurrence {
+            id: Uuid::new_v4().into(),
+            event_id,
+            location_id: first.location_id,
+            start: first.start,
+            duration: first.duration,
+            doors_open: first.doors_open,
+            open_end: first.open_end,
+            stream_url: first.stream_url,
+            interval_weeks: recurrence.interval_weeks as i32,
+            until: recurrence.until,
+            expanded_until: None,
+        }
+    }
+
+    /// The occurrence taking place at `start`, attributed to this recurrence.
+    pub fn occurrence_at(&self, start: NaiveDateTime) -> SqlOccurrence {
+        SqlOccurrence {
+            id: Uuid::new_v4().into(),
+            event_id: self.event_id.clone(),
+            start,
+            duration: self.duration,
+            location_id: self.location_id.clone(),
+            doors_open: self.doors_open,
+            open_end: self.open_end,
+            stream_url: self.stream_url.clone(),
+            recurrence_id: Some(self.id.clone()),
+            cancelled: false,
+            cancellation_reason: None,
+        }
+    }
+
+    /// Converts the recurrence back, together with its exceptions.
+    pub fn with_exceptions(
+        self,
+        exceptions: Vec<SqlRecurrenceException>,
+    ) -> (Id<Recurrence>, Recurrence) {
+        let (_, first) = self.occurrence_at(self.start).into();
+
+        (
+            self.id.into(),
+            Recurrence {
+                first,
+                interval_weeks: self.interval_weeks as u32,
+                until: self.until,
+                exceptions: exceptions
+                    .into_iter()
+                    .map(|exception| exception.into())
+                    .collect(),
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Insertable, Clone, Debug, Identifiable, Associations)]
+#[belongs_to(SqlRecurrence, foreign_key = "recurrence_id")]
+#[table_name = "recurrence_exceptions"]
+#[primary_key(recurrence_id, date)]
+pub struct SqlRecurrenceException {
+    pub recurrence_id: SqlId<Recurrence>,
+    pub date: NaiveDate,
+    pub cancelled: bool,
+    pub start: Option<NaiveDateTime>,
+    pub location_id: Option<SqlId<Location>>,
+}
+
+impl SqlRecurrenceException {
+    pub fn new(exception: RecurrenceException, recurrence_id: SqlId<Recurrence>) -> Self {
+        SqlRecurrenceException {
+            recurrence_id,
+            date: exception.date,
+            cancelled: exception.cancelled,
+            start: exception.start,
+            location_id: exception.location_id.map(|id| id.into()),
+        }
+    }
+
+    /// Applies the exception to the occurrence the recurrence would create on its date.
+    pub fn apply(&self, mut occurrence: SqlOccurrence) -> Option<SqlOccurrence> {
+        if self.cancelled {
+            return None;
+        }
+        if let Some(start) = self.start {
+            occurrence.start = start;
+        }
+        if let Some(location_id) = &self.location_id {
+            occurrence.location_id = Some(location_id.clone());
+        }
+        Some(occurrence)
+    }
+}
+
+impl From<SqlRecurrenceException> for RecurrenceException {
+    fn from(exception: SqlRecurrenceException) -> Self {
+        RecurrenceException {
+            date: exception.date,
+            cancelled: exception.cancelled,
+            start: exception.start,
+            location_id: exception.location_id.map(|id| id.into()),
+        }
+    }
+}
+
+/// Removing a location's coordinates has to clear them. Since trashed locations cannot be
+/// updated, their `deleted_at` is never cleared by this.
 #[derive(Queryable, Clone, Identifiable, Insertable, Debug, AsChangeset)]
 #[table_name = "locations"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct SqlLocation {
     pub id: SqlId<Location>,
     pub name: String,
-    pub address: String,
+    pub street: String,
+    pub postal_code: String,
+    pub city: String,
+    /// When the location was moved to the trash, if it was.
+    pub deleted_at: Option<NaiveDateTime>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub manual_coordinates: bool,
 }
 impl From<Location> for SqlLocation {
     fn from(location: Location) -> SqlLocation {
@@ -213,7 +622,15 @@ impl From<Location> for SqlLocation {
         SqlLocation {
             id: id.into(),
             name: location.name,
-            address: location.address,
+            street: location.address.street,
+            postal_code: location.address.postal_code,
+            city: location.address.city,
+            deleted_at: None,
+            latitude: location.coordinates.map(|coordinates| coordinates.latitude),
+            longitude: location
+                .coordinates
+                .map(|coordinates| coordinates.longitude),
+            manual_coordinates: location.manual_coordinates,
         }
     }
 }
@@ -223,7 +640,478 @@ impl From<SqlLocation> for (Id<Location>, Location) {
             location.id.into(),
             Location {
                 name: location.name,
-                address: location.address,
+                address: Address {
+                    street: location.street,
+                    postal_code: location.postal_code,
+                    city: location.city,
+                },
+                coordinates: match (location.latitude, location.longitude) {
+                    (Some(latitude), Some(longitude)) => Some(Coordinates {
+                        latitude,
+                        longitude,
+                    }),
+                    _ => None,
+                },
+                manual_coordinates: location.manual_coordinates,
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug)]
+#[table_name = "submissions"]
+pub struct SqlSubmission {
+    pub id: SqlId<Submission>,
+    pub title: String,
+    pub teaser: String,
+    pub description: String,
+    pub organizer_name: String,
+    pub organizer_email: String,
+    pub start: NaiveDateTime,
+    pub duration: i32,
+    pub location_id: SqlId<Location>,
+}
+
+impl From<Submission> for SqlSubmission {
+    fn from(submission: Submission) -> SqlSubmission {
+        let id = Uuid::new_v4();
+
+        SqlSubmission {
+            id: id.into(),
+            title: submission.title,
+            teaser: submission.teaser,
+            description: submission.description,
+            organizer_name: submission.organizer_name,
+            organizer_email: submission.organizer_email,
+            start: submission.start,
+            duration: submission.duration.num_minutes() as i32,
+            location_id: submission.location_id.into(),
+        }
+    }
+}
+
+impl From<SqlSubmission> for (Id<Submission>, Submission) {
+    fn from(submission: SqlSubmission) -> (Id<Submission>, Submission) {
+        (
+            submission.id.into(),
+            Submission {
+                title: submission.title,
+                teaser: submission.teaser,
+                description: submission.description,
+                organizer_name: submission.organizer_name,
+                organizer_email: submission.organizer_email,
+                start: submission.start,
+                duration: chrono::Duration::minutes(submission.duration.into()),
+                location_id: submission.location_id.into(),
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug, AsChangeset)]
+#[table_name = "newsletters"]
+pub struct SqlNewsletter {
+    pub id: SqlId<Newsletter>,
+    pub title: String,
+    pub date: NaiveDate,
+    pub content: String,
+}
+
+impl From<Newsletter> for SqlNewsletter {
+    fn from(newsletter: Newsletter) -> SqlNewsletter {
+        let id = Uuid::new_v4();
+
+        SqlNewsletter {
+            id: id.into(),
+            title: newsletter.title,
+            date: newsletter.date,
+            content: newsletter.content,
+        }
+    }
+}
+
+impl From<SqlNewsletter> for (Id<Newsletter>, Newsletter) {
+    fn from(newsletter: SqlNewsletter) -> (Id<Newsletter>, Newsletter) {
+        (
+            newsletter.id.into(),
+            Newsletter {
+                title: newsletter.title,
+                date: newsletter.date,
+                content: newsletter.content,
+            },
+        )
+    }
+}
+
+/// The latest attempt to send a newsletter to a subscriber.
+#[derive(Queryable, Insertable, Debug)]
+#[table_name = "newsletter_deliveries"]
+pub struct SqlDelivery {
+    pub newsletter_id: SqlId<Newsletter>,
+    pub subscriber_id: SqlId<Subscriber>,
+    pub sent_at: NaiveDateTime,
+    pub error: Option<String>,
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug)]
+#[table_name = "api_keys"]
+pub struct SqlApiKey {
+    pub id: SqlId<ApiKey>,
+    pub secret_hash: String,
+    pub name: String,
+    pub daily_quota: i32,
+}
+
+/// The key with the hash of its secret.
+impl From<(ApiKey, String)> for SqlApiKey {
+    fn from((api_key, secret_hash): (ApiKey, String)) -> SqlApiKey {
+        let id = Uuid::new_v4();
+
+        SqlApiKey {
+            id: id.into(),
+            secret_hash,
+            name: api_key.name,
+            daily_quota: api_key.daily_quota as i32,
+        }
+    }
+}
+
+impl From<SqlApiKey> for ApiKey {
+    fn from(api_key: SqlApiKey) -> ApiKey {
+        ApiKey {
+            name: api_key.name,
+            daily_quota: api_key.daily_quota as u32,
+        }
+    }
+}
+
+/// How many requests were made with a key on a day.
+#[derive(Queryable, Insertable, Debug)]
+#[table_name = "api_key_usage"]
+pub struct SqlApiKeyUsage {
+    pub api_key_id: SqlId<ApiKey>,
+    pub date: NaiveDate,
+    pub requests: i32,
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug)]
+#[table_name = "subscribers"]
+pub struct SqlSubscriber {
+    pub id: SqlId<Subscriber>,
+    pub email: String,
+    pub name: Option<String>,
+    pub consented_at: NaiveDate,
+    pub confirmed: bool,
+    /// Until the subscriber confirms the address, the secret from the link in the
+    /// confirmation mail.
+    pub confirmation_token: Option<String>,
+    /// The secret from the link to unsubscribe in every newsletter.
+    pub unsubscribe_token: String,
+}
+
+impl From<Subscriber> for SqlSubscriber {
+    fn from(subscriber: Subscriber) -> SqlSubscriber {
+        let id = Uuid::new_v4();
+
+        SqlSubscriber {
+            id: id.into(),
+            email: subscriber.email,
+            name: subscriber.name,
+            consented_at: subscriber.consented_at,
+            confirmed: subscriber.confirmed,
+            confirmation_token: None,
+            unsubscribe_token: super::new_token(),
+        }
+    }
+}
+
+impl From<SqlSubscriber> for (Id<Subscriber>, Subscriber) {
+    fn from(subscriber: SqlSubscriber) -> (Id<Subscriber>, Subscriber) {
+        (
+            subscriber.id.into(),
+            Subscriber {
+                email: subscriber.email,
+                name: subscriber.name,
+                consented_at: subscriber.consented_at,
+                confirmed: subscriber.confirmed,
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug, Associations)]
+#[belongs_to(SqlEvent, foreign_key = "event_id")]
+#[table_name = "comments"]
+pub struct SqlComment {
+    pub id: SqlId<Comment>,
+    pub event_id: SqlId<Event>,
+    pub name: String,
+    pub text: String,
+    pub created_at: NaiveDateTime,
+    pub approved: bool,
+}
+
+impl From<(Comment, SqlId<Event>)> for SqlComment {
+    fn from((comment, event_id): (Comment, SqlId<Event>)) -> SqlComment {
+        let id = Uuid::new_v4();
+
+        SqlComment {
+            id: id.into(),
+            event_id,
+            name: comment.name,
+            text: comment.text,
+            created_at: comment.created_at,
+            approved: comment.approved,
+        }
+    }
+}
+
+impl From<SqlComment> for (Id<Comment>, Comment) {
+    fn from(comment: SqlComment) -> (Id<Comment>, Comment) {
+        (
+            comment.id.into(),
+            Comment {
+                name: comment.name,
+                text: comment.text,
+                created_at: comment.created_at,
+                approved: comment.approved,
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug)]
+#[table_name = "event_images"]
+pub struct SqlEventImage {
+    pub id: SqlId<EventImage>,
+    pub event_id: SqlId<Event>,
+    pub file_name: String,
+    pub original_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub uploaded_at: NaiveDateTime,
+}
+
+impl From<(EventImage, SqlId<Event>)> for SqlEventImage {
+    fn from((image, event_id): (EventImage, SqlId<Event>)) -> SqlEventImage {
+        let id = Uuid::new_v4();
+
+        SqlEventImage {
+            id: id.into(),
+            event_id,
+            file_name: image.file_name,
+            original_name: image.original_name,
+            content_type: image.content_type,
+            size: image.size as i64,
+            uploaded_at: image.uploaded_at,
+        }
+    }
+}
+
+impl From<SqlEventImage> for (Id<EventImage>, EventImage) {
+    fn from(image: SqlEventImage) -> (Id<EventImage>, EventImage) {
+        (
+            image.id.into(),
+            EventImage {
+                file_name: image.file_name,
+                original_name: image.original_name,
+                content_type: image.content_type,
+                size: image.size as u64,
+                uploaded_at: image.uploaded_at,
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug, AsChangeset)]
+#[table_name = "nav_items"]
+pub struct SqlNavItem {
+    pub id: SqlId<NavItem>,
+    pub label: String,
+    pub url: String,
+    pub position: i32,
+    pub visible: bool,
+}
+
+impl From<NavItem> for SqlNavItem {
+    fn from(item: NavItem) -> SqlNavItem {
+        let id = Uuid::new_v4();
+
+        SqlNavItem {
+            id: id.into(),
+            label: item.label,
+            url: item.url,
+            position: item.position,
+            visible: item.visible,
+        }
+    }
+}
+
+impl From<SqlNavItem> for (Id<NavItem>, NavItem) {
+    fn from(item: SqlNavItem) -> (Id<NavItem>, NavItem) {
+        (
+            item.id.into(),
+            NavItem {
+                label: item.label,
+                url: item.url,
+                position: item.position,
+                visible: item.visible,
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug, AsChangeset)]
+#[table_name = "pages"]
+pub struct SqlPage {
+    pub id: SqlId<Page>,
+    pub slug: String,
+    pub title: String,
+    pub body: String,
+    pub published: bool,
+}
+
+impl From<Page> for SqlPage {
+    fn from(page: Page) -> SqlPage {
+        let id = Uuid::new_v4();
+
+        SqlPage {
+            id: id.into(),
+            slug: page.slug,
+            title: page.title,
+            body: page.body,
+            published: page.published,
+        }
+    }
+}
+
+impl From<SqlPage> for (Id<Page>, Page) {
+    fn from(page: SqlPage) -> (Id<Page>, Page) {
+        (
+            page.id.into(),
+            Page {
+                slug: page.slug,
+                title: page.title,
+                body: page.body,
+                published: page.published,
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug, AsChangeset)]
+#[table_name = "faq_entries"]
+pub struct SqlFaqEntry {
+    pub id: SqlId<FaqEntry>,
+    pub question: String,
+    pub answer: String,
+    pub category: String,
+    pub position: i32,
+}
+
+impl From<FaqEntry> for SqlFaqEntry {
+    fn from(entry: FaqEntry) -> SqlFaqEntry {
+        let id = Uuid::new_v4();
+
+        SqlFaqEntry {
+            id: id.into(),
+            question: entry.question,
+            answer: entry.answer,
+            category: entry.category,
+            position: entry.position,
+        }
+    }
+}
+
+impl From<SqlFaqEntry> for (Id<FaqEntry>, FaqEntry) {
+    fn from(entry: SqlFaqEntry) -> (Id<FaqEntry>, FaqEntry) {
+        (
+            entry.id.into(),
+            FaqEntry {
+                question: entry.question,
+                answer: entry.answer,
+                category: entry.category,
+                position: entry.position,
+            },
+        )
+    }
+}
+
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug, AsChangeset)]
+#[table_name = "redirects"]
+pub struct SqlRedirect {
+    pub id: SqlId<UrlRedirect>,
+    pub source: String,
+    pub target: String,
+    pub status: i32,
+}
+
+impl From<UrlRedirect> for SqlRedirect {
+    fn from(redirect: UrlRedirect) -> SqlRedirect {
+        let id = Uuid::new_v4();
+
+        SqlRedirect {
+            id: id.into(),
+            source: redirect.source,
+            target: redirect.target,
+            status: i32::from(redirect.status),
+        }
+    }
+}
+
+impl From<SqlRedirect> for (Id<UrlRedirect>, UrlRedirect) {
+    fn from(redirect: SqlRedirect) -> (Id<UrlRedirect>, UrlRedirect) {
+        (
+            redirect.id.into(),
+            UrlRedirect {
+                source: redirect.source,
+                target: redirect.target,
+                status: redirect.status as u16,
+            },
+        )
+    }
+}
+
+/// Remembers deleted events, so their pages can tell visitors that they are gone.
+#[derive(Queryable, Clone, Identifiable, Insertable, Debug)]
+#[table_name = "deleted_events"]
+pub struct SqlDeletedEvent {
+    pub id: SqlId<Event>,
+    pub title: String,
+    pub deleted_at: NaiveDateTime,
+    pub slug: String,
+}
+
+/// An entry of the audit log. Entries are numbered in the order they were recorded.
+#[derive(Queryable, Debug)]
+pub struct SqlAuditEntry {
+    pub id: i32,
+    pub recorded_at: NaiveDateTime,
+    pub entity: String,
+    pub entity_id: Vec<u8>,
+    pub action: String,
+    /// The changes as JSON.
+    pub changes: String,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "audit_log"]
+pub struct NewSqlAuditEntry {
+    pub recorded_at: NaiveDateTime,
+    pub entity: String,
+    pub entity_id: Vec<u8>,
+    pub action: String,
+    pub changes: String,
+}
+
+impl From<SqlDeletedEvent> for (Id<Event>, DeletedEvent) {
+    fn from(deleted: SqlDeletedEvent) -> (Id<Event>, DeletedEvent) {
+        (
+            deleted.id.into(),
+            DeletedEvent {
+                title: deleted.title,
+                slug: deleted.slug,
+                deleted_at: deleted.deleted_at,
             },
         )
     }
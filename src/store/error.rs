@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fmt;
+
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, status::Custom, Responder};
+
+use lindyhop_aachen_types::ValidationErrors;
+
+/// Why the store could not load or save something.
+#[derive(Debug)]
+pub enum StoreError {
+    /// There is no item with the requested id, or it was deleted.
+    NotFound,
+    /// The event is locked, so it cannot be changed until it is unlocked.
+    Locked,
+    /// Another connection kept the database locked while the write was retried.
+    Busy,
+    /// The data to save does not pass validation, so it was not saved.
+    Invalid(ValidationErrors),
+    /// The store serves a read-only snapshot, which has no database to load from or save to.
+    ReadOnly,
+    /// Any other failure of the database, which is not the client's fault.
+    Database(diesel::result::Error),
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+impl StoreError {
+    pub fn status(&self) -> Status {
+        match self {
+            StoreError::NotFound => Status::NotFound,
+            StoreError::Locked => Status::Locked,
+            StoreError::Busy => Status::ServiceUnavailable,
+            StoreError::Invalid(_) => Status::UnprocessableEntity,
+            StoreError::ReadOnly | StoreError::Database(_) => Status::InternalServerError,
+        }
+    }
+}
+
+impl From<diesel::result::Error> for StoreError {
+    fn from(err: diesel::result::Error) -> Self {
+        use diesel::result::Error::{DatabaseError, NotFound};
+
+        match err {
+            NotFound => StoreError::NotFound,
+            DatabaseError(_, ref info)
+                if info.message().contains("database is locked")
+                    || info.message().contains("database is busy") =>
+            {
+                StoreError::Busy
+            }
+            err => StoreError::Database(err),
+        }
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::NotFound => write!(f, "The item does not exist."),
+            StoreError::Locked => write!(
+                f,
+                "The event is locked and has to be unlocked before changing it."
+            ),
+            StoreError::Busy => write!(f, "The database is busy, please try again."),
+            StoreError::Invalid(errors) => write!(f, "{}", errors),
+            StoreError::ReadOnly => write!(f, "The store serves a read-only snapshot."),
+            StoreError::Database(err) => write!(f, "The database failed: {}", err),
+        }
+    }
+}
+
+impl Error for StoreError {}
+
+/// The API answers with the message, so that the admin can show it.
+impl From<StoreError> for Custom<String> {
+    fn from(err: StoreError) -> Self {
+        if let StoreError::ReadOnly | StoreError::Database(_) = err {
+            eprintln!("{}", err);
+        }
+        Custom(err.status(), err.to_string())
+    }
+}
+
+/// Pages fail with the status, so that Rocket shows its error page.
+impl<'r> Responder<'r> for StoreError {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        if let StoreError::ReadOnly | StoreError::Database(_) = self {
+            eprintln!("{}", self);
+        }
+        Err(self.status())
+    }
+}
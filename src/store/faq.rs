@@ -0,0 +1,105 @@
+use diesel::{self, prelude::*};
+
+use super::db::{SqlFaqEntry, SqlId};
+use super::*;
+
+use db::schema::faq_entries::dsl::faq_entries;
+
+/// Like the navigation, the FAQ is part of the snapshot but not of the audit log.
+impl Actions<FaqEntry> for Store {
+    type Id = Id<FaqEntry>;
+
+    fn all(&self) -> StoreResult<HashMap<Self::Id, FaqEntry>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.faq_entries().clone());
+        }
+
+        Ok(faq_entries
+            .load::<SqlFaqEntry>(self.connection()?)?
+            .into_iter()
+            .map(|sql_entry| sql_entry.into())
+            .collect())
+    }
+
+    fn create(&self, entry: FaqEntry) -> StoreResult<Self::Id> {
+        let sql_entry: SqlFaqEntry = entry.into();
+        self.write(|| {
+            diesel::insert_into(faq_entries)
+                .values(&sql_entry)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_entry.id.into())
+    }
+
+    fn read(&self, id: Self::Id) -> StoreResult<FaqEntry> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot
+                .faq_entries()
+                .get(&id)
+                .cloned()
+                .ok_or(StoreError::NotFound);
+        }
+
+        faq_entries
+            .find(SqlId::from(id))
+            .first::<SqlFaqEntry>(self.connection()?)
+            .map(|sql_entry| sql_entry.into())
+            .map(|(_, entry)| entry)
+            .map_err(StoreError::from)
+    }
+
+    fn update(&self, id: Self::Id, new_entry: FaqEntry) -> StoreResult<FaqEntry> {
+        let raw_id: SqlId<FaqEntry> = id.into();
+        let mut sql_entry: SqlFaqEntry = new_entry.into();
+        sql_entry.id = raw_id.clone();
+        self.write(|| {
+            let (_, previous): (Id<FaqEntry>, FaqEntry) = faq_entries
+                .find(&raw_id)
+                .first::<SqlFaqEntry>(self.connection()?)?
+                .into();
+            diesel::update(faq_entries.find(&raw_id))
+                .set(&sql_entry)
+                .execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+
+    fn delete(&self, id: Self::Id) -> StoreResult<FaqEntry> {
+        let raw_id: SqlId<FaqEntry> = id.into();
+        self.write(|| {
+            let (_, previous): (Id<FaqEntry>, FaqEntry) = faq_entries
+                .find(&raw_id)
+                .first::<SqlFaqEntry>(self.connection()?)?
+                .into();
+            diesel::delete(faq_entries.find(&raw_id)).execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+}
+
+impl Store {
+    /// The questions grouped by category, in order.
+    pub fn faq(&self) -> StoreResult<Vec<(String, Vec<FaqEntry>)>> {
+        let all: HashMap<Id<FaqEntry>, FaqEntry> = self.all()?;
+        let mut entries: Vec<FaqEntry> = all.into_iter().map(|(_, entry)| entry).collect();
+        // Questions at the same position are ordered by their text, so that the order does
+        // not change between requests.
+        entries.sort_by(|a, b| (a.position, &a.question).cmp(&(b.position, &b.question)));
+
+        let mut categories: Vec<(String, Vec<FaqEntry>)> = Vec::new();
+        for entry in entries {
+            match categories
+                .iter_mut()
+                .find(|(category, _)| *category == entry.category)
+            {
+                Some((_, category_entries)) => category_entries.push(entry),
+                None => categories.push((entry.category.clone(), vec![entry])),
+            }
+        }
+        Ok(categories)
+    }
+}
@@ -0,0 +1,77 @@
+use diesel::{self, prelude::*};
+
+use super::db::{SqlEventImage, SqlId};
+use super::*;
+
+/// Only the metadata of images is stored here, their files are kept in the media directory.
+impl Store {
+    pub fn event_images(
+        &self,
+        event_id: Id<Event>,
+    ) -> StoreResult<HashMap<Id<EventImage>, EventImage>> {
+        use db::schema::event_images::dsl::{event_id as image_event_id, event_images};
+        use db::schema::events::dsl::{deleted_at, events};
+
+        let sql_event_id: SqlId<Event> = event_id.into();
+        // Fails if the event does not exist.
+        events
+            .find(&sql_event_id)
+            .filter(deleted_at.is_null())
+            .first::<SqlEvent>(self.connection()?)?;
+
+        Ok(event_images
+            .filter(image_event_id.eq(&sql_event_id))
+            .load::<SqlEventImage>(self.connection()?)?
+            .into_iter()
+            .map(|sql_image| sql_image.into())
+            .collect())
+    }
+
+    pub fn create_event_image(
+        &self,
+        event_id: Id<Event>,
+        image: EventImage,
+    ) -> StoreResult<Id<EventImage>> {
+        use db::schema::event_images::dsl::event_images;
+        use db::schema::events::dsl::{deleted_at, events};
+
+        let sql_event_id: SqlId<Event> = event_id.into();
+        let sql_image: SqlEventImage = (image, sql_event_id.clone()).into();
+        self.write(|| {
+            // Fails if the event does not exist.
+            events
+                .find(&sql_event_id)
+                .filter(deleted_at.is_null())
+                .first::<SqlEvent>(self.connection()?)?;
+
+            diesel::insert_into(event_images)
+                .values(&sql_image)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_image.id.into())
+    }
+
+    /// Removes the image from the event and returns it, so that its file can be deleted.
+    pub fn delete_event_image(
+        &self,
+        event_id: Id<Event>,
+        id: Id<EventImage>,
+    ) -> StoreResult<EventImage> {
+        use db::schema::event_images::dsl::{event_id as image_event_id, event_images};
+
+        let sql_event_id: SqlId<Event> = event_id.into();
+        let sql_id: SqlId<EventImage> = id.into();
+        self.write(|| {
+            let sql_image = event_images
+                .find(&sql_id)
+                .filter(image_event_id.eq(&sql_event_id))
+                .first::<SqlEventImage>(self.connection()?)?;
+            diesel::delete(&sql_image).execute(self.connection()?)?;
+
+            let (_, image) = sql_image.into();
+            Ok(image)
+        })
+    }
+}
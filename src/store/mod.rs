@@ -1,123 +1,390 @@
+mod api_key;
+mod audit;
+#[cfg(test)]
+mod bench;
+mod changes;
 mod db;
-mod model;
-
-use std::collections::{BTreeMap, HashMap};
-use std::io::Cursor;
-use std::marker::PhantomData;
-
-use chrono::{NaiveDate, NaiveDateTime};
-use rocket::http::RawStr;
-use rocket::http::Status;
-use rocket::request::{FormItem, FromParam, FromQuery, FromRequest, Outcome, Query, Request};
-use rocket::response::{self, Responder, Response};
-use rocket::{fairing, fairing::Fairing, Rocket};
-use rocket_contrib::uuid::Uuid as RocketUuid;
-use uuid::Uuid;
+mod error;
+mod faq;
+mod image;
+mod moderation;
+mod navigation;
+mod newsletter;
+mod page;
+mod recurrence;
+mod redirect;
+mod slug;
+mod snapshot;
+mod subscription;
+mod teaser;
+mod trash;
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::{fairing, fairing::Fairing, Rocket, State};
 
 use db::{SqlEvent, SqlLocation, SqlOccurrence};
-use diesel::result::QueryResult;
 use diesel::{self, prelude::*};
-use serde::{Deserialize, Serialize};
+use rand::Rng;
+use serde::Deserialize;
+
+pub use api_key::DailyUsage;
+pub use audit::AuditEntry;
+pub use changes::{Change, ChangeAction, ChangeBus, Entity};
+pub use error::{StoreError, StoreResult};
+pub use lindyhop_aachen_types::*;
+pub use newsletter::Recipient;
+use snapshot::Snapshot;
+
+/// How often a write is attempted while the database is locked by another connection.
+const BUSY_ATTEMPTS: u32 = 5;
+/// The delay before the first retry, doubled for every further one.
+const BUSY_BACKOFF_MS: u64 = 20;
+/// How long SQLite waits for another connection's lock before failing with SQLITE_BUSY.
+/// Reads are not retried, so without it they fail while a background write is saved.
+const BUSY_TIMEOUT_MS: u32 = 2000;
+
+/// A secret for links confirming or unsubscribing an address, or for API keys. It is
+/// random rather than derived from anything, so nobody can guess it.
+fn new_token() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
 
-pub use model::*;
+/// How many related events are suggested for an event.
+const MAX_RELATED_EVENTS: usize = 5;
+
+pub struct Store {
+    source: Source,
+    options: Arc<StoreOptions>,
+    changes: Arc<ChangeBus>,
+    /// The changes made by the current write, published once it is committed.
+    pending_changes: RefCell<Vec<Change>>,
+    /// How deeply writes are nested. Only the outermost one commits.
+    write_depth: Cell<u32>,
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
-#[serde(transparent)]
-pub struct Id<Item> {
-    id: Uuid,
-    #[serde(skip)]
-    phantom: PhantomData<Item>,
+/// Settings for how the store treats the data it saves.
+pub struct StoreOptions {
+    /// Whether events saved with an empty teaser get one derived from their description,
+    /// configured as `derive_empty_teasers`.
+    derive_empty_teasers: bool,
+    /// How far ahead recurrences are expanded into occurrences.
+    schedule_horizon: chrono::Duration,
 }
 
-impl<'a, T> FromParam<'a> for Id<T> {
-    type Error = <RocketUuid as FromParam<'a>>::Error;
+fn initialize_options(rocket: Rocket) -> Result<Rocket, Rocket> {
+    let derive_empty_teasers = rocket
+        .config()
+        .get_bool("derive_empty_teasers")
+        .unwrap_or(true);
+    let schedule_horizon = rocket.state::<ScheduleHorizon>().unwrap().0;
+
+    Ok(rocket
+        .manage(Arc::new(StoreOptions {
+            derive_empty_teasers,
+            schedule_horizon,
+        }))
+        .manage(Arc::new(ChangeBus::default())))
+}
 
-    /// A value is successfully parsed if `param` is a properly formatted Uuid.
-    /// Otherwise, a `ParseError` is returned.
-    #[inline(always)]
-    fn from_param(param: &'a RawStr) -> Result<Id<T>, Self::Error> {
-        RocketUuid::from_param(param).map(|uuid| uuid.into_inner().into())
-    }
+enum Source {
+    Database(db::Connection),
+    Snapshot(Arc<Snapshot>),
 }
 
-impl<Item> From<Uuid> for Id<Item> {
-    fn from(uuid: Uuid) -> Self {
-        Id {
-            id: uuid,
-            phantom: PhantomData,
+impl Source {
+    /// The busy timeout applies to the connection, but pooled connections cannot be set up
+    /// when they are opened, so it is set whenever one is taken from the pool.
+    fn database(connection: db::Connection) -> Source {
+        use diesel::connection::SimpleConnection;
+
+        let pragma = format!("PRAGMA busy_timeout = {};", BUSY_TIMEOUT_MS);
+        if let Err(err) = connection.batch_execute(&pragma) {
+            eprintln!("Failed to set the busy timeout of the database: {}", err);
         }
+        Source::Database(connection)
     }
 }
 
-pub struct Store(db::Connection);
+/// Whether the store serves reads from an immutable in-memory snapshot
+/// instead of the database. Mutations are unavailable in this mode.
+pub fn is_read_only(rocket: &Rocket) -> bool {
+    rocket
+        .config()
+        .get_bool("read_only_snapshot")
+        .unwrap_or(false)
+}
 
 impl Store {
     pub fn fairing() -> StoreFairing {
         StoreFairing
     }
 
-    pub fn read_all(&self, filter: &OccurrenceFilter) -> Overview {
-        let locs: HashMap<Id<Location>, Location> = self.all();
+    /// Fails in read-only snapshot mode. There the routes that would need the database
+    /// are not mounted, so this only happens if one was mounted by mistake.
+    fn connection(&self) -> StoreResult<&SqliteConnection> {
+        match &self.source {
+            Source::Database(connection) => Ok(&*connection),
+            Source::Snapshot(_) => Err(StoreError::ReadOnly),
+        }
+    }
+
+    /// Runs the write in a transaction. While another connection holds the database lock,
+    /// SQLite fails with SQLITE_BUSY, so the write is retried a few times after a random delay
+    /// to let simultaneous saves succeed.
+    ///
+    /// Once the outermost write has been committed, the changes it recorded are published.
+    fn write<T>(&self, operation: impl FnMut() -> StoreResult<T>) -> StoreResult<T> {
+        let depth = self.write_depth.get();
+        let pending_before = self.pending_changes.borrow().len();
+        self.write_depth.set(depth + 1);
+        let result = self.retry_while_busy(operation, pending_before);
+        self.write_depth.set(depth);
+
+        if result.is_err() {
+            self.pending_changes.borrow_mut().truncate(pending_before);
+        }
+        if depth == 0 {
+            for change in self.pending_changes.replace(Vec::new()) {
+                self.changes.publish(&change);
+            }
+        }
+        result
+    }
+
+    fn retry_while_busy<T>(
+        &self,
+        mut operation: impl FnMut() -> StoreResult<T>,
+        pending_before: usize,
+    ) -> StoreResult<T> {
+        let mut attempt = 1;
+        loop {
+            // The changes of a failed attempt were rolled back.
+            self.pending_changes.borrow_mut().truncate(pending_before);
+            match self.connection()?.transaction(|| operation()) {
+                Err(StoreError::Busy) if attempt < BUSY_ATTEMPTS => {
+                    let backoff = BUSY_BACKOFF_MS << (attempt - 1);
+                    let delay = rand::thread_rng().gen_range(backoff / 2, backoff + 1);
+                    eprintln!(
+                        "The database is busy, retrying the write in {}ms (attempt {} of {}).",
+                        delay, attempt, BUSY_ATTEMPTS
+                    );
+                    thread::sleep(Duration::from_millis(delay));
+                    attempt += 1;
+                }
+                result => {
+                    if let Err(StoreError::Busy) = result {
+                        eprintln!(
+                            "The database is still busy after {} attempts, giving up.",
+                            BUSY_ATTEMPTS
+                        );
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Runs several writes as one, so that either all of them are saved or none.
+    pub fn transaction<T>(&self, operation: impl FnMut() -> StoreResult<T>) -> StoreResult<T> {
+        self.write(operation)
+    }
+
+    /// Whether the store serves a snapshot, which cannot be changed.
+    pub fn is_read_only(&self) -> bool {
+        self.snapshot().is_some()
+    }
+
+    fn snapshot(&self) -> Option<&Snapshot> {
+        match &self.source {
+            Source::Database(_) => None,
+            Source::Snapshot(snapshot) => Some(snapshot),
+        }
+    }
+
+    pub fn read_all(&self, filter: &OccurrenceFilter) -> StoreResult<Overview> {
+        let locs: HashMap<Id<Location>, Location> = self.all()?;
         let evts: HashMap<Id<Event>, EventWithOccurrences> =
-            self.all_events_with_occurrences(filter);
+            self.all_events_with_occurrences(filter)?;
 
-        Overview {
+        Ok(Overview {
             locations: locs,
             events: evts,
-        }
+        })
+    }
+
+    /// The events in order of their first occurrence, those without any last, taking `limit`
+    /// of them after skipping the first `offset`.
+    pub fn read_page(
+        &self,
+        filter: &OccurrenceFilter,
+        offset: usize,
+        limit: usize,
+    ) -> StoreResult<OverviewPage> {
+        let Overview { locations, events } = self.read_all(filter)?;
+        let total_events = events.len();
+        let total_occurrences = events.values().map(|entry| entry.occurrences.len()).sum();
+
+        let mut events: Vec<(Id<Event>, EventWithOccurrences)> = events.into_iter().collect();
+        events.sort_by_cached_key(|(id, entry)| {
+            let first = entry
+                .occurrences
+                .iter()
+                .map(|occurrence| occurrence.occurrence.start)
+                .min();
+            (first.is_none(), first, id.to_string())
+        });
+
+        Ok(OverviewPage {
+            locations,
+            events: events.into_iter().skip(offset).take(limit).collect(),
+            offset,
+            limit,
+            total_events,
+            total_occurrences,
+        })
+    }
+
+    /// How the week starting on the Monday differs from the week before.
+    pub fn schedule_diff(&self, monday: NaiveDate) -> StoreResult<ScheduleDiff> {
+        let week = |monday: NaiveDate| -> StoreResult<Vec<OccurrenceWithEvent>> {
+            let start = monday.and_hms(0, 0, 0);
+            let filter = OccurrenceFilter {
+                // Occurrences starting at exactly `after` are excluded.
+                after: Some(start - chrono::Duration::seconds(1)),
+                before: Some(start + chrono::Duration::weeks(1)),
+                ..OccurrenceFilter::default()
+            };
+            Ok(self
+                .occurrences_by_date(&filter)?
+                .into_iter()
+                .flat_map(|(_, entries)| entries)
+                .collect())
+        };
+
+        Ok(ScheduleDiff::between(
+            &week(monday - chrono::Duration::weeks(1))?,
+            &week(monday)?,
+        ))
     }
 
     pub fn occurrences_by_date(
         &self,
         filter: &OccurrenceFilter,
-    ) -> BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>> {
+    ) -> StoreResult<BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.occurrences_by_date(filter));
+        }
+
         use db::schema::events::dsl::events;
         use db::schema::occurrences::dsl::{occurrences, start};
 
         let sql_occurrences = occurrences
             .filter(apply_occurrence_filter(filter))
             .order(start.asc())
-            .load::<SqlOccurrence>(&*self.0)
-            .unwrap();
+            .load::<SqlOccurrence>(self.connection()?)?;
 
-        sql_occurrences
+        let entries = sql_occurrences
             .into_iter()
             .map(|sql_occurrence| {
                 let sql_event = events
                     .find(sql_occurrence.event_id.clone())
-                    .first::<SqlEvent>(&*self.0)
-                    .unwrap();
+                    .first::<SqlEvent>(self.connection()?)?;
                 let (_, occurrence) = sql_occurrence.into();
-                let (_, event) = sql_event.into();
-                OccurrenceWithEvent { occurrence, event }
+                let (event_id, event) = sql_event.into();
+                Ok(OccurrenceWithEvent {
+                    occurrence,
+                    event_id,
+                    event,
+                })
             })
-            .fold(
-                BTreeMap::new(),
-                |mut acc: BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>, entry| {
-                    acc.entry(entry.occurrence.occurrence.start.date())
-                        .and_modify(|entries| entries.push(entry.clone()))
-                        .or_insert_with(|| vec![entry]);
-                    acc
-                },
-            )
+            .collect::<StoreResult<Vec<OccurrenceWithEvent>>>()?;
+
+        Ok(entries.into_iter().fold(
+            BTreeMap::new(),
+            |mut acc: BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>, entry| {
+                acc.entry(entry.occurrence.occurrence.start.date())
+                    .and_modify(|entries| entries.push(entry.clone()))
+                    .or_insert_with(|| vec![entry]);
+                acc
+            },
+        ))
+    }
+
+    pub fn occurrence_with_event(&self, id: Id<Occurrence>) -> StoreResult<OccurrenceWithEvent> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.occurrence_with_event(&id);
+        }
+
+        use db::schema::events::dsl::{deleted_at, events};
+        use db::schema::occurrences::dsl::occurrences;
+
+        let sql_occurrence = occurrences
+            .find(db::SqlId::from(id))
+            .first::<SqlOccurrence>(self.connection()?)?;
+        let sql_event = events
+            .find(sql_occurrence.event_id.clone())
+            .filter(deleted_at.is_null())
+            .filter(is_public(chrono::Local::now().naive_local()))
+            .first::<SqlEvent>(self.connection()?)?;
+        let (_, occurrence) = sql_occurrence.into();
+        let (event_id, event) = sql_event.into();
+
+        Ok(OccurrenceWithEvent {
+            occurrence,
+            event_id,
+            event,
+        })
+    }
+
+    /// Past occurrences grouped by the season they took place in.
+    pub fn past_occurrences_by_season(
+        &self,
+        seasons: &SeasonBoundaries,
+    ) -> StoreResult<BTreeMap<Season, BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>>> {
+        let filter = OccurrenceFilter {
+            before: Some(chrono::Local::now().naive_local()),
+            ..OccurrenceFilter::default()
+        };
+
+        Ok(self.occurrences_by_date(&filter)?.into_iter().fold(
+            BTreeMap::new(),
+            |mut acc: BTreeMap<Season, BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>>,
+             (date, entries)| {
+                acc.entry(seasons.season_of(date))
+                    .or_insert_with(BTreeMap::new)
+                    .insert(date, entries);
+                acc
+            },
+        ))
     }
 
     pub fn locations_with_occurrences(
         &self,
         filter: &OccurrenceFilter,
-    ) -> HashMap<Id<Location>, LocationWithOccurrences> {
-        use db::schema::locations::dsl::locations;
+    ) -> StoreResult<HashMap<Id<Location>, LocationWithOccurrences>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.locations_with_occurrences(filter));
+        }
+
+        use db::schema::locations::dsl::{deleted_at, locations};
 
         locations
-            .load::<SqlLocation>(&*self.0)
-            .expect("Loading from database failed.")
+            .filter(deleted_at.is_null())
+            .load::<SqlLocation>(self.connection()?)?
             .into_iter()
             .map(|sql_location| {
                 let occurrences: HashMap<Id<Occurrence>, Occurrence> =
                     SqlOccurrence::belonging_to(&sql_location)
                         .filter(apply_occurrence_filter(filter))
-                        .load::<SqlOccurrence>(&*self.0)
-                        .expect("Loading from database failed.")
+                        .load::<SqlOccurrence>(self.connection()?)?
                         .into_iter()
                         .map(|sql_occurrence| {
                             let (id, occurrence) = sql_occurrence.into();
@@ -128,174 +395,391 @@ impl Store {
 
                 let (id, location) = sql_location.into();
 
-                (
+                Ok((
                     id,
                     LocationWithOccurrences {
                         location,
                         occurrences,
                     },
-                )
+                ))
             })
             .collect()
     }
+
+    pub fn location_reports(
+        &self,
+        filter: &OccurrenceFilter,
+    ) -> StoreResult<HashMap<Id<Location>, LocationReport>> {
+        Ok(self
+            .locations_with_occurrences(filter)?
+            .into_iter()
+            .map(|(id, entry)| {
+                let mut occurrences_per_month = BTreeMap::new();
+                let mut total_minutes = 0;
+                for occurrence in entry.occurrences.values() {
+                    *occurrences_per_month
+                        .entry(occurrence.start.format("%Y-%m").to_string())
+                        .or_insert(0) += 1;
+                    total_minutes += occurrence.duration.num_minutes();
+                }
+
+                (
+                    id,
+                    LocationReport {
+                        location: entry.location,
+                        occurrences_per_month,
+                        total_hours: total_minutes as f64 / 60.0,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// The weeks from the current one up to the schedule horizon in which no published event
+    /// takes place. Cancelled occurrences do not count, and the week the horizon ends in is
+    /// left out, since its occurrences may not be planned yet.
+    pub fn schedule_gaps(&self, now: NaiveDateTime) -> StoreResult<Vec<ScheduleGap>> {
+        let today = now.date();
+        let first_monday =
+            today - chrono::Duration::days(today.weekday().num_days_from_monday().into());
+        let horizon = now + self.options.schedule_horizon;
+        let filter = OccurrenceFilter {
+            // Occurrences starting at exactly `after` are excluded.
+            after: Some(first_monday.and_hms(0, 0, 0) - chrono::Duration::seconds(1)),
+            before: Some(horizon),
+            ..OccurrenceFilter::default()
+        };
+        let busy_mondays: HashSet<NaiveDate> = self
+            .occurrences_by_date(&filter)?
+            .into_iter()
+            .filter(|(_, entries)| {
+                entries
+                    .iter()
+                    .any(|entry| !entry.occurrence.occurrence.cancelled)
+            })
+            .map(|(date, _)| {
+                date - chrono::Duration::days(date.weekday().num_days_from_monday().into())
+            })
+            .collect();
+
+        let mut gaps = Vec::new();
+        let mut monday = first_monday;
+        while monday + chrono::Duration::weeks(1) <= horizon.date() {
+            if !busy_mondays.contains(&monday) {
+                gaps.push(ScheduleGap {
+                    week: monday.format("%G-W%V").to_string(),
+                    monday,
+                });
+            }
+            monday += chrono::Duration::weeks(1);
+        }
+        Ok(gaps)
+    }
+
+    /// Aggregates all occurrences that have started by now, leaving out cancelled ones.
+    pub fn statistics(&self) -> StoreResult<Statistics> {
+        let filter = OccurrenceFilter {
+            before: Some(chrono::Local::now().naive_local()),
+            ..OccurrenceFilter::default()
+        };
+
+        let mut events_per_year = BTreeMap::new();
+        let mut total_minutes = 0;
+        for entry in self.all_events_with_occurrences(&filter)?.values() {
+            let held: Vec<&Occurrence> = entry
+                .occurrences
+                .iter()
+                .map(|occurrence| &occurrence.occurrence)
+                .filter(|occurrence| !occurrence.cancelled)
+                .collect();
+            let years: HashSet<i32> = held
+                .iter()
+                .map(|occurrence| occurrence.start.year())
+                .collect();
+            for year in years {
+                *events_per_year.entry(year).or_insert(0) += 1;
+            }
+            total_minutes += held
+                .iter()
+                .map(|occurrence| occurrence.duration.num_minutes())
+                .sum::<i64>();
+        }
+
+        let most_used_location = self
+            .locations_with_occurrences(&filter)?
+            .into_iter()
+            .map(|(_, entry)| {
+                let count = entry
+                    .occurrences
+                    .values()
+                    .filter(|occurrence| !occurrence.cancelled)
+                    .count();
+                (entry.location, count)
+            })
+            .filter(|(_, count)| *count > 0)
+            .max_by(|(a, a_count), (b, b_count)| {
+                // Prefer the alphabetically first name on ties, so the page does not flicker.
+                a_count.cmp(b_count).then_with(|| b.name.cmp(&a.name))
+            });
+
+        Ok(Statistics {
+            events_per_year,
+            total_hours: total_minutes as f64 / 60.0,
+            most_used_location,
+        })
+    }
 }
 
 pub trait Actions<T> {
     type Id;
 
-    fn all(&self) -> HashMap<Self::Id, T>;
-    fn create(&self, item: T) -> QueryResult<Self::Id>;
-    fn read(&self, id: Self::Id) -> QueryResult<T>;
-    fn update(&self, id: Self::Id, new_item: T) -> QueryResult<T>;
-    fn delete(&self, id: Self::Id) -> QueryResult<T>;
+    fn all(&self) -> StoreResult<HashMap<Self::Id, T>>;
+    fn create(&self, item: T) -> StoreResult<Self::Id>;
+    fn read(&self, id: Self::Id) -> StoreResult<T>;
+    fn update(&self, id: Self::Id, new_item: T) -> StoreResult<T>;
+    fn delete(&self, id: Self::Id) -> StoreResult<T>;
 }
 
 use db::schema::locations::dsl::locations as schema;
 impl Actions<Location> for Store {
     type Id = Id<Location>;
 
-    fn all(&self) -> HashMap<Self::Id, Location> {
-        schema
-            .load::<SqlLocation>(&*self.0)
-            .expect("Could not load database")
+    fn all(&self) -> StoreResult<HashMap<Self::Id, Location>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.locations());
+        }
+
+        use db::schema::locations::dsl::deleted_at;
+
+        Ok(schema
+            .filter(deleted_at.is_null())
+            .load::<SqlLocation>(self.connection()?)?
             .into_iter()
             .map(|x| x.into())
-            .collect()
+            .collect())
     }
 
-    fn create(&self, item: Location) -> QueryResult<Self::Id> {
+    fn create(&self, item: Location) -> StoreResult<Self::Id> {
+        let created = item.clone();
         let sql_item: SqlLocation = item.into();
-        diesel::insert_into(schema)
-            .values(&sql_item)
-            .execute(&*self.0)?;
+        self.write(|| {
+            diesel::insert_into(schema)
+                .values(&sql_item)
+                .execute(self.connection()?)?;
+            self.record_change(Entity::Location, &sql_item.id, None, Some(&created))
+                .map_err(StoreError::from)
+        })?;
 
         Ok(sql_item.id.into())
     }
 
-    fn read(&self, item_id: Self::Id) -> QueryResult<Location> {
+    fn read(&self, item_id: Self::Id) -> StoreResult<Location> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.location(&item_id);
+        }
+
+        use db::schema::locations::dsl::deleted_at;
         use db::SqlId;
 
         schema
             .find(SqlId::from(item_id))
-            .first::<SqlLocation>(&*self.0)
+            .filter(deleted_at.is_null())
+            .first::<SqlLocation>(self.connection()?)
             .map(|x| x.into())
             .map(|(_, x)| x)
+            .map_err(StoreError::from)
     }
 
-    fn update(&self, item_id: Self::Id, new_item: Location) -> QueryResult<Location> {
+    fn update(&self, item_id: Self::Id, new_item: Location) -> StoreResult<Location> {
+        use db::schema::locations::dsl::deleted_at;
         use db::SqlId;
 
         let raw_id: SqlId<Location> = item_id.into();
-        let (_, previous): (Id<Location>, Location) =
-            schema.find(&raw_id).first::<SqlLocation>(&*self.0)?.into();
-
-        diesel::update(schema.find(&raw_id))
-            .set::<SqlLocation>(new_item.into())
-            .execute(&*self.0)?;
-
-        Ok(previous)
+        let updated = new_item.clone();
+        let sql_item: SqlLocation = new_item.into();
+        self.write(|| {
+            let (_, previous): (Id<Location>, Location) = schema
+                .find(&raw_id)
+                .filter(deleted_at.is_null())
+                .first::<SqlLocation>(self.connection()?)?
+                .into();
+
+            diesel::update(schema.find(&raw_id))
+                .set(&sql_item)
+                .execute(self.connection()?)?;
+            self.record_change(Entity::Location, &raw_id, Some(&previous), Some(&updated))?;
+
+            Ok(previous)
+        })
     }
 
-    fn delete(&self, id: Self::Id) -> QueryResult<Location> {
+    /// Moves the location to the trash. Occurrences taking place there become undecided,
+    /// and stay so when the location is restored.
+    fn delete(&self, id: Self::Id) -> StoreResult<Location> {
+        use db::schema::locations::dsl::deleted_at;
+        use db::schema::{occurrences, recurrence_exceptions, recurrences};
         use db::SqlId;
-        let raw_id: SqlId<Location> = id.into();
-        let (_, previous): (Id<Location>, Location) =
-            schema.find(&raw_id).first::<SqlLocation>(&*self.0)?.into();
-
-        diesel::delete(schema.find(&raw_id)).execute(&*self.0)?;
 
-        Ok(previous)
+        let raw_id: SqlId<Location> = id.into();
+        self.write(|| {
+            let (_, previous): (Id<Location>, Location) = schema
+                .find(&raw_id)
+                .filter(deleted_at.is_null())
+                .first::<SqlLocation>(self.connection()?)?
+                .into();
+
+            let undecided = None::<SqlId<Location>>;
+            diesel::update(occurrences::table.filter(occurrences::location_id.eq(&raw_id)))
+                .set(occurrences::location_id.eq(&undecided))
+                .execute(self.connection()?)?;
+            diesel::update(recurrences::table.filter(recurrences::location_id.eq(&raw_id)))
+                .set(recurrences::location_id.eq(&undecided))
+                .execute(self.connection()?)?;
+            diesel::update(
+                recurrence_exceptions::table.filter(recurrence_exceptions::location_id.eq(&raw_id)),
+            )
+            .set(recurrence_exceptions::location_id.eq(&undecided))
+            .execute(self.connection()?)?;
+            diesel::update(schema.find(&raw_id))
+                .set(deleted_at.eq(Some(chrono::Local::now().naive_local())))
+                .execute(self.connection()?)?;
+            self.record_change(Entity::Location, &raw_id, Some(&previous), None)?;
+
+            Ok(previous)
+        })
     }
 }
 
-#[derive(Debug)]
-pub struct OccurrenceFilter {
-    pub before: Option<NaiveDateTime>,
-    pub after: Option<NaiveDateTime>,
+impl Store {
+    /// Stores the coordinates looked up for the address, unless the location has moved or
+    /// its coordinates have been entered by hand in the meantime.
+    pub fn set_located_coordinates(
+        &self,
+        id: Id<Location>,
+        address: &Address,
+        coordinates: Coordinates,
+    ) -> StoreResult<()> {
+        self.write(|| {
+            let location: Location = self.read(id.clone())?;
+            if location.manual_coordinates || location.address != *address {
+                return Ok(());
+            }
+
+            self.update(
+                id.clone(),
+                Location {
+                    coordinates: Some(coordinates),
+                    ..location
+                },
+            )?;
+            Ok(())
+        })
+    }
 }
 
-impl Default for OccurrenceFilter {
-    fn default() -> Self {
-        OccurrenceFilter {
-            before: None,
-            after: None,
-        }
+const DEFAULT_DISPLAY_CUTOFF_MINUTES: i64 = 0;
+
+fn initialize_display_cutoff(rocket: Rocket) -> Result<Rocket, Rocket> {
+    let minutes = rocket
+        .config()
+        .get_int("display_cutoff_minutes")
+        .unwrap_or(DEFAULT_DISPLAY_CUTOFF_MINUTES);
+    if minutes < 0 {
+        eprintln!(
+            "The display cutoff must not be negative, but is {} minutes.",
+            minutes
+        );
+        return Err(rocket);
     }
+
+    Ok(rocket.manage(DisplayCutoff(chrono::Duration::minutes(minutes))))
 }
 
-impl OccurrenceFilter {
-    pub fn upcoming() -> Self {
-        let today = NaiveDateTime::new(
-            chrono::Local::today().naive_local(),
-            chrono::NaiveTime::from_hms(0, 0, 0),
+const DEFAULT_SCHEDULE_HORIZON_DAYS: i64 = 183;
+
+fn initialize_schedule_horizon(rocket: Rocket) -> Result<Rocket, Rocket> {
+    let days = rocket
+        .config()
+        .get_int("schedule_horizon_days")
+        .unwrap_or(DEFAULT_SCHEDULE_HORIZON_DAYS);
+    if days <= 0 {
+        eprintln!(
+            "The schedule horizon must be positive, but is {} days.",
+            days
         );
-        OccurrenceFilter {
-            before: None,
-            after: Some(today),
-        }
+        return Err(rocket);
     }
-}
 
-#[derive(Debug, Serialize)]
-pub enum OccurrenceFilterError {
-    InvalidBeforeDate,
-    InvalidAfterDate,
-    InvalidRange,
+    Ok(rocket.manage(ScheduleHorizon(chrono::Duration::days(days))))
 }
 
-impl<'r> Responder<'r> for OccurrenceFilterError {
-    fn respond_to(self, _: &Request) -> response::Result<'r> {
-        Response::build()
-            .sized_body(Cursor::new(serde_json::to_string(&self).unwrap()))
-            .status(Status::UnprocessableEntity)
-            .ok()
-    }
+/// The months in which the summer and the winter season start.
+pub struct SeasonBoundaries {
+    pub summer_month: u32,
+    pub winter_month: u32,
 }
 
-impl<'q> FromQuery<'q> for OccurrenceFilter {
-    type Error = OccurrenceFilterError;
+fn initialize_season_boundaries(rocket: Rocket) -> Result<Rocket, Rocket> {
+    let summer_month = rocket.config().get_int("summer_season_month").unwrap_or(4);
+    let winter_month = rocket.config().get_int("winter_season_month").unwrap_or(10);
+    if !(1 <= summer_month && summer_month < winter_month && winter_month <= 12) {
+        eprintln!(
+            "The summer season has to start before the winter season, but they start in months {} and {}.",
+            summer_month, winter_month
+        );
+        return Err(rocket);
+    }
 
-    fn from_query(mut query: Query<'q>) -> Result<Self, Self::Error> {
-        use OccurrenceFilterError::*;
-        let before: Option<NaiveDateTime> = query
-            .clone()
-            .find(|i| i.key == "before")
-            .map(|item| decode_datetime(item).ok_or(InvalidBeforeDate))
-            .transpose()?;
-        let after: Option<NaiveDateTime> = query
-            .find(|i| i.key == "after")
-            .map(|item| decode_datetime(item).ok_or(InvalidAfterDate))
-            .transpose()?;
+    Ok(rocket.manage(SeasonBoundaries {
+        summer_month: summer_month as u32,
+        winter_month: winter_month as u32,
+    }))
+}
 
-        if after < before {
-            return Err(InvalidRange)?;
+impl SeasonBoundaries {
+    pub fn season_of(&self, date: NaiveDate) -> Season {
+        if date.month() < self.summer_month {
+            Season {
+                year: date.year() - 1,
+                half: SeasonHalf::Winter,
+            }
+        } else if date.month() < self.winter_month {
+            Season {
+                year: date.year(),
+                half: SeasonHalf::Summer,
+            }
+        } else {
+            Season {
+                year: date.year(),
+                half: SeasonHalf::Winter,
+            }
         }
-
-        Ok(OccurrenceFilter { before, after })
     }
 }
 
-fn decode_datetime(item: FormItem) -> Option<NaiveDateTime> {
-    chrono::NaiveDateTime::parse_from_str(&item.value.url_decode_lossy(), "%Y-%m-%dT%H:%M:%S").ok()
-}
-
 impl Store {
     pub fn all_events_with_occurrences(
         &self,
         filter: &OccurrenceFilter,
-    ) -> HashMap<Id<Event>, EventWithOccurrences> {
-        use db::schema::events::dsl::events;
+    ) -> StoreResult<HashMap<Id<Event>, EventWithOccurrences>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.events_with_occurrences(filter));
+        }
+
+        use db::schema::events::dsl::{deleted_at, events};
 
-        events
-            .load::<SqlEvent>(&*self.0)
-            .expect("Loading from database failed.")
+        let mut query = events.filter(deleted_at.is_null()).into_boxed();
+        if !filter.include_drafts {
+            query = query.filter(is_public(chrono::Local::now().naive_local()));
+        }
+        query
+            .load::<SqlEvent>(self.connection()?)?
             .into_iter()
             .map(|sql_event| {
                 let occurrences: Vec<OccurrenceWithLocation> =
                     SqlOccurrence::belonging_to(&sql_event)
                         .filter(apply_occurrence_filter(filter))
-                        .load::<SqlOccurrence>(&*self.0)
-                        .expect("Loading from database failed.")
+                        .load::<SqlOccurrence>(self.connection()?)?
                         .into_iter()
                         .map(|sql_occurrence| {
                             let (_, occurrence) = sql_occurrence.into();
@@ -306,30 +790,157 @@ impl Store {
 
                 let (id, event) = sql_event.into();
 
-                (id, EventWithOccurrences { event, occurrences })
+                Ok((id, EventWithOccurrences { event, occurrences }))
             })
             .collect()
     }
 
+    /// Events taking place at the same locations as the given one, most similar first.
+    /// Only events with occurrences matching the filter are considered.
+    pub fn related_events(
+        &self,
+        id: Id<Event>,
+        filter: &OccurrenceFilter,
+    ) -> StoreResult<Vec<RelatedEvent>> {
+        let locations: HashSet<Id<Location>> = self
+            .read_event_with_occurrences(id.clone(), &OccurrenceFilter::default())?
+            .occurrences
+            .into_iter()
+            .filter_map(|occurrence| occurrence.location_id)
+            .collect();
+
+        let mut related: Vec<(usize, RelatedEvent)> = self
+            .all_events_with_occurrences(filter)?
+            .into_iter()
+            .filter(|(other_id, _)| other_id != &id)
+            .filter_map(|(event_id, entry)| {
+                let shared_locations = entry
+                    .occurrences
+                    .iter()
+                    .filter_map(|occurrence| occurrence.location_id.as_ref())
+                    .filter(|location_id| locations.contains(location_id))
+                    .collect::<HashSet<_>>()
+                    .len();
+                if shared_locations == 0 {
+                    return None;
+                }
+
+                Some((
+                    shared_locations,
+                    RelatedEvent {
+                        event_id,
+                        event: entry.event,
+                    },
+                ))
+            })
+            .collect();
+        related.sort_by(|(a_shared, a), (b_shared, b)| {
+            b_shared
+                .cmp(a_shared)
+                .then_with(|| a.event.title.cmp(&b.event.title))
+        });
+
+        Ok(related
+            .into_iter()
+            .take(MAX_RELATED_EVENTS)
+            .map(|(_, related)| related)
+            .collect())
+    }
+
+    /// The occurrences of other events that overlap with the given ones at the same
+    /// location, including drafts. Cancelled occurrences leave their location free, so
+    /// they are left out.
+    pub fn booking_conflicts(
+        &self,
+        id: &Id<Event>,
+        occurrences: &[OccurrenceWithLocation],
+    ) -> StoreResult<Vec<BookingConflict>> {
+        let booked: Vec<(&Id<Location>, &Occurrence)> = occurrences
+            .iter()
+            .filter(|occurrence| !occurrence.occurrence.cancelled)
+            .filter_map(|occurrence| {
+                let location_id = occurrence.location_id.as_ref()?;
+                Some((location_id, &occurrence.occurrence))
+            })
+            .collect();
+        if booked.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let filter = OccurrenceFilter {
+            include_drafts: true,
+            ..OccurrenceFilter::default()
+        };
+        let mut conflicts = Vec::new();
+        for (event_id, entry) in self.all_events_with_occurrences(&filter)? {
+            if &event_id == id {
+                continue;
+            }
+            for other in &entry.occurrences {
+                let other_location_id = match &other.location_id {
+                    Some(location_id) if !other.occurrence.cancelled => location_id,
+                    _ => continue,
+                };
+                for &(location_id, occurrence) in &booked {
+                    if location_id == other_location_id && occurrence.overlaps(&other.occurrence) {
+                        conflicts.push(BookingConflict {
+                            location_id: location_id.clone(),
+                            start: occurrence.start,
+                            event_id: event_id.clone(),
+                            event_title: entry.event.title.clone(),
+                            other_start: other.occurrence.start,
+                        });
+                    }
+                }
+            }
+        }
+        // Ordered, so that the order does not change between requests.
+        conflicts.sort_by(|a, b| {
+            (a.start, a.other_start, &a.event_title).cmp(&(b.start, b.other_start, &b.event_title))
+        });
+        Ok(conflicts)
+    }
+
+    fn prepare_event(&self, mut event: Event) -> Event {
+        if self.options.derive_empty_teasers && event.teaser.trim().is_empty() {
+            event.teaser = teaser::derive_teaser(&event.description);
+        }
+        event
+    }
+
     pub fn create_event_with_occurrences(
         &self,
         item: EventWithOccurrences,
-    ) -> QueryResult<Id<Event>> {
+    ) -> StoreResult<Id<Event>> {
         use db::schema::events::dsl::events;
-        let sql_event: SqlEvent = item.event.into();
-        diesel::insert_into(events)
-            .values(&sql_event)
-            .execute(&*self.0)?;
-
         use db::schema::occurrences::dsl::occurrences;
+
+        item.validate().map_err(StoreError::Invalid)?;
+        let mut sql_event: SqlEvent = self.prepare_event(item.event).into();
         let sql_occurrences: Vec<SqlOccurrence> = item
             .occurrences
             .into_iter()
             .map(|occurrence| (occurrence, sql_event.id.clone()).into())
             .collect();
-        diesel::insert_into(occurrences)
-            .values(&sql_occurrences)
-            .execute(&*self.0)?;
+        self.write(|| {
+            sql_event.slug = slug::unique_slug(self.connection()?, &sql_event.title)?;
+            diesel::insert_into(events)
+                .values(&sql_event)
+                .execute(self.connection()?)?;
+            diesel::insert_into(occurrences)
+                .values(&sql_occurrences)
+                .execute(self.connection()?)?;
+
+            let created = self.read_event_with_occurrences(
+                sql_event.id.clone().into(),
+                &OccurrenceFilter {
+                    include_drafts: true,
+                    ..OccurrenceFilter::default()
+                },
+            )?;
+            self.record_change(Entity::Event, &sql_event.id, None, Some(&created))?;
+            Ok(())
+        })?;
 
         Ok(sql_event.id.into())
     }
@@ -338,16 +949,25 @@ impl Store {
         &self,
         item_id: Id<Event>,
         filter: &OccurrenceFilter,
-    ) -> QueryResult<EventWithOccurrences> {
-        use db::schema::events::dsl::events;
+    ) -> StoreResult<EventWithOccurrences> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.event_with_occurrences(&item_id, filter);
+        }
+
+        use db::schema::events::dsl::{deleted_at, events};
         use db::SqlId;
-        let sql_event = events
+        let mut query = events
             .find(SqlId::from(item_id))
-            .first::<SqlEvent>(&*self.0)?;
+            .filter(deleted_at.is_null())
+            .into_boxed();
+        if !filter.include_drafts {
+            query = query.filter(is_public(chrono::Local::now().naive_local()));
+        }
+        let sql_event = query.first::<SqlEvent>(self.connection()?)?;
 
         let occurrences: Vec<OccurrenceWithLocation> = SqlOccurrence::belonging_to(&sql_event)
             .filter(apply_occurrence_filter(filter))
-            .load::<SqlOccurrence>(&*self.0)?
+            .load::<SqlOccurrence>(self.connection()?)?
             .into_iter()
             .map(|sql_occurrence| {
                 let (_, occurrence) = sql_occurrence.into();
@@ -366,81 +986,276 @@ impl Store {
         item_id: Id<Event>,
         new_item: EventWithOccurrences,
         filter: &OccurrenceFilter,
-    ) -> QueryResult<EventWithOccurrences> {
+    ) -> StoreResult<EventWithOccurrences> {
         use db::SqlId;
 
-        let raw_id: SqlId<Event> = item_id.into();
-        use db::schema::events::dsl::events;
-        let sql_previous = events.find(raw_id.clone()).first::<SqlEvent>(&*self.0)?;
+        use db::schema::occurrences::dsl::occurrences as occurrences_table;
 
-        let associated_occurrences = SqlOccurrence::belonging_to(&sql_previous);
-        let previous_occurrences: Vec<OccurrenceWithLocation> = associated_occurrences
-            .filter(apply_occurrence_filter(filter))
-            .load::<SqlOccurrence>(&*self.0)?
+        // The event is addressed by its id, so it is replaced even if it is a draft.
+        new_item.validate().map_err(StoreError::Invalid)?;
+        let filter = &OccurrenceFilter {
+            include_drafts: true,
+            ..filter.clone()
+        };
+        let raw_id: SqlId<Event> = item_id.into();
+        let mut new_sql_item: SqlEvent = self.prepare_event(new_item.event).into();
+        let mut sql_occurrences: Vec<SqlOccurrence> = new_item
+            .occurrences
             .into_iter()
-            .map(|sql_occurrence| {
-                let (_, occurrence) = sql_occurrence.into();
-
-                occurrence
-            })
+            .map(|occurrence| (occurrence, raw_id.clone()).into())
             .collect();
 
-        diesel::delete(associated_occurrences.filter(apply_occurrence_filter(&filter)))
-            .execute(&*self.0)?;
+        self.write(|| {
+            let sql_previous = self.unlocked_event(&raw_id)?;
+
+            let associated_occurrences = SqlOccurrence::belonging_to(&sql_previous);
+            let previous_sql_occurrences = associated_occurrences
+                .filter(apply_occurrence_filter(filter))
+                .load::<SqlOccurrence>(self.connection()?)?;
+
+            // The API does not expose which recurrence an occurrence was created from, so
+            // occurrences sent back unchanged keep belonging to their recurrence.
+            let recurrences: HashMap<_, _> = previous_sql_occurrences
+                .iter()
+                .filter_map(|previous| {
+                    previous.recurrence_id.clone().map(|recurrence_id| {
+                        (
+                            (previous.start, previous.location_id.clone()),
+                            recurrence_id,
+                        )
+                    })
+                })
+                .collect();
+            for sql_occurrence in &mut sql_occurrences {
+                sql_occurrence.recurrence_id = recurrences
+                    .get(&(sql_occurrence.start, sql_occurrence.location_id.clone()))
+                    .cloned();
+            }
+
+            let previous_occurrences: Vec<OccurrenceWithLocation> = previous_sql_occurrences
+                .into_iter()
+                .map(|sql_occurrence| {
+                    let (_, occurrence) = sql_occurrence.into();
+
+                    occurrence
+                })
+                .collect();
+
+            diesel::delete(associated_occurrences.filter(apply_occurrence_filter(filter)))
+                .execute(self.connection()?)?;
+
+            new_sql_item.slug = sql_previous.slug.clone();
+            // Locking is only changed through `set_event_locked`.
+            new_sql_item.locked = sql_previous.locked;
+            diesel::update(&sql_previous)
+                .set(&new_sql_item)
+                .execute(self.connection()?)?;
+
+            diesel::insert_into(occurrences_table)
+                .values(&sql_occurrences)
+                .execute(self.connection()?)?;
+
+            let (_, previous) = sql_previous.into();
+            let previous = EventWithOccurrences {
+                event: previous,
+                occurrences: previous_occurrences,
+            };
+            let updated = self.read_event_with_occurrences(raw_id.clone().into(), filter)?;
+            self.record_change(Entity::Event, &raw_id, Some(&previous), Some(&updated))?;
+
+            Ok(previous)
+        })
+    }
 
-        let new_sql_item: SqlEvent = new_item.event.into();
-        diesel::update(&sql_previous)
-            .set(new_sql_item)
-            .execute(&*self.0)?;
+    /// Fails if the event does not exist or is locked. Called within writes, so that the
+    /// event cannot be locked before the write is saved.
+    fn unlocked_event(&self, id: &db::SqlId<Event>) -> StoreResult<SqlEvent> {
+        use db::schema::events::dsl::{deleted_at, events};
 
-        use db::schema::occurrences::dsl::occurrences as occurrences_table;
-        let sql_occurrences: Vec<SqlOccurrence> = new_item
-            .occurrences
-            .into_iter()
-            .map(|occurrence| (occurrence, raw_id.clone()).into())
-            .collect();
-        diesel::insert_into(occurrences_table)
-            .values(&sql_occurrences)
-            .execute(&*self.0)?;
-
-        let (_, previous) = sql_previous.into();
-        Ok(EventWithOccurrences {
-            event: previous,
-            occurrences: previous_occurrences,
+        let sql_event = events
+            .find(id)
+            .filter(deleted_at.is_null())
+            .first::<SqlEvent>(self.connection()?)?;
+        if sql_event.locked {
+            return Err(StoreError::Locked);
+        }
+        Ok(sql_event)
+    }
+
+    /// Returns whether the event was locked before.
+    pub fn set_event_locked(&self, id: Id<Event>, new_locked: bool) -> StoreResult<bool> {
+        use db::schema::events::dsl::{deleted_at, events, locked};
+        use db::SqlId;
+
+        let raw_id: SqlId<Event> = id.into();
+        self.write(|| {
+            let previous = events
+                .find(&raw_id)
+                .filter(deleted_at.is_null())
+                .select(locked)
+                .first(self.connection()?)?;
+            diesel::update(events.find(&raw_id))
+                .set(locked.eq(new_locked))
+                .execute(self.connection()?)?;
+            self.record_change(
+                Entity::Event,
+                &raw_id,
+                Some(&serde_json::json!({ "event": { "locked": previous } })),
+                Some(&serde_json::json!({ "event": { "locked": new_locked } })),
+            )?;
+
+            Ok(previous)
         })
     }
 
+    /// The event with this id, if it has been deleted.
+    pub fn deleted_event(&self, id: Id<Event>) -> StoreResult<Option<DeletedEvent>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.deleted_event(&id));
+        }
+
+        use db::schema::deleted_events::dsl::deleted_events;
+        use db::SqlDeletedEvent;
+
+        let sql_deleted = deleted_events
+            .find(db::SqlId::from(id))
+            .first::<SqlDeletedEvent>(self.connection()?)
+            .optional()?;
+        Ok(sql_deleted.map(|sql_deleted| {
+            let (_, deleted) = sql_deleted.into();
+            deleted
+        }))
+    }
+
+    /// The id of the event with this slug, including deleted events.
+    pub fn event_id_by_slug(&self, slug: &str) -> StoreResult<Option<Id<Event>>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.event_id_by_slug(slug));
+        }
+
+        use db::schema::deleted_events::dsl::{deleted_events, slug as deleted_slug};
+        use db::schema::events::dsl::{events, id, slug as event_slug};
+        use db::SqlId;
+
+        let sql_id = events
+            .select(id)
+            .filter(event_slug.eq(slug))
+            .first::<SqlId<Event>>(self.connection()?)
+            .optional()?;
+        let sql_id = match sql_id {
+            Some(sql_id) => Some(sql_id),
+            None => deleted_events
+                .select(db::schema::deleted_events::dsl::id)
+                .filter(deleted_slug.eq(slug))
+                .first::<SqlId<Event>>(self.connection()?)
+                .optional()?,
+        };
+        Ok(sql_id.map(Into::into))
+    }
+
+    /// Moves the event to the trash. Its occurrences, recurrences, and comments are kept,
+    /// so that it can be restored as it was.
     pub fn delete_event_with_occurrences(
         &self,
         id: Id<Event>,
-    ) -> QueryResult<EventWithOccurrences> {
+    ) -> StoreResult<EventWithOccurrences> {
         use db::SqlId;
 
+        use db::schema::deleted_events::dsl::deleted_events;
+        use db::schema::events::dsl::deleted_at;
+
         let raw_id: SqlId<Event> = id.into();
-        use db::schema::events::dsl::events;
-        let sql_previous = events.find(raw_id).first::<SqlEvent>(&*self.0)?;
+        self.write(|| {
+            let sql_previous = self.unlocked_event(&raw_id)?;
+
+            let occurrences: Vec<OccurrenceWithLocation> =
+                SqlOccurrence::belonging_to(&sql_previous)
+                    .load::<SqlOccurrence>(self.connection()?)?
+                    .into_iter()
+                    .map(|sql_occurrence| {
+                        let (_, occurrence) = sql_occurrence.into();
+
+                        occurrence
+                    })
+                    .collect();
+
+            let now = chrono::Local::now().naive_local();
+            diesel::update(&sql_previous)
+                .set(deleted_at.eq(Some(now)))
+                .execute(self.connection()?)?;
+
+            // Lets the event's page tell visitors that it is gone.
+            diesel::replace_into(deleted_events)
+                .values(&db::SqlDeletedEvent {
+                    id: sql_previous.id.clone(),
+                    title: sql_previous.title.clone(),
+                    deleted_at: now,
+                    slug: sql_previous.slug.clone(),
+                })
+                .execute(self.connection()?)?;
+
+            let (_, previous) = sql_previous.into();
+            let previous = EventWithOccurrences {
+                event: previous,
+                occurrences,
+            };
+            self.record_change(Entity::Event, &raw_id, Some(&previous), None)?;
+
+            Ok(previous)
+        })
+    }
+}
 
-        let occurrences: Vec<OccurrenceWithLocation> = SqlOccurrence::belonging_to(&sql_previous)
-            .load::<SqlOccurrence>(&*self.0)
-            .expect("Loading from database failed.")
-            .into_iter()
-            .map(|sql_occurrence| {
-                let (_, occurrence) = sql_occurrence.into();
+/// Whether the occurrence belongs to an event that is not in the trash. The occurrences of
+/// trashed events are kept, so that restoring the event brings them back.
+fn of_live_event() -> Box<
+    dyn BoxableExpression<
+        db::schema::occurrences::table,
+        diesel::sqlite::Sqlite,
+        SqlType = diesel::sql_types::Bool,
+    >,
+> {
+    use db::schema::events;
+    use db::schema::occurrences::event_id;
+
+    Box::new(
+        event_id.ne_all(
+            events::table
+                .select(events::id)
+                .filter(events::deleted_at.is_not_null()),
+        ),
+    )
+}
 
-                occurrence
-            })
-            .collect();
+/// Whether the event is shown on the public pages at `now`, see `Event::is_public`.
+fn is_public(
+    now: NaiveDateTime,
+) -> Box<
+    dyn BoxableExpression<
+        db::schema::events::table,
+        diesel::sqlite::Sqlite,
+        SqlType = diesel::sql_types::Bool,
+    >,
+> {
+    use db::schema::events::dsl::{publish_at, published};
 
-        diesel::delete(SqlOccurrence::belonging_to(&sql_previous)).execute(&*self.0)?;
+    Box::new(
+        published
+            .eq(true)
+            .and(publish_at.is_null().or(publish_at.le(now))),
+    )
+}
 
-        diesel::delete(&sql_previous).execute(&*self.0)?;
+/// The ids of the events that are drafts or scheduled to be published after `now`.
+fn unpublished_events(
+    now: NaiveDateTime,
+) -> db::schema::events::BoxedQuery<'static, diesel::sqlite::Sqlite, diesel::sql_types::Binary> {
+    use db::schema::events;
 
-        let (_, previous) = sql_previous.into();
-        Ok(EventWithOccurrences {
-            event: previous,
-            occurrences,
-        })
-    }
+    events::table
+        .select(events::id)
+        .filter(diesel::dsl::not(is_public(now)))
+        .into_boxed()
 }
 
 fn apply_occurrence_filter(
@@ -459,13 +1274,68 @@ fn apply_occurrence_filter(
             diesel::sqlite::Sqlite,
             SqlType = diesel::sql_types::Bool,
         >,
-    > = Box::new(true.into_sql::<diesel::sql_types::Bool>());
+    > = of_live_event();
+    if !filter.include_drafts {
+        query = Box::new(
+            query.and(event_id.ne_all(unpublished_events(chrono::Local::now().naive_local()))),
+        )
+    }
     if let Some(before) = filter.before {
         query = Box::new(query.and(start.lt(before)))
     }
     if let Some(after) = filter.after {
         query = Box::new(query.and(start.gt(after)))
     }
+    if let Some(from) = filter.from {
+        query = Box::new(query.and(start.ge(from.and_hms(0, 0, 0))))
+    }
+    // Nothing starts after the last day chrono can represent, so it needs no bound.
+    if let Some(next_day) = filter.until.and_then(|until| until.succ_opt()) {
+        query = Box::new(query.and(start.lt(next_day.and_hms(0, 0, 0))))
+    }
+    if let Some(ends_after) = filter.ends_after {
+        // SQLite stores timestamps as text, so the end has to be computed with its date functions.
+        query = Box::new(
+            query.and(
+                diesel::dsl::sql::<diesel::sql_types::Bool>(
+                    "datetime(start, '+' || duration || ' minutes') > ",
+                )
+                .bind::<diesel::sql_types::Timestamp, _>(ends_after),
+            ),
+        )
+    }
+    if !filter.weekdays.is_empty() {
+        // Like `Weekday::num_days_from_sunday`, SQLite counts the days from Sunday as 0.
+        let days: Vec<i32> = filter
+            .weekdays
+            .iter()
+            .map(|weekday| weekday.num_days_from_sunday() as i32)
+            .collect();
+        query = Box::new(
+            query.and(
+                diesel::dsl::sql::<diesel::sql_types::Integer>(
+                    "CAST(strftime('%w', start) AS INTEGER)",
+                )
+                .eq_any(days),
+            ),
+        )
+    }
+    if let Some(time_from) = filter.time_from {
+        query = Box::new(
+            query.and(
+                diesel::dsl::sql::<diesel::sql_types::Bool>("time(start) >= ")
+                    .bind::<diesel::sql_types::Time, _>(time_from),
+            ),
+        )
+    }
+    if let Some(time_until) = filter.time_until {
+        query = Box::new(
+            query.and(
+                diesel::dsl::sql::<diesel::sql_types::Bool>("time(start) <= ")
+                    .bind::<diesel::sql_types::Time, _>(time_until),
+            ),
+        )
+    }
 
     query
 }
@@ -484,6 +1354,31 @@ impl Fairing for StoreFairing {
         db::Connection::fairing()
             .on_attach(rocket)
             .and_then(db::initialize)
+            .and_then(initialize_display_cutoff)
+            .and_then(initialize_schedule_horizon)
+            .and_then(initialize_season_boundaries)
+            .and_then(initialize_options)
+            .and_then(recurrence::initialize)
+            // Last, so that the snapshot includes everything the other steps changed.
+            .and_then(snapshot::initialize)
+    }
+}
+
+impl Store {
+    /// A store for work done outside of requests, which keeps one of the pooled connections
+    /// for as long as it lives. Returns `None` in read-only snapshot mode.
+    pub fn detached(rocket: &Rocket) -> Option<Store> {
+        if is_read_only(rocket) {
+            return None;
+        }
+
+        Some(Store {
+            source: Source::database(db::Connection::get_one(rocket)?),
+            options: rocket.state::<Arc<StoreOptions>>()?.clone(),
+            changes: rocket.state::<Arc<ChangeBus>>()?.clone(),
+            pending_changes: RefCell::new(Vec::new()),
+            write_depth: Cell::new(0),
+        })
     }
 }
 
@@ -491,6 +1386,212 @@ impl<'a, 'r> FromRequest<'a, 'r> for Store {
     type Error = <db::Connection as FromRequest<'a, 'r>>::Error;
 
     fn from_request(request: &'a Request<'r>) -> Outcome<Self, Self::Error> {
-        db::Connection::from_request(request).map(Store)
+        let read_mode = request.guard::<State<snapshot::ReadMode>>()?;
+        let options = request.guard::<State<Arc<StoreOptions>>>()?.clone();
+        let changes = request.guard::<State<Arc<ChangeBus>>>()?.clone();
+        let store = |source| Store {
+            source,
+            options,
+            changes,
+            pending_changes: RefCell::new(Vec::new()),
+            write_depth: Cell::new(0),
+        };
+        if let Some(snapshot) = &read_mode.0 {
+            return rocket::Outcome::Success(store(Source::Snapshot(snapshot.clone())));
+        }
+
+        db::Connection::from_request(request).map(|connection| store(Source::database(connection)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use diesel::connection::SimpleConnection;
+    use rocket::config::{Config, Environment, Value};
+    use uuid::Uuid;
+
+    use super::*;
+
+    /// A store on a fresh database file, which is removed afterwards. Unlike an in-memory
+    /// database, it can be opened by a second connection.
+    pub struct TestDatabase {
+        pub store: Store,
+        pub db_path: PathBuf,
+    }
+
+    impl Drop for TestDatabase {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.db_path);
+        }
+    }
+
+    impl TestDatabase {
+        pub fn new() -> TestDatabase {
+            let db_path =
+                std::env::temp_dir().join(format!("lindyhop-store-{}.sqlite", Uuid::new_v4()));
+            let mut database = HashMap::new();
+            database.insert("url", Value::from(db_path.to_str().unwrap()));
+            let mut databases = HashMap::new();
+            databases.insert("sqlite_database", database);
+            let config = Config::build(Environment::Development)
+                .extra("databases", databases)
+                .finalize()
+                .unwrap();
+            let rocket = rocket::custom(config).attach(Store::fairing());
+            let store = Store::detached(&rocket).unwrap();
+
+            TestDatabase { store, db_path }
+        }
+
+        /// Locks the database for writing from a second connection, until the duration has
+        /// passed.
+        fn hold_write_lock(&self, duration: Duration) -> thread::JoinHandle<()> {
+            let connection = SqliteConnection::establish(self.db_path.to_str().unwrap()).unwrap();
+            connection.batch_execute("BEGIN IMMEDIATE;").unwrap();
+            thread::spawn(move || {
+                thread::sleep(duration);
+                connection.batch_execute("COMMIT;").unwrap();
+            })
+        }
+
+        /// Attempts a write, and returns its result and how often it was attempted.
+        fn attempt_write(&self) -> (StoreResult<()>, u32) {
+            // Otherwise SQLite would wait for the lock itself, instead of failing at once.
+            self.store
+                .connection()
+                .unwrap()
+                .batch_execute("PRAGMA busy_timeout = 0;")
+                .unwrap();
+
+            let mut attempts = 0;
+            let result = self.store.write(|| {
+                attempts += 1;
+                self.store
+                    .connection()?
+                    .batch_execute("DELETE FROM locations;")
+                    .map_err(StoreError::from)
+            });
+            (result, attempts)
+        }
+    }
+
+    #[test]
+    fn writes_are_retried_while_the_database_is_busy() {
+        let database = TestDatabase::new();
+        let lock = database.hold_write_lock(Duration::from_millis(30));
+
+        let (result, attempts) = database.attempt_write();
+        assert!(result.is_ok());
+        assert!(attempts > 1);
+        lock.join().unwrap();
+    }
+
+    #[test]
+    fn writes_give_up_if_the_database_stays_busy() {
+        let database = TestDatabase::new();
+        let lock = database.hold_write_lock(Duration::from_secs(2));
+
+        let (result, attempts) = database.attempt_write();
+        match result {
+            Err(StoreError::Busy) => {}
+            result => panic!("Expected the write to give up, but got {:?}", result),
+        }
+        assert_eq!(attempts, BUSY_ATTEMPTS);
+        lock.join().unwrap();
+    }
+
+    fn upcoming() -> OccurrenceFilter {
+        OccurrenceFilter::upcoming(
+            &DisplayCutoff(chrono::Duration::hours(3)),
+            &ScheduleHorizon(chrono::Duration::weeks(4)),
+        )
+    }
+
+    /// Whether the filter lists an occurrence with this start that lasts an hour.
+    fn lists(filter: &OccurrenceFilter, start: NaiveDateTime) -> bool {
+        let database = TestDatabase::new();
+        let occurrence = Occurrence {
+            start,
+            duration: chrono::Duration::hours(1),
+            doors_open: None,
+            open_end: false,
+            stream_url: None,
+            cancelled: false,
+            cancellation_reason: None,
+        };
+        database
+            .store
+            .create_event_with_occurrences(EventWithOccurrences {
+                event: Event {
+                    title: "Social Dance".to_string(),
+                    teaser: "Zum Tanzen.".to_string(),
+                    description: String::new(),
+                    contact_name: None,
+                    contact_email: None,
+                    locked: false,
+                    slug: String::new(),
+                    published: true,
+                    publish_at: None,
+                },
+                occurrences: vec![OccurrenceWithLocation {
+                    occurrence,
+                    location_id: None,
+                }],
+            })
+            .unwrap();
+
+        !database
+            .store
+            .occurrences_by_date(filter)
+            .unwrap()
+            .is_empty()
+    }
+
+    #[test]
+    fn occurrences_are_listed_until_the_display_cutoff_after_their_end() {
+        let filter = upcoming();
+        let cutoff = filter.ends_after.unwrap();
+        assert!(!lists(&filter, cutoff - chrono::Duration::hours(1)));
+        assert!(lists(
+            &filter,
+            cutoff - chrono::Duration::hours(1) + chrono::Duration::seconds(1)
+        ));
+    }
+
+    #[test]
+    fn occurrences_are_listed_if_they_start_before_the_schedule_horizon() {
+        let filter = upcoming();
+        let horizon = filter.before.unwrap();
+        assert!(lists(&filter, horizon - chrono::Duration::seconds(1)));
+        assert!(!lists(&filter, horizon));
+    }
+
+    fn season_of(year: i32, month: u32, day: u32) -> Season {
+        let seasons = SeasonBoundaries {
+            summer_month: 4,
+            winter_month: 10,
+        };
+        seasons.season_of(NaiveDate::from_ymd(year, month, day))
+    }
+
+    fn season(year: i32, half: SeasonHalf) -> Season {
+        Season { year, half }
+    }
+
+    #[test]
+    fn seasons_start_on_the_first_day_of_their_month() {
+        assert_eq!(season_of(2019, 3, 31), season(2018, SeasonHalf::Winter));
+        assert_eq!(season_of(2019, 4, 1), season(2019, SeasonHalf::Summer));
+        assert_eq!(season_of(2019, 9, 30), season(2019, SeasonHalf::Summer));
+        assert_eq!(season_of(2019, 10, 1), season(2019, SeasonHalf::Winter));
+    }
+
+    #[test]
+    fn winter_seasons_belong_to_the_year_they_start_in() {
+        assert_eq!(season_of(2019, 12, 31), season(2019, SeasonHalf::Winter));
+        assert_eq!(season_of(2020, 1, 1), season(2019, SeasonHalf::Winter));
     }
 }
@@ -0,0 +1,157 @@
+use diesel::{self, prelude::*};
+
+use super::db::{SqlComment, SqlSubmission};
+use super::*;
+
+impl Store {
+    pub fn create_submission(&self, submission: Submission) -> StoreResult<Id<Submission>> {
+        use db::schema::submissions::dsl::submissions;
+
+        EventWithOccurrences::from(submission.clone())
+            .validate()
+            .map_err(StoreError::Invalid)?;
+        let sql_submission: SqlSubmission = submission.into();
+        self.write(|| {
+            diesel::insert_into(submissions)
+                .values(&sql_submission)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_submission.id.into())
+    }
+
+    /// All submissions that have been neither approved nor rejected yet.
+    pub fn pending_submissions(&self) -> StoreResult<HashMap<Id<Submission>, Submission>> {
+        use db::schema::submissions::dsl::submissions;
+
+        Ok(submissions
+            .load::<SqlSubmission>(self.connection()?)?
+            .into_iter()
+            .map(|sql_submission| sql_submission.into())
+            .collect())
+    }
+
+    /// Turns the submission into an event and removes it from the queue.
+    pub fn approve_submission(&self, id: Id<Submission>) -> StoreResult<Id<Event>> {
+        self.write(|| {
+            let submission = self.reject_submission(id.clone())?;
+            self.create_event_with_occurrences(submission.into())
+        })
+    }
+
+    /// Removes the submission from the queue.
+    pub fn reject_submission(&self, id: Id<Submission>) -> StoreResult<Submission> {
+        use db::schema::submissions::dsl::submissions;
+
+        let sql_id: db::SqlId<Submission> = id.into();
+        self.write(|| {
+            let sql_submission = submissions
+                .find(&sql_id)
+                .first::<SqlSubmission>(self.connection()?)?;
+            diesel::delete(submissions.find(&sql_id)).execute(self.connection()?)?;
+
+            let (_, submission) = sql_submission.into();
+            Ok(submission)
+        })
+    }
+}
+
+impl Store {
+    pub fn create_comment(
+        &self,
+        event_id: Id<Event>,
+        comment: Comment,
+    ) -> StoreResult<Id<Comment>> {
+        use db::schema::comments::dsl::comments;
+        use db::schema::events::dsl::{deleted_at, events};
+
+        let sql_event_id: db::SqlId<Event> = event_id.into();
+        let sql_comment: SqlComment = (comment, sql_event_id.clone()).into();
+        self.write(|| {
+            // Fails if the event does not exist.
+            events
+                .find(&sql_event_id)
+                .filter(deleted_at.is_null())
+                .first::<SqlEvent>(self.connection()?)?;
+
+            diesel::insert_into(comments)
+                .values(&sql_comment)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_comment.id.into())
+    }
+
+    /// The comments to show on the event's page, oldest first.
+    pub fn approved_comments(&self, event_id: Id<Event>) -> StoreResult<Vec<Comment>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.approved_comments(&event_id));
+        }
+
+        use db::schema::comments::dsl::{
+            approved, comments, created_at, event_id as comment_event_id,
+        };
+
+        Ok(comments
+            .filter(comment_event_id.eq(db::SqlId::from(event_id)))
+            .filter(approved.eq(true))
+            .order(created_at.asc())
+            .load::<SqlComment>(self.connection()?)?
+            .into_iter()
+            .map(|sql_comment| {
+                let (_, comment) = sql_comment.into();
+                comment
+            })
+            .collect())
+    }
+
+    /// All comments that still need to be approved or deleted.
+    pub fn pending_comments(&self) -> StoreResult<HashMap<Id<Comment>, CommentWithEvent>> {
+        use db::schema::comments::dsl::{approved, comments};
+
+        Ok(comments
+            .filter(approved.eq(false))
+            .load::<SqlComment>(self.connection()?)?
+            .into_iter()
+            .map(|sql_comment| {
+                let event_id = sql_comment.event_id.clone().into();
+                let (id, comment) = sql_comment.into();
+                (id, CommentWithEvent { comment, event_id })
+            })
+            .collect())
+    }
+
+    pub fn approve_comment(&self, id: Id<Comment>) -> StoreResult<Comment> {
+        use db::schema::comments::dsl::{approved, comments};
+
+        let sql_id: db::SqlId<Comment> = id.into();
+        self.write(|| {
+            diesel::update(comments.find(&sql_id))
+                .set(approved.eq(true))
+                .execute(self.connection()?)?;
+
+            let (_, comment) = comments
+                .find(&sql_id)
+                .first::<SqlComment>(self.connection()?)?
+                .into();
+            Ok(comment)
+        })
+    }
+
+    pub fn delete_comment(&self, id: Id<Comment>) -> StoreResult<Comment> {
+        use db::schema::comments::dsl::comments;
+
+        let sql_id: db::SqlId<Comment> = id.into();
+        self.write(|| {
+            let sql_comment = comments
+                .find(&sql_id)
+                .first::<SqlComment>(self.connection()?)?;
+            diesel::delete(comments.find(&sql_id)).execute(self.connection()?)?;
+
+            let (_, comment) = sql_comment.into();
+            Ok(comment)
+        })
+    }
+}
@@ -0,0 +1,99 @@
+use diesel::{self, prelude::*};
+
+use super::db::{SqlId, SqlNavItem};
+use super::*;
+
+use db::schema::nav_items::dsl::nav_items;
+
+/// The navigation is part of every page, so it is part of the snapshot. Like newsletters, it
+/// is not part of the audit log.
+impl Actions<NavItem> for Store {
+    type Id = Id<NavItem>;
+
+    fn all(&self) -> StoreResult<HashMap<Self::Id, NavItem>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.nav_items().clone());
+        }
+
+        Ok(nav_items
+            .load::<SqlNavItem>(self.connection()?)?
+            .into_iter()
+            .map(|sql_item| sql_item.into())
+            .collect())
+    }
+
+    fn create(&self, item: NavItem) -> StoreResult<Self::Id> {
+        let sql_item: SqlNavItem = item.into();
+        self.write(|| {
+            diesel::insert_into(nav_items)
+                .values(&sql_item)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_item.id.into())
+    }
+
+    fn read(&self, id: Self::Id) -> StoreResult<NavItem> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot
+                .nav_items()
+                .get(&id)
+                .cloned()
+                .ok_or(StoreError::NotFound);
+        }
+
+        nav_items
+            .find(SqlId::from(id))
+            .first::<SqlNavItem>(self.connection()?)
+            .map(|sql_item| sql_item.into())
+            .map(|(_, item)| item)
+            .map_err(StoreError::from)
+    }
+
+    fn update(&self, id: Self::Id, new_item: NavItem) -> StoreResult<NavItem> {
+        let raw_id: SqlId<NavItem> = id.into();
+        let mut sql_item: SqlNavItem = new_item.into();
+        sql_item.id = raw_id.clone();
+        self.write(|| {
+            let (_, previous): (Id<NavItem>, NavItem) = nav_items
+                .find(&raw_id)
+                .first::<SqlNavItem>(self.connection()?)?
+                .into();
+            diesel::update(nav_items.find(&raw_id))
+                .set(&sql_item)
+                .execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+
+    fn delete(&self, id: Self::Id) -> StoreResult<NavItem> {
+        let raw_id: SqlId<NavItem> = id.into();
+        self.write(|| {
+            let (_, previous): (Id<NavItem>, NavItem) = nav_items
+                .find(&raw_id)
+                .first::<SqlNavItem>(self.connection()?)?
+                .into();
+            diesel::delete(nav_items.find(&raw_id)).execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+}
+
+impl Store {
+    /// The items to show in the navigation menu, in order.
+    pub fn navigation(&self) -> StoreResult<Vec<NavItem>> {
+        let all: HashMap<Id<NavItem>, NavItem> = self.all()?;
+        let mut visible: Vec<NavItem> = all
+            .into_iter()
+            .map(|(_, item)| item)
+            .filter(|item| item.visible)
+            .collect();
+        // Items at the same position are ordered by label, so that the order does not change
+        // between requests.
+        visible.sort_by(|a, b| (a.position, &a.label).cmp(&(b.position, &b.label)));
+        Ok(visible)
+    }
+}
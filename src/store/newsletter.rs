@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+
+use diesel::{self, prelude::*};
+
+use super::db::{SqlDelivery, SqlId, SqlNewsletter, SqlSubscriber};
+use super::*;
+
+use db::schema::newsletters::dsl::newsletters;
+
+/// Whom to send a newsletter to.
+pub struct Recipient {
+    pub id: Id<Subscriber>,
+    pub email: String,
+    /// For the link to unsubscribe, see `/newsletter/abmelden/<token>`.
+    pub unsubscribe_token: String,
+}
+
+/// Newsletters are not public, so they are neither part of the snapshot nor of the audit log.
+impl Actions<Newsletter> for Store {
+    type Id = Id<Newsletter>;
+
+    fn all(&self) -> StoreResult<HashMap<Self::Id, Newsletter>> {
+        Ok(newsletters
+            .load::<SqlNewsletter>(self.connection()?)?
+            .into_iter()
+            .map(|sql_newsletter| sql_newsletter.into())
+            .collect())
+    }
+
+    fn create(&self, item: Newsletter) -> StoreResult<Self::Id> {
+        let sql_item: SqlNewsletter = item.into();
+        self.write(|| {
+            diesel::insert_into(newsletters)
+                .values(&sql_item)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_item.id.into())
+    }
+
+    fn read(&self, id: Self::Id) -> StoreResult<Newsletter> {
+        newsletters
+            .find(SqlId::from(id))
+            .first::<SqlNewsletter>(self.connection()?)
+            .map(|sql_newsletter| sql_newsletter.into())
+            .map(|(_, newsletter)| newsletter)
+            .map_err(StoreError::from)
+    }
+
+    fn update(&self, id: Self::Id, new_item: Newsletter) -> StoreResult<Newsletter> {
+        let raw_id: SqlId<Newsletter> = id.into();
+        let mut sql_item: SqlNewsletter = new_item.into();
+        sql_item.id = raw_id.clone();
+        self.write(|| {
+            let (_, previous): (Id<Newsletter>, Newsletter) = newsletters
+                .find(&raw_id)
+                .first::<SqlNewsletter>(self.connection()?)?
+                .into();
+            diesel::update(newsletters.find(&raw_id))
+                .set(&sql_item)
+                .execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+
+    fn delete(&self, id: Self::Id) -> StoreResult<Newsletter> {
+        use db::schema::newsletter_deliveries::dsl::{newsletter_deliveries, newsletter_id};
+
+        let raw_id: SqlId<Newsletter> = id.into();
+        self.write(|| {
+            let (_, previous): (Id<Newsletter>, Newsletter) = newsletters
+                .find(&raw_id)
+                .first::<SqlNewsletter>(self.connection()?)?
+                .into();
+            diesel::delete(newsletter_deliveries.filter(newsletter_id.eq(&raw_id)))
+                .execute(self.connection()?)?;
+            diesel::delete(newsletters.find(&raw_id)).execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+}
+
+impl Store {
+    /// The newsletters sent up to the day, most recent first, for the public archive.
+    pub fn past_newsletters(
+        &self,
+        today: NaiveDate,
+    ) -> StoreResult<Vec<(Id<Newsletter>, Newsletter)>> {
+        use db::schema::newsletters::dsl::date;
+
+        Ok(newsletters
+            .filter(date.le(today))
+            .order(date.desc())
+            .load::<SqlNewsletter>(self.connection()?)?
+            .into_iter()
+            .map(|sql_newsletter| sql_newsletter.into())
+            .collect())
+    }
+
+    /// The confirmed subscribers the newsletter has not reached yet, so that sending it
+    /// again only retries those it failed for.
+    pub fn pending_recipients(&self, id: Id<Newsletter>) -> StoreResult<Vec<Recipient>> {
+        use db::schema::newsletter_deliveries::dsl::{
+            error, newsletter_deliveries, newsletter_id, subscriber_id,
+        };
+        use db::schema::subscribers::dsl::{confirmed, subscribers};
+
+        let reached: HashSet<Id<Subscriber>> = newsletter_deliveries
+            .select(subscriber_id)
+            .filter(newsletter_id.eq(SqlId::from(id)))
+            .filter(error.is_null())
+            .load::<SqlId<Subscriber>>(self.connection()?)?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(subscribers
+            .filter(confirmed.eq(true))
+            .load::<SqlSubscriber>(self.connection()?)?
+            .into_iter()
+            .map(|subscriber| Recipient {
+                id: subscriber.id.into(),
+                email: subscriber.email,
+                unsubscribe_token: subscriber.unsubscribe_token,
+            })
+            .filter(|recipient| !reached.contains(&recipient.id))
+            .collect())
+    }
+
+    /// Records the latest attempt to send the newsletter to the subscriber.
+    pub fn record_delivery(
+        &self,
+        newsletter: Id<Newsletter>,
+        subscriber: Id<Subscriber>,
+        error: Option<String>,
+    ) -> StoreResult<()> {
+        use db::schema::newsletter_deliveries::dsl::newsletter_deliveries;
+
+        let delivery = SqlDelivery {
+            newsletter_id: newsletter.into(),
+            subscriber_id: subscriber.into(),
+            sent_at: chrono::Local::now().naive_local(),
+            error,
+        };
+        self.write(|| {
+            diesel::replace_into(newsletter_deliveries)
+                .values(&delivery)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+        Ok(())
+    }
+
+    /// Whether the newsletter reached each subscriber it was sent to, ordered by email.
+    pub fn deliveries(&self, id: Id<Newsletter>) -> StoreResult<Vec<Delivery>> {
+        use db::schema::newsletter_deliveries::dsl::{newsletter_deliveries, newsletter_id};
+
+        let all_subscribers = self.subscribers()?;
+        let mut deliveries: Vec<Delivery> = newsletter_deliveries
+            .filter(newsletter_id.eq(SqlId::from(id)))
+            .load::<SqlDelivery>(self.connection()?)?
+            .into_iter()
+            .filter_map(|sql_delivery| {
+                let SqlDelivery {
+                    subscriber_id,
+                    sent_at,
+                    error,
+                    ..
+                } = sql_delivery;
+                let subscriber_id: Id<Subscriber> = subscriber_id.into();
+                all_subscribers
+                    .get(&subscriber_id)
+                    .map(|subscriber| Delivery {
+                        email: subscriber.email.clone(),
+                        sent_at,
+                        error,
+                    })
+            })
+            .collect();
+        deliveries.sort_by(|a, b| a.email.cmp(&b.email));
+        Ok(deliveries)
+    }
+}
@@ -0,0 +1,102 @@
+use diesel::{self, prelude::*};
+
+use super::db::{SqlId, SqlPage};
+use super::*;
+
+use db::schema::pages::dsl::{pages, slug as page_slug};
+
+/// Like the navigation, pages are part of the snapshot but not of the audit log.
+impl Actions<Page> for Store {
+    type Id = Id<Page>;
+
+    fn all(&self) -> StoreResult<HashMap<Self::Id, Page>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.pages().clone());
+        }
+
+        Ok(pages
+            .load::<SqlPage>(self.connection()?)?
+            .into_iter()
+            .map(|sql_page| sql_page.into())
+            .collect())
+    }
+
+    fn create(&self, page: Page) -> StoreResult<Self::Id> {
+        let sql_page: SqlPage = page.into();
+        self.write(|| {
+            diesel::insert_into(pages)
+                .values(&sql_page)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_page.id.into())
+    }
+
+    fn read(&self, id: Self::Id) -> StoreResult<Page> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot
+                .pages()
+                .get(&id)
+                .cloned()
+                .ok_or(StoreError::NotFound);
+        }
+
+        pages
+            .find(SqlId::from(id))
+            .first::<SqlPage>(self.connection()?)
+            .map(|sql_page| sql_page.into())
+            .map(|(_, page)| page)
+            .map_err(StoreError::from)
+    }
+
+    fn update(&self, id: Self::Id, new_page: Page) -> StoreResult<Page> {
+        let raw_id: SqlId<Page> = id.into();
+        let mut sql_page: SqlPage = new_page.into();
+        sql_page.id = raw_id.clone();
+        self.write(|| {
+            let (_, previous): (Id<Page>, Page) = pages
+                .find(&raw_id)
+                .first::<SqlPage>(self.connection()?)?
+                .into();
+            diesel::update(pages.find(&raw_id))
+                .set(&sql_page)
+                .execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+
+    fn delete(&self, id: Self::Id) -> StoreResult<Page> {
+        let raw_id: SqlId<Page> = id.into();
+        self.write(|| {
+            let (_, previous): (Id<Page>, Page) = pages
+                .find(&raw_id)
+                .first::<SqlPage>(self.connection()?)?
+                .into();
+            diesel::delete(pages.find(&raw_id)).execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+}
+
+impl Store {
+    /// The page with the slug, including drafts unless serving a snapshot.
+    pub fn page_by_slug(&self, slug: &str) -> StoreResult<Option<(Id<Page>, Page)>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot
+                .pages()
+                .iter()
+                .find(|(_, page)| page.slug == slug)
+                .map(|(id, page)| (id.clone(), page.clone())));
+        }
+
+        pages
+            .filter(page_slug.eq(slug))
+            .first::<SqlPage>(self.connection()?)
+            .optional()
+            .map(|sql_page| sql_page.map(|sql_page| sql_page.into()))
+            .map_err(StoreError::from)
+    }
+}
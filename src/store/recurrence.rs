@@ -0,0 +1,266 @@
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use diesel::result::QueryResult;
+use diesel::{self, prelude::*};
+use rocket::Rocket;
+
+use super::db::{SqlId, SqlRecurrence, SqlRecurrenceException};
+use super::*;
+
+/// Expands all recurrences up to the schedule horizon. This happens at startup and whenever
+/// a recurrence changes, so a server running for longer than the horizon needs a restart
+/// to list occurrences beyond it.
+pub fn initialize(rocket: Rocket) -> Result<Rocket, Rocket> {
+    use db::schema::events;
+    use db::schema::recurrences::dsl::{event_id, recurrences};
+
+    let conn = db::Connection::get_one(&rocket).expect("Database connection failed.");
+    let end = expansion_end(rocket.state::<Arc<StoreOptions>>().unwrap());
+    // The recurrences of trashed events are expanded when the event is restored.
+    let trashed_events = events::table
+        .select(events::id)
+        .filter(events::deleted_at.is_not_null());
+    let result: QueryResult<()> = conn.transaction(|| {
+        for recurrence in recurrences
+            .filter(event_id.ne_all(trashed_events))
+            .load::<SqlRecurrence>(&*conn)?
+        {
+            expand(&*conn, recurrence, end)?;
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => Ok(rocket),
+        Err(e) => {
+            eprintln!("Failed to expand the recurrences: {:?}", e);
+            Err(rocket)
+        }
+    }
+}
+
+pub(super) fn expansion_end(options: &StoreOptions) -> NaiveDateTime {
+    Local::now().naive_local() + options.schedule_horizon
+}
+
+/// Converts the recurrence for storage, with its exceptions referring to it.
+fn to_sql(
+    recurrence: Recurrence,
+    event_id: SqlId<Event>,
+) -> (SqlRecurrence, Vec<SqlRecurrenceException>) {
+    let exceptions = recurrence.exceptions.clone();
+    let sql_recurrence = SqlRecurrence::new(recurrence, event_id);
+    let sql_exceptions = exceptions
+        .into_iter()
+        .map(|exception| SqlRecurrenceException::new(exception, sql_recurrence.id.clone()))
+        .collect();
+    (sql_recurrence, sql_exceptions)
+}
+
+/// Creates the recurrence's occurrences after the ones created before, up to `end`,
+/// taking its exceptions into account.
+pub(super) fn expand(
+    conn: &SqliteConnection,
+    recurrence: SqlRecurrence,
+    end: NaiveDateTime,
+) -> QueryResult<()> {
+    use db::schema::occurrences::dsl::occurrences;
+    use db::schema::recurrences::dsl::expanded_until;
+
+    let exceptions: HashMap<NaiveDate, SqlRecurrenceException> =
+        SqlRecurrenceException::belonging_to(&recurrence)
+            .load::<SqlRecurrenceException>(conn)?
+            .into_iter()
+            .map(|exception| (exception.date, exception))
+            .collect();
+
+    let interval = Duration::weeks(recurrence.interval_weeks.into());
+    let mut start = recurrence.start;
+    if let Some(previous_end) = recurrence.expanded_until {
+        while start <= previous_end {
+            start += interval;
+        }
+    }
+
+    let mut new_occurrences = Vec::new();
+    while start <= end && recurrence.until.map_or(true, |until| start.date() <= until) {
+        let occurrence = recurrence.occurrence_at(start);
+        let occurrence = match exceptions.get(&start.date()) {
+            Some(exception) => exception.apply(occurrence),
+            None => Some(occurrence),
+        };
+        new_occurrences.extend(occurrence);
+        start += interval;
+    }
+
+    diesel::insert_into(occurrences)
+        .values(&new_occurrences)
+        .execute(conn)?;
+    diesel::update(&recurrence)
+        .set(expanded_until.eq(Some(end)))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn replace_exceptions(
+    conn: &SqliteConnection,
+    id: &SqlId<Recurrence>,
+    exceptions: &[SqlRecurrenceException],
+) -> QueryResult<()> {
+    use db::schema::recurrence_exceptions::dsl::{recurrence_exceptions, recurrence_id};
+
+    diesel::delete(recurrence_exceptions.filter(recurrence_id.eq(id))).execute(conn)?;
+    diesel::insert_into(recurrence_exceptions)
+        .values(exceptions)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Removes the occurrences created from the recurrence that have not started yet.
+fn remove_upcoming_occurrences(
+    conn: &SqliteConnection,
+    id: &SqlId<Recurrence>,
+    now: NaiveDateTime,
+) -> QueryResult<usize> {
+    use db::schema::occurrences::dsl::{occurrences, recurrence_id, start};
+
+    diesel::delete(
+        occurrences
+            .filter(recurrence_id.eq(id))
+            .filter(start.ge(now)),
+    )
+    .execute(conn)
+}
+
+impl Store {
+    pub fn recurrences(
+        &self,
+        event_id: Id<Event>,
+    ) -> StoreResult<HashMap<Id<Recurrence>, Recurrence>> {
+        use db::schema::recurrences::dsl::{event_id as recurrence_event_id, recurrences};
+
+        let sql_recurrences = recurrences
+            .filter(recurrence_event_id.eq(SqlId::from(event_id)))
+            .load::<SqlRecurrence>(self.connection()?)?;
+        let sql_exceptions = SqlRecurrenceException::belonging_to(&sql_recurrences)
+            .load::<SqlRecurrenceException>(self.connection()?)?
+            .grouped_by(&sql_recurrences);
+
+        Ok(sql_recurrences
+            .into_iter()
+            .zip(sql_exceptions)
+            .map(|(sql_recurrence, sql_exceptions)| sql_recurrence.with_exceptions(sql_exceptions))
+            .collect())
+    }
+
+    /// Saves the recurrence and creates its occurrences up to the schedule horizon.
+    pub fn create_recurrence(
+        &self,
+        event_id: Id<Event>,
+        recurrence: Recurrence,
+    ) -> StoreResult<Id<Recurrence>> {
+        use db::schema::recurrences::dsl::recurrences;
+
+        let created = recurrence.clone();
+        let (sql_recurrence, sql_exceptions) = to_sql(recurrence, event_id.into());
+        self.write(|| {
+            self.unlocked_event(&sql_recurrence.event_id)?;
+
+            diesel::insert_into(recurrences)
+                .values(&sql_recurrence)
+                .execute(self.connection()?)?;
+            replace_exceptions(self.connection()?, &sql_recurrence.id, &sql_exceptions)?;
+            self.record_change(Entity::Recurrence, &sql_recurrence.id, None, Some(&created))?;
+            expand(
+                self.connection()?,
+                sql_recurrence.clone(),
+                expansion_end(&self.options),
+            )
+            .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_recurrence.id.into())
+    }
+
+    /// Replaces the recurrence and returns the previous version. Its upcoming occurrences
+    /// are recreated, the past ones are kept.
+    pub fn update_recurrence(
+        &self,
+        event_id: Id<Event>,
+        id: Id<Recurrence>,
+        recurrence: Recurrence,
+    ) -> StoreResult<Recurrence> {
+        use db::schema::recurrences::dsl::{event_id as recurrence_event_id, recurrences};
+
+        let raw_id: SqlId<Recurrence> = id.into();
+        let raw_event_id: SqlId<Event> = event_id.into();
+        self.write(|| {
+            self.unlocked_event(&raw_event_id)?;
+            let previous = recurrences
+                .find(&raw_id)
+                .filter(recurrence_event_id.eq(&raw_event_id))
+                .first::<SqlRecurrence>(self.connection()?)?;
+
+            let previous_exceptions = SqlRecurrenceException::belonging_to(&previous)
+                .load::<SqlRecurrenceException>(self.connection()?)?;
+
+            let now = Local::now().naive_local();
+            remove_upcoming_occurrences(self.connection()?, &raw_id, now)?;
+            let (mut updated, mut updated_exceptions) =
+                to_sql(recurrence.clone(), previous.event_id.clone());
+            updated.id = raw_id.clone();
+            updated.expanded_until = Some(now);
+            for exception in &mut updated_exceptions {
+                exception.recurrence_id = raw_id.clone();
+            }
+            diesel::update(&previous)
+                .set(&updated)
+                .execute(self.connection()?)?;
+            replace_exceptions(self.connection()?, &raw_id, &updated_exceptions)?;
+            expand(self.connection()?, updated, expansion_end(&self.options))?;
+
+            let (_, previous) = previous.with_exceptions(previous_exceptions);
+            self.record_change(
+                Entity::Recurrence,
+                &raw_id,
+                Some(&previous),
+                Some(&recurrence),
+            )?;
+            Ok(previous)
+        })
+    }
+
+    /// Deletes the recurrence with its upcoming occurrences and returns it. The past
+    /// occurrences are kept.
+    pub fn delete_recurrence(
+        &self,
+        event_id: Id<Event>,
+        id: Id<Recurrence>,
+    ) -> StoreResult<Recurrence> {
+        use db::schema::occurrences::dsl::{occurrences, recurrence_id};
+        use db::schema::recurrences::dsl::{event_id as recurrence_event_id, recurrences};
+
+        let raw_id: SqlId<Recurrence> = id.into();
+        let raw_event_id: SqlId<Event> = event_id.into();
+        self.write(|| {
+            self.unlocked_event(&raw_event_id)?;
+            let previous = recurrences
+                .find(&raw_id)
+                .filter(recurrence_event_id.eq(&raw_event_id))
+                .first::<SqlRecurrence>(self.connection()?)?;
+
+            let previous_exceptions = SqlRecurrenceException::belonging_to(&previous)
+                .load::<SqlRecurrenceException>(self.connection()?)?;
+
+            remove_upcoming_occurrences(self.connection()?, &raw_id, Local::now().naive_local())?;
+            diesel::update(occurrences.filter(recurrence_id.eq(&raw_id)))
+                .set(recurrence_id.eq(None::<SqlId<Recurrence>>))
+                .execute(self.connection()?)?;
+            replace_exceptions(self.connection()?, &raw_id, &[])?;
+            diesel::delete(&previous).execute(self.connection()?)?;
+
+            let (_, previous) = previous.with_exceptions(previous_exceptions);
+            self.record_change(Entity::Recurrence, &raw_id, Some(&previous), None)?;
+            Ok(previous)
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use diesel::{self, prelude::*};
+
+use super::db::{SqlId, SqlRedirect};
+use super::*;
+
+use db::schema::redirects::dsl::redirects;
+
+/// Redirects are followed by visitors of the public pages, so like the navigation they are
+/// part of the snapshot but not of the audit log.
+impl Actions<UrlRedirect> for Store {
+    type Id = Id<UrlRedirect>;
+
+    fn all(&self) -> StoreResult<HashMap<Self::Id, UrlRedirect>> {
+        if let Some(snapshot) = self.snapshot() {
+            return Ok(snapshot.redirects().clone());
+        }
+
+        Ok(redirects
+            .load::<SqlRedirect>(self.connection()?)?
+            .into_iter()
+            .map(|sql_redirect| sql_redirect.into())
+            .collect())
+    }
+
+    fn create(&self, entry: UrlRedirect) -> StoreResult<Self::Id> {
+        let sql_redirect: SqlRedirect = entry.into();
+        self.write(|| {
+            diesel::insert_into(redirects)
+                .values(&sql_redirect)
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+
+        Ok(sql_redirect.id.into())
+    }
+
+    fn read(&self, id: Self::Id) -> StoreResult<UrlRedirect> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot
+                .redirects()
+                .get(&id)
+                .cloned()
+                .ok_or(StoreError::NotFound);
+        }
+
+        redirects
+            .find(SqlId::from(id))
+            .first::<SqlRedirect>(self.connection()?)
+            .map(|sql_redirect| sql_redirect.into())
+            .map(|(_, entry)| entry)
+            .map_err(StoreError::from)
+    }
+
+    fn update(&self, id: Self::Id, new_redirect: UrlRedirect) -> StoreResult<UrlRedirect> {
+        let raw_id: SqlId<UrlRedirect> = id.into();
+        let mut sql_redirect: SqlRedirect = new_redirect.into();
+        sql_redirect.id = raw_id.clone();
+        self.write(|| {
+            let (_, previous): (Id<UrlRedirect>, UrlRedirect) = redirects
+                .find(&raw_id)
+                .first::<SqlRedirect>(self.connection()?)?
+                .into();
+            diesel::update(redirects.find(&raw_id))
+                .set(&sql_redirect)
+                .execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+
+    fn delete(&self, id: Self::Id) -> StoreResult<UrlRedirect> {
+        let raw_id: SqlId<UrlRedirect> = id.into();
+        self.write(|| {
+            let (_, previous): (Id<UrlRedirect>, UrlRedirect) = redirects
+                .find(&raw_id)
+                .first::<SqlRedirect>(self.connection()?)?
+                .into();
+            diesel::delete(redirects.find(&raw_id)).execute(self.connection()?)?;
+
+            Ok(previous)
+        })
+    }
+}
+
+impl Store {
+    /// The redirect whose source is the path. A trailing slash is ignored, since the old
+    /// site ended its paths with one.
+    pub fn redirect_for(&self, path: &str) -> StoreResult<Option<(Id<UrlRedirect>, UrlRedirect)>> {
+        let path = path.trim_end_matches('/');
+        let all: HashMap<Id<UrlRedirect>, UrlRedirect> = self.all()?;
+        Ok(all
+            .into_iter()
+            .find(|(_, redirect)| redirect.source.trim_end_matches('/') == path))
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use diesel::{self, prelude::*};
+
+use super::db::{SqlDeletedEvent, SqlEvent};
+use crate::text;
+
+/// Used for titles that contain nothing a slug can be made of.
+const FALLBACK_SLUG: &str = "veranstaltung";
+
+/// A slug for the title that is neither used by an event nor by a deleted one, whose
+/// old links should keep answering that the event is gone. Collisions are numbered,
+/// e.g. `social-dance-2`.
+pub fn unique_slug(conn: &SqliteConnection, title: &str) -> QueryResult<String> {
+    use super::db::schema::deleted_events::dsl::{deleted_events, slug as deleted_slug};
+    use super::db::schema::events::dsl::{events, slug};
+
+    let mut base = text::slugify(title);
+    if base.is_empty() {
+        base = FALLBACK_SLUG.to_string();
+    }
+
+    let pattern = format!("{}%", base);
+    let mut taken: HashSet<String> = events
+        .select(slug)
+        .filter(slug.like(&pattern))
+        .load(conn)?
+        .into_iter()
+        .collect();
+    taken.extend(
+        deleted_events
+            .select(deleted_slug)
+            .filter(deleted_slug.like(&pattern))
+            .load::<String>(conn)?,
+    );
+
+    let candidate = (1..)
+        .map(|number| match number {
+            1 => base.clone(),
+            number => format!("{}-{}", base, number),
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap();
+    Ok(candidate)
+}
+
+/// Assigns slugs to the events created or deleted before slugs existed.
+pub fn assign_missing(conn: &SqliteConnection) -> QueryResult<()> {
+    use super::db::schema::deleted_events::dsl::{deleted_events, slug as deleted_slug};
+    use super::db::schema::events::dsl::{events, slug};
+
+    conn.transaction(|| {
+        let missing = events.filter(slug.eq("")).load::<SqlEvent>(conn)?;
+        for sql_event in missing {
+            let new_slug = unique_slug(conn, &sql_event.title)?;
+            diesel::update(events.find(sql_event.id))
+                .set(slug.eq(new_slug))
+                .execute(conn)?;
+        }
+
+        let missing_deleted = deleted_events
+            .filter(deleted_slug.eq(""))
+            .load::<SqlDeletedEvent>(conn)?;
+        for sql_deleted in missing_deleted {
+            let new_slug = unique_slug(conn, &sql_deleted.title)?;
+            diesel::update(deleted_events.find(sql_deleted.id))
+                .set(deleted_slug.eq(new_slug))
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
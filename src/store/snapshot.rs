@@ -0,0 +1,319 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use diesel::{self, prelude::*};
+use rocket::Rocket;
+
+use super::db::{
+    self, SqlComment, SqlDeletedEvent, SqlEvent, SqlFaqEntry, SqlLocation, SqlNavItem,
+    SqlOccurrence, SqlPage, SqlRedirect,
+};
+use super::*;
+
+/// Managed state telling the `Store` request guard where to read from.
+pub struct ReadMode(pub Option<Arc<Snapshot>>);
+
+pub fn initialize(rocket: Rocket) -> Result<Rocket, Rocket> {
+    if !is_read_only(&rocket) {
+        return Ok(rocket.manage(ReadMode(None)));
+    }
+
+    let conn = db::Connection::get_one(&rocket).expect("Database connection failed.");
+    match Snapshot::load(&*conn) {
+        Ok(snapshot) => Ok(rocket.manage(ReadMode(Some(Arc::new(snapshot))))),
+        Err(e) => {
+            eprintln!("Failed to load the read-only snapshot: {:?}", e);
+            Err(rocket)
+        }
+    }
+}
+
+/// An immutable copy of all events, occurrences, and locations, and of the navigation, the
+/// pages, the FAQ, and the redirects.
+pub struct Snapshot {
+    events: HashMap<Id<Event>, Event>,
+    locations: HashMap<Id<Location>, Location>,
+    /// Sorted by start.
+    occurrences: Vec<SnapshotOccurrence>,
+    /// Only the approved ones, sorted by creation.
+    comments: HashMap<Id<Event>, Vec<Comment>>,
+    deleted_events: HashMap<Id<Event>, DeletedEvent>,
+    nav_items: HashMap<Id<NavItem>, NavItem>,
+    /// Only the published ones.
+    pages: HashMap<Id<Page>, Page>,
+    faq_entries: HashMap<Id<FaqEntry>, FaqEntry>,
+    redirects: HashMap<Id<UrlRedirect>, UrlRedirect>,
+}
+
+struct SnapshotOccurrence {
+    id: Id<Occurrence>,
+    event_id: Id<Event>,
+    occurrence: OccurrenceWithLocation,
+}
+
+impl Snapshot {
+    fn load(conn: &SqliteConnection) -> StoreResult<Snapshot> {
+        use db::schema::comments::dsl::{approved, comments, created_at};
+        use db::schema::deleted_events::dsl::deleted_events;
+        use db::schema::events::dsl::{deleted_at as event_deleted_at, events, published};
+        use db::schema::faq_entries::dsl::faq_entries;
+        use db::schema::locations::dsl::{deleted_at as location_deleted_at, locations};
+        use db::schema::nav_items::dsl::nav_items;
+        use db::schema::occurrences::dsl::{occurrences, start};
+        use db::schema::pages::dsl::{pages, published as page_published};
+        use db::schema::redirects::dsl::redirects;
+
+        // Read-only servers only serve the public pages, so drafts are left out. Events
+        // scheduled to be published later are kept and hidden until then.
+        let all_events = events
+            .filter(event_deleted_at.is_null())
+            .filter(published.eq(true))
+            .load::<SqlEvent>(conn)?
+            .into_iter()
+            .map(|sql_event| sql_event.into())
+            .collect();
+        let all_locations = locations
+            .filter(location_deleted_at.is_null())
+            .load::<SqlLocation>(conn)?
+            .into_iter()
+            .map(|sql_location| sql_location.into())
+            .collect();
+        let all_occurrences = occurrences
+            .filter(of_live_event())
+            .order(start.asc())
+            .load::<SqlOccurrence>(conn)?
+            .into_iter()
+            .map(|sql_occurrence| {
+                let event_id = sql_occurrence.event_id.clone().into();
+                let (id, occurrence) = sql_occurrence.into();
+
+                SnapshotOccurrence {
+                    id,
+                    event_id,
+                    occurrence,
+                }
+            })
+            .collect();
+        let approved_comments = comments
+            .filter(approved.eq(true))
+            .order(created_at.asc())
+            .load::<SqlComment>(conn)?
+            .into_iter()
+            .fold(
+                HashMap::new(),
+                |mut acc: HashMap<Id<Event>, Vec<Comment>>, sql_comment| {
+                    let event_id = sql_comment.event_id.clone().into();
+                    let (_, comment) = sql_comment.into();
+                    acc.entry(event_id).or_insert_with(Vec::new).push(comment);
+                    acc
+                },
+            );
+
+        let all_deleted_events = deleted_events
+            .load::<SqlDeletedEvent>(conn)?
+            .into_iter()
+            .map(|sql_deleted| sql_deleted.into())
+            .collect();
+        let all_nav_items = nav_items
+            .load::<SqlNavItem>(conn)?
+            .into_iter()
+            .map(|sql_item| sql_item.into())
+            .collect();
+        let published_pages = pages
+            .filter(page_published.eq(true))
+            .load::<SqlPage>(conn)?
+            .into_iter()
+            .map(|sql_page| sql_page.into())
+            .collect();
+        let all_faq_entries = faq_entries
+            .load::<SqlFaqEntry>(conn)?
+            .into_iter()
+            .map(|sql_entry| sql_entry.into())
+            .collect();
+        let all_redirects = redirects
+            .load::<SqlRedirect>(conn)?
+            .into_iter()
+            .map(|sql_redirect| sql_redirect.into())
+            .collect();
+
+        Ok(Snapshot {
+            events: all_events,
+            locations: all_locations,
+            occurrences: all_occurrences,
+            comments: approved_comments,
+            deleted_events: all_deleted_events,
+            nav_items: all_nav_items,
+            pages: published_pages,
+            faq_entries: all_faq_entries,
+            redirects: all_redirects,
+        })
+    }
+
+    /// The event, unless it is scheduled to be published later.
+    fn public_event(&self, id: &Id<Event>) -> Option<&Event> {
+        let now = chrono::Local::now().naive_local();
+        self.events.get(id).filter(|event| event.is_public(now))
+    }
+
+    /// The occurrences matching the filter whose events are public. Occurrences of drafts
+    /// were not loaded, so their events are missing.
+    fn filtered<'a>(
+        &'a self,
+        filter: &'a OccurrenceFilter,
+    ) -> impl Iterator<Item = &'a SnapshotOccurrence> + 'a {
+        self.occurrences.iter().filter(move |entry| {
+            filter.matches(&entry.occurrence.occurrence)
+                && self.public_event(&entry.event_id).is_some()
+        })
+    }
+
+    pub fn locations(&self) -> HashMap<Id<Location>, Location> {
+        self.locations.clone()
+    }
+
+    pub fn location(&self, id: &Id<Location>) -> StoreResult<Location> {
+        self.locations.get(id).cloned().ok_or(StoreError::NotFound)
+    }
+
+    pub fn occurrences_by_date(
+        &self,
+        filter: &OccurrenceFilter,
+    ) -> BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>> {
+        self.filtered(filter)
+            .filter_map(|entry| {
+                self.public_event(&entry.event_id)
+                    .map(|event| OccurrenceWithEvent {
+                        occurrence: entry.occurrence.clone(),
+                        event_id: entry.event_id.clone(),
+                        event: event.clone(),
+                    })
+            })
+            .fold(
+                BTreeMap::new(),
+                |mut acc: BTreeMap<NaiveDate, Vec<OccurrenceWithEvent>>, entry| {
+                    acc.entry(entry.occurrence.occurrence.start.date())
+                        .or_insert_with(Vec::new)
+                        .push(entry);
+                    acc
+                },
+            )
+    }
+
+    pub fn occurrence_with_event(&self, id: &Id<Occurrence>) -> StoreResult<OccurrenceWithEvent> {
+        let entry = self
+            .occurrences
+            .iter()
+            .find(|entry| &entry.id == id)
+            .ok_or(StoreError::NotFound)?;
+        let event = self
+            .public_event(&entry.event_id)
+            .ok_or(StoreError::NotFound)?;
+
+        Ok(OccurrenceWithEvent {
+            occurrence: entry.occurrence.clone(),
+            event_id: entry.event_id.clone(),
+            event: event.clone(),
+        })
+    }
+
+    pub fn locations_with_occurrences(
+        &self,
+        filter: &OccurrenceFilter,
+    ) -> HashMap<Id<Location>, LocationWithOccurrences> {
+        self.locations
+            .iter()
+            .map(|(id, location)| {
+                let occurrences = self
+                    .filtered(filter)
+                    .filter(|entry| entry.occurrence.location_id.as_ref() == Some(id))
+                    .map(|entry| (entry.id.clone(), entry.occurrence.occurrence.clone()))
+                    .collect();
+
+                (
+                    id.clone(),
+                    LocationWithOccurrences {
+                        location: location.clone(),
+                        occurrences,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    pub fn events_with_occurrences(
+        &self,
+        filter: &OccurrenceFilter,
+    ) -> HashMap<Id<Event>, EventWithOccurrences> {
+        let now = chrono::Local::now().naive_local();
+        self.events
+            .iter()
+            .filter(|(_, event)| event.is_public(now))
+            .map(|(id, event)| (id.clone(), self.with_occurrences(id, event, filter)))
+            .collect()
+    }
+
+    pub fn event_with_occurrences(
+        &self,
+        id: &Id<Event>,
+        filter: &OccurrenceFilter,
+    ) -> StoreResult<EventWithOccurrences> {
+        self.public_event(id)
+            .map(|event| self.with_occurrences(id, event, filter))
+            .ok_or(StoreError::NotFound)
+    }
+
+    pub fn nav_items(&self) -> &HashMap<Id<NavItem>, NavItem> {
+        &self.nav_items
+    }
+
+    pub fn pages(&self) -> &HashMap<Id<Page>, Page> {
+        &self.pages
+    }
+
+    pub fn faq_entries(&self) -> &HashMap<Id<FaqEntry>, FaqEntry> {
+        &self.faq_entries
+    }
+
+    pub fn redirects(&self) -> &HashMap<Id<UrlRedirect>, UrlRedirect> {
+        &self.redirects
+    }
+
+    pub fn approved_comments(&self, event_id: &Id<Event>) -> Vec<Comment> {
+        self.comments.get(event_id).cloned().unwrap_or_default()
+    }
+
+    pub fn event_id_by_slug(&self, slug: &str) -> Option<Id<Event>> {
+        self.events
+            .iter()
+            .map(|(id, event)| (id, &event.slug))
+            .chain(
+                self.deleted_events
+                    .iter()
+                    .map(|(id, deleted)| (id, &deleted.slug)),
+            )
+            .find(|(_, event_slug)| *event_slug == slug)
+            .map(|(id, _)| id.clone())
+    }
+
+    pub fn deleted_event(&self, id: &Id<Event>) -> Option<DeletedEvent> {
+        self.deleted_events.get(id).cloned()
+    }
+
+    fn with_occurrences(
+        &self,
+        id: &Id<Event>,
+        event: &Event,
+        filter: &OccurrenceFilter,
+    ) -> EventWithOccurrences {
+        let occurrences = self
+            .filtered(filter)
+            .filter(|entry| &entry.event_id == id)
+            .map(|entry| entry.occurrence.clone())
+            .collect();
+
+        EventWithOccurrences {
+            event: event.clone(),
+            occurrences,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use chrono::Local;
+use diesel::{self, prelude::*};
+
+use super::db::SqlSubscriber;
+use super::*;
+
+impl Store {
+    pub fn subscribers(&self) -> StoreResult<HashMap<Id<Subscriber>, Subscriber>> {
+        use db::schema::subscribers::dsl::subscribers;
+
+        Ok(subscribers
+            .load::<SqlSubscriber>(self.connection()?)?
+            .into_iter()
+            .map(|sql_subscriber| sql_subscriber.into())
+            .collect())
+    }
+
+    /// Adds the subscribers whose address is not subscribed yet. Either all of them are
+    /// added or, if anything fails, none.
+    pub fn import_subscribers(
+        &self,
+        new_subscribers: Vec<Subscriber>,
+    ) -> StoreResult<SubscriberImport> {
+        use db::schema::subscribers::dsl::{email, subscribers};
+
+        self.write(|| {
+            let mut known: HashSet<String> = subscribers
+                .select(email)
+                .load::<String>(self.connection()?)?
+                .into_iter()
+                .collect();
+
+            let mut import = SubscriberImport::default();
+            let mut sql_subscribers = Vec::new();
+            for subscriber in new_subscribers.iter().cloned() {
+                if known.insert(subscriber.email.clone()) {
+                    sql_subscribers.push(SqlSubscriber::from(subscriber));
+                } else {
+                    import.duplicates.push(subscriber.email);
+                }
+            }
+            import.imported = diesel::insert_into(subscribers)
+                .values(&sql_subscribers)
+                .execute(self.connection()?)?;
+
+            Ok(import)
+        })
+    }
+
+    /// Subscribes the address without confirming it, and returns the token to confirm it
+    /// with. Signing up again before confirming replaces the token, so only the link in
+    /// the latest mail works. Returns `None` if the address is confirmed already.
+    pub fn subscribe(&self, address: &str, name: Option<String>) -> StoreResult<Option<String>> {
+        use db::schema::subscribers::dsl::{confirmation_token, email, subscribers};
+
+        let address = address.to_lowercase();
+        let token = new_token();
+        self.write(|| {
+            let existing = subscribers
+                .filter(email.eq(&address))
+                .first::<SqlSubscriber>(self.connection()?)
+                .optional()?;
+            match existing {
+                Some(subscriber) => {
+                    if subscriber.confirmed {
+                        return Ok(None);
+                    }
+                    diesel::update(&subscriber)
+                        .set(confirmation_token.eq(&token))
+                        .execute(self.connection()?)?;
+                }
+                None => {
+                    let mut subscriber = SqlSubscriber::from(Subscriber {
+                        email: address.clone(),
+                        name: name.clone(),
+                        consented_at: Local::today().naive_local(),
+                        confirmed: false,
+                    });
+                    subscriber.confirmation_token = Some(token.clone());
+                    diesel::insert_into(subscribers)
+                        .values(&subscriber)
+                        .execute(self.connection()?)?;
+                }
+            }
+            Ok(Some(token.clone()))
+        })
+    }
+
+    /// Confirms the address the token was sent to, which counts as consenting today. The
+    /// token can only be used once. Returns whether it belonged to an address.
+    pub fn confirm_subscription(&self, token: &str) -> StoreResult<bool> {
+        use db::schema::subscribers::dsl::{
+            confirmation_token, confirmed, consented_at, subscribers,
+        };
+
+        let today = Local::today().naive_local();
+        let updated = self.write(|| {
+            diesel::update(subscribers.filter(confirmation_token.eq(token)))
+                .set((
+                    confirmed.eq(true),
+                    consented_at.eq(today),
+                    confirmation_token.eq(None::<String>),
+                ))
+                .execute(self.connection()?)
+                .map_err(StoreError::from)
+        })?;
+        Ok(updated > 0)
+    }
+
+    /// Removes the subscriber the token belongs to, along with the record of what they
+    /// were sent. Returns whether it belonged to a subscriber.
+    pub fn unsubscribe(&self, token: &str) -> StoreResult<bool> {
+        use db::schema::newsletter_deliveries::dsl::{newsletter_deliveries, subscriber_id};
+        use db::schema::subscribers::dsl::{subscribers, unsubscribe_token};
+
+        self.write(|| {
+            let subscriber = match subscribers
+                .filter(unsubscribe_token.eq(token))
+                .first::<SqlSubscriber>(self.connection()?)
+                .optional()?
+            {
+                Some(subscriber) => subscriber,
+                None => return Ok(false),
+            };
+            diesel::delete(newsletter_deliveries.filter(subscriber_id.eq(&subscriber.id)))
+                .execute(self.connection()?)?;
+            diesel::delete(&subscriber).execute(self.connection()?)?;
+            Ok(true)
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use crate::text;
+
+/// Teasers are shown in the schedule's cards, so they should stay short.
+const MAX_TEASER_CHARS: usize = 160;
+
+/// Derives a teaser from the first sentence of the description, stripped of Markdown.
+/// Headings are skipped and sentences too long for a teaser are cut at a word boundary.
+pub fn derive_teaser(description: &str) -> String {
+    let paragraph = description
+        .split("\n\n")
+        .filter(|paragraph| !paragraph.trim_start().starts_with('#'))
+        .map(strip_markdown)
+        .find(|paragraph| !paragraph.is_empty())
+        .unwrap_or_default();
+    text::truncate(first_sentence(&paragraph), MAX_TEASER_CHARS)
+}
+
+fn first_sentence(text: &str) -> &str {
+    let end = text
+        .char_indices()
+        .zip(text.chars().skip(1).chain(Some(' ')))
+        .find(|((_, c), next)| ['.', '!', '?'].contains(c) && next.is_whitespace())
+        .map(|((index, c), _)| index + c.len_utf8());
+
+    match end {
+        Some(end) => &text[..end],
+        None => text,
+    }
+}
+
+/// Removes the Markdown syntax commonly used in descriptions and joins the lines.
+fn strip_markdown(paragraph: &str) -> String {
+    let text = paragraph
+        .lines()
+        .map(|line| {
+            line.trim()
+                .trim_start_matches(|c| c == '#' || c == '>')
+                .trim_start_matches("- ")
+                .trim_start_matches("* ")
+                .trim()
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    strip_links(&text)
+        .chars()
+        .filter(|c| !['*', '_', '`'].contains(c))
+        .collect()
+}
+
+/// Replaces `[text](url)` with `text`.
+fn strip_links(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let link = rest[start..]
+            .find("](")
+            .and_then(|middle| rest[start + middle..].find(')').map(|end| (middle, end)));
+        match link {
+            Some((middle, end)) => {
+                result.push_str(&rest[..start]);
+                result.push_str(&rest[start + 1..start + middle]);
+                rest = &rest[start + middle + end + 1..];
+            }
+            None => break,
+        }
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,116 @@
+use chrono::NaiveDateTime;
+use diesel::{self, prelude::*};
+
+use super::db::{SqlId, SqlRecurrence};
+use super::*;
+
+impl Store {
+    /// Everything that has been deleted and can be restored.
+    pub fn trash(&self) -> StoreResult<Trash> {
+        use db::schema::events::dsl::{deleted_at as event_deleted_at, events};
+        use db::schema::locations::dsl::{deleted_at as location_deleted_at, locations};
+
+        let trashed_locations = locations
+            .filter(location_deleted_at.is_not_null())
+            .load::<SqlLocation>(self.connection()?)?
+            .into_iter()
+            .filter_map(|sql_location| {
+                let deleted_at = sql_location.deleted_at?;
+                let (id, location) = sql_location.into();
+                Some((
+                    id,
+                    Trashed {
+                        item: location,
+                        deleted_at,
+                    },
+                ))
+            })
+            .collect();
+
+        let mut trashed_events = HashMap::new();
+        for sql_event in events
+            .filter(event_deleted_at.is_not_null())
+            .load::<SqlEvent>(self.connection()?)?
+        {
+            let occurrences = SqlOccurrence::belonging_to(&sql_event)
+                .load::<SqlOccurrence>(self.connection()?)?
+                .into_iter()
+                .map(|sql_occurrence| {
+                    let (_, occurrence) = sql_occurrence.into();
+                    occurrence
+                })
+                .collect();
+            if let Some(deleted_at) = sql_event.deleted_at {
+                let (id, event) = sql_event.into();
+                trashed_events.insert(
+                    id,
+                    Trashed {
+                        item: EventWithOccurrences { event, occurrences },
+                        deleted_at,
+                    },
+                );
+            }
+        }
+
+        Ok(Trash {
+            locations: trashed_locations,
+            events: trashed_events,
+        })
+    }
+
+    /// Takes the location out of the trash. Occurrences that took place there stay undecided.
+    pub fn restore_location(&self, id: Id<Location>) -> StoreResult<Location> {
+        use db::schema::locations::dsl::{deleted_at, locations};
+
+        let raw_id: SqlId<Location> = id.into();
+        self.write(|| {
+            let sql_location = locations
+                .find(&raw_id)
+                .filter(deleted_at.is_not_null())
+                .first::<SqlLocation>(self.connection()?)?;
+            diesel::update(&sql_location)
+                .set(deleted_at.eq(None::<NaiveDateTime>))
+                .execute(self.connection()?)?;
+
+            let (_, location) = sql_location.into();
+            self.record_change(Entity::Location, &raw_id, None, Some(&location))?;
+            Ok(location)
+        })
+    }
+
+    /// Takes the event out of the trash with its occurrences, recurrences, and comments.
+    /// Recurrences are expanded up to the schedule horizon again.
+    pub fn restore_event(&self, id: Id<Event>) -> StoreResult<EventWithOccurrences> {
+        use db::schema::deleted_events::dsl::deleted_events;
+        use db::schema::events::dsl::{deleted_at, events};
+
+        let raw_id: SqlId<Event> = id.into();
+        self.write(|| {
+            let sql_event = events
+                .find(&raw_id)
+                .filter(deleted_at.is_not_null())
+                .first::<SqlEvent>(self.connection()?)?;
+            diesel::update(&sql_event)
+                .set(deleted_at.eq(None::<NaiveDateTime>))
+                .execute(self.connection()?)?;
+            diesel::delete(deleted_events.find(&raw_id)).execute(self.connection()?)?;
+
+            let end = recurrence::expansion_end(&self.options);
+            for sql_recurrence in
+                SqlRecurrence::belonging_to(&sql_event).load::<SqlRecurrence>(self.connection()?)?
+            {
+                recurrence::expand(self.connection()?, sql_recurrence, end)?;
+            }
+
+            let restored = self.read_event_with_occurrences(
+                raw_id.clone().into(),
+                &OccurrenceFilter {
+                    include_drafts: true,
+                    ..OccurrenceFilter::default()
+                },
+            )?;
+            self.record_change(Entity::Event, &raw_id, None, Some(&restored))?;
+            Ok(restored)
+        })
+    }
+}
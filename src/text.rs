@@ -0,0 +1,155 @@
+/// Shortens the text to at most `max_length` characters as perceived by readers, including
+/// the ellipsis that marks the cut. The text is cut at the last word boundary that fits,
+/// and only within a word if its first word alone is too long, e.g. for a URL.
+///
+/// Lengths are counted in graphemes, so that a decomposed umlaut like `u` followed by a
+/// combining diaeresis counts once and is never separated from its dots.
+pub fn truncate(text: &str, max_length: usize) -> String {
+    let graphemes = graphemes(text);
+    if graphemes.len() <= max_length {
+        return text.to_string();
+    }
+    if max_length == 0 {
+        return String::new();
+    }
+
+    // Leave room for the ellipsis.
+    let kept = &graphemes[..max_length - 1];
+    let word_end = kept
+        .iter()
+        .zip(&graphemes[1..])
+        .rposition(|(grapheme, next)| !is_whitespace(grapheme) && is_whitespace(next));
+    let end = match word_end {
+        Some(index) => index + 1,
+        None => kept.len(),
+    };
+
+    let mut truncated: String = graphemes[..end].concat();
+    truncated.truncate(truncated.trim_end().len());
+    truncated.push('…');
+    truncated
+}
+
+/// Turns the text into a readable URL segment, e.g. `Tanzen im Café Über` into
+/// `tanzen-im-cafe-ueber`. Umlauts are transliterated the German way, other accents
+/// are dropped and everything else besides ASCII letters and digits separates words.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut separated = false;
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let replacement = match c {
+            'ä' => "ae",
+            'ö' => "oe",
+            'ü' => "ue",
+            'ß' => "ss",
+            'à' | 'á' | 'â' | 'å' => "a",
+            'ç' => "c",
+            'è' | 'é' | 'ê' | 'ë' => "e",
+            'ì' | 'í' | 'î' | 'ï' => "i",
+            'ñ' => "n",
+            'ò' | 'ó' | 'ô' => "o",
+            'ù' | 'ú' | 'û' => "u",
+            // A decomposed umlaut.
+            '\u{0308}' if slug.ends_with(|c| c == 'a' || c == 'o' || c == 'u') => "e",
+            c if is_extending(c) => "",
+            c if c.is_ascii_alphanumeric() => {
+                if separated && !slug.is_empty() {
+                    slug.push('-');
+                }
+                separated = false;
+                slug.push(c);
+                continue;
+            }
+            _ => {
+                separated = true;
+                continue;
+            }
+        };
+        if separated && !slug.is_empty() && !replacement.is_empty() {
+            slug.push('-');
+            separated = false;
+        }
+        slug.push_str(replacement);
+    }
+    slug
+}
+
+fn is_whitespace(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+/// Splits the text into graphemes, approximated as a character together with the combining
+/// marks and variation selectors following it, and with characters joined by zero-width joiners.
+fn graphemes(text: &str) -> Vec<&str> {
+    let mut graphemes = Vec::new();
+    let mut start = 0;
+    let mut joined = false;
+    for (index, c) in text.char_indices() {
+        let continues = index > 0 && (joined || is_extending(c));
+        if !continues && index > start {
+            graphemes.push(&text[start..index]);
+            start = index;
+        }
+        joined = c == '\u{200D}';
+    }
+    if start < text.len() {
+        graphemes.push(&text[start..]);
+    }
+    graphemes
+}
+
+fn is_extending(c: char) -> bool {
+    match c {
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{200D}'
+        | '\u{1F3FB}'..='\u{1F3FF}' => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_text_is_kept() {
+        assert_eq!(truncate("Zum Tanzen.", 11), "Zum Tanzen.");
+    }
+
+    #[test]
+    fn text_is_cut_at_word_boundary() {
+        assert_eq!(truncate("Tanzen bis in die Nacht", 12), "Tanzen bis…");
+        assert_eq!(truncate("Tanzen bis in die Nacht", 11), "Tanzen bis…");
+        assert_eq!(truncate("Tanzen bis in die Nacht", 10), "Tanzen…");
+    }
+
+    #[test]
+    fn long_word_is_cut_within() {
+        assert_eq!(truncate("https://lindyhop-aachen.de", 10), "https://l…");
+    }
+
+    #[test]
+    fn slugs_are_readable() {
+        assert_eq!(slugify("Social Dance"), "social-dance");
+        assert_eq!(slugify("Tanzen im Café Über"), "tanzen-im-cafe-ueber");
+        assert_eq!(
+            slugify("  Lindy Hop: Workshop (Level 2)!  "),
+            "lindy-hop-workshop-level-2"
+        );
+        assert_eq!(slugify("Große Bu\u{0308}hne"), "grosse-buehne");
+        assert_eq!(slugify("🎷"), "");
+    }
+
+    #[test]
+    fn combining_marks_stay_with_their_character() {
+        let decomposed = "Bu\u{0308}hne";
+        assert_eq!(truncate(decomposed, 5), decomposed);
+        assert_eq!(truncate("Grüße aus Aachen", 9), "Grüße…");
+        assert_eq!(truncate("Bu\u{0308}u\u{0308}u\u{0308}", 3), "Bu\u{0308}…");
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rocket::fairing::{self, Fairing};
+use rocket::{Data, Request, Response, Rocket, State};
+
+/// How many of the most recent response times are kept per route.
+const WINDOW_SIZE: usize = 100;
+/// Percentiles over fewer samples are too noisy to alert on.
+const MIN_SAMPLES: usize = 20;
+const DEFAULT_BUDGET_MS: i64 = 500;
+
+/// Records per-route response times and reports routes whose 95th percentile
+/// exceeds the budget configured as `route_budget_ms`.
+pub struct RouteTimingFairing;
+
+struct RequestStart(Instant);
+
+struct RouteTimings {
+    budget: Duration,
+    routes: Mutex<HashMap<String, RouteWindow>>,
+}
+
+#[derive(Default)]
+struct RouteWindow {
+    samples: VecDeque<Duration>,
+    over_budget: bool,
+}
+
+impl RouteWindow {
+    fn record(&mut self, elapsed: Duration) {
+        if self.samples.len() == WINDOW_SIZE {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(elapsed);
+    }
+
+    fn p95(&self) -> Option<Duration> {
+        if self.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().cloned().collect();
+        sorted.sort();
+        let index = (sorted.len() * 95 / 100).min(sorted.len() - 1);
+        Some(sorted[index])
+    }
+}
+
+impl Fairing for RouteTimingFairing {
+    fn info(&self) -> fairing::Info {
+        fairing::Info {
+            name: "Route Timing Fairing",
+            kind: fairing::Kind::Attach | fairing::Kind::Request | fairing::Kind::Response,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> Result<Rocket, Rocket> {
+        let budget_ms = rocket
+            .config()
+            .get_int("route_budget_ms")
+            .unwrap_or(DEFAULT_BUDGET_MS);
+        if budget_ms <= 0 {
+            eprintln!("The route budget must be positive, but is {}ms.", budget_ms);
+            return Err(rocket);
+        }
+
+        Ok(rocket.manage(RouteTimings {
+            budget: Duration::from_millis(budget_ms as u64),
+            routes: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    fn on_request(&self, request: &mut Request, _: &Data) {
+        request.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    fn on_response(&self, request: &Request, _: &mut Response) {
+        let route = match request.route() {
+            Some(route) => format!("{} {}", route.method, route.uri),
+            None => return,
+        };
+        let elapsed = request
+            .local_cache(|| RequestStart(Instant::now()))
+            .0
+            .elapsed();
+        let timings = match request.guard::<State<RouteTimings>>().succeeded() {
+            Some(timings) => timings,
+            None => return,
+        };
+
+        let mut routes = timings.routes.lock().unwrap();
+        let window = routes.entry(route.clone()).or_default();
+        window.record(elapsed);
+
+        if let Some(p95) = window.p95() {
+            let over_budget = p95 > timings.budget;
+            // Only report when a route crosses the budget, not on every request.
+            if over_budget && !window.over_budget {
+                eprintln!(
+                    "Route '{}' exceeds its response time budget: p95 is {}ms, budget is {}ms.",
+                    route,
+                    p95.as_millis(),
+                    timings.budget.as_millis()
+                );
+            } else if !over_budget && window.over_budget {
+                eprintln!(
+                    "Route '{}' is back within its response time budget: p95 is {}ms.",
+                    route,
+                    p95.as_millis()
+                );
+            }
+            window.over_budget = over_budget;
+        }
+    }
+}
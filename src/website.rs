@@ -0,0 +1,1324 @@
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::prelude::*;
+use maud::{html, Markup, PreEscaped, DOCTYPE};
+use rocket::fairing::AdHoc;
+use rocket::http::uri::Uri;
+use rocket::http::{RawStr, Status};
+use rocket::request::{self, Form, FromRequest, Request};
+use rocket::response::status::Custom;
+use rocket::response::Redirect;
+use rocket::{Outcome, Route, State};
+use uuid::Uuid;
+
+use crate::features::{Calendar, Comments, Enabled, Features, Newsletter, Submissions};
+use crate::mail::{self, Mailer};
+use crate::maintenance::{Maintenance, MaintenancePage};
+use crate::markdown;
+use crate::spam::{Candidate, ClientIp, Feature, SpamFilter};
+use crate::store::{
+    is_email_address, Actions, Address, ChangeBus, Comment, Coordinates, DisplayCutoff, Event,
+    FaqEntry, Id, Location, NavItem, Occurrence, OccurrenceFilter, OccurrenceWithEvent,
+    OccurrenceWithLocation, Page, ScheduleHorizon, SeasonBoundaries, Statistics, Store, StoreError,
+    StoreResult, Submission, UrlRedirect, MAX_DURATION_MINUTES,
+};
+
+/// Where the website is served, for links that are followed from elsewhere, like mails.
+pub const SITE_URL: &str = "https://lindyhop-aachen.de";
+
+#[get("/")]
+fn index(
+    store: Store,
+    layout: Layout,
+    cutoff: State<DisplayCutoff>,
+    horizon: State<ScheduleHorizon>,
+) -> Result<Markup, StoreError> {
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+    let days = store.occurrences_by_date(&OccurrenceFilter::upcoming(&cutoff, &horizon))?;
+
+    Ok(base_html(
+        &layout,
+        html! {
+            ol.schedule {
+                @for occurrences_for_date in days {
+                    li { ( render_entry(&occurrences_for_date, &locations) ) }
+                }
+            }
+        },
+    ))
+}
+
+#[get("/archiv")]
+fn archive(
+    store: Store,
+    layout: Layout,
+    seasons: State<SeasonBoundaries>,
+) -> Result<Markup, StoreError> {
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+    let seasons = store.past_occurrences_by_season(&seasons)?;
+
+    Ok(base_html(
+        &layout,
+        html! {
+            h1 { "Archiv" }
+            @for (season, occurrences_by_date) in seasons.into_iter().rev() {
+                section.season {
+                    h2 { ( season ) }
+                    ol.schedule {
+                        @for occurrences_for_date in occurrences_by_date {
+                            li { ( render_entry(&occurrences_for_date, &locations) ) }
+                        }
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// The statistics cover the whole history, so they are only recomputed this often, unless
+/// the stored data changes.
+const STATISTICS_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// The most recently computed statistics, managed as state.
+#[derive(Default, Clone)]
+pub struct StatisticsCache(Arc<Mutex<Option<(Instant, Statistics)>>>);
+
+impl StatisticsCache {
+    /// Manages the cache and drops it whenever the store changes, so it has to be attached
+    /// after the store.
+    pub fn fairing() -> AdHoc {
+        AdHoc::on_attach("Statistics Cache", |rocket| {
+            let cache = StatisticsCache::default();
+            let subscribed = cache.clone();
+            match rocket.state::<Arc<ChangeBus>>() {
+                Some(changes) => changes.subscribe(move |_| subscribed.invalidate()),
+                None => {
+                    eprintln!("The statistics cache has to be attached after the store.");
+                    return Err(rocket);
+                }
+            }
+            Ok(rocket.manage(cache))
+        })
+    }
+
+    fn get(&self, store: &Store) -> StoreResult<Statistics> {
+        let mut cached = self.0.lock().unwrap();
+        match &*cached {
+            Some((computed_at, statistics)) if computed_at.elapsed() < STATISTICS_MAX_AGE => {
+                Ok(statistics.clone())
+            }
+            _ => {
+                let statistics = store.statistics()?;
+                *cached = Some((Instant::now(), statistics.clone()));
+                Ok(statistics)
+            }
+        }
+    }
+
+    /// Makes the next request recompute the statistics, e.g. after the database was edited
+    /// by hand.
+    pub fn invalidate(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+#[get("/statistik")]
+fn statistics(
+    store: Store,
+    layout: Layout,
+    cache: State<StatisticsCache>,
+) -> Result<Markup, StoreError> {
+    let statistics = cache.get(&store)?;
+
+    Ok(base_html(
+        &layout,
+        html! {
+            h1 { "Statistik" }
+            dl.statistics {
+                dt { "Getanzte Stunden" }
+                dd { ( format!("{:.0}", statistics.total_hours) ) }
+                @if let Some((location, occurrences)) = &statistics.most_used_location {
+                    dt { "Beliebtester Ort" }
+                    dd {
+                    ( location.name ) " (" ( occurrences )
+                    @if *occurrences == 1 { " Termin)" } @else { " Termine)" }
+                }
+                }
+            }
+            @if !statistics.events_per_year.is_empty() {
+                h2 { "Veranstaltungen pro Jahr" }
+                table.statistics {
+                    @for (year, events) in statistics.events_per_year.iter().rev() {
+                        tr {
+                            th { ( year ) }
+                            td { ( events ) }
+                        }
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// What every page shows around its content.
+pub struct Layout<'r> {
+    features: State<'r, Features>,
+    navigation: Vec<NavItem>,
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Layout<'r> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let features = request.guard::<State<Features>>()?;
+        // A page without its menu is better than no page.
+        let navigation = request
+            .guard::<Store>()
+            .succeeded()
+            .and_then(|store| store.navigation().ok())
+            .unwrap_or_default();
+
+        Outcome::Success(Layout {
+            features,
+            navigation,
+        })
+    }
+}
+
+fn base_html(layout: &Layout, content: Markup) -> Markup {
+    html! {
+        ( DOCTYPE )
+        html lang="de" {
+            head {
+                meta name="viewport" content="width=device-width, initial-scale=1";
+
+                link href="/static/main.css" rel="stylesheet";
+                link href="/manifest.webmanifest" rel="manifest";
+                script {
+                    ( PreEscaped("if ('serviceWorker' in navigator) { navigator.serviceWorker.register('/sw.js'); }") )
+                }
+            }
+            body {
+                header {
+                    a href="/" { h1 { "Lindy Hop Aachen" } }
+                    @if !layout.navigation.is_empty() {
+                        nav {
+                            ul {
+                                @for item in &layout.navigation {
+                                    li { a href=( item.url ) { ( item.label ) } }
+                                }
+                            }
+                        }
+                    }
+                }
+                main {
+                    ( content )
+                }
+                footer {
+                    a href="/archiv" { "Archiv" }
+                    " · "
+                    a href="/statistik" { "Statistik" }
+                    @if layout.features.is_enabled::<Submissions>() {
+                        " · "
+                        a href="/einreichen" { "Veranstaltung einreichen" }
+                    }
+                    @if layout.features.is_enabled::<Calendar>() {
+                        " · "
+                        a href="/kalender.ics" { "Kalender abonnieren" }
+                    }
+                    @if layout.features.is_enabled::<Newsletter>() {
+                        " · "
+                        a href="/newsletter/anmelden" { "Newsletter" }
+                    }
+                }
+                script { ( PreEscaped(EMAIL_SCRIPT) ) }
+            }
+        }
+    }
+}
+
+fn render_entry<S: BuildHasher>(
+    (date, entries): &(NaiveDate, Vec<OccurrenceWithEvent>),
+    locations: &HashMap<Id<Location>, Location, S>,
+) -> Markup {
+    html! {
+        div.date { ( format_date(date) ) }
+        ol.events {
+            @for occurrence_entry in entries {
+                li.event.cancelled[occurrence_entry.occurrence.occurrence.cancelled] {
+                    ( render_occurrence(occurrence_entry, locations) )
+                }
+            }
+        }
+    }
+}
+
+// Kept taking a reference as it always did, changing every caller is not worth it.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+pub fn format_date(date: &NaiveDate) -> String {
+    use chrono::Weekday::*;
+
+    let day = match date.weekday() {
+        Mon => "Mo",
+        Tue => "Di",
+        Wed => "Mi",
+        Thu => "Do",
+        Fri => "Fr",
+        Sat => "Sa",
+        Sun => "So",
+    };
+    let format = format!("{}, %d.%m.", day);
+
+    date.format(&format).to_string()
+}
+
+fn render_occurrence<S: BuildHasher>(
+    entry: &OccurrenceWithEvent,
+    locations: &HashMap<Id<Location>, Location, S>,
+) -> Markup {
+    html! {
+        @let entry_html =  html_from_occurrence(&entry.occurrence, &entry.event, locations);
+        div.quick-info { ( entry_html.quick_info ) }
+        @if let Some(cancellation) = entry_html.cancellation {
+            div.cancellation { ( cancellation ) }
+        }
+        @if let Some(stream) = entry_html.stream {
+            ( stream )
+        }
+        h2.title { a href=( event_url(&entry.event.slug) ) { ( entry_html.title ) } }
+        div.content {
+            div.description {
+                div.teaser { ( entry_html.teaser ) }
+            }
+            @if let Some(contact) = entry_html.contact {
+                div.contact { ( contact ) }
+            }
+        }
+        ( structured_data(entry, locations) )
+    }
+}
+
+/// Describes the occurrence as a schema.org `Event`, so search engines can list it in their event search.
+fn structured_data<S: BuildHasher>(
+    entry: &OccurrenceWithEvent,
+    locations: &HashMap<Id<Location>, Location, S>,
+) -> Markup {
+    let occurrence = &entry.occurrence.occurrence;
+    let mut data = serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "Event",
+        "name": entry.event.title,
+        "description": entry.event.teaser,
+        "startDate": format_schema_date(occurrence.start),
+        "endDate": format_schema_date(occurrence.end()),
+    });
+    if occurrence.cancelled {
+        data["eventStatus"] = serde_json::json!("https://schema.org/EventCancelled");
+    }
+    if let Some(location) = entry.occurrence.location(locations) {
+        data["location"] = serde_json::json!({
+            "@type": "Place",
+            "name": location.name,
+            "address": {
+                "@type": "PostalAddress",
+                "streetAddress": location.address.street,
+                "postalCode": location.address.postal_code,
+                "addressLocality": location.address.city,
+            },
+        });
+        if let Some(coordinates) = location.coordinates {
+            data["location"]["geo"] = serde_json::json!({
+                "@type": "GeoCoordinates",
+                "latitude": coordinates.latitude,
+                "longitude": coordinates.longitude,
+            });
+        }
+    }
+
+    json_ld(&data)
+}
+
+fn json_ld(data: &serde_json::Value) -> Markup {
+    // A `</script>` within a title must not end the script early. Outside of strings,
+    // JSON contains no `<`, so escaping it is always valid.
+    let json = data.to_string().replace('<', "\\u003c");
+    html! {
+        script type="application/ld+json" { ( PreEscaped(json) ) }
+    }
+}
+
+/// Dates are stored in local time, but search engines need to know the offset.
+fn format_schema_date(date_time: NaiveDateTime) -> String {
+    match Local.from_local_datetime(&date_time).single() {
+        Some(date_time) => date_time.to_rfc3339(),
+        None => date_time.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    }
+}
+
+struct OccurrenceHtml {
+    title: Markup,
+    quick_info: Markup,
+    teaser: Markup,
+    contact: Option<Markup>,
+    /// Only present shortly before and while the occurrence takes place.
+    stream: Option<Markup>,
+    /// Only present if the occurrence has been cancelled.
+    cancellation: Option<Markup>,
+}
+
+fn html_from_occurrence<S: BuildHasher>(
+    occurrence: &OccurrenceWithLocation,
+    event: &Event,
+    locations: &HashMap<Id<Location>, Location, S>,
+) -> OccurrenceHtml {
+    let location_html = match (&occurrence.location_id, occurrence.location(locations)) {
+        (Some(id), Some(location)) => html! { a href=( location_url(id) ) { ( location.name ) } },
+        _ => html! { "Steht noch nicht fest." },
+    };
+
+    let start = occurrence.occurrence.start.format("%H:%M");
+    let time = match (
+        occurrence.occurrence.doors_open_at(),
+        occurrence.occurrence.open_end,
+    ) {
+        (Some(doors_open), false) => {
+            format!("Einlass {}, Beginn {}", doors_open.format("%H:%M"), start)
+        }
+        (Some(doors_open), true) => format!(
+            "Einlass {}, Beginn {} (open end)",
+            doors_open.format("%H:%M"),
+            start
+        ),
+        (None, false) => start.to_string(),
+        (None, true) => format!("ab {} (open end)", start),
+    };
+
+    OccurrenceHtml {
+        title: html! { ( event.title ) },
+        quick_info: html! { ( time ) " - " ( location_html ) },
+        teaser: html! { ( event.teaser ) },
+        contact: render_contact(event),
+        stream: occurrence
+            .occurrence
+            .current_stream_url(Local::now().naive_local())
+            .map(|stream_url| {
+                html! {
+                    a.stream href=( stream_url ) { "Zum Livestream" }
+                }
+            }),
+        cancellation: render_cancellation(&occurrence.occurrence),
+    }
+}
+
+fn render_cancellation(occurrence: &Occurrence) -> Option<Markup> {
+    if !occurrence.cancelled {
+        return None;
+    }
+    Some(html! {
+        "Fällt aus"
+        @if let Some(reason) = &occurrence.cancellation_reason {
+            ": " ( reason )
+        }
+    })
+}
+
+fn render_contact(event: &Event) -> Option<Markup> {
+    match (&event.contact_name, &event.contact_email) {
+        (None, None) => None,
+        (name, email) => Some(html! {
+            "Kontakt: "
+            @if let Some(name) = name {
+                ( name )
+            }
+            @if name.is_some() && email.is_some() {
+                ", "
+            }
+            @if let Some(email) = email {
+                ( obfuscated_email(email) )
+            }
+        }),
+    }
+}
+
+/// Renders an email address that harvesting bots cannot read from the markup.
+/// The address is stored reversed and only turned into a link by `EMAIL_SCRIPT`.
+fn obfuscated_email(email: &str) -> Markup {
+    let reversed: String = email.chars().rev().collect();
+    let readable = email.replace('@', " [at] ").replace('.', " [punkt] ");
+
+    html! {
+        a.email data-reversed-email=( reversed ) { ( readable ) }
+    }
+}
+
+const EMAIL_SCRIPT: &str = "document.querySelectorAll('a[data-reversed-email]').forEach(function (link) { var email = link.getAttribute('data-reversed-email').split('').reverse().join(''); link.href = 'mailto:' + email; link.textContent = email; });";
+
+pub fn event_url(slug: &str) -> String {
+    format!("/veranstaltungen/{}", slug)
+}
+
+/// Event pages used to be addressed by id, so links shared back then lead to the slug.
+#[get("/veranstaltungen/<id>")]
+fn event_page_by_id(store: Store, id: Id<Event>) -> Result<Redirect, StoreError> {
+    let slug = match store.read_event_with_occurrences(id.clone(), &OccurrenceFilter::default()) {
+        Ok(entry) => entry.event.slug,
+        Err(StoreError::NotFound) => store.deleted_event(id)?.ok_or(StoreError::NotFound)?.slug,
+        Err(err) => return Err(err),
+    };
+    Ok(Redirect::permanent(event_url(&slug)))
+}
+
+/// Deleted events answer with 410 Gone instead of a generic 404, pointing visitors
+/// who followed an old link to the current schedule.
+#[get("/veranstaltungen/<slug>", rank = 2)]
+fn event_page(
+    store: Store,
+    layout: Layout,
+    cutoff: State<DisplayCutoff>,
+    horizon: State<ScheduleHorizon>,
+    slug: String,
+) -> Result<Custom<Markup>, StoreError> {
+    let id = store.event_id_by_slug(&slug)?.ok_or(StoreError::NotFound)?;
+    let upcoming = OccurrenceFilter::upcoming(&cutoff, &horizon);
+    match render_event_page(&store, &layout, &upcoming, id.clone(), None) {
+        Err(StoreError::NotFound) => {}
+        page => return page.map(|page| Custom(Status::Ok, page)),
+    }
+
+    let deleted = store.deleted_event(id)?.ok_or(StoreError::NotFound)?;
+    Ok(Custom(
+        Status::Gone,
+        base_html(
+            &layout,
+            html! {
+                h1 { ( deleted.title ) }
+                p { "Diese Veranstaltung findet nicht mehr statt." }
+                p { a href="/" { "Zu den aktuellen Veranstaltungen" } }
+            },
+        ),
+    ))
+}
+
+fn render_event_page(
+    store: &Store,
+    layout: &Layout,
+    upcoming: &OccurrenceFilter,
+    id: Id<Event>,
+    notice: Option<&str>,
+) -> StoreResult<Markup> {
+    let entry = store.read_event_with_occurrences(id.clone(), upcoming)?;
+    let comments = store.approved_comments(id.clone())?;
+    let related = store.related_events(id.clone(), upcoming)?;
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+
+    Ok(base_html(
+        layout,
+        html! {
+            article.event-page {
+                h1 { ( entry.event.title ) }
+                p.teaser { ( entry.event.teaser ) }
+                div.description { ( entry.event.description ) }
+                @if let Some(contact) = render_contact(&entry.event) {
+                    div.contact { ( contact ) }
+                }
+                @if !entry.occurrences.is_empty() {
+                    h2 { "Termine" }
+                    ul.occurrences {
+                        @for occurrence in &entry.occurrences {
+                            @let occurrence_html = html_from_occurrence(occurrence, &entry.event, &locations);
+                            li.cancelled[occurrence.occurrence.cancelled] {
+                                span.quick-info {
+                                    ( format_date(&occurrence.occurrence.start.date()) ) ", "
+                                    ( occurrence_html.quick_info )
+                                }
+                                @if let Some(cancellation) = occurrence_html.cancellation {
+                                    " " span.cancellation { ( cancellation ) }
+                                }
+                                @if let Some(stream) = occurrence_html.stream {
+                                    " " ( stream )
+                                }
+                            }
+                        }
+                    }
+                    @if layout.features.is_enabled::<Calendar>() {
+                        a href={ "/api/events/" ( id ) "/calendar.ics" } { "Termine abonnieren" }
+                    }
+                }
+                @if !related.is_empty() {
+                    section.related {
+                        h2 { "Ähnliche Veranstaltungen" }
+                        ul {
+                            @for entry in &related {
+                                li { a href=( event_url(&entry.event.slug) ) { ( entry.event.title ) } }
+                            }
+                        }
+                    }
+                }
+                @if layout.features.is_enabled::<Comments>() {
+                    section.comments {
+                        h2 { "Fragen und Kommentare" }
+                        @for comment in &comments {
+                            div.comment {
+                                div.comment-author { ( comment.name ) }
+                                p { ( comment.text ) }
+                            }
+                        }
+                        @if let Some(notice) = notice {
+                            p.notice { ( notice ) }
+                        }
+                        @if !store.is_read_only() {
+                            ( render_comment_form(&id) )
+                        }
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// A single date of an event, so that announcements can link to it.
+#[get("/termine/<id>")]
+fn occurrence_page(store: Store, layout: Layout, id: Id<Occurrence>) -> Result<Markup, StoreError> {
+    let entry = store.occurrence_with_event(id)?;
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+    let occurrence_html = html_from_occurrence(&entry.occurrence, &entry.event, &locations);
+    let location = entry.occurrence.location(&locations);
+
+    Ok(base_html(
+        &layout,
+        html! {
+            article.occurrence-page.cancelled[entry.occurrence.occurrence.cancelled] {
+                h1 { ( entry.event.title ) }
+                p.quick-info {
+                    ( format_date(&entry.occurrence.occurrence.start.date()) ) ", "
+                    ( occurrence_html.quick_info )
+                }
+                @if let Some(cancellation) = occurrence_html.cancellation {
+                    p.cancellation { ( cancellation ) }
+                }
+                @if let Some(stream) = occurrence_html.stream {
+                    ( stream )
+                }
+                @if let (Some(location_id), Some(location)) = (&entry.occurrence.location_id, location) {
+                    ( render_location(location_id, location) )
+                }
+                div.description { ( entry.event.description ) }
+                @if let Some(contact) = occurrence_html.contact {
+                    div.contact { ( contact ) }
+                }
+                p { a href=( event_url(&entry.event.slug) ) { "Alle Termine dieser Veranstaltung" } }
+            }
+        },
+    ))
+}
+
+fn render_location(id: &Id<Location>, location: &Location) -> Markup {
+    let address = &location.address;
+    html! {
+        p.location {
+            a href=( location_url(id) ) { ( location.name ) } br;
+            ( address.street ) br;
+            ( address.postal_code ) " " ( address.city ) br;
+            a href=( map_url(address) ) { "Auf der Karte zeigen" }
+        }
+    }
+}
+
+pub fn location_url(id: &Id<Location>) -> String {
+    format!("/orte/{}", id)
+}
+
+/// Shows where the location is and what takes place there next.
+#[get("/orte/<id>")]
+fn location_page(
+    store: Store,
+    layout: Layout,
+    cutoff: State<DisplayCutoff>,
+    horizon: State<ScheduleHorizon>,
+    id: Id<Location>,
+) -> Result<Markup, StoreError> {
+    let location: Location = store.read(id.clone())?;
+    let locations: HashMap<Id<Location>, Location> = store.all()?;
+    let upcoming: Vec<(NaiveDate, Vec<OccurrenceWithEvent>)> = store
+        .occurrences_by_date(&OccurrenceFilter::upcoming(&cutoff, &horizon))?
+        .into_iter()
+        .map(|(date, entries)| {
+            let here = entries
+                .into_iter()
+                .filter(|entry| entry.occurrence.location_id.as_ref() == Some(&id))
+                .collect();
+            (date, here)
+        })
+        .filter(|(_, entries): &(NaiveDate, Vec<OccurrenceWithEvent>)| !entries.is_empty())
+        .collect();
+    let address = &location.address;
+
+    Ok(base_html(
+        &layout,
+        html! {
+            article.location-page {
+                h1 { ( location.name ) }
+                p.address {
+                    ( address.street ) br;
+                    ( address.postal_code ) " " ( address.city ) br;
+                    a href=( map_url(address) ) { "Auf der Karte zeigen" }
+                }
+                @if let Some(coordinates) = location.coordinates {
+                    iframe.map src=( map_embed_url(coordinates) ) title=( format!("Karte: {}", location.name) ) {}
+                }
+                h2 { "Nächste Termine" }
+                @if upcoming.is_empty() {
+                    p { "Hier ist gerade nichts geplant." }
+                } @else {
+                    ol.schedule {
+                        @for occurrences_for_date in &upcoming {
+                            li { ( render_entry(occurrences_for_date, &locations) ) }
+                        }
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// An OpenStreetMap section of a few streets around the coordinates, with a marker on them.
+fn map_embed_url(coordinates: Coordinates) -> String {
+    const MARGIN_DEGREES: f64 = 0.003;
+    format!(
+        "https://www.openstreetmap.org/export/embed.html?bbox={},{},{},{}&layer=mapnik&marker={},{}",
+        coordinates.longitude - MARGIN_DEGREES,
+        coordinates.latitude - MARGIN_DEGREES,
+        coordinates.longitude + MARGIN_DEGREES,
+        coordinates.latitude + MARGIN_DEGREES,
+        coordinates.latitude,
+        coordinates.longitude
+    )
+}
+
+fn map_url(address: &Address) -> String {
+    format!(
+        "https://www.openstreetmap.org/search?query={}",
+        Uri::percent_encode(&address.to_string())
+    )
+}
+
+fn render_comment_form(id: &Id<Event>) -> Markup {
+    html! {
+        form.comment-form method="post" action=( format!("/veranstaltungen/{}/kommentare", id) ) {
+            label { "Name" input type="text" name="name" required?; }
+            label { "Kommentar" textarea name="text" required? maxlength=( MAX_COMMENT_LENGTH ) {} }
+            // Hidden from humans, so anything entered here comes from a bot.
+            label.honeypot aria-hidden="true" { "Homepage" input type="text" name="homepage" tabindex="-1" autocomplete="off"; }
+            button type="submit" { "Absenden" }
+        }
+    }
+}
+
+const MAX_COMMENT_LENGTH: usize = 1000;
+
+#[derive(FromForm)]
+struct CommentForm {
+    name: String,
+    text: String,
+    homepage: String,
+}
+
+#[post("/veranstaltungen/<id>/kommentare", data = "<form>")]
+// Each argument is a request guard, bundling them would only obscure that.
+#[allow(clippy::too_many_arguments)]
+fn submit_comment(
+    _enabled: Enabled<Comments>,
+    store: Store,
+    layout: Layout,
+    cutoff: State<DisplayCutoff>,
+    horizon: State<ScheduleHorizon>,
+    spam: State<SpamFilter>,
+    client: ClientIp,
+    id: Id<Event>,
+    form: Form<CommentForm>,
+) -> Result<Markup, StoreError> {
+    let name = form.name.trim();
+    let text = form.text.trim();
+    let notice = if name.is_empty() || text.is_empty() {
+        "Bitte gib deinen Namen und einen Kommentar an."
+    } else if text.chars().count() > MAX_COMMENT_LENGTH {
+        "Dein Kommentar ist leider zu lang."
+    } else if spam.is_spam(
+        Feature::Comments,
+        &Candidate {
+            honeypot: Some(&form.homepage),
+            texts: &[name, text],
+            client,
+        },
+    ) {
+        // Bots should not learn that they have been caught.
+        "Danke! Dein Kommentar wird angezeigt, sobald wir ihn freigegeben haben."
+    } else {
+        let comment = Comment {
+            name: name.to_string(),
+            text: text.to_string(),
+            created_at: Local::now().naive_local(),
+            approved: false,
+        };
+        store.create_comment(id.clone(), comment)?;
+        "Danke! Dein Kommentar wird angezeigt, sobald wir ihn freigegeben haben."
+    };
+
+    let upcoming = OccurrenceFilter::upcoming(&cutoff, &horizon);
+    render_event_page(&store, &layout, &upcoming, id, Some(notice))
+}
+
+#[derive(FromForm)]
+struct SubmissionForm {
+    title: String,
+    teaser: String,
+    description: String,
+    organizer_name: String,
+    organizer_email: String,
+    homepage: String,
+    /// As sent by a `datetime-local` input, e.g. `2019-06-12T20:00`.
+    start: String,
+    duration: i64,
+    location_id: String,
+}
+
+impl SubmissionForm {
+    fn to_submission(&self, store: &Store) -> Result<Submission, &'static str> {
+        let required = [
+            &self.title,
+            &self.teaser,
+            &self.organizer_name,
+            &self.organizer_email,
+        ];
+        if required.iter().any(|field| field.trim().is_empty()) {
+            return Err("Bitte fülle alle Pflichtfelder aus.");
+        }
+        let start = NaiveDateTime::parse_from_str(&self.start, "%Y-%m-%dT%H:%M")
+            .map_err(|_| "Bitte gib einen gültigen Beginn an.")?;
+        if self.duration <= 0 || self.duration > MAX_DURATION_MINUTES {
+            return Err("Bitte gib eine gültige Dauer an.");
+        }
+        let location_id: Id<Location> = Uuid::parse_str(&self.location_id)
+            .map(Id::from)
+            .map_err(|_| "Bitte wähle einen Ort aus.")?;
+        let location: StoreResult<Location> = store.read(location_id.clone());
+        if location.is_err() {
+            return Err("Bitte wähle einen Ort aus.");
+        }
+
+        Ok(Submission {
+            title: self.title.trim().to_string(),
+            teaser: self.teaser.trim().to_string(),
+            description: self.description.trim().to_string(),
+            organizer_name: self.organizer_name.trim().to_string(),
+            organizer_email: self.organizer_email.trim().to_string(),
+            start,
+            duration: chrono::Duration::minutes(self.duration),
+            location_id,
+        })
+    }
+}
+
+#[get("/einreichen")]
+fn submission_form(
+    _enabled: Enabled<Submissions>,
+    store: Store,
+    layout: Layout,
+) -> Result<Markup, StoreError> {
+    Ok(base_html(
+        &layout,
+        render_submission_form(&store, None, None)?,
+    ))
+}
+
+#[post("/einreichen", data = "<form>")]
+fn submit(
+    _enabled: Enabled<Submissions>,
+    store: Store,
+    layout: Layout,
+    spam: State<SpamFilter>,
+    client: ClientIp,
+    form: Form<SubmissionForm>,
+) -> Result<Markup, StoreError> {
+    let candidate = Candidate {
+        honeypot: Some(&form.homepage),
+        texts: &[&form.title, &form.teaser, &form.description],
+        client,
+    };
+    if spam.is_spam(Feature::Submissions, &candidate) {
+        // Bots should not learn that they have been caught.
+        return Ok(submission_thanks(&layout));
+    }
+
+    let result = form.to_submission(&store).and_then(|submission| {
+        store
+            .create_submission(submission)
+            .map_err(|err| match err {
+                StoreError::Invalid(_) => "Bitte überprüfe deine Angaben.",
+                _ => "Die Veranstaltung konnte nicht gespeichert werden.",
+            })
+    });
+
+    match result {
+        Ok(_) => Ok(submission_thanks(&layout)),
+        Err(error) => Ok(base_html(
+            &layout,
+            render_submission_form(&store, Some(&form), Some(error))?,
+        )),
+    }
+}
+
+fn submission_thanks(layout: &Layout) -> Markup {
+    base_html(
+        layout,
+        html! {
+            h1 { "Danke!" }
+            p { "Wir schauen uns die Veranstaltung an und melden uns bei dir." }
+        },
+    )
+}
+
+fn render_submission_form(
+    store: &Store,
+    values: Option<&SubmissionForm>,
+    error: Option<&str>,
+) -> StoreResult<Markup> {
+    let mut locations: Vec<(Id<Location>, Location)> = store.all()?.into_iter().collect();
+    locations.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+    let value = |field: fn(&SubmissionForm) -> String| values.map(field).unwrap_or_default();
+    let selected_location = value(|form| form.location_id.clone());
+
+    Ok(html! {
+        h1 { "Veranstaltung einreichen" }
+        p { "Du organisierst eine Veranstaltung? Schlag sie uns vor, wir nehmen sie nach einer kurzen Prüfung in den Kalender auf." }
+        @if let Some(error) = error {
+            p.error { ( error ) }
+        }
+        form.submission method="post" action="/einreichen" {
+            label { "Titel" input type="text" name="title" required? value=( value(|form| form.title.clone()) ); }
+            label { "Kurzbeschreibung" input type="text" name="teaser" required? value=( value(|form| form.teaser.clone()) ); }
+            label { "Beschreibung" textarea name="description" { ( value(|form| form.description.clone()) ) } }
+            label { "Beginn" input type="datetime-local" name="start" required? value=( value(|form| form.start.clone()) ); }
+            label { "Dauer in Minuten" input type="number" name="duration" min="1" max=( MAX_DURATION_MINUTES ) required? value=( value(|form| form.duration.to_string()) ); }
+            label {
+                "Ort"
+                select name="location_id" required? {
+                    @for (id, location) in &locations {
+                        @let id = id.to_string();
+                        option value=( id ) selected?[id == selected_location] { ( location.name ) }
+                    }
+                }
+            }
+            label { "Dein Name" input type="text" name="organizer_name" required? value=( value(|form| form.organizer_name.clone()) ); }
+            label { "Deine E-Mail-Adresse" input type="email" name="organizer_email" required? value=( value(|form| form.organizer_email.clone()) ); }
+            label.honeypot aria-hidden="true" { "Homepage" input type="text" name="homepage" tabindex="-1" autocomplete="off"; }
+            button type="submit" { "Einreichen" }
+        }
+    })
+}
+
+#[derive(FromForm)]
+struct SubscriptionForm {
+    email: String,
+    name: String,
+    homepage: String,
+}
+
+#[get("/newsletter/anmelden")]
+fn subscription_form(_enabled: Enabled<Newsletter>, layout: Layout) -> Markup {
+    base_html(&layout, render_subscription_form(None, None))
+}
+
+/// Sends a mail with a link to confirm the address, so nobody can subscribe others.
+#[post("/newsletter/anmelden", data = "<form>")]
+fn subscribe(
+    _enabled: Enabled<Newsletter>,
+    store: Store,
+    layout: Layout,
+    spam: State<SpamFilter>,
+    mailer: State<Mailer>,
+    client: ClientIp,
+    form: Form<SubscriptionForm>,
+) -> Markup {
+    let email = form.email.trim();
+    let name = form.name.trim();
+    let candidate = Candidate {
+        honeypot: Some(&form.homepage),
+        texts: &[email, name],
+        client,
+    };
+    if spam.is_spam(Feature::Newsletter, &candidate) {
+        // Bots should not learn that they have been caught.
+        return subscription_thanks(&layout);
+    }
+
+    let result = if !is_email_address(email) {
+        Err("Bitte gib eine gültige E-Mail-Adresse an.")
+    } else {
+        let name = Some(name.to_string()).filter(|name| !name.is_empty());
+        match store.subscribe(email, name) {
+            Ok(Some(token)) => mailer
+                .send(
+                    email,
+                    "Bitte bestätige deine Anmeldung",
+                    &mail::render_confirmation(&token),
+                )
+                .map_err(|_| {
+                    "Die E-Mail zur Bestätigung konnte nicht verschickt werden. Bitte versuche es später noch einmal."
+                }),
+            // Whether an address is subscribed is nobody else's business.
+            Ok(None) => Ok(()),
+            Err(_) => Err("Die Anmeldung konnte nicht gespeichert werden."),
+        }
+    };
+
+    match result {
+        Ok(()) => subscription_thanks(&layout),
+        Err(error) => base_html(&layout, render_subscription_form(Some(&form), Some(error))),
+    }
+}
+
+#[get("/newsletter/bestaetigen/<token>")]
+fn confirm_subscription(
+    _enabled: Enabled<Newsletter>,
+    store: Store,
+    layout: Layout,
+    token: String,
+) -> Result<Custom<Markup>, StoreError> {
+    let (status, content) = if store.confirm_subscription(&token)? {
+        (
+            Status::Ok,
+            html! {
+                h1 { "Danke!" }
+                p { "Deine Anmeldung ist bestätigt. Ab jetzt bekommst du unseren Newsletter." }
+            },
+        )
+    } else {
+        (
+            Status::NotFound,
+            html! {
+                h1 { "Link ungültig" }
+                p {
+                    "Dieser Link ist ungültig oder wurde schon verwendet. "
+                    a href="/newsletter/anmelden" { "Melde dich einfach noch einmal an." }
+                }
+            },
+        )
+    };
+    Ok(Custom(status, base_html(&layout, content)))
+}
+
+/// Linked from every newsletter. Unlike signing up, this works even if the feature is
+/// disabled, so nobody keeps receiving mail they do not want.
+#[get("/newsletter/abmelden/<token>")]
+fn unsubscribe(store: Store, layout: Layout, token: String) -> Result<Custom<Markup>, StoreError> {
+    let (status, content) = if store.unsubscribe(&token)? {
+        (
+            Status::Ok,
+            html! {
+                h1 { "Abgemeldet" }
+                p { "Du bekommst ab jetzt keinen Newsletter mehr von uns." }
+            },
+        )
+    } else {
+        (
+            Status::NotFound,
+            html! {
+                h1 { "Link ungültig" }
+                p { "Dieser Link ist ungültig, oder du bist schon abgemeldet." }
+            },
+        )
+    };
+    Ok(Custom(status, base_html(&layout, content)))
+}
+
+/// Mail clients unsubscribe with a POST to the link, see RFC 8058.
+#[post("/newsletter/abmelden/<token>")]
+fn unsubscribe_with_one_click(
+    store: Store,
+    layout: Layout,
+    token: String,
+) -> Result<Custom<Markup>, StoreError> {
+    unsubscribe(store, layout, token)
+}
+
+/// The newsletters sent so far, for those who are not subscribed.
+#[get("/newsletter/archiv")]
+fn newsletter_archive(
+    _enabled: Enabled<Newsletter>,
+    store: Store,
+    layout: Layout,
+) -> Result<Markup, StoreError> {
+    let newsletters = store.past_newsletters(Local::today().naive_local())?;
+
+    Ok(base_html(
+        &layout,
+        html! {
+            h1 { "Newsletter-Archiv" }
+            p {
+                "Alle bisherigen Ausgaben unseres Newsletters. "
+                a href="/newsletter/anmelden" { "Abonniere ihn" }
+                ", um keine mehr zu verpassen."
+            }
+            ol.newsletters {
+                @for (id, newsletter) in &newsletters {
+                    li {
+                        a href={ "/newsletter/archiv/" ( id ) } { ( newsletter.title ) }
+                        " vom " ( newsletter.date.format("%d.%m.%Y").to_string() )
+                    }
+                }
+            }
+        },
+    ))
+}
+
+#[get("/newsletter/archiv/<id>")]
+fn newsletter_issue(
+    _enabled: Enabled<Newsletter>,
+    store: Store,
+    layout: Layout,
+    id: Id<crate::store::Newsletter>,
+) -> Result<Markup, StoreError> {
+    let newsletter: crate::store::Newsletter = store.read(id)?;
+    // Issues that are still being drafted are not public yet.
+    if newsletter.date > Local::today().naive_local() {
+        return Err(StoreError::NotFound);
+    }
+
+    Ok(base_html(
+        &layout,
+        html! {
+            article.newsletter {
+                ( mail::render_newsletter_content(&newsletter) )
+                p.date { "Verschickt am " ( newsletter.date.format("%d.%m.%Y").to_string() ) }
+            }
+            p { a href="/newsletter/archiv" { "Alle Ausgaben" } }
+        },
+    ))
+}
+
+fn subscription_thanks(layout: &Layout) -> Markup {
+    base_html(
+        layout,
+        html! {
+            h1 { "Fast geschafft!" }
+            p { "Wir haben dir eine E-Mail geschickt. Bitte bestätige deine Anmeldung mit dem Link darin." }
+        },
+    )
+}
+
+fn render_subscription_form(values: Option<&SubscriptionForm>, error: Option<&str>) -> Markup {
+    let email = values.map(|form| form.email.as_str()).unwrap_or_default();
+    let name = values.map(|form| form.name.as_str()).unwrap_or_default();
+
+    html! {
+        h1 { "Newsletter" }
+        p {
+            "Einmal im Monat erfährst du, was in der Aachener Lindy-Hop-Szene los ist. "
+            a href="/newsletter/archiv" { "Hier kannst du frühere Ausgaben lesen." }
+        }
+        @if let Some(error) = error {
+            p.error { ( error ) }
+        }
+        form.subscription method="post" action="/newsletter/anmelden" {
+            label { "Deine E-Mail-Adresse" input type="email" name="email" required? value=( email ); }
+            label { "Dein Name (optional)" input type="text" name="name" value=( name ); }
+            label.honeypot aria-hidden="true" { "Homepage" input type="text" name="homepage" tabindex="-1" autocomplete="off"; }
+            button type="submit" { "Anmelden" }
+        }
+    }
+}
+
+/// The first path segments that other routes are served under, which pages cannot use as
+/// their slug.
+pub const RESERVED_SLUGS: &[&str] = &[
+    "admin",
+    "api",
+    "archiv",
+    "einreichen",
+    "faq",
+    "media",
+    "newsletter",
+    "orte",
+    "static",
+    "statistik",
+    "termine",
+    "veranstaltungen",
+    "wartung",
+];
+
+/// Each answer is folded away under its question, so that newcomers find theirs at a glance.
+#[get("/faq")]
+fn faq(store: Store, layout: Layout) -> Result<Markup, StoreError> {
+    let categories = store.faq()?;
+
+    Ok(base_html(
+        &layout,
+        html! {
+            h1 { "Häufige Fragen" }
+            @if categories.is_empty() {
+                p { "Noch gibt es hier keine Fragen." }
+            }
+            @for (category, entries) in &categories {
+                section.faq {
+                    h2 { ( category ) }
+                    @for entry in entries {
+                        details {
+                            summary { ( entry.question ) }
+                            ( markdown::render(&entry.answer) )
+                        }
+                    }
+                }
+            }
+            ( faq_structured_data(&categories) )
+        },
+    ))
+}
+
+/// Describes the questions as a schema.org `FAQPage`, so that search engines can show the
+/// answers right away.
+fn faq_structured_data(categories: &[(String, Vec<FaqEntry>)]) -> Markup {
+    let questions: Vec<serde_json::Value> = categories
+        .iter()
+        .flat_map(|(_, entries)| entries)
+        .map(|entry| {
+            serde_json::json!({
+                "@type": "Question",
+                "name": entry.question,
+                "acceptedAnswer": {
+                    "@type": "Answer",
+                    "text": markdown::render(&entry.answer).into_string(),
+                },
+            })
+        })
+        .collect();
+
+    json_ld(&serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "FAQPage",
+        "mainEntity": questions,
+    }))
+}
+
+/// Informational pages like "Über uns" are served after all other routes.
+#[get("/<slug>", rank = 3)]
+fn page(store: Store, layout: Layout, slug: String) -> Result<Markup, StoreError> {
+    let (_, page): (Id<Page>, Page) = store.page_by_slug(&slug)?.ok_or(StoreError::NotFound)?;
+    if !page.published {
+        return Err(StoreError::NotFound);
+    }
+
+    Ok(base_html(
+        &layout,
+        html! {
+            article.page {
+                h1 { ( page.title ) }
+                ( markdown::render(&page.body) )
+            }
+        },
+    ))
+}
+
+/// Every public request is sent here while the site is in maintenance, see `Maintenance`.
+#[get("/wartung")]
+fn maintenance_page(maintenance: State<Maintenance>, layout: Layout) -> Option<MaintenancePage> {
+    if !maintenance.is_enabled() {
+        return None;
+    }
+
+    Some(MaintenancePage(base_html(
+        &layout,
+        html! {
+            article.maintenance {
+                h1 { "Wir räumen auf" }
+                p { "Die Seite wird gerade gewartet und ist bald wieder für euch da. Bis gleich!" }
+            }
+        },
+    )))
+}
+
+/// The redirect configured for the requested path. Forwards if there is none.
+struct LegacyUrl(UrlRedirect);
+
+impl<'a, 'r> FromRequest<'a, 'r> for LegacyUrl {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let store = match request.guard::<Store>().succeeded() {
+            Some(store) => store,
+            None => return Outcome::Forward(()),
+        };
+        let path = RawStr::from_str(request.uri().path()).percent_decode_lossy();
+
+        match store.redirect_for(&path) {
+            Ok(Some((_, redirect))) => Outcome::Success(LegacyUrl(redirect)),
+            Ok(None) => Outcome::Forward(()),
+            Err(err) => {
+                eprintln!("Failed to look up a redirect for '{}': {}", path, err);
+                Outcome::Failure((err.status(), ()))
+            }
+        }
+    }
+}
+
+/// Keeps links to the old site working. Ranked before the routes that look up slugs, since
+/// those answer unknown slugs with 404 instead of forwarding.
+#[get("/<path..>", rank = 1)]
+#[allow(unused_variables)]
+fn legacy_redirect(path: PathBuf, legacy: LegacyUrl) -> Redirect {
+    let LegacyUrl(redirect) = legacy;
+    match redirect.status {
+        302 => Redirect::found(redirect.target),
+        307 => Redirect::temporary(redirect.target),
+        308 => Redirect::permanent(redirect.target),
+        _ => Redirect::moved(redirect.target),
+    }
+}
+
+pub fn routes(read_only: bool) -> Vec<Route> {
+    // Submissions and subscriptions need a writable database, and newsletters are not
+    // part of the snapshot.
+    if read_only {
+        routes![
+            index,
+            archive,
+            statistics,
+            event_page_by_id,
+            event_page,
+            occurrence_page,
+            location_page,
+            faq,
+            maintenance_page,
+            legacy_redirect,
+            page
+        ]
+    } else {
+        routes![
+            index,
+            archive,
+            statistics,
+            event_page_by_id,
+            event_page,
+            occurrence_page,
+            submit_comment,
+            submission_form,
+            submit,
+            subscription_form,
+            subscribe,
+            confirm_subscription,
+            unsubscribe,
+            unsubscribe_with_one_click,
+            newsletter_archive,
+            newsletter_issue,
+            location_page,
+            faq,
+            maintenance_page,
+            legacy_redirect,
+            page
+        ]
+    }
+}
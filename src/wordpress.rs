@@ -0,0 +1,599 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+
+use chrono::NaiveDateTime;
+use rocket::Rocket;
+
+use crate::store::{
+    Actions, Address, Event, EventWithOccurrences, Id, Location, Occurrence, OccurrenceFilter,
+    OccurrenceWithLocation, Store, StoreResult, MAX_DURATION_MINUTES,
+};
+
+const USAGE: &str = "Usage: lindyhop-aachen import-wordpress <export.xml> [--commit]";
+
+/// Used when an event has no end, which the old site allowed.
+const DEFAULT_DURATION_MINUTES: i64 = 180;
+
+/// Imports the events and venues of the old WordPress site from its export, as written by
+/// "Tools > Export" with The Events Calendar plugin. By default, it only reports what would
+/// be imported and what is ambiguous, so that the export can be fixed first. With
+/// `--commit`, everything is imported at once, or nothing if saving fails.
+pub fn import_command(rocket: &Rocket, args: &[String]) -> Result<(), String> {
+    let (path, commit) = match args {
+        [path] => (path, false),
+        [path, flag] if flag == "--commit" => (path, true),
+        _ => return Err(USAGE.to_string()),
+    };
+    let store = Store::detached(rocket)
+        .ok_or_else(|| "The database is not available in read-only mode.".to_string())?;
+    let xml =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+
+    let items = parse_items(&xml)?;
+    let existing_titles = store
+        .all_events_with_occurrences(&OccurrenceFilter {
+            include_drafts: true,
+            ..OccurrenceFilter::default()
+        })
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .map(|(_, entry)| entry.event.title)
+        .collect();
+    let locations = store.all().map_err(|err| err.to_string())?;
+    let plan = plan(&items, &locations, &existing_titles);
+    print!("{}", plan.report());
+
+    if commit {
+        plan.import(&store)
+            .map_err(|err| format!("Nothing was imported, since saving failed: {}", err))?;
+        println!("Imported everything listed above.");
+    } else {
+        println!("This was a dry run. Run again with --commit to import.");
+    }
+    Ok(())
+}
+
+/// A post of the export, e. g. an event or a venue.
+#[derive(Debug, PartialEq)]
+struct Item {
+    post_id: String,
+    post_type: String,
+    status: String,
+    title: String,
+    content: String,
+    excerpt: String,
+    meta: HashMap<String, String>,
+}
+
+impl Item {
+    fn meta(&self, key: &str) -> &str {
+        self.meta.get(key).map_or("", |value| value.trim())
+    }
+}
+
+fn parse_items(xml: &str) -> Result<Vec<Item>, String> {
+    let items: Vec<Item> = elements(xml, "item")
+        .into_iter()
+        .map(|item| {
+            let field = |tag: &str| element(item, tag).map(text).unwrap_or_default();
+            Item {
+                post_id: field("wp:post_id"),
+                post_type: field("wp:post_type"),
+                status: field("wp:status"),
+                title: field("title"),
+                content: field("content:encoded"),
+                excerpt: field("excerpt:encoded"),
+                meta: elements(item, "wp:postmeta")
+                    .into_iter()
+                    .map(|meta| {
+                        let part = |tag: &str| element(meta, tag).map(text).unwrap_or_default();
+                        (part("wp:meta_key"), part("wp:meta_value"))
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    if items.is_empty() && !xml.contains("<rss") {
+        return Err("The file is not a WordPress export.".to_string());
+    }
+    Ok(items)
+}
+
+/// What the import would save.
+#[derive(Debug, Default)]
+struct Plan {
+    /// By the id of the venue's post.
+    locations: BTreeMap<String, PlannedLocation>,
+    /// By title, since the old site had a post for every date of a series.
+    events: BTreeMap<String, PlannedEvent>,
+    ambiguities: Vec<String>,
+}
+
+#[derive(Debug)]
+struct PlannedLocation {
+    location: Location,
+    /// A location with the same name, which is used instead of creating another.
+    existing: Option<Id<Location>>,
+}
+
+#[derive(Debug)]
+struct PlannedEvent {
+    event: Event,
+    /// With the id of the venue's post, if there is one.
+    occurrences: Vec<(Occurrence, Option<String>)>,
+}
+
+impl PlannedEvent {
+    fn with_locations(&self, location_ids: &HashMap<&str, Id<Location>>) -> EventWithOccurrences {
+        let occurrences = self
+            .occurrences
+            .iter()
+            .map(|(occurrence, venue_id)| OccurrenceWithLocation {
+                occurrence: occurrence.clone(),
+                location_id: venue_id
+                    .as_ref()
+                    .and_then(|venue_id| location_ids.get(venue_id.as_str()))
+                    .cloned(),
+            })
+            .collect();
+
+        EventWithOccurrences {
+            event: self.event.clone(),
+            occurrences,
+        }
+    }
+}
+
+fn plan(
+    items: &[Item],
+    existing_locations: &HashMap<Id<Location>, Location>,
+    existing_titles: &HashSet<String>,
+) -> Plan {
+    let mut plan = Plan::default();
+
+    for venue in items.iter().filter(|item| item.post_type == "tribe_venue") {
+        let name = venue.title.trim().to_string();
+        let location = Location {
+            name: name.clone(),
+            address: Address {
+                street: venue.meta("_VenueAddress").to_string(),
+                postal_code: venue.meta("_VenueZip").to_string(),
+                city: venue.meta("_VenueCity").to_string(),
+            },
+            coordinates: None,
+            manual_coordinates: false,
+        };
+        if let Err(err) = location.validate() {
+            plan.ambiguities.push(format!(
+                "Venue '{}' (#{}) is skipped and its events get no location: {}",
+                name,
+                venue.post_id,
+                err.to_string().replace('\n', " ")
+            ));
+            continue;
+        }
+        let existing = existing_locations
+            .iter()
+            .find(|(_, existing)| existing.name.to_lowercase() == name.to_lowercase())
+            .map(|(id, _)| id.clone());
+        plan.locations.insert(
+            venue.post_id.clone(),
+            PlannedLocation { location, existing },
+        );
+    }
+
+    for post in items.iter().filter(|item| item.post_type == "tribe_events") {
+        let title = post.title.trim().to_string();
+        let described = format!("Event '{}' (#{})", title, post.post_id);
+        if post.status == "trash" {
+            continue;
+        }
+        if existing_titles.contains(&title) {
+            plan.ambiguities.push(format!(
+                "{} is skipped, since an event with this title exists already.",
+                described
+            ));
+            continue;
+        }
+
+        let start = match parse_date_time(post.meta("_EventStartDate")) {
+            Some(start) => start,
+            None => {
+                plan.ambiguities
+                    .push(format!("{} is skipped, since it has no start.", described));
+                continue;
+            }
+        };
+        let duration = match parse_date_time(post.meta("_EventEndDate")) {
+            Some(end) if end > start => end - start,
+            _ => {
+                plan.ambiguities.push(format!(
+                    "{} has no end after its start, so it is assumed to last {} minutes.",
+                    described, DEFAULT_DURATION_MINUTES
+                ));
+                chrono::Duration::minutes(DEFAULT_DURATION_MINUTES)
+            }
+        };
+        if duration.num_minutes() > MAX_DURATION_MINUTES {
+            plan.ambiguities.push(format!(
+                "{} is skipped, since it lasts longer than a day. Import it by hand.",
+                described
+            ));
+            continue;
+        }
+
+        let venue_id = match post.meta("_EventVenueID") {
+            "" | "0" => None,
+            venue_id if plan.locations.contains_key(venue_id) => Some(venue_id.to_string()),
+            venue_id => {
+                plan.ambiguities.push(format!(
+                    "{} takes place at the unknown venue #{}, so it gets no location.",
+                    described, venue_id
+                ));
+                None
+            }
+        };
+        let occurrence = Occurrence {
+            start,
+            duration,
+            doors_open: None,
+            open_end: false,
+            stream_url: None,
+            cancelled: false,
+            cancellation_reason: None,
+        };
+
+        let description = html_to_text(&post.content);
+        match plan.events.get_mut(&title) {
+            Some(planned) => {
+                if planned.event.description != description {
+                    plan.ambiguities.push(format!(
+                        "{} is described differently than the other dates of '{}'. The first \
+                         description is kept.",
+                        described, title
+                    ));
+                }
+                planned.occurrences.push((occurrence, venue_id));
+            }
+            None => {
+                let event = Event {
+                    title: title.clone(),
+                    teaser: html_to_text(&post.excerpt),
+                    description,
+                    contact_name: None,
+                    contact_email: None,
+                    locked: false,
+                    slug: String::new(),
+                    published: post.status == "publish",
+                    publish_at: None,
+                };
+                plan.events.insert(
+                    title,
+                    PlannedEvent {
+                        event,
+                        occurrences: vec![(occurrence, venue_id)],
+                    },
+                );
+            }
+        }
+    }
+
+    for planned in plan.events.values_mut() {
+        planned
+            .occurrences
+            .sort_by_key(|(occurrence, _)| occurrence.start);
+    }
+    let invalid: Vec<(String, String)> = plan
+        .events
+        .iter()
+        .filter_map(|(title, planned)| {
+            let errors = planned.with_locations(&HashMap::new()).validate().err()?;
+            Some((title.clone(), errors.to_string().replace('\n', " ")))
+        })
+        .collect();
+    for (title, errors) in invalid {
+        plan.events.remove(&title);
+        plan.ambiguities
+            .push(format!("Event '{}' is skipped: {}", title, errors));
+    }
+    plan
+}
+
+impl Plan {
+    fn report(&self) -> String {
+        let mut report = String::new();
+        report.push_str(&format!("Locations ({}):\n", self.locations.len()));
+        for planned in self.locations.values() {
+            let marker = if planned.existing.is_some() {
+                "= "
+            } else {
+                "+ "
+            };
+            report.push_str(&format!(
+                "  {}{}, {}\n",
+                marker, planned.location.name, planned.location.address
+            ));
+        }
+        report.push_str(&format!("Events ({}):\n", self.events.len()));
+        for planned in self.events.values() {
+            report.push_str(&format!(
+                "  + {} with {} date(s){}\n",
+                planned.event.title,
+                planned.occurrences.len(),
+                if planned.event.published {
+                    ""
+                } else {
+                    " as a draft"
+                }
+            ));
+        }
+        report.push_str(&format!("Ambiguities ({}):\n", self.ambiguities.len()));
+        for ambiguity in &self.ambiguities {
+            report.push_str(&format!("  ! {}\n", ambiguity));
+        }
+        report
+    }
+
+    fn import(&self, store: &Store) -> StoreResult<()> {
+        store.transaction(|| {
+            let mut location_ids: HashMap<&str, Id<Location>> = HashMap::new();
+            for (venue_id, planned) in &self.locations {
+                let id = match &planned.existing {
+                    Some(id) => id.clone(),
+                    None => store.create(planned.location.clone())?,
+                };
+                location_ids.insert(venue_id, id);
+            }
+
+            for planned in self.events.values() {
+                store.create_event_with_occurrences(planned.with_locations(&location_ids))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn parse_date_time(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// The raw contents of all elements with the tag that are not nested in one another.
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some((content, after)) = next_element(rest, tag) {
+        found.push(content);
+        rest = after;
+    }
+    found
+}
+
+fn element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    next_element(xml, tag).map(|(content, _)| content)
+}
+
+/// The raw content of the next element with the tag, and the XML after it.
+fn next_element<'a>(xml: &'a str, tag: &str) -> Option<(&'a str, &'a str)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut rest = xml;
+    loop {
+        let name_end = rest.find(&open)? + open.len();
+        let tag_end = name_end + rest[name_end..].find('>')?;
+        let attributes = &rest[name_end..tag_end];
+        // Skips longer tags starting alike, like `<items>` when looking for `<item>`.
+        if !(attributes.is_empty() || attributes.starts_with(' ') || attributes.starts_with('/')) {
+            rest = &rest[tag_end..];
+            continue;
+        }
+        if attributes.ends_with('/') {
+            return Some(("", &rest[tag_end + 1..]));
+        }
+
+        let content_start = tag_end + 1;
+        let content_end = content_start + rest[content_start..].find(&close)?;
+        return Some((
+            &rest[content_start..content_end],
+            &rest[content_end + close.len()..],
+        ));
+    }
+}
+
+/// The text of an element, with CDATA sections unwrapped and entities decoded elsewhere.
+fn text(raw: &str) -> String {
+    let mut text = String::new();
+    let mut rest = raw;
+    while let Some(start) = rest.find("<![CDATA[") {
+        text.push_str(&decode_entities(&rest[..start]));
+        let content = &rest[start + "<![CDATA[".len()..];
+        let end = content.find("]]>").unwrap_or_else(|| content.len());
+        text.push_str(&content[..end]);
+        rest = content.get(end + "]]>".len()..).unwrap_or("");
+    }
+    text.push_str(&decode_entities(rest));
+    text.trim().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ if name.starts_with("#x") => u32::from_str_radix(&name[2..], 16)
+            .ok()
+            .and_then(std::char::from_u32),
+        _ if name.starts_with('#') => name[1..].parse().ok().and_then(std::char::from_u32),
+        _ => None,
+    }
+}
+
+/// Posts are written in HTML, while descriptions are plain text. Paragraphs and line breaks
+/// are kept, everything else is dropped.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let tag = rest[start + 1..end].trim().to_lowercase();
+        if tag.starts_with("br") {
+            text.push('\n');
+        } else if tag == "/p" || tag.starts_with("/h") || tag == "/li" {
+            text.push_str("\n\n");
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = decode_entities(&text);
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut paragraphs: Vec<String> = Vec::new();
+    for paragraph in lines.split(|line| line.is_empty()) {
+        if !paragraph.is_empty() {
+            paragraphs.push(paragraph.join("\n"));
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<rss version="2.0" xmlns:wp="http://wordpress.org/export/1.2/">
+<channel>
+    <item>
+        <title>Chico Mend&#232;s</title>
+        <wp:post_id>12</wp:post_id>
+        <wp:status><![CDATA[publish]]></wp:status>
+        <wp:post_type><![CDATA[tribe_venue]]></wp:post_type>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_VenueAddress]]></wp:meta_key>
+            <wp:meta_value><![CDATA[Pontstraße 74-76]]></wp:meta_value>
+        </wp:postmeta>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_VenueZip]]></wp:meta_key>
+            <wp:meta_value><![CDATA[52062]]></wp:meta_value>
+        </wp:postmeta>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_VenueCity]]></wp:meta_key>
+            <wp:meta_value><![CDATA[Aachen]]></wp:meta_value>
+        </wp:postmeta>
+    </item>
+    <item>
+        <title>Social Dance</title>
+        <content:encoded><![CDATA[<p>Wir tanzen &amp; feiern.<br />Jeden Monat.</p>
+<p>Eintritt frei!</p>]]></content:encoded>
+        <excerpt:encoded/>
+        <wp:post_id>20</wp:post_id>
+        <wp:status><![CDATA[publish]]></wp:status>
+        <wp:post_type><![CDATA[tribe_events]]></wp:post_type>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_EventStartDate]]></wp:meta_key>
+            <wp:meta_value><![CDATA[2018-05-12 20:00:00]]></wp:meta_value>
+        </wp:postmeta>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_EventEndDate]]></wp:meta_key>
+            <wp:meta_value><![CDATA[2018-05-12 23:00:00]]></wp:meta_value>
+        </wp:postmeta>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_EventVenueID]]></wp:meta_key>
+            <wp:meta_value><![CDATA[12]]></wp:meta_value>
+        </wp:postmeta>
+    </item>
+    <item>
+        <title>Social Dance</title>
+        <content:encoded><![CDATA[Anders.]]></content:encoded>
+        <wp:post_id>21</wp:post_id>
+        <wp:status><![CDATA[publish]]></wp:status>
+        <wp:post_type><![CDATA[tribe_events]]></wp:post_type>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_EventStartDate]]></wp:meta_key>
+            <wp:meta_value><![CDATA[2018-04-14 20:00:00]]></wp:meta_value>
+        </wp:postmeta>
+        <wp:postmeta>
+            <wp:meta_key><![CDATA[_EventVenueID]]></wp:meta_key>
+            <wp:meta_value><![CDATA[99]]></wp:meta_value>
+        </wp:postmeta>
+    </item>
+    <item>
+        <title>Über uns</title>
+        <wp:post_id>30</wp:post_id>
+        <wp:post_type><![CDATA[page]]></wp:post_type>
+    </item>
+</channel>
+</rss>"#;
+
+    #[test]
+    fn posts_are_read_from_the_export() {
+        let items = parse_items(EXPORT).unwrap();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].title, "Chico Mendès");
+        assert_eq!(items[0].meta("_VenueAddress"), "Pontstraße 74-76");
+        assert_eq!(items[1].excerpt, "");
+        assert_eq!(
+            html_to_text(&items[1].content),
+            "Wir tanzen & feiern.\nJeden Monat.\n\nEintritt frei!"
+        );
+        assert!(parse_items("<html></html>").is_err());
+    }
+
+    #[test]
+    fn dates_of_a_series_become_one_event() {
+        let items = parse_items(EXPORT).unwrap();
+        let plan = plan(&items, &HashMap::new(), &HashSet::new());
+
+        assert_eq!(plan.locations.len(), 1);
+        let social_dance = &plan.events["Social Dance"];
+        let starts: Vec<String> = social_dance
+            .occurrences
+            .iter()
+            .map(|(occurrence, _)| occurrence.start.to_string())
+            .collect();
+        assert_eq!(starts, vec!["2018-04-14 20:00:00", "2018-05-12 20:00:00"]);
+        assert_eq!(social_dance.occurrences[0].1, None);
+        assert_eq!(social_dance.occurrences[1].1, Some("12".to_string()));
+        assert_eq!(plan.ambiguities.len(), 3);
+        assert!(plan.ambiguities[0].contains("assumed to last 180 minutes"));
+        assert!(plan.ambiguities[1].contains("unknown venue #99"));
+        assert!(plan.ambiguities[2].contains("described differently"));
+
+        let mut existing_titles = HashSet::new();
+        existing_titles.insert("Social Dance".to_string());
+        let plan = super::plan(&items, &HashMap::new(), &existing_titles);
+        assert!(plan.events.is_empty());
+    }
+}
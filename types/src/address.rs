@@ -0,0 +1,183 @@
+use std::fmt;
+
+use serde::de::Deserializer;
+use serde::{Deserialize, Serialize};
+
+/// A postal address, so that it can be shown alike everywhere and found on maps.
+#[derive(Serialize, Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Address {
+    pub street: String,
+    pub postal_code: String,
+    pub city: String,
+}
+
+impl Address {
+    /// Splits an address written on one line like `Pontstraße 74-76, 52062 Aachen`, or with
+    /// the street on a line of its own, as well as it can. Whatever cannot be split ends up
+    /// in the street, so that `validate` points out what is missing.
+    pub fn parse(text: &str) -> Address {
+        let is_separator = |c: char| c == ',' || c.is_whitespace();
+        let normalized = text.replace('\n', ",");
+        let normalized = normalized.trim_matches(is_separator);
+        let (street, rest) = match normalized.rfind(',') {
+            Some(index) => (&normalized[..index], &normalized[index + 1..]),
+            None => (normalized, ""),
+        };
+        let street = street.trim_matches(is_separator).to_string();
+        let rest = rest.trim_matches(is_separator);
+
+        let mut words = rest.split_whitespace().peekable();
+        let mut postal_code = String::new();
+        if let Some(digits) = words.next() {
+            postal_code.push_str(digits);
+        }
+        // Dutch postal codes end with two letters, e.g. `6211 LN`.
+        if let Some(letters) = words.peek() {
+            if postal_code.len() == 4 && is_dutch_suffix(letters) {
+                postal_code.push(' ');
+                postal_code.push_str(letters);
+                words.next();
+            }
+        }
+        let city = words.collect::<Vec<_>>().join(" ");
+
+        Address {
+            street,
+            postal_code,
+            city,
+        }
+    }
+
+    /// Checks that every part is present and that the postal code is one used around Aachen,
+    /// which borders Belgium and the Netherlands: five digits in Germany, four in Belgium,
+    /// and four followed by two letters in the Netherlands.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.street.trim().is_empty() {
+            return Err("The address is missing the street.".to_string());
+        }
+        if self.city.trim().is_empty() {
+            return Err("The address is missing the city.".to_string());
+        }
+
+        let mut parts = self.postal_code.split(' ');
+        let digits = parts.next().unwrap_or("");
+        let letters = parts.next();
+        let valid = digits.chars().all(|c| c.is_ascii_digit())
+            && parts.next().is_none()
+            && match (digits.len(), letters) {
+                (5, None) | (4, None) => true,
+                (4, Some(letters)) => is_dutch_suffix(letters),
+                _ => false,
+            };
+        if !valid {
+            return Err(format!(
+                "'{}' is not a German, Belgian, or Dutch postal code.",
+                self.postal_code
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn is_dutch_suffix(letters: &str) -> bool {
+    letters.len() == 2 && letters.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Formats the address on one line, e.g. `Pontstraße 74-76, 52062 Aachen`.
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let locality = format!("{} {}", self.postal_code, self.city);
+        match (self.street.as_str(), locality.trim()) {
+            (street, "") => write!(f, "{}", street),
+            ("", locality) => write!(f, "{}", locality),
+            (street, locality) => write!(f, "{}, {}", street, locality),
+        }
+    }
+}
+
+/// Accepts the parts of the address as well as the whole address as text, which is split
+/// with `Address::parse`.
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Parts {
+                street: String,
+                postal_code: String,
+                city: String,
+            },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => Address::parse(&text),
+            Raw::Parts {
+                street,
+                postal_code,
+                city,
+            } => Address {
+                street,
+                postal_code,
+                city,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(street: &str, postal_code: &str, city: &str) -> Address {
+        Address {
+            street: street.to_string(),
+            postal_code: postal_code.to_string(),
+            city: city.to_string(),
+        }
+    }
+
+    #[test]
+    fn addresses_are_split() {
+        let pontstrasse = address("Pontstraße 74-76", "52062", "Aachen");
+        assert_eq!(
+            Address::parse("Pontstraße 74-76, 52062 Aachen"),
+            pontstrasse
+        );
+        assert_eq!(
+            Address::parse("Pontstraße 74-76,\r\n52062 Aachen\n"),
+            pontstrasse
+        );
+        assert_eq!(
+            Address::parse("c/o Tanzschule, Markt 1, 6211 CK Maastricht"),
+            address("c/o Tanzschule, Markt 1", "6211 CK", "Maastricht")
+        );
+        assert_eq!(
+            Address::parse("Klosterstraße 9 Eupen"),
+            address("Klosterstraße 9 Eupen", "", "")
+        );
+    }
+
+    #[test]
+    fn postal_codes_are_validated() {
+        assert!(address("Markt 1", "52062", "Aachen").validate().is_ok());
+        assert!(address("Markt 1", "4700", "Eupen").validate().is_ok());
+        assert!(address("Markt 1", "6211 CK", "Maastricht")
+            .validate()
+            .is_ok());
+        assert!(address("Markt 1", "520620", "Aachen").validate().is_err());
+        assert!(address("Markt 1", "52O62", "Aachen").validate().is_err());
+        assert!(address("Markt 1", "6211 ck", "Maastricht")
+            .validate()
+            .is_err());
+        assert!(address("Markt 1", "52062", "").validate().is_err());
+    }
+
+    #[test]
+    fn addresses_are_formatted_on_one_line() {
+        let pontstrasse = address("Pontstraße 74-76", "52062", "Aachen");
+        assert_eq!(pontstrasse.to_string(), "Pontstraße 74-76, 52062 Aachen");
+        assert_eq!(address("Irgendwo", "", "").to_string(), "Irgendwo");
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::{Datelike, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{Id, Location, OccurrenceWithEvent, OccurrenceWithLocation};
+
+/// How a week's schedule differs from the week before, for the "Was ist neu" section of
+/// the newsletter.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ScheduleDiff {
+    /// Occurrences of events that did not take place the week before.
+    pub new: Vec<OccurrenceWithEvent>,
+    /// Occurrences of events that took place the week before, but on another day, at
+    /// another time, or at another location.
+    pub moved: Vec<MovedOccurrence>,
+    pub cancelled: Vec<OccurrenceWithEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MovedOccurrence {
+    pub occurrence: OccurrenceWithEvent,
+    /// The occurrence of the week before that this one replaces.
+    pub previous: OccurrenceWithLocation,
+}
+
+/// When and where an occurrence takes place within its week. Occurrences of regular events
+/// are the same in every week.
+fn slot(occurrence: &OccurrenceWithLocation) -> (Weekday, NaiveTime, Option<Id<Location>>) {
+    let start = occurrence.occurrence.start;
+    (
+        start.weekday(),
+        start.time(),
+        occurrence.location_id.clone(),
+    )
+}
+
+impl ScheduleDiff {
+    /// Compares the occurrences of a week with those of the week before, both in order of
+    /// their start. Occurrences of an event match those of the week before in the same
+    /// slot. The remaining ones are moved from an unmatched one of the week before,
+    /// preferably on the same day of the week, or new if there is none left.
+    pub fn between(
+        previous_week: &[OccurrenceWithEvent],
+        week: &[OccurrenceWithEvent],
+    ) -> ScheduleDiff {
+        let mut diff = ScheduleDiff::default();
+        let mut unmatched: Vec<&OccurrenceWithEvent> = previous_week
+            .iter()
+            .filter(|previous| !previous.occurrence.occurrence.cancelled)
+            .collect();
+
+        let mut unchanged = vec![false; week.len()];
+        for (index, entry) in week.iter().enumerate() {
+            if entry.occurrence.occurrence.cancelled {
+                continue;
+            }
+            if let Some(position) = unmatched.iter().position(|previous| {
+                previous.event_id == entry.event_id
+                    && slot(&previous.occurrence) == slot(&entry.occurrence)
+            }) {
+                unmatched.remove(position);
+                unchanged[index] = true;
+            }
+        }
+
+        for (entry, unchanged) in week.iter().zip(unchanged) {
+            if unchanged {
+                continue;
+            }
+            if entry.occurrence.occurrence.cancelled {
+                diff.cancelled.push(entry.clone());
+                continue;
+            }
+            let same_event = |previous: &&OccurrenceWithEvent| previous.event_id == entry.event_id;
+            let same_weekday = |previous: &&OccurrenceWithEvent| {
+                previous.occurrence.occurrence.start.weekday()
+                    == entry.occurrence.occurrence.start.weekday()
+            };
+            match unmatched
+                .iter()
+                .position(|previous| same_event(previous) && same_weekday(previous))
+                .or_else(|| unmatched.iter().position(same_event))
+            {
+                Some(position) => diff.moved.push(MovedOccurrence {
+                    occurrence: entry.clone(),
+                    previous: unmatched.remove(position).occurrence.clone(),
+                }),
+                None => diff.new.push(entry.clone()),
+            }
+        }
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Event, Occurrence};
+
+    fn entry(event_id: &Id<Event>, start: &str, cancelled: bool) -> OccurrenceWithEvent {
+        OccurrenceWithEvent {
+            occurrence: OccurrenceWithLocation {
+                occurrence: Occurrence {
+                    start: start.parse().unwrap(),
+                    duration: chrono::Duration::minutes(180),
+                    doors_open: None,
+                    open_end: false,
+                    stream_url: None,
+                    cancelled,
+                    cancellation_reason: None,
+                },
+                location_id: None,
+            },
+            event_id: event_id.clone(),
+            event: Event {
+                title: "Social Dance".to_string(),
+                teaser: String::new(),
+                description: String::new(),
+                contact_name: None,
+                contact_email: None,
+                locked: false,
+                slug: "social-dance".to_string(),
+                published: true,
+                publish_at: None,
+            },
+        }
+    }
+
+    #[test]
+    fn occurrences_are_compared_with_the_week_before() {
+        let id = |byte| -> Id<Event> { uuid::Uuid::from_bytes([byte; 16]).into() };
+        let (weekly, moved, cancelled, new) = (id(1), id(2), id(3), id(4));
+        // The 10th and the 17th of June 2019 are Mondays.
+        let previous_week = [
+            entry(&weekly, "2019-06-12T20:00:00", false),
+            entry(&moved, "2019-06-13T20:00:00", false),
+            entry(&cancelled, "2019-06-14T20:00:00", false),
+        ];
+        let week = [
+            entry(&weekly, "2019-06-19T20:00:00", false),
+            entry(&moved, "2019-06-20T21:00:00", false),
+            entry(&cancelled, "2019-06-21T20:00:00", true),
+            entry(&new, "2019-06-22T20:00:00", false),
+        ];
+
+        let diff = ScheduleDiff::between(&previous_week, &week);
+        assert_eq!(diff.new.len(), 1);
+        assert_eq!(diff.new[0].event_id, new);
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].occurrence.event_id, moved);
+        assert_eq!(
+            diff.moved[0].previous.occurrence.start,
+            "2019-06-13T20:00:00".parse().unwrap()
+        );
+        assert_eq!(diff.cancelled.len(), 1);
+        assert_eq!(diff.cancelled[0].event_id, cancelled);
+    }
+}
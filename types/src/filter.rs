@@ -0,0 +1,100 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use serde::Serialize;
+
+use crate::Occurrence;
+
+#[derive(Debug, Clone)]
+pub struct OccurrenceFilter {
+    pub before: Option<NaiveDateTime>,
+    pub after: Option<NaiveDateTime>,
+    /// Only occurrences that end after this time, i. e. whose `start + duration` is later.
+    pub ends_after: Option<NaiveDateTime>,
+    /// Only occurrences starting on this day or later.
+    pub from: Option<NaiveDate>,
+    /// Only occurrences starting on this day or earlier.
+    pub until: Option<NaiveDate>,
+    /// Only occurrences starting on one of these days of the week, or on any if empty.
+    pub weekdays: Vec<Weekday>,
+    /// Only occurrences starting at this time of day or later, whatever the date.
+    pub time_from: Option<NaiveTime>,
+    /// Only occurrences starting at this time of day or earlier, whatever the date.
+    pub time_until: Option<NaiveTime>,
+    /// Whether the occurrences of unpublished events are included. Only the admin asks
+    /// for them, the public pages never show drafts.
+    pub include_drafts: bool,
+}
+
+impl Default for OccurrenceFilter {
+    fn default() -> Self {
+        OccurrenceFilter {
+            before: None,
+            after: None,
+            ends_after: None,
+            from: None,
+            until: None,
+            weekdays: Vec::new(),
+            time_from: None,
+            time_until: None,
+            include_drafts: false,
+        }
+    }
+}
+
+/// How long after its end an occurrence is still listed as upcoming.
+pub struct DisplayCutoff(pub chrono::Duration);
+
+/// How far into the future occurrences are publicly listed.
+pub struct ScheduleHorizon(pub chrono::Duration);
+
+impl OccurrenceFilter {
+    /// Occurrences that have not ended yet, or ended less than the cutoff ago, and start
+    /// within the horizon. Occurrences that already started are therefore still listed
+    /// while they last.
+    pub fn upcoming(cutoff: &DisplayCutoff, horizon: &ScheduleHorizon) -> Self {
+        let now = chrono::Local::now()
+            .naive_local()
+            .with_nanosecond(0)
+            .unwrap();
+        OccurrenceFilter {
+            before: Some(now + horizon.0),
+            ends_after: Some(now - cutoff.0),
+            ..OccurrenceFilter::default()
+        }
+    }
+
+    /// Mirrors the server's SQL filter for occurrences held in memory.
+    pub fn matches(&self, occurrence: &Occurrence) -> bool {
+        self.before.map_or(true, |before| occurrence.start < before)
+            && self.after.map_or(true, |after| occurrence.start > after)
+            && self
+                .ends_after
+                .map_or(true, |ends_after| occurrence.end() > ends_after)
+            && self
+                .from
+                .map_or(true, |from| occurrence.start.date() >= from)
+            && self
+                .until
+                .map_or(true, |until| occurrence.start.date() <= until)
+            && (self.weekdays.is_empty() || self.weekdays.contains(&occurrence.start.weekday()))
+            && self
+                .time_from
+                .map_or(true, |from| occurrence.start.time() >= from)
+            && self
+                .time_until
+                .map_or(true, |until| occurrence.start.time() <= until)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub enum OccurrenceFilterError {
+    InvalidBeforeDate,
+    InvalidAfterDate,
+    InvalidRange,
+    InvalidFromDate,
+    InvalidUntilDate,
+    InvalidDateRange,
+    InvalidWeekday,
+    InvalidTimeFrom,
+    InvalidTimeUntil,
+    InvalidTimeRange,
+}
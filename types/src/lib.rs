@@ -0,0 +1,79 @@
+//! The domain types shared by the server and anything talking to its API.
+//! They do not depend on Rocket or Diesel, unless the `rocket` feature is enabled.
+
+mod address;
+mod diff;
+mod filter;
+mod model;
+mod subscriber;
+mod suggestion;
+mod validation;
+#[cfg(feature = "rocket")]
+mod web;
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use address::*;
+pub use diff::*;
+pub use filter::*;
+pub use model::*;
+pub use subscriber::*;
+pub use suggestion::*;
+pub use validation::*;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Id<Item> {
+    id: Uuid,
+    #[serde(skip)]
+    phantom: PhantomData<Item>,
+}
+
+// Clone, PartialEq, Eq, and Hash are implemented manually, because Derive does not understand
+// bounds on the PhantomData and would require them from `Item`, too.
+// See https://github.com/rust-lang/rust/issues/26925
+impl<Item> Clone for Id<Item> {
+    fn clone(&self) -> Self {
+        self.id.into()
+    }
+}
+
+impl<Item> PartialEq for Id<Item> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<Item> Eq for Id<Item> {}
+
+impl<Item> Hash for Id<Item> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<Item> fmt::Display for Id<Item> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.id.fmt(f)
+    }
+}
+
+impl<Item> From<Uuid> for Id<Item> {
+    fn from(uuid: Uuid) -> Self {
+        Id {
+            id: uuid,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Item> From<Id<Item>> for Uuid {
+    fn from(id: Id<Item>) -> Self {
+        id.id
+    }
+}
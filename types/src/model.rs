@@ -0,0 +1,654 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::BuildHasher;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{Address, Id};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Event {
+    pub title: String,
+    pub teaser: String,
+    pub description: String,
+    #[serde(default)]
+    pub contact_name: Option<String>,
+    #[serde(default)]
+    pub contact_email: Option<String>,
+    /// Locked events cannot be edited or deleted until they are unlocked.
+    #[serde(default)]
+    pub locked: bool,
+    /// Identifies the event in the URL of its page. It is assigned when the event is
+    /// created and kept when it is renamed, so shared links stay valid.
+    #[serde(default)]
+    pub slug: String,
+    /// Drafts are only listed in the admin, so events can be prepared before they are
+    /// announced. Events are published unless stated otherwise.
+    #[serde(default = "published_by_default")]
+    pub published: bool,
+    /// Until then, a published event is hidden like a draft, so it can be announced at a
+    /// set time.
+    #[serde(default)]
+    pub publish_at: Option<NaiveDateTime>,
+}
+
+impl Event {
+    /// Whether the event is shown on the public pages at `now`.
+    pub fn is_public(&self, now: NaiveDateTime) -> bool {
+        self.published && self.publish_at.map_or(true, |publish_at| publish_at <= now)
+    }
+}
+
+fn published_by_default() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub start: NaiveDateTime,
+    #[serde(with = "minutes")]
+    pub duration: Duration,
+    /// How long before the start the doors open, if that differs from the start.
+    #[serde(default, with = "optional_minutes")]
+    pub doors_open: Option<Duration>,
+    /// Whether the occurrence has no fixed end. The `duration` is then only an estimate.
+    #[serde(default)]
+    pub open_end: bool,
+    /// Where to watch the occurrence when it takes place online. Shown shortly before the start.
+    #[serde(default, deserialize_with = "web_url::deserialize")]
+    pub stream_url: Option<String>,
+    /// Cancelled occurrences stay listed, so that nobody turns up in vain.
+    #[serde(default)]
+    pub cancelled: bool,
+    #[serde(default)]
+    pub cancellation_reason: Option<String>,
+}
+
+/// Occurrences longer than this are rejected, since they are almost certainly a typo.
+pub const MAX_DURATION_MINUTES: i64 = 24 * 60;
+
+impl Occurrence {
+    pub fn end(&self) -> NaiveDateTime {
+        self.start + self.duration
+    }
+
+    /// Whether the two take place at the same time for some while. Occurrences that merely
+    /// follow each other do not overlap.
+    pub fn overlaps(&self, other: &Occurrence) -> bool {
+        self.start < other.end() && other.start < self.end()
+    }
+
+    pub fn doors_open_at(&self) -> Option<NaiveDateTime> {
+        self.doors_open.map(|doors_open| self.start - doors_open)
+    }
+
+    /// The stream URL, if the occurrence is about to start or running at `now`.
+    pub fn current_stream_url(&self, now: NaiveDateTime) -> Option<&str> {
+        if self.cancelled {
+            return None;
+        }
+        let stream_url = self.stream_url.as_ref()?;
+        let shown_from = self.start - Duration::minutes(STREAM_LEAD_MINUTES);
+        if shown_from <= now && now < self.end() {
+            Some(stream_url)
+        } else {
+            None
+        }
+    }
+}
+
+/// How long before the start an occurrence's stream URL is shown. Earlier, people might
+/// join an empty stream and think it is broken.
+const STREAM_LEAD_MINUTES: i64 = 30;
+
+/// Recurrences repeat at least once a year.
+const MAX_INTERVAL_WEEKS: u32 = 52;
+
+mod interval_weeks {
+    use serde::de::{self, Deserialize, Deserializer};
+
+    use super::MAX_INTERVAL_WEEKS;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        let weeks = u32::deserialize(deserializer)?;
+        if weeks == 0 || weeks > MAX_INTERVAL_WEEKS {
+            return Err(de::Error::custom(format!(
+                "interval of {} weeks is not between 1 and {} weeks",
+                weeks, MAX_INTERVAL_WEEKS
+            )));
+        }
+
+        Ok(weeks)
+    }
+}
+
+/// Only accepts web addresses, so that stream links cannot run scripts.
+mod web_url {
+    use serde::de::{self, Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<String>, D::Error> {
+        let url = match Option::<String>::deserialize(deserializer)? {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        if url.trim().is_empty() {
+            Ok(None)
+        } else if url.starts_with("https://") || url.starts_with("http://") {
+            Ok(Some(url))
+        } else {
+            Err(de::Error::custom(format!(
+                "'{}' is not a web address starting with https:// or http://",
+                url
+            )))
+        }
+    }
+}
+
+/// (De)serializes a `Duration` as whole minutes, which is what the API exposes.
+pub(crate) mod minutes {
+    use chrono::Duration;
+    use serde::de::{self, Deserialize, Deserializer};
+    use serde::Serializer;
+
+    use super::MAX_DURATION_MINUTES;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_minutes())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let minutes = u32::deserialize(deserializer)?;
+        if i64::from(minutes) > MAX_DURATION_MINUTES {
+            return Err(de::Error::custom(format!(
+                "duration of {} minutes exceeds the maximum of {} minutes",
+                minutes, MAX_DURATION_MINUTES
+            )));
+        }
+
+        Ok(Duration::minutes(minutes.into()))
+    }
+}
+
+pub(crate) mod optional_minutes {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::minutes::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        #[derive(Deserialize)]
+        struct Minutes(#[serde(with = "super::minutes")] Duration);
+
+        Ok(Option::<Minutes>::deserialize(deserializer)?.map(|Minutes(duration)| duration))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Location {
+    pub name: String,
+    pub address: Address,
+    /// Where the location is on a map, so that map apps can route people there.
+    #[serde(default)]
+    pub coordinates: Option<Coordinates>,
+    /// Whether the coordinates were entered by hand. Otherwise, they are looked up from the
+    /// address.
+    #[serde(default)]
+    pub manual_coordinates: bool,
+}
+
+/// In degrees, as used by GPS.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    pub fn validate(self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(format!(
+                "The latitude {} is not between -90 and 90 degrees.",
+                self.latitude
+            ));
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(format!(
+                "The longitude {} is not between -180 and 180 degrees.",
+                self.longitude
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LocationWithOccurrences {
+    pub location: Location,
+    pub occurrences: HashMap<Id<Occurrence>, Occurrence>,
+}
+
+/// How much a location is used, to back negotiations with venues about regular slots.
+#[derive(Serialize, Debug)]
+pub struct LocationReport {
+    pub location: Location,
+    /// Keyed by month in the format `2019-06`.
+    pub occurrences_per_month: BTreeMap<String, usize>,
+    pub total_hours: f64,
+}
+
+/// A week within the schedule horizon in which nothing takes place, so that organizers
+/// notice it early enough to plan something.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ScheduleGap {
+    /// The ISO week like `2019-W24`.
+    pub week: String,
+    pub monday: NaiveDate,
+}
+
+/// Aggregate numbers about everything that has taken place so far, shown publicly
+/// to showcase the scene's activity.
+#[derive(Serialize, Debug, Clone)]
+pub struct Statistics {
+    /// How many different events took place in each year.
+    pub events_per_year: BTreeMap<i32, usize>,
+    pub total_hours: f64,
+    /// The location hosting the most occurrences, with their number.
+    pub most_used_location: Option<(Location, usize)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OccurrenceWithLocation {
+    #[serde(flatten)]
+    pub occurrence: Occurrence,
+    /// `None` while the location has not been decided yet.
+    pub location_id: Option<Id<Location>>,
+}
+
+impl OccurrenceWithLocation {
+    pub fn location<'a, S: BuildHasher>(
+        &self,
+        locations: &'a HashMap<Id<Location>, Location, S>,
+    ) -> Option<&'a Location> {
+        self.location_id.as_ref().and_then(|id| locations.get(id))
+    }
+}
+
+/// Repeats an occurrence every few weeks, so that regular events need not be entered
+/// date by date. The store expands it into concrete occurrences up to the schedule horizon.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Recurrence {
+    /// The first occurrence. The following ones take place at the same time of day.
+    #[serde(flatten)]
+    pub first: OccurrenceWithLocation,
+    /// 1 repeats weekly, 2 every other week, and so on.
+    #[serde(deserialize_with = "interval_weeks::deserialize")]
+    pub interval_weeks: u32,
+    /// The last day an occurrence may take place on, if the recurrence ends at all.
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+    #[serde(default)]
+    pub exceptions: Vec<RecurrenceException>,
+}
+
+impl Recurrence {
+    /// Whether the recurrence takes place on the date, unless an exception cancels it.
+    pub fn is_scheduled_on(&self, date: NaiveDate) -> bool {
+        let days_since_first = (date - self.first.occurrence.start.date()).num_days();
+        days_since_first >= 0
+            && days_since_first % (7 * i64::from(self.interval_weeks)) == 0
+            && self.until.map_or(true, |until| date <= until)
+    }
+
+    /// Checks that every exception refers to a different date the recurrence is scheduled on.
+    pub fn check_exceptions(&self) -> Result<(), String> {
+        let mut dates = HashSet::new();
+        for exception in &self.exceptions {
+            if !self.is_scheduled_on(exception.date) {
+                return Err(format!(
+                    "The recurrence does not take place on {}.",
+                    exception.date
+                ));
+            }
+            if !dates.insert(exception.date) {
+                return Err(format!(
+                    "There is more than one exception for {}.",
+                    exception.date
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deviates from a recurrence on a single date, e.g. to cancel it on a holiday or to
+/// move it to another location for a week.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecurrenceException {
+    /// The date the recurrence would take place on.
+    pub date: NaiveDate,
+    /// No occurrence takes place, so the other changes do not matter.
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Replaces the start, which may also move the occurrence to another date.
+    #[serde(default)]
+    pub start: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub location_id: Option<Id<Location>>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Overview {
+    pub locations: HashMap<Id<Location>, Location>,
+    pub events: HashMap<Id<Event>, EventWithOccurrences>,
+}
+
+/// A part of the overview, for clients that fetch it incrementally. Its events are those
+/// following the first `offset` ones in order of their first occurrence.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct OverviewPage {
+    /// All locations, since there are few of them.
+    pub locations: HashMap<Id<Location>, Location>,
+    /// In order, so that clients can show the pages one after another.
+    pub events: Vec<(Id<Event>, EventWithOccurrences)>,
+    pub offset: usize,
+    pub limit: usize,
+    /// How many events and occurrences match the filter on all pages.
+    pub total_events: usize,
+    pub total_occurrences: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EventWithOccurrences {
+    pub event: Event,
+    pub occurrences: Vec<OccurrenceWithLocation>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OccurrenceWithEvent {
+    pub occurrence: OccurrenceWithLocation,
+    pub event_id: Id<Event>,
+    pub event: Event,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DeletedEvent {
+    pub title: String,
+    pub slug: String,
+    pub deleted_at: NaiveDateTime,
+}
+
+/// A deleted item, which can still be restored.
+#[derive(Serialize, Debug)]
+pub struct Trashed<T> {
+    pub item: T,
+    pub deleted_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Trash {
+    pub locations: HashMap<Id<Location>, Trashed<Location>>,
+    pub events: HashMap<Id<Event>, Trashed<EventWithOccurrences>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RelatedEvent {
+    pub event_id: Id<Event>,
+    pub event: Event,
+}
+
+/// An occurrence that overlaps with an occurrence of another event at the same location.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BookingConflict {
+    pub location_id: Id<Location>,
+    /// The start of the occurrence that was saved.
+    pub start: NaiveDateTime,
+    /// The other event, which is booked at the same time.
+    pub event_id: Id<Event>,
+    pub event_title: String,
+    /// The start of the other event's occurrence.
+    pub other_start: NaiveDateTime,
+}
+
+/// A public question or remark on an event. Only shown once an admin approved it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Comment {
+    pub name: String,
+    pub text: String,
+    pub created_at: NaiveDateTime,
+    #[serde(default)]
+    pub approved: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CommentWithEvent {
+    pub comment: Comment,
+    pub event_id: Id<Event>,
+}
+
+/// A picture uploaded for an event, served at `/media/<file_name>`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EventImage {
+    pub file_name: String,
+    /// The name of the file on the uploader's computer.
+    pub original_name: String,
+    pub content_type: String,
+    /// In bytes.
+    pub size: u64,
+    pub uploaded_at: NaiveDateTime,
+}
+
+/// An entry of the website's navigation menu, e. g. for a special event.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NavItem {
+    pub label: String,
+    pub url: String,
+    /// Items are shown in ascending order of their position.
+    pub position: i32,
+    /// Hidden items are kept for later, e. g. for next year's ball.
+    pub visible: bool,
+}
+
+/// An informational page of the website, like "Über uns" or the FAQ, served at `/<slug>`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Page {
+    pub slug: String,
+    pub title: String,
+    /// Written in Markdown.
+    pub body: String,
+    /// Drafts are only shown in the admin.
+    pub published: bool,
+}
+
+/// A frequently asked question, shown on the FAQ page.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FaqEntry {
+    pub question: String,
+    /// Written in Markdown.
+    pub answer: String,
+    /// Questions are grouped by their category, e. g. "Kurse".
+    pub category: String,
+    /// Questions are shown in ascending order of their position, and categories in the order
+    /// of their first question.
+    pub position: i32,
+}
+
+/// Sends visitors of an old URL, e. g. of the previous WordPress site, to its new place.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UrlRedirect {
+    /// The path of the old URL, like `/2018/05/social-dance/`.
+    pub source: String,
+    /// A path of this site or a URL elsewhere.
+    pub target: String,
+    /// 301 or 308 if the old URL is gone for good, 302 or 307 otherwise.
+    #[serde(default = "default_redirect_status")]
+    pub status: u16,
+}
+
+fn default_redirect_status() -> u16 {
+    301
+}
+
+/// What a search result is, so that results can be grouped by it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchResultKind {
+    Event,
+    Page,
+    Faq,
+}
+
+/// An event, page or question that contains every word searched for.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchResult {
+    #[serde(rename = "type")]
+    pub kind: SearchResultKind,
+    pub title: String,
+    /// The beginning of the text, shortened.
+    pub excerpt: String,
+    /// The path of the public page showing it.
+    pub url: String,
+}
+
+/// The result of checking the external links of all events.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LinkReport {
+    pub checked_at: NaiveDateTime,
+    /// How many distinct links were checked.
+    pub checked: usize,
+    pub broken: Vec<BrokenLink>,
+    /// The distinct HTTPS links whose server accepts connections, but whose pages could not
+    /// be requested without TLS. They are not counted as checked.
+    pub unchecked: Vec<String>,
+}
+
+/// A link in an event's texts or an occurrence's stream URL that could not be followed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BrokenLink {
+    pub url: String,
+    pub event_id: Id<Event>,
+    pub event_title: String,
+    /// The HTTP status the server responded with, or why it could not be reached.
+    pub problem: String,
+}
+
+/// An issue of the newsletter, drafted and stored in the admin.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Newsletter {
+    pub title: String,
+    /// The date the issue is sent on.
+    pub date: NaiveDate,
+    pub content: String,
+}
+
+/// Whether a newsletter reached a subscriber.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Delivery {
+    pub email: String,
+    pub sent_at: NaiveDateTime,
+    /// Why the mail server did not accept the newsletter, if it did not.
+    pub error: Option<String>,
+}
+
+/// Gives a partner access to the public API, up to a number of requests per day.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    /// Who the key was issued to.
+    pub name: String,
+    pub daily_quota: u32,
+}
+
+/// An API key as listed in the admin. Its secret is not stored, so it is not listed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssuedApiKey {
+    pub name: String,
+    pub daily_quota: u32,
+    pub used_today: u32,
+}
+
+/// A key that was just issued. Only a hash of the secret is stored, so this is the only
+/// time it can be passed on to the partner.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewApiKey {
+    pub id: Id<ApiKey>,
+    /// What the partner sends in the `X-Api-Key` header.
+    pub secret: String,
+}
+
+/// An event proposed by an external organizer, waiting for moderation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Submission {
+    pub title: String,
+    pub teaser: String,
+    pub description: String,
+    pub organizer_name: String,
+    pub organizer_email: String,
+    pub start: NaiveDateTime,
+    #[serde(with = "minutes")]
+    pub duration: Duration,
+    pub location_id: Id<Location>,
+}
+
+impl From<Submission> for EventWithOccurrences {
+    fn from(submission: Submission) -> Self {
+        EventWithOccurrences {
+            event: Event {
+                title: submission.title,
+                teaser: submission.teaser,
+                description: submission.description,
+                contact_name: Some(submission.organizer_name),
+                contact_email: Some(submission.organizer_email),
+                locked: false,
+                slug: String::new(),
+                published: true,
+                publish_at: None,
+            },
+            occurrences: vec![OccurrenceWithLocation {
+                occurrence: Occurrence {
+                    start: submission.start,
+                    duration: submission.duration,
+                    doors_open: None,
+                    open_end: false,
+                    stream_url: None,
+                    cancelled: false,
+                    cancellation_reason: None,
+                },
+                location_id: Some(submission.location_id),
+            }],
+        }
+    }
+}
+
+/// A semester-like half of the year, as the community refers to past courses.
+/// A winter season belongs to the year it starts in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Season {
+    pub year: i32,
+    pub half: SeasonHalf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SeasonHalf {
+    Summer,
+    Winter,
+}
+
+impl std::fmt::Display for Season {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.half {
+            SeasonHalf::Summer => write!(f, "Sommer {}", self.year),
+            SeasonHalf::Winter => write!(f, "Winter {}/{:02}", self.year, (self.year + 1) % 100),
+        }
+    }
+}
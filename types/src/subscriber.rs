@@ -0,0 +1,197 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Someone who receives the newsletter.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Subscriber {
+    /// Stored in lowercase, so that every address is subscribed only once.
+    pub email: String,
+    pub name: Option<String>,
+    /// When the subscriber agreed to receive the newsletter.
+    pub consented_at: NaiveDate,
+    /// Whether the subscriber confirmed the address.
+    pub confirmed: bool,
+}
+
+/// Whether the text looks like `local@domain.tld`. Line breaks, spaces, and angle brackets
+/// are rejected in particular, since the address ends up in SMTP commands and mail headers,
+/// where they would let the sender add recipients or headers of their own.
+pub fn is_email_address(address: &str) -> bool {
+    const SPECIALS: &[char] = &['<', '>', '(', ')', '[', ']', ',', ';', ':', '"', '\\'];
+
+    let mut parts = address.split('@');
+    let (local, domain) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(local), Some(domain), None) => (local, domain),
+        _ => return false,
+    };
+    address.len() <= 254
+        && address
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !SPECIALS.contains(&c))
+        && !local.is_empty()
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+/// The outcome of importing subscribers.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct SubscriberImport {
+    pub imported: usize,
+    /// The addresses that were subscribed already, or listed more than once.
+    pub duplicates: Vec<String>,
+}
+
+impl Subscriber {
+    /// Reads subscribers from CSV lines of email, optional name, and consent date, as
+    /// exported from the old mailing list. The header line is optional. Consent dates may
+    /// carry a time, which is ignored. The subscribers already confirmed their address with
+    /// the old list, so they are confirmed.
+    pub fn parse_csv(csv: &str) -> Result<Vec<Subscriber>, String> {
+        let mut subscribers = Vec::new();
+        for (index, line) in csv.lines().enumerate() {
+            let line_number = index + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields = split_csv_line(line)
+                .ok_or_else(|| format!("Line {} has an unclosed quote.", line_number))?;
+            if index == 0 && fields[0].eq_ignore_ascii_case("email") {
+                continue;
+            }
+            let (email, name, consented_at) = match fields.as_slice() {
+                [email, name, consented_at] => (email, name, consented_at),
+                _ => {
+                    return Err(format!(
+                        "Line {} has {} fields instead of email, name, and consent date.",
+                        line_number,
+                        fields.len()
+                    ))
+                }
+            };
+
+            let email = email.to_lowercase();
+            if !is_email_address(&email) {
+                return Err(format!(
+                    "Line {}: '{}' is not an email address.",
+                    line_number, email
+                ));
+            }
+            let consented_at = consented_at
+                .get(..10)
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+                .ok_or_else(|| {
+                    format!(
+                        "Line {}: '{}' is not a date like 2019-06-12.",
+                        line_number, consented_at
+                    )
+                })?;
+
+            subscribers.push(Subscriber {
+                email,
+                name: Some(name.clone()).filter(|name| !name.is_empty()),
+                consented_at,
+                confirmed: true,
+            });
+        }
+        Ok(subscribers)
+    }
+}
+
+/// Splits a line at commas outside of double quotes, see RFC 4180. Quotes within quoted
+/// fields are doubled. Returns `None` if a quote is not closed.
+fn split_csv_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            if quoted && chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                quoted = !quoted;
+            }
+        } else if c == ',' && !quoted {
+            fields.push(std::mem::replace(&mut field, String::new()));
+        } else {
+            field.push(c);
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(
+        fields
+            .into_iter()
+            .map(|field| field.trim().to_string())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribers_are_read_from_csv() {
+        let csv = "Email,Name,Consent date\r\n\
+                   Kim@Example.com,\"Doe, Kim\",2019-03-04 12:34:56\r\n\
+                   \r\n\
+                   alex@example.com,,2019-05-01\r\n";
+
+        assert_eq!(
+            Subscriber::parse_csv(csv),
+            Ok(vec![
+                Subscriber {
+                    email: "kim@example.com".to_string(),
+                    name: Some("Doe, Kim".to_string()),
+                    consented_at: NaiveDate::from_ymd(2019, 3, 4),
+                    confirmed: true,
+                },
+                Subscriber {
+                    email: "alex@example.com".to_string(),
+                    name: None,
+                    consented_at: NaiveDate::from_ymd(2019, 5, 1),
+                    confirmed: true,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn only_plain_addresses_are_email_addresses() {
+        assert!(is_email_address("kim@example.com"));
+        assert!(is_email_address("kim.doe+newsletter@mail.example.com"));
+        for address in &[
+            "kim",
+            "@example.com",
+            "kim@",
+            "kim@example",
+            "kim@example..com",
+            "kim@doe@example.com",
+            "kim doe@example.com",
+            "kim@example.com\r\nRCPT TO:<victim@example.com>",
+            "kim@example.com\nBcc: victim@example.com",
+            "<kim@example.com>",
+            "kim@exämple.com",
+        ] {
+            assert!(!is_email_address(address), "{}", address);
+        }
+    }
+
+    #[test]
+    fn malformed_lines_are_pointed_out() {
+        assert!(Subscriber::parse_csv("kim@example.com,Kim")
+            .unwrap_err()
+            .starts_with("Line 1"));
+        assert!(
+            Subscriber::parse_csv("kim@example.com,Kim,2019-03-04\nalex,,2019-05-01")
+                .unwrap_err()
+                .starts_with("Line 2")
+        );
+        assert!(Subscriber::parse_csv("kim@example.com,\"Kim,2019-03-04").is_err());
+        assert!(Subscriber::parse_csv("kim@example.com,Kim,04.03.2019").is_err());
+    }
+}
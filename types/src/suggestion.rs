@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{Id, Location, OccurrenceWithLocation};
+
+/// Defaults for the next occurrence of an event, so that the admin does not have to enter
+/// the same time and place every time. Each is `None` if there is nothing to go by.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct OccurrenceSuggestion {
+    /// The next date on the day of the week the event usually takes place, at its usual
+    /// time. It is after the last occurrence, and not before today.
+    pub start: Option<NaiveDateTime>,
+    #[serde(default, with = "crate::model::optional_minutes")]
+    pub duration: Option<Duration>,
+    pub location_id: Option<Id<Location>>,
+}
+
+/// The most common of the values, or of those the one used last. They must be in order
+/// of time.
+fn most_common<T: Eq + Hash>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut uses: HashMap<T, (usize, usize)> = HashMap::new();
+    for (index, value) in values.enumerate() {
+        let (count, last) = uses.entry(value).or_insert((0, index));
+        *count += 1;
+        *last = index;
+    }
+
+    uses.into_iter()
+        .max_by_key(|(_, uses)| *uses)
+        .map(|(value, _)| value)
+}
+
+/// The first date that is on the weekday, starting with `from`.
+fn next_on(weekday: Weekday, from: NaiveDate) -> NaiveDate {
+    let days_until =
+        (7 + weekday.num_days_from_monday() - from.weekday().num_days_from_monday()) % 7;
+    from + Duration::days(days_until.into())
+}
+
+impl OccurrenceSuggestion {
+    /// Goes by the occurrences that already took place, or by the planned ones if there
+    /// are none yet. Cancelled occurrences are not taken into account.
+    pub fn from_history(occurrences: &[OccurrenceWithLocation], now: NaiveDateTime) -> Self {
+        let mut planned: Vec<&OccurrenceWithLocation> = occurrences
+            .iter()
+            .filter(|occurrence| !occurrence.occurrence.cancelled)
+            .collect();
+        planned.sort_by_key(|occurrence| occurrence.occurrence.start);
+        let past: Vec<&OccurrenceWithLocation> = planned
+            .iter()
+            .cloned()
+            .filter(|occurrence| occurrence.occurrence.start < now)
+            .collect();
+        let history = if past.is_empty() { &planned } else { &past };
+
+        let slot: Option<(Weekday, NaiveTime)> = most_common(history.iter().map(|occurrence| {
+            let start = occurrence.occurrence.start;
+            (start.weekday(), start.time())
+        }));
+        let start = slot.map(|(weekday, time)| {
+            let after_last = planned
+                .last()
+                .map_or(now.date(), |last| last.occurrence.start.date().succ());
+            next_on(weekday, after_last.max(now.date())).and_time(time)
+        });
+
+        OccurrenceSuggestion {
+            start,
+            duration: most_common(
+                history
+                    .iter()
+                    .map(|occurrence| occurrence.occurrence.duration),
+            ),
+            location_id: most_common(
+                history
+                    .iter()
+                    .filter_map(|occurrence| occurrence.location_id.clone()),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Occurrence;
+
+    fn occurrence(
+        start: &str,
+        minutes: i64,
+        location: u8,
+        cancelled: bool,
+    ) -> OccurrenceWithLocation {
+        OccurrenceWithLocation {
+            occurrence: Occurrence {
+                start: start.parse().unwrap(),
+                duration: Duration::minutes(minutes),
+                doors_open: None,
+                open_end: false,
+                stream_url: None,
+                cancelled,
+                cancellation_reason: None,
+            },
+            location_id: Some(uuid::Uuid::from_bytes([location; 16]).into()),
+        }
+    }
+
+    #[test]
+    fn the_most_common_values_are_suggested() {
+        // The 12th of June 2019 is a Wednesday.
+        let occurrences = [
+            occurrence("2019-05-29T20:00:00", 180, 1, false),
+            occurrence("2019-06-04T19:00:00", 120, 2, false),
+            occurrence("2019-06-05T20:00:00", 180, 1, false),
+            occurrence("2019-06-12T20:00:00", 120, 2, false),
+            occurrence("2019-06-13T21:00:00", 60, 3, true),
+        ];
+
+        let suggestion = OccurrenceSuggestion::from_history(
+            &occurrences,
+            "2019-06-14T12:00:00".parse().unwrap(),
+        );
+        assert_eq!(
+            suggestion.start,
+            Some("2019-06-19T20:00:00".parse().unwrap())
+        );
+        // Both durations and locations are used twice, the one used last wins.
+        assert_eq!(suggestion.duration, Some(Duration::minutes(120)));
+        assert_eq!(
+            suggestion.location_id,
+            Some(uuid::Uuid::from_bytes([2; 16]).into())
+        );
+    }
+
+    #[test]
+    fn planned_occurrences_are_used_without_history() {
+        let occurrences = [occurrence("2019-06-21T19:30:00", 90, 1, false)];
+
+        let suggestion = OccurrenceSuggestion::from_history(
+            &occurrences,
+            "2019-06-14T12:00:00".parse().unwrap(),
+        );
+        assert_eq!(
+            suggestion.start,
+            Some("2019-06-28T19:30:00".parse().unwrap())
+        );
+        assert_eq!(suggestion.duration, Some(Duration::minutes(90)));
+
+        assert_eq!(
+            OccurrenceSuggestion::from_history(&[], "2019-06-14T12:00:00".parse().unwrap()),
+            OccurrenceSuggestion::default()
+        );
+    }
+}
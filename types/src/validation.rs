@@ -0,0 +1,147 @@
+use std::fmt;
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::{EventWithOccurrences, Location, Occurrence, Recurrence};
+
+/// The problems found with submitted data, each with the field it concerns, like
+/// `occurrences[1].duration`. All of them are collected, so that they can be fixed at once.
+#[derive(Debug, Default, PartialEq)]
+pub struct ValidationErrors(Vec<(String, String)>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &str, message: &str) {
+        self.0.push((field.to_string(), message.to_string()));
+    }
+
+    fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// One problem per line, e. g. `event.title: The title must not be empty.`
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self
+            .0
+            .iter()
+            .map(|(field, message)| format!("{}: {}", field, message))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Occurrences starting earlier are typos like 0219 for 2019, since the group was founded
+/// long after.
+fn earliest_start() -> NaiveDateTime {
+    NaiveDate::from_ymd(2000, 1, 1).and_hms(0, 0, 0)
+}
+
+impl Occurrence {
+    fn check(&self, field: &str, errors: &mut ValidationErrors) {
+        if self.duration.num_minutes() <= 0 {
+            errors.add(
+                &format!("{}.duration", field),
+                "The duration must be at least one minute.",
+            );
+        }
+        if self.start < earliest_start() {
+            errors.add(
+                &format!("{}.start", field),
+                &format!("The start {} is too far in the past.", self.start),
+            );
+        }
+    }
+}
+
+impl EventWithOccurrences {
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.event.title.trim().is_empty() {
+            errors.add("event.title", "The title must not be empty.");
+        }
+        for (index, occurrence) in self.occurrences.iter().enumerate() {
+            occurrence
+                .occurrence
+                .check(&format!("occurrences[{}]", index), &mut errors);
+        }
+        errors.into_result()
+    }
+}
+
+impl Recurrence {
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        self.first.occurrence.check("first", &mut errors);
+        if let Err(message) = self.check_exceptions() {
+            errors.add("exceptions", &message);
+        }
+        errors.into_result()
+    }
+}
+
+impl Location {
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.name.trim().is_empty() {
+            errors.add("name", "The name must not be empty.");
+        }
+        if let Err(message) = self.address.validate() {
+            errors.add("address", &message);
+        }
+        if let Some(Err(message)) = self.coordinates.map(|coordinates| coordinates.validate()) {
+            errors.add("coordinates", &message);
+        }
+        errors.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Event, OccurrenceWithLocation};
+
+    #[test]
+    fn every_problem_is_reported_with_its_field() {
+        let occurrence = |start: &str, minutes: i64| OccurrenceWithLocation {
+            occurrence: Occurrence {
+                start: start.parse().unwrap(),
+                duration: chrono::Duration::minutes(minutes),
+                doors_open: None,
+                open_end: false,
+                stream_url: None,
+                cancelled: false,
+                cancellation_reason: None,
+            },
+            location_id: None,
+        };
+        let event = EventWithOccurrences {
+            event: Event {
+                title: " ".to_string(),
+                teaser: String::new(),
+                description: String::new(),
+                contact_name: None,
+                contact_email: None,
+                locked: false,
+                slug: String::new(),
+                published: true,
+                publish_at: None,
+            },
+            occurrences: vec![
+                occurrence("2019-06-12T20:00:00", 180),
+                occurrence("0219-06-19T20:00:00", 0),
+            ],
+        };
+
+        assert_eq!(
+            event.validate().unwrap_err().to_string(),
+            "event.title: The title must not be empty.\n\
+             occurrences[1].duration: The duration must be at least one minute.\n\
+             occurrences[1].start: The start 0219-06-19 20:00:00 is too far in the past."
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::Cursor;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use rocket_dep::http::{RawStr, Status};
+use rocket_dep::request::{FormItem, FromParam, FromQuery, Query, Request};
+use rocket_dep::response::{self, Responder, Response};
+use uuid::Uuid;
+
+use crate::{Id, OccurrenceFilter, OccurrenceFilterError};
+
+impl<'a, T> FromParam<'a> for Id<T> {
+    type Error = &'a RawStr;
+
+    /// A value is successfully parsed if `param` is a properly formatted Uuid.
+    #[inline(always)]
+    fn from_param(param: &'a RawStr) -> Result<Id<T>, Self::Error> {
+        Uuid::parse_str(param.as_str())
+            .map(Id::from)
+            .map_err(|_| param)
+    }
+}
+
+impl<'r> Responder<'r> for OccurrenceFilterError {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        Response::build()
+            .sized_body(Cursor::new(serde_json::to_string(&self).unwrap()))
+            .status(Status::UnprocessableEntity)
+            .ok()
+    }
+}
+
+impl<'q> FromQuery<'q> for OccurrenceFilter {
+    type Error = OccurrenceFilterError;
+
+    fn from_query(mut query: Query<'q>) -> Result<Self, Self::Error> {
+        use OccurrenceFilterError::*;
+        let before: Option<NaiveDateTime> = query
+            .clone()
+            .find(|i| i.key == "before")
+            .map(|item| decode_datetime(item).ok_or(InvalidBeforeDate))
+            .transpose()?;
+        let after: Option<NaiveDateTime> = query
+            .clone()
+            .find(|i| i.key == "after")
+            .map(|item| decode_datetime(item).ok_or(InvalidAfterDate))
+            .transpose()?;
+        let from: Option<NaiveDate> = query
+            .clone()
+            .find(|i| i.key == "from")
+            .map(|item| decode_date(item).ok_or(InvalidFromDate))
+            .transpose()?;
+        let until: Option<NaiveDate> = query
+            .clone()
+            .find(|i| i.key == "until")
+            .map(|item| decode_date(item).ok_or(InvalidUntilDate))
+            .transpose()?;
+        let weekdays: Vec<Weekday> = query
+            .clone()
+            .filter(|i| i.key == "weekday")
+            .map(|item| {
+                item.value
+                    .url_decode_lossy()
+                    .parse()
+                    .map_err(|_| InvalidWeekday)
+            })
+            .collect::<Result<_, _>>()?;
+        let time_from: Option<NaiveTime> = query
+            .clone()
+            .find(|i| i.key == "time_from")
+            .map(|item| decode_time(item).ok_or(InvalidTimeFrom))
+            .transpose()?;
+        let time_until: Option<NaiveTime> = query
+            .clone()
+            .find(|i| i.key == "time_until")
+            .map(|item| decode_time(item).ok_or(InvalidTimeUntil))
+            .transpose()?;
+        let include_drafts = query.any(|i| i.key == "drafts" && i.value == "true");
+
+        if let (Some(after), Some(before)) = (after, before) {
+            if after > before {
+                return Err(InvalidRange);
+            }
+        }
+        if let (Some(from), Some(until)) = (from, until) {
+            if from > until {
+                return Err(InvalidDateRange);
+            }
+        }
+        if let (Some(from), Some(until)) = (time_from, time_until) {
+            if from > until {
+                return Err(InvalidTimeRange);
+            }
+        }
+
+        Ok(OccurrenceFilter {
+            before,
+            after,
+            from,
+            until,
+            weekdays,
+            time_from,
+            time_until,
+            include_drafts,
+            ..OccurrenceFilter::default()
+        })
+    }
+}
+
+fn decode_datetime(item: FormItem) -> Option<NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(&item.value.url_decode_lossy(), "%Y-%m-%dT%H:%M:%S").ok()
+}
+
+fn decode_date(item: FormItem) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&item.value.url_decode_lossy(), "%Y-%m-%d").ok()
+}
+
+fn decode_time(item: FormItem) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(&item.value.url_decode_lossy(), "%H:%M").ok()
+}